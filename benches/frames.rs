@@ -0,0 +1,50 @@
+//! How many network frames a second the engine gets through, on the smallest replay in `replays/`.
+//!
+//! Run with `cargo bench`. Criterion compares each run with the last one, so a slowdown shows up as
+//! a regression in its report.
+
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rl_replay_zone_visualizer::timeline::Timeline;
+use rl_replay_zone_visualizer::{parse_replay, zones, ReplayEngine};
+
+const SAMPLE_REPLAY: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/replays/97d29586-530f-484e-aa0b-ac8ad8b54217.replay"
+);
+
+fn frames(c: &mut Criterion) {
+    let engine = ReplayEngine::new(
+        parse_replay(Path::new(SAMPLE_REPLAY)).expect("the sample replay parses"),
+    );
+    let mut group = c.benchmark_group("frames");
+    group.throughput(Throughput::Elements(engine.frame_count() as u64));
+
+    group.bench_function("step", |b| {
+        b.iter(|| {
+            let mut engine = engine.clone();
+            while engine.frame_index() < engine.frame_count() {
+                engine.step();
+            }
+            engine
+        })
+    });
+    // Cars move every frame, so the diagram is built again every time
+    group.bench_function("zone_control", |b| {
+        b.iter(|| {
+            let mut engine = engine.clone();
+            let mut frames = 0;
+            while engine.frame_index() < engine.frame_count() {
+                engine.step();
+                frames += zones::zone_control(&engine).is_some() as usize;
+            }
+            frames
+        })
+    });
+    group.bench_function("preprocess", |b| b.iter(|| Timeline::build(&engine)));
+    group.finish();
+}
+
+criterion_group!(benches, frames);
+criterion_main!(benches);
@@ -0,0 +1,606 @@
+use std::collections::HashMap;
+
+use boxcars::{
+    ActorId, Attribute, Frame, NewActor, ObjectId, RigidBody, UniqueId, UpdatedAttribute, Vector3f,
+};
+
+use crate::{PlayerDetails, Team, PURPLE};
+
+/// Something that happened to a player, car or the ball in a network frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActorEvent {
+    /// A player's replication info was created. They're called "Unknown" and drawn in purple until
+    /// their name and team replicate.
+    PlayerJoined {
+        player: ActorId,
+    },
+    /// A player's platform id matched one who left earlier, so they have that player's name and
+    /// color back.
+    PlayerRejoined {
+        player: ActorId,
+    },
+    /// A player's replication info went away, taking their car with it.
+    PlayerLeft {
+        player: ActorId,
+    },
+    NameChanged {
+        player: ActorId,
+        name: String,
+    },
+    TeamChanged {
+        player: ActorId,
+        team: Team,
+    },
+    /// A player was given a car, at kickoff or when they respawn.
+    CarAssigned {
+        player: ActorId,
+        car: ActorId,
+    },
+    CarMoved {
+        car: ActorId,
+        body: RigidBody,
+    },
+    BallMoved {
+        body: RigidBody,
+    },
+    /// A player's car was blown up where it was last seen.
+    Demolition {
+        player: ActorId,
+        car: ActorId,
+        location: Vector3f,
+        /// The player whose car did it, if the replay says and their car is known.
+        attacker: Option<ActorId>,
+    },
+}
+
+/// Follows which actors are players, their cars and the ball through a replay's network frames, and
+/// where the cars and ball are.
+///
+/// `ReplayEngine` feeds it each actor in the order the frame lists them, between handling the
+/// actors it follows itself, like boost pads and the game clock.
+#[derive(Debug, Clone, Default)]
+pub struct ActorTracker {
+    players: HashMap<ActorId, PlayerDetails>,
+    cars: HashMap<ActorId, Option<RigidBody>>,
+    ball: Option<RigidBody>,
+    // Players whose PRI went away, kept so they look the same if they rejoin
+    departed: HashMap<UniqueId, PlayerDetails>,
+
+    // Semi-Stable Actor IDs
+    ball_actor_id: Option<ActorId>,
+    orange_team_actor_id: Option<ActorId>,
+    blue_team_actor_id: Option<ActorId>,
+
+    // Object IDs
+    ball_object_id: Option<ObjectId>,
+    orange_team_object_id: Option<ObjectId>,
+    blue_team_object_id: Option<ObjectId>,
+    car_object_id: Option<ObjectId>,
+    player_object_id: Option<ObjectId>,
+    player_car_object_id: Option<ObjectId>,
+    player_name_object_id: Option<ObjectId>,
+    player_unique_id_object_id: Option<ObjectId>,
+    player_team_object_id: Option<ObjectId>,
+    rigid_body_moved_object_id: Option<ObjectId>,
+}
+
+impl ActorTracker {
+    /// A tracker for a replay whose object table is `objects`, before its first frame.
+    pub fn new(objects: &[String]) -> Self {
+        let mut tracker = Self::default();
+        for (index, object_name) in objects.iter().enumerate() {
+            let id = Some(ObjectId(index as i32));
+            match object_name.as_str() {
+                "Archetypes.Ball.Ball_Default" => tracker.ball_object_id = id,
+                "Archetypes.Teams.Team0" => tracker.blue_team_object_id = id,
+                "Archetypes.Teams.Team1" => tracker.orange_team_object_id = id,
+                "Archetypes.Car.Car_Default" => tracker.car_object_id = id,
+                "TAGame.Default__PRI_TA" => tracker.player_object_id = id,
+                "Engine.Pawn:PlayerReplicationInfo" => tracker.player_car_object_id = id,
+                "Engine.PlayerReplicationInfo:PlayerName" => tracker.player_name_object_id = id,
+                "Engine.PlayerReplicationInfo:UniqueId" => tracker.player_unique_id_object_id = id,
+                "Engine.PlayerReplicationInfo:Team" => tracker.player_team_object_id = id,
+                "TAGame.RBActor_TA:ReplicatedRBState" => tracker.rigid_body_moved_object_id = id,
+                _ => {}
+            }
+        }
+        tracker
+    }
+
+    /// Applies every new, updated and deleted actor in `frame`, in that order.
+    pub fn apply_frame(&mut self, frame: &Frame) -> Vec<ActorEvent> {
+        let new = frame
+            .new_actors
+            .iter()
+            .filter_map(|actor| self.apply_new(actor));
+        let mut events: Vec<_> = new.collect();
+        events.extend(
+            frame
+                .updated_actors
+                .iter()
+                .filter_map(|actor| self.apply_update(actor)),
+        );
+        events.extend(
+            frame
+                .deleted_actors
+                .iter()
+                .filter_map(|actor| self.apply_deleted(*actor)),
+        );
+        events
+    }
+
+    pub fn apply_new(&mut self, actor: &NewActor) -> Option<ActorEvent> {
+        let object_id = Some(actor.object_id);
+        if object_id == self.ball_object_id {
+            self.ball_actor_id = Some(actor.actor_id);
+        } else if object_id == self.car_object_id {
+            self.cars.insert(actor.actor_id, None);
+        } else if object_id == self.orange_team_object_id {
+            self.orange_team_actor_id = Some(actor.actor_id);
+        } else if object_id == self.blue_team_object_id {
+            self.blue_team_actor_id = Some(actor.actor_id);
+        } else if object_id == self.player_object_id && !self.players.contains_key(&actor.actor_id)
+        {
+            self.players.insert(
+                actor.actor_id,
+                PlayerDetails {
+                    platform_id: None,
+                    name: "Unknown".to_string(),
+                    color: PURPLE,
+                    car_actor_id: None,
+                    team: Team::Blue,
+                },
+            );
+            return Some(ActorEvent::PlayerJoined {
+                player: actor.actor_id,
+            });
+        }
+        None
+    }
+
+    pub fn apply_update(&mut self, actor: &UpdatedAttribute) -> Option<ActorEvent> {
+        let player = actor.actor_id;
+        match actor.object_id {
+            // When a player team is set or changed
+            object_id if self.player_team_object_id == Some(object_id) => {
+                let team = match actor.attribute {
+                    Attribute::ActiveActor(team)
+                        if self.orange_team_actor_id == Some(team.actor) =>
+                    {
+                        Team::Orange
+                    }
+                    Attribute::ActiveActor(team) if self.blue_team_actor_id == Some(team.actor) => {
+                        Team::Blue
+                    }
+                    _ => return None,
+                };
+                self.players.get_mut(&player)?.team = team;
+                Some(ActorEvent::TeamChanged { player, team })
+            }
+            // When a player's platform id is set, which tells us if they've been in the game before
+            object_id if self.player_unique_id_object_id == Some(object_id) => {
+                let (Attribute::UniqueId(platform_id), Some(details)) =
+                    (&actor.attribute, self.players.get_mut(&player))
+                else {
+                    return None;
+                };
+                details.platform_id = Some((**platform_id).clone());
+                let departed = self.departed.remove(platform_id)?;
+                details.name = departed.name;
+                details.color = departed.color;
+                Some(ActorEvent::PlayerRejoined { player })
+            }
+            // When a player name is set or changed
+            object_id if self.player_name_object_id == Some(object_id) => {
+                let (Attribute::String(name), Some(details)) =
+                    (&actor.attribute, self.players.get_mut(&player))
+                else {
+                    return None;
+                };
+                details.name = name.clone();
+                Some(ActorEvent::NameChanged {
+                    player,
+                    name: name.clone(),
+                })
+            }
+            // When a player car is set or changed, which is sent by the car
+            object_id if self.player_car_object_id == Some(object_id) => {
+                let Attribute::ActiveActor(owner) = &actor.attribute else {
+                    return None;
+                };
+                self.players.get_mut(&owner.actor)?.car_actor_id = Some(actor.actor_id);
+                Some(ActorEvent::CarAssigned {
+                    player: owner.actor,
+                    car: actor.actor_id,
+                })
+            }
+            // When a car or the ball moves
+            object_id if self.rigid_body_moved_object_id == Some(object_id) => {
+                let Attribute::RigidBody(body) = actor.attribute else {
+                    return None;
+                };
+                if let Some(car) = self.cars.get_mut(&actor.actor_id) {
+                    car.replace(body);
+                    Some(ActorEvent::CarMoved {
+                        car: actor.actor_id,
+                        body,
+                    })
+                } else if self.ball_actor_id == Some(actor.actor_id) {
+                    self.ball = Some(body);
+                    Some(ActorEvent::BallMoved { body })
+                } else {
+                    None
+                }
+            }
+            _ => {
+                let (victim, attacker) = match &actor.attribute {
+                    Attribute::DemolishFx(demo) => {
+                        (demo.victim, demo.attacker_flag.then_some(demo.attacker))
+                    }
+                    Attribute::Demolish(demo) => {
+                        (demo.victim, demo.attacker_flag.then_some(demo.attacker))
+                    }
+                    _ => return None,
+                };
+                // Both demolish attributes can be sent for the same demolition, so only the first
+                // one counts
+                let body = self.cars.remove(&victim)??;
+                let (player, _) = self.car_owner(victim)?;
+                let attacker = attacker
+                    .and_then(|car| self.car_owner(car))
+                    .map(|(attacker, _)| attacker);
+                Some(ActorEvent::Demolition {
+                    player,
+                    car: victim,
+                    location: body.location,
+                    attacker,
+                })
+            }
+        }
+    }
+
+    pub fn apply_deleted(&mut self, actor: ActorId) -> Option<ActorEvent> {
+        // Anything else deleted, like a car that's respawning, just goes away
+        self.cars.remove(&actor);
+
+        let mut details = self.players.remove(&actor)?;
+        if let Some(car) = details.car_actor_id.take() {
+            self.cars.remove(&car);
+        }
+        if let Some(platform_id) = details.platform_id.clone() {
+            self.departed.insert(platform_id, details);
+        }
+        Some(ActorEvent::PlayerLeft { player: actor })
+    }
+
+    pub fn players(&self) -> &HashMap<ActorId, PlayerDetails> {
+        &self.players
+    }
+
+    pub fn player_mut(&mut self, player: ActorId) -> Option<&mut PlayerDetails> {
+        self.players.get_mut(&player)
+    }
+
+    /// Players who left, as they were when they did.
+    pub fn departed(&self) -> impl Iterator<Item = &PlayerDetails> {
+        self.departed.values()
+    }
+
+    /// The player driving `car`.
+    pub fn car_owner(&self, car: ActorId) -> Option<(ActorId, &PlayerDetails)> {
+        self.players
+            .iter()
+            .find(|(_, details)| details.car_actor_id == Some(car))
+            .map(|(player, details)| (*player, details))
+    }
+
+    /// Every car by its actor id, with its rigid body once it's replicated.
+    pub fn cars(&self) -> &HashMap<ActorId, Option<RigidBody>> {
+        &self.cars
+    }
+
+    pub fn ball(&self) -> Option<RigidBody> {
+        self.ball
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boxcars::{ActiveActor, Demolish, Quaternion, RemoteId, StreamId, Trajectory};
+
+    use super::*;
+
+    const OBJECTS: [&str; 9] = [
+        "Archetypes.Ball.Ball_Default",
+        "Archetypes.Teams.Team0",
+        "Archetypes.Teams.Team1",
+        "Archetypes.Car.Car_Default",
+        "TAGame.Default__PRI_TA",
+        "Engine.Pawn:PlayerReplicationInfo",
+        "Engine.PlayerReplicationInfo:PlayerName",
+        "Engine.PlayerReplicationInfo:Team",
+        "TAGame.RBActor_TA:ReplicatedRBState",
+    ];
+    const BALL: ObjectId = ObjectId(0);
+    const BLUE: ObjectId = ObjectId(1);
+    const CAR: ObjectId = ObjectId(3);
+    const PLAYER: ObjectId = ObjectId(4);
+    const PLAYER_CAR: ObjectId = ObjectId(5);
+    const PLAYER_NAME: ObjectId = ObjectId(6);
+    const PLAYER_TEAM: ObjectId = ObjectId(7);
+    const RIGID_BODY: ObjectId = ObjectId(8);
+
+    fn tracker() -> ActorTracker {
+        ActorTracker::new(&OBJECTS.map(String::from))
+    }
+
+    fn new_actor(actor: i32, object_id: ObjectId) -> NewActor {
+        NewActor {
+            actor_id: ActorId(actor),
+            name_id: None,
+            object_id,
+            initial_trajectory: Trajectory {
+                location: None,
+                rotation: None,
+            },
+        }
+    }
+
+    fn update(actor: i32, object_id: ObjectId, attribute: Attribute) -> UpdatedAttribute {
+        UpdatedAttribute {
+            actor_id: ActorId(actor),
+            stream_id: StreamId(0),
+            object_id,
+            attribute,
+        }
+    }
+
+    fn frame(
+        new_actors: Vec<NewActor>,
+        updated_actors: Vec<UpdatedAttribute>,
+        deleted_actors: Vec<ActorId>,
+    ) -> Frame {
+        Frame {
+            time: 0.0,
+            delta: 1.0 / 30.0,
+            new_actors,
+            deleted_actors,
+            updated_actors,
+        }
+    }
+
+    fn active(actor: i32) -> Attribute {
+        Attribute::ActiveActor(ActiveActor {
+            active: true,
+            actor: ActorId(actor),
+        })
+    }
+
+    fn body(x: f32, y: f32) -> RigidBody {
+        RigidBody {
+            sleeping: false,
+            location: Vector3f { x, y, z: 17.0 },
+            rotation: Quaternion {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+                w: 1.0,
+            },
+            linear_velocity: None,
+            angular_velocity: None,
+        }
+    }
+
+    /// A tracker with a blue team, actor 1, and a player, actor 2, named `name` and driving car
+    /// actor 3.
+    fn with_player(name: &str) -> ActorTracker {
+        let mut tracker = tracker();
+        tracker.apply_frame(&frame(
+            vec![new_actor(1, BLUE), new_actor(2, PLAYER), new_actor(3, CAR)],
+            vec![
+                update(2, PLAYER_NAME, Attribute::String(name.to_string())),
+                update(2, PLAYER_TEAM, active(1)),
+                update(3, PLAYER_CAR, active(2)),
+            ],
+            vec![],
+        ));
+        tracker
+    }
+
+    #[test]
+    fn player_joins_and_picks_a_team_and_car() {
+        let mut tracker = tracker();
+        let events = tracker.apply_frame(&frame(
+            vec![new_actor(1, BLUE), new_actor(2, PLAYER), new_actor(3, CAR)],
+            vec![
+                update(2, PLAYER_NAME, Attribute::String("popo".to_string())),
+                update(2, PLAYER_TEAM, active(1)),
+                update(3, PLAYER_CAR, active(2)),
+            ],
+            vec![],
+        ));
+        assert_eq!(
+            events,
+            [
+                ActorEvent::PlayerJoined { player: ActorId(2) },
+                ActorEvent::NameChanged {
+                    player: ActorId(2),
+                    name: "popo".to_string()
+                },
+                ActorEvent::TeamChanged {
+                    player: ActorId(2),
+                    team: Team::Blue
+                },
+                ActorEvent::CarAssigned {
+                    player: ActorId(2),
+                    car: ActorId(3)
+                },
+            ]
+        );
+
+        let details = &tracker.players()[&ActorId(2)];
+        assert_eq!(details.name, "popo");
+        assert_eq!(details.team, Team::Blue);
+        assert_eq!(details.car_actor_id, Some(ActorId(3)));
+        // Colors are left for the engine to pick
+        assert_eq!(details.color, PURPLE);
+    }
+
+    #[test]
+    fn updates_for_unknown_actors_are_ignored() {
+        let mut tracker = tracker();
+        let events = tracker.apply_frame(&frame(
+            vec![],
+            vec![
+                update(2, PLAYER_NAME, Attribute::String("popo".to_string())),
+                update(3, PLAYER_CAR, active(2)),
+                update(4, RIGID_BODY, Attribute::RigidBody(body(0.0, 0.0))),
+            ],
+            vec![],
+        ));
+        assert!(events.is_empty());
+        assert!(tracker.players().is_empty());
+        assert!(tracker.cars().is_empty());
+    }
+
+    #[test]
+    fn cars_and_ball_move() {
+        let mut tracker = with_player("popo");
+        let events = tracker.apply_frame(&frame(
+            vec![new_actor(4, BALL)],
+            vec![
+                update(3, RIGID_BODY, Attribute::RigidBody(body(100.0, -200.0))),
+                update(4, RIGID_BODY, Attribute::RigidBody(body(0.0, 50.0))),
+            ],
+            vec![],
+        ));
+        assert_eq!(
+            events,
+            [
+                ActorEvent::CarMoved {
+                    car: ActorId(3),
+                    body: body(100.0, -200.0)
+                },
+                ActorEvent::BallMoved {
+                    body: body(0.0, 50.0)
+                },
+            ]
+        );
+        assert_eq!(tracker.cars()[&ActorId(3)], Some(body(100.0, -200.0)));
+        assert_eq!(tracker.ball(), Some(body(0.0, 50.0)));
+        assert_eq!(
+            tracker.car_owner(ActorId(3)).map(|(player, _)| player),
+            Some(ActorId(2))
+        );
+    }
+
+    #[test]
+    fn demolition_is_only_counted_once() {
+        let mut tracker = with_player("popo");
+        tracker.apply_frame(&frame(
+            vec![],
+            vec![update(
+                3,
+                RIGID_BODY,
+                Attribute::RigidBody(body(10.0, 20.0)),
+            )],
+            vec![],
+        ));
+
+        let demolish = Attribute::Demolish(Box::new(Demolish {
+            attacker_flag: true,
+            attacker: ActorId(9),
+            victim_flag: true,
+            victim: ActorId(3),
+            attack_velocity: Vector3f {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            victim_velocity: Vector3f {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        }));
+        let events = tracker.apply_frame(&frame(
+            vec![],
+            vec![
+                update(9, ObjectId(99), demolish.clone()),
+                update(9, ObjectId(99), demolish),
+            ],
+            vec![],
+        ));
+        assert_eq!(
+            events,
+            [ActorEvent::Demolition {
+                player: ActorId(2),
+                car: ActorId(3),
+                location: Vector3f {
+                    x: 10.0,
+                    y: 20.0,
+                    z: 17.0
+                },
+                attacker: None,
+            }]
+        );
+        assert!(!tracker.cars().contains_key(&ActorId(3)));
+    }
+
+    #[test]
+    fn leaving_player_takes_their_car() {
+        let mut tracker = with_player("popo");
+        let events = tracker.apply_frame(&frame(vec![], vec![], vec![ActorId(2)]));
+        assert_eq!(events, [ActorEvent::PlayerLeft { player: ActorId(2) }]);
+        assert!(tracker.players().is_empty());
+        assert!(tracker.cars().is_empty());
+    }
+
+    #[test]
+    fn rejoining_player_looks_the_same() {
+        let objects: Vec<_> = OBJECTS
+            .iter()
+            .map(|name| name.to_string())
+            .chain(["Engine.PlayerReplicationInfo:UniqueId".to_string()])
+            .collect();
+        let unique_id_object = ObjectId(OBJECTS.len() as i32);
+        let mut tracker = ActorTracker::new(&objects);
+        let platform_id = Attribute::UniqueId(Box::new(UniqueId {
+            system_id: 1,
+            remote_id: RemoteId::Steam(76561198000000000),
+            local_id: 0,
+        }));
+
+        tracker.apply_frame(&frame(
+            vec![new_actor(2, PLAYER)],
+            vec![
+                update(2, PLAYER_NAME, Attribute::String("popo".to_string())),
+                update(2, unique_id_object, platform_id.clone()),
+            ],
+            vec![],
+        ));
+        tracker
+            .player_mut(ActorId(2))
+            .expect("the player joined")
+            .color = [1.0, 0.5, 0.0, 1.0];
+        tracker.apply_frame(&frame(vec![], vec![], vec![ActorId(2)]));
+        assert_eq!(tracker.departed().count(), 1);
+
+        let events = tracker.apply_frame(&frame(
+            vec![new_actor(5, PLAYER)],
+            vec![update(5, unique_id_object, platform_id)],
+            vec![],
+        ));
+        assert_eq!(
+            events,
+            [
+                ActorEvent::PlayerJoined { player: ActorId(5) },
+                ActorEvent::PlayerRejoined { player: ActorId(5) }
+            ]
+        );
+        let details = &tracker.players()[&ActorId(5)];
+        assert_eq!(details.name, "popo");
+        assert_eq!(details.color, [1.0, 0.5, 0.0, 1.0]);
+        assert_eq!(tracker.departed().count(), 0);
+    }
+}
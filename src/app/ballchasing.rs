@@ -0,0 +1,68 @@
+use std::env;
+use std::error;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::create;
+
+/// Environment variable holding the ballchasing.com API token used to download replays.
+const TOKEN_VAR: &str = "BALLCHASING_TOKEN";
+
+/// Pulls the replay id out of a ballchasing.com replay URL, or returns `value` if it's already an
+/// id. Ids are UUIDs, so anything else is rejected rather than used as a file name.
+fn replay_id(value: &str) -> Result<&str, String> {
+    let id = match value.split_once("/replay/") {
+        Some((_, rest)) => rest.split(['/', '?', '#']).next().unwrap_or_default(),
+        None => value,
+    };
+
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+        return Err(format!(
+            "`{value}` is not a ballchasing.com replay id or URL, expected something like https://ballchasing.com/replay/<id>"
+        ));
+    }
+    Ok(id)
+}
+
+/// Downloads a replay from ballchasing.com, or reuses an earlier download, returning where it was
+/// saved. Replays are cached under the user's cache directory by id.
+pub fn fetch(id_or_url: &str) -> Result<PathBuf, Box<dyn error::Error>> {
+    let id = replay_id(id_or_url)?;
+    let cache = dirs::cache_dir()
+        .ok_or("couldn't find a cache directory to download the replay into")?
+        .join("rl-replay-zone-visualizer")
+        .join("ballchasing");
+    let path = cache.join(format!("{id}.replay"));
+    if path.exists() {
+        return Ok(path);
+    }
+
+    let token = env::var(TOKEN_VAR).map_err(|_| {
+        format!("set {TOKEN_VAR} to your ballchasing.com API token, from https://ballchasing.com/upload")
+    })?;
+
+    eprintln!("downloading replay {id} from ballchasing.com");
+    let response = match ureq::get(&format!("https://ballchasing.com/api/replays/{id}/file"))
+        .set("Authorization", &token)
+        .call()
+    {
+        Ok(response) => response,
+        Err(ureq::Error::Status(401, _)) => {
+            return Err(format!("ballchasing.com rejected the token in {TOKEN_VAR}").into())
+        }
+        Err(ureq::Error::Status(404, _)) => {
+            return Err(format!("ballchasing.com has no replay with id {id}").into())
+        }
+        Err(e) => return Err(format!("failed to download replay {id}: {e}").into()),
+    };
+
+    // Download next to the cache entry and move it in place once complete, so an interrupted
+    // download isn't mistaken for a cached replay next time
+    fs::create_dir_all(&cache)?;
+    let partial = cache.join(format!("{id}.replay.part"));
+    io::copy(&mut response.into_reader(), &mut create(&partial)?)?;
+    fs::rename(&partial, &path)?;
+
+    Ok(path)
+}
@@ -0,0 +1,277 @@
+use std::error;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use crate::app::canvas::save_png;
+use crate::app::export::render_canvas;
+use crate::app::render::RenderState;
+use crate::app::shot_chart;
+use crate::app::DisplayType;
+use crate::arena::Arena;
+use crate::shots::{self, Shot};
+use crate::{create, parse_replay, ReplayEngine, Team, TeamColors};
+
+/// Totals for one replay, written as a row of `summary.csv`.
+#[derive(Debug, Default, Clone)]
+struct ReplaySummary {
+    name: String,
+    frames: usize,
+    seconds: f32,
+    orange_goals: usize,
+    blue_goals: usize,
+    /// Sum of each team's zone control over every frame that had one, and how many frames did.
+    orange_control: f64,
+    blue_control: f64,
+    control_frames: usize,
+}
+
+impl ReplaySummary {
+    fn add(&mut self, other: &ReplaySummary) {
+        self.frames += other.frames;
+        self.seconds += other.seconds;
+        self.orange_goals += other.orange_goals;
+        self.blue_goals += other.blue_goals;
+        self.orange_control += other.orange_control;
+        self.blue_control += other.blue_control;
+        self.control_frames += other.control_frames;
+    }
+
+    fn write_row(&self, out: &mut impl Write) -> Result<(), Box<dyn error::Error>> {
+        let average = |sum: f64| {
+            if self.control_frames == 0 {
+                String::new()
+            } else {
+                format!("{:.4}", sum / self.control_frames as f64)
+            }
+        };
+        writeln!(
+            out,
+            "{},{},{:.2},{},{},{},{}",
+            csv_field(&self.name),
+            self.frames,
+            self.seconds,
+            self.orange_goals,
+            self.blue_goals,
+            average(self.orange_control),
+            average(self.blue_control),
+        )?;
+        Ok(())
+    }
+}
+
+/// Quotes a CSV field if it contains anything that would break the row apart.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writes how many big and small boost pads each player has picked up so far, how much boost they
+/// collected and used, and how long they spent empty and full, as CSV rows.
+pub fn write_boost_stats(engine: &ReplayEngine, out: &mut impl Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "player,team,big_pads,small_pads,collected,used,seconds_empty,seconds_full"
+    )?;
+    for (player, team, boost) in engine.boost_stats().players() {
+        writeln!(
+            out,
+            "{},{team:?},{},{},{:.1},{:.1},{:.2},{:.2}",
+            csv_field(player),
+            boost.big_pads,
+            boost.small_pads,
+            boost.collected,
+            boost.used,
+            boost.seconds_empty,
+            boost.seconds_full,
+        )?;
+    }
+    Ok(())
+}
+
+/// Every `.replay` file in `dir`, sorted by name.
+pub fn replay_paths(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn error::Error>> {
+    let mut paths: Vec<_> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "replay"));
+    paths.sort();
+    if paths.is_empty() {
+        return Err(format!("no .replay files in {}", dir.display()).into());
+    }
+    Ok(paths)
+}
+
+/// Processes every `.replay` file in `dir`, writing into `out`:
+///
+/// - `<replay>.csv` with every player's position, boost and third of the field, each team's zone
+///   control, and the score and game phase, on every frame
+/// - `<replay>.thirds.csv` with how much of the live play each player spent in each third
+/// - `<replay>.boost.csv` with how many big and small boost pads each player picked up
+/// - `<replay>.heatmap.png` with the zone control heatmap over the whole replay
+/// - `<replay>.shots.csv` with where every shot was taken from and every save was made
+/// - `summary.csv` with one row per replay and a final `all` row covering every replay
+/// - `shot-chart.png` with every replay's shots and saves marked on one field
+///
+/// Replays that fail to parse are reported and skipped so one bad file doesn't stop the batch. With
+/// `skip_goal_replays`, the frames between each goal and the next kickoff are left out of all of them.
+pub fn run_batch(
+    dir: &Path,
+    out: &Path,
+    skip_goal_replays: bool,
+) -> Result<(), Box<dyn error::Error>> {
+    let paths = replay_paths(dir)?;
+    fs::create_dir_all(out)?;
+    // Replays are processed across every thread in rayon's pool, then summed up in name order
+    let results: Vec<_> = paths
+        .par_iter()
+        .map(|path| {
+            let name = path
+                .file_stem()
+                .expect("files matched by extension have a stem")
+                .to_string_lossy()
+                .into_owned();
+            eprintln!("processing {}", path.display());
+            // Boxed errors can't be sent between threads, so only their messages come back
+            process_replay(path, &name, out, skip_goal_replays).map_err(|e| e.to_string())
+        })
+        .collect();
+    let mut summaries = vec![];
+    let mut all_shots = vec![];
+    for (path, result) in paths.iter().zip(results) {
+        match result {
+            Ok((summary, mut shots)) => {
+                summaries.push(summary);
+                all_shots.append(&mut shots);
+            }
+            Err(e) => eprintln!("skipping {}: {e}", path.display()),
+        }
+    }
+
+    let mut all = ReplaySummary {
+        name: "all".to_string(),
+        ..Default::default()
+    };
+    let mut summary_file = BufWriter::new(create(&out.join("summary.csv"))?);
+    writeln!(
+        summary_file,
+        "replay,frames,seconds,orange_goals,blue_goals,orange_control,blue_control"
+    )?;
+    for summary in &summaries {
+        summary.write_row(&mut summary_file)?;
+        all.add(summary);
+    }
+    all.write_row(&mut summary_file)?;
+    summary_file.flush()?;
+
+    // Replays can be on any map, so they're all marked on a standard field in the default colors
+    let chart = shot_chart::render_shot_chart(&Arena::STANDARD, &TeamColors::default(), &all_shots);
+    save_png(&chart.into_image(), &out.join("shot-chart.png"))?;
+
+    eprintln!(
+        "processed {} of {} replays into {}",
+        summaries.len(),
+        paths.len(),
+        out.display()
+    );
+    Ok(())
+}
+
+fn process_replay(
+    path: &Path,
+    name: &str,
+    out: &Path,
+    skip_goal_replays: bool,
+) -> Result<(ReplaySummary, Vec<Shot>), Box<dyn error::Error>> {
+    let mut engine = ReplayEngine::new(parse_replay(path)?);
+    engine.skip_goal_replays(skip_goal_replays);
+    let mut state = RenderState::new(DisplayType::HEATMAP, engine.arena());
+    let mut summary = ReplaySummary {
+        name: name.to_string(),
+        ..Default::default()
+    };
+    for goal in engine.goals() {
+        match goal.team {
+            Team::Orange => summary.orange_goals += 1,
+            Team::Blue => summary.blue_goals += 1,
+        }
+    }
+
+    let mut csv = BufWriter::new(create(&out.join(format!("{name}.csv")))?);
+    writeln!(
+        csv,
+        "frame,time,player,team,x,y,z,boost,orange_control,blue_control,orange_score,blue_score,phase,third"
+    )?;
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+        state.observe(&engine);
+
+        let frame = engine.summary();
+        let (orange, blue) = match frame.zone_control {
+            Some(control) => {
+                summary.orange_control += control.orange;
+                summary.blue_control += control.blue;
+                summary.control_frames += 1;
+                (
+                    format!("{:.4}", control.orange),
+                    format!("{:.4}", control.blue),
+                )
+            }
+            None => Default::default(),
+        };
+        for player in &frame.players {
+            writeln!(
+                csv,
+                "{},{},{},{:?},{},{},{},{},{orange},{blue},{},{},{:?},{:?}",
+                frame.frame,
+                frame.time,
+                csv_field(&player.name),
+                player.team,
+                player.location.x,
+                player.location.y,
+                player.location.z,
+                player
+                    .boost
+                    .map(|boost| boost.to_string())
+                    .unwrap_or_default(),
+                frame.score.orange,
+                frame.score.blue,
+                frame.phase,
+                player.third,
+            )?;
+        }
+    }
+    csv.flush()?;
+    summary.frames = engine.frame_count();
+    summary.seconds = engine.elapsed();
+
+    let mut thirds = BufWriter::new(create(&out.join(format!("{name}.thirds.csv")))?);
+    writeln!(thirds, "player,team,defensive,middle,offensive")?;
+    for (player, team, [defensive, middle, offensive]) in engine.occupancy().shares() {
+        writeln!(
+            thirds,
+            "{},{team:?},{defensive:.3},{middle:.3},{offensive:.3}",
+            csv_field(player),
+        )?;
+    }
+    thirds.flush()?;
+
+    let mut boost = BufWriter::new(create(&out.join(format!("{name}.boost.csv")))?);
+    write_boost_stats(&engine, &mut boost)?;
+    boost.flush()?;
+
+    let shots = shots::shots(&engine);
+    let mut shot_csv = BufWriter::new(create(&out.join(format!("{name}.shots.csv")))?);
+    shot_chart::write_shots(&shots, &mut shot_csv)?;
+    shot_csv.flush()?;
+
+    let heatmap = render_canvas(&engine, &state).into_image();
+    save_png(&heatmap, &out.join(format!("{name}.heatmap.png")))?;
+
+    Ok((summary, shots))
+}
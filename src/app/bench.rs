@@ -0,0 +1,44 @@
+use std::error;
+use std::time::Instant;
+
+use crate::timeline::Timeline;
+use crate::{zones, ReplayEngine};
+
+/// Times stepping through every network frame, doing the same while building the voronoi diagram
+/// for zone control, and preprocessing into a timeline, printing how many frames a second each
+/// gets through. A quicker check than `cargo bench` for whether a change made a replay slower.
+pub fn run_bench(engine: &ReplayEngine) -> Result<(), Box<dyn error::Error>> {
+    let frames = engine.frame_count() - engine.frame_index();
+    if frames == 0 {
+        return Err("the replay has no network frames to time".into());
+    }
+    println!("{frames} network frames");
+
+    let report = |name: &str, start: Instant| {
+        let seconds = start.elapsed().as_secs_f64();
+        println!(
+            "{name:<14}{seconds:>8.3}s {:>10.0} frames/s",
+            frames as f64 / seconds
+        );
+    };
+
+    let start = Instant::now();
+    let mut stepped = engine.clone();
+    while stepped.frame_index() < stepped.frame_count() {
+        stepped.step();
+    }
+    report("step", start);
+
+    let start = Instant::now();
+    let mut stepped = engine.clone();
+    while stepped.frame_index() < stepped.frame_count() {
+        stepped.step();
+        zones::zone_control(&stepped);
+    }
+    report("zone control", start);
+
+    let start = Instant::now();
+    Timeline::build(engine);
+    report("preprocess", start);
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use std::error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{write, VisError};
+
+/// Moments marked in a replay, by network frame, kept in a file next to the replay so they're
+/// still there the next time it's opened.
+#[derive(Debug)]
+pub struct Bookmarks {
+    path: PathBuf,
+    /// Always sorted.
+    frames: Vec<usize>,
+}
+
+impl Bookmarks {
+    /// Reads the bookmarks saved for the replay at `replay`, which is none if it's never had any.
+    pub fn load(replay: &Path) -> Result<Self, Box<dyn error::Error>> {
+        let path = replay.with_extension("bookmarks.json");
+        let frames: Vec<usize> = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| format!("couldn't read {}: {e}", path.display()))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(format!("couldn't read {}: {e}", path.display()).into()),
+        };
+
+        let mut bookmarks = Self { path, frames };
+        bookmarks.frames.sort_unstable();
+        bookmarks.frames.dedup();
+        Ok(bookmarks)
+    }
+
+    pub fn frames(&self) -> &[usize] {
+        &self.frames
+    }
+
+    /// Bookmarks `frame`, or takes its bookmark away if it already has one, and saves the change.
+    pub fn toggle(&mut self, frame: usize) -> Result<(), VisError> {
+        match self.frames.binary_search(&frame) {
+            Ok(index) => {
+                self.frames.remove(index);
+            }
+            Err(index) => self.frames.insert(index, frame),
+        }
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), VisError> {
+        // Don't leave an empty file behind once the last bookmark is gone
+        if self.frames.is_empty() {
+            return match fs::remove_file(&self.path) {
+                Err(source) if source.kind() != io::ErrorKind::NotFound => Err(VisError::Write {
+                    path: self.path.clone(),
+                    source,
+                }),
+                _ => Ok(()),
+            };
+        }
+        write(&self.path, serde_json::to_string_pretty(&self.frames)?)
+    }
+}
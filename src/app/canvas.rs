@@ -0,0 +1,163 @@
+use std::error;
+use std::io::Cursor;
+use std::path::Path;
+
+use graphics::types::Color;
+use graphics::{DrawState, Graphics, ImageSize};
+use image::{ImageFormat, RgbaImage};
+
+use crate::write;
+
+/// A software `Graphics` backend that rasterizes into an RGBA pixel buffer, so frames can be
+/// rendered without a window or a GPU.
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// Textures aren't supported on the canvas; anything textured is skipped.
+pub struct CanvasTexture;
+
+impl ImageSize for CanvasTexture {
+    fn get_size(&self) -> (u32, u32) {
+        (0, 0)
+    }
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    /// Raw pixels, row-major RGBA with 8 bits per channel.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn into_image(self) -> RgbaImage {
+        RgbaImage::from_raw(self.width, self.height, self.pixels)
+            .expect("canvas buffer always matches its dimensions")
+    }
+
+    /// Draws `color` over the pixel, keeping whatever shows through of a see-through background.
+    fn blend(&mut self, x: u32, y: u32, color: &Color) {
+        let index = ((y * self.width + x) * 4) as usize;
+        let alpha = color[3].clamp(0.0, 1.0);
+        let below = self.pixels[index + 3] as f32 / 255.0;
+        let covered = alpha + below * (1.0 - alpha);
+        if covered == 0.0 {
+            return;
+        }
+        for (pixel, channel) in self.pixels[index..index + 3].iter_mut().zip(color) {
+            let existing = *pixel as f32 / 255.0;
+            let blended = (channel * alpha + existing * below * (1.0 - alpha)) / covered;
+            *pixel = (blended.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+        self.pixels[index + 3] = (covered * 255.0).round() as u8;
+    }
+
+    /// Fills a triangle given in normalized device coordinates, testing each pixel's center.
+    fn fill_triangle(&mut self, triangle: &[[f32; 2]], color: &Color) {
+        let to_pixels = |v: [f32; 2]| {
+            [
+                (v[0] + 1.0) / 2.0 * self.width as f32,
+                (1.0 - v[1]) / 2.0 * self.height as f32,
+            ]
+        };
+        let [a, b, c] = [
+            to_pixels(triangle[0]),
+            to_pixels(triangle[1]),
+            to_pixels(triangle[2]),
+        ];
+
+        let edge = |p: [f32; 2], q: [f32; 2], r: [f32; 2]| {
+            (q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0])
+        };
+        let area = edge(a, b, c);
+        if area == 0.0 {
+            return;
+        }
+
+        let min_x = a[0].min(b[0]).min(c[0]).floor().max(0.0) as u32;
+        let min_y = a[1].min(b[1]).min(c[1]).floor().max(0.0) as u32;
+        let max_x = (a[0].max(b[0]).max(c[0]).ceil() as u32).min(self.width);
+        let max_y = (a[1].max(b[1]).max(c[1]).ceil() as u32).min(self.height);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let p = [x as f32 + 0.5, y as f32 + 0.5];
+                let w0 = edge(b, c, p) / area;
+                let w1 = edge(c, a, p) / area;
+                let w2 = edge(a, b, p) / area;
+                if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
+                    self.blend(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+impl Graphics for Canvas {
+    type Texture = CanvasTexture;
+
+    fn clear_color(&mut self, color: Color) {
+        let rgba = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&rgba);
+        }
+    }
+
+    fn clear_stencil(&mut self, _value: u8) {}
+
+    fn tri_list<F>(&mut self, _draw_state: &DrawState, color: &[f32; 4], mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+    {
+        f(&mut |vertices| {
+            for triangle in vertices.chunks_exact(3) {
+                self.fill_triangle(triangle, color);
+            }
+        });
+    }
+
+    fn tri_list_c<F>(&mut self, _draw_state: &DrawState, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 4]])),
+    {
+        f(&mut |vertices, colors| {
+            for (triangle, colors) in vertices.chunks_exact(3).zip(colors.chunks_exact(3)) {
+                self.fill_triangle(triangle, &colors[0]);
+            }
+        });
+    }
+
+    fn tri_list_uv<F>(
+        &mut self,
+        _draw_state: &DrawState,
+        _color: &[f32; 4],
+        _texture: &CanvasTexture,
+        _f: F,
+    ) where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]])),
+    {
+    }
+
+    fn tri_list_uv_c<F>(&mut self, _draw_state: &DrawState, _texture: &CanvasTexture, _f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]])),
+    {
+    }
+}
+
+/// Writes `image` to `path` as a PNG, saying which file if it can't be written.
+pub fn save_png(image: &RgbaImage, path: &Path) -> Result<(), Box<dyn error::Error>> {
+    let mut png = Cursor::new(vec![]);
+    image.write_to(&mut png, ImageFormat::Png)?;
+    write(path, png.into_inner())?;
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use std::error;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use graphics::Context;
+
+use crate::app::canvas::{save_png, Canvas};
+use crate::app::render::BACKGROUND;
+use crate::zones;
+use crate::{create, GamePhase, ReplayEngine, Team};
+
+const CHART_WIDTH: u32 = 800;
+const CHART_HEIGHT: u32 = 200;
+/// Seconds of play each point on the chart is averaged over, to smooth out the ball bouncing back
+/// and forth.
+const SMOOTHING_SECONDS: f32 = 10.0;
+const TICK_HEIGHT: f64 = 6.0;
+
+/// A filled rectangle in chart pixels, as `[x, y, width, height]`, which is all the chart is made
+/// of so it comes out the same as a PNG or an SVG.
+type Mark = ([f32; 4], [f64; 4]);
+
+/// Walks the replay and lays out a chart of which team was pressing when, with orange pressing
+/// upwards and blue downwards. Frames outside of live play are left out.
+fn pressure_marks(mut engine: ReplayEngine) -> Vec<Mark> {
+    let colors = *engine.team_colors();
+    let mut samples = vec![];
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+        let live = matches!(engine.phase(), GamePhase::Regulation | GamePhase::Overtime);
+        let pressure = zones::field_tilt(&engine)
+            .filter(|_| live)
+            .map(|tilt| tilt.pressure());
+        samples.push((engine.frame_index() - 1, engine.elapsed(), pressure));
+    }
+
+    let duration = engine.elapsed().max(f32::EPSILON);
+    let column_of = |elapsed: f32| {
+        ((elapsed / duration * CHART_WIDTH as f32) as usize).min(CHART_WIDTH as usize - 1)
+    };
+
+    let mut sums = vec![(0.0, 0usize); CHART_WIDTH as usize];
+    for (_, elapsed, pressure) in &samples {
+        if let Some(pressure) = pressure {
+            let column = &mut sums[column_of(*elapsed)];
+            column.0 += pressure;
+            column.1 += 1;
+        }
+    }
+
+    let half_window = (SMOOTHING_SECONDS / duration * CHART_WIDTH as f32 / 2.0) as usize;
+    let middle = CHART_HEIGHT as f64 / 2.0;
+    let mut marks = vec![(
+        BACKGROUND,
+        [0.0, 0.0, CHART_WIDTH as f64, CHART_HEIGHT as f64],
+    )];
+    for column in 0..sums.len() {
+        let window =
+            &sums[column.saturating_sub(half_window)..(column + half_window + 1).min(sums.len())];
+        let (sum, count) = window.iter().fold((0.0, 0), |(sum, count), column| {
+            (sum + column.0, count + column.1)
+        });
+        if count == 0 {
+            continue;
+        }
+        let pressure = sum / count as f64;
+        let height = pressure.abs() * middle;
+        let (color, top) = if pressure > 0.0 {
+            (colors.team(Team::Orange), middle - height)
+        } else {
+            (colors.team(Team::Blue), middle)
+        };
+        marks.push((color, [column as f64, top, 1.0, height]));
+    }
+
+    marks.push((
+        [1.0, 1.0, 1.0, 0.6],
+        [0.0, middle - 0.5, CHART_WIDTH as f64, 1.0],
+    ));
+    for minute in 1..=(duration / 60.0) as usize {
+        let x = column_of(minute as f32 * 60.0) as f64;
+        marks.push((
+            [1.0, 1.0, 1.0, 0.6],
+            [x, CHART_HEIGHT as f64 - TICK_HEIGHT, 1.0, TICK_HEIGHT],
+        ));
+    }
+    for goal in engine.goals() {
+        // Goals during a skipped goal replay land on the next frame that was processed
+        let Some((_, elapsed, _)) = samples.iter().find(|(frame, ..)| *frame >= goal.frame) else {
+            continue;
+        };
+        let color = colors.team(goal.team);
+        let x = column_of(*elapsed) as f64;
+        marks.push((
+            [1.0, 1.0, 1.0, 1.0],
+            [x - 1.5, 0.0, 3.0, CHART_HEIGHT as f64],
+        ));
+        marks.push((color, [x - 0.5, 0.0, 1.0, CHART_HEIGHT as f64]));
+    }
+    marks
+}
+
+fn write_png(marks: &[Mark], path: &Path) -> Result<(), Box<dyn error::Error>> {
+    let mut canvas = Canvas::new(CHART_WIDTH, CHART_HEIGHT);
+    let c = Context::new_abs(CHART_WIDTH as f64, CHART_HEIGHT as f64);
+    for (color, rect) in marks {
+        graphics::rectangle(*color, *rect, c.transform, &mut canvas);
+    }
+    save_png(&canvas.into_image(), path)
+}
+
+fn write_svg(marks: &[Mark], path: &Path) -> Result<(), Box<dyn error::Error>> {
+    let mut out = BufWriter::new(create(path)?);
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{CHART_WIDTH}" height="{CHART_HEIGHT}">"#
+    )?;
+    for ([r, g, b, a], [x, y, width, height]) in marks {
+        let channel = |value: &f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        writeln!(
+            out,
+            r#"<rect x="{x}" y="{y}" width="{width}" height="{height}" fill="rgb({},{},{})" fill-opacity="{a}"/>"#,
+            channel(r),
+            channel(g),
+            channel(b),
+        )?;
+    }
+    writeln!(out, "</svg>")?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Writes a chart of which team was pressing over the course of the replay, going by where the
+/// ball was and how much of the field each team controlled, with a line at each goal. Paths
+/// ending in `.svg` get an SVG and anything else a PNG.
+pub fn write_pressure_chart(
+    engine: ReplayEngine,
+    path: &Path,
+) -> Result<(), Box<dyn error::Error>> {
+    let marks = pressure_marks(engine);
+    let svg = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+    if svg {
+        write_svg(&marks, path)
+    } else {
+        write_png(&marks, path)
+    }
+}
@@ -0,0 +1,108 @@
+use crate::events;
+use crate::{Goal, ReplayEngine};
+
+use crate::app::render::RenderState;
+
+/// A second replay played along with the one in the window, kept in step with it so the two games
+/// can be compared moment by moment.
+pub struct Comparison {
+    pub engine: ReplayEngine,
+    pub state: RenderState,
+    pub goals: Vec<Goal>,
+    /// Whether it's drawn over the first replay's field rather than beside it.
+    pub overlay: bool,
+    /// The one player drawn over the first replay's field, when ghosting a player.
+    pub ghost: Option<String>,
+    /// The frames each replay's kickoffs started on, when lining the replays up by kickoffs
+    /// instead of the game clock.
+    kickoffs: Option<(Vec<usize>, Vec<usize>)>,
+}
+
+impl Comparison {
+    pub fn new(engine: ReplayEngine, state: RenderState, overlay: bool) -> Self {
+        Self {
+            goals: engine.goals(),
+            engine,
+            state,
+            overlay,
+            ghost: None,
+            kickoffs: None,
+        }
+    }
+
+    /// Draws just `player` over `primary`'s field, lined up by kickoffs so each of `primary`'s
+    /// kickoffs plays along with the same kickoff of this replay.
+    pub fn with_ghost(mut self, primary: &ReplayEngine, player: String) -> Self {
+        self.overlay = true;
+        self.ghost = Some(player);
+        self.kickoffs = Some((events::kickoffs(primary), events::kickoffs(&self.engine)));
+        self
+    }
+
+    /// Catches up with `primary` after it's stepped or seeked.
+    pub fn follow(&mut self, primary: &ReplayEngine) {
+        match &self.kickoffs {
+            Some(_) => self.follow_kickoffs(primary),
+            None => self.follow_clock(primary),
+        }
+    }
+
+    /// Keeps the same number of frames since the latest kickoff as `primary`, or since the start
+    /// before the first one. Past this replay's last kickoff it stays where it is.
+    fn follow_kickoffs(&mut self, primary: &ReplayEngine) {
+        let Some((ours, theirs)) = &self.kickoffs else {
+            return;
+        };
+        let frame = primary.frame_index();
+        let target = match ours.partition_point(|&kickoff| kickoff < frame) {
+            0 => frame,
+            started => match theirs.get(started - 1) {
+                Some(theirs) => theirs + (frame - ours[started - 1]),
+                None => return,
+            },
+        };
+
+        let target = target.min(self.engine.frame_count());
+        if target == self.engine.frame_index() + 1 {
+            self.step();
+        } else if target != self.engine.frame_index() {
+            self.engine.seek(target);
+            self.state.observe(&self.engine);
+        }
+    }
+
+    /// Keeps the same game clock as `primary`. Both play frame for frame while their clocks are
+    /// within a second of each other, and this one waits while it's ahead, like when `primary` is
+    /// showing a goal replay. Anything further apart, like after seeking, jumps straight to
+    /// `primary`'s clock. Replays without a clock just play frame for frame.
+    fn follow_clock(&mut self, primary: &ReplayEngine) {
+        let target = primary.clock_at(primary.frame_index().saturating_sub(1));
+        let clock = self
+            .engine
+            .clock_at(self.engine.frame_index().saturating_sub(1));
+        let (Some(target), Some(clock)) = (target, clock) else {
+            self.step();
+            return;
+        };
+
+        // The clock counts down, so a lower clock is further into the game
+        match clock - target {
+            -1 => {}
+            0 | 1 => self.step(),
+            _ => {
+                // A clock time this replay never shows, like past its end, leaves it where it is
+                if let Some(frame) = self.engine.clock_frame(target as f32) {
+                    self.engine.seek(frame + 1);
+                    self.state.observe(&self.engine);
+                }
+            }
+        }
+    }
+
+    fn step(&mut self) {
+        if self.engine.frame_index() < self.engine.frame_count() {
+            self.engine.step();
+            self.state.observe(&self.engine);
+        }
+    }
+}
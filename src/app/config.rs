@@ -0,0 +1,305 @@
+use std::error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use piston::Key;
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+
+use crate::app::render::Theme;
+use crate::app::{DisplayType, TeamColorPreset};
+use crate::rotation::Rule;
+
+/// Settings read from a TOML file, used for anything not given on the command line.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// How fast to play replays, as a multiple of real time.
+    #[serde(deserialize_with = "some_speed")]
+    pub speed: Option<f64>,
+    /// Network frames to process per second, as another way to give the speed.
+    pub ups: Option<u64>,
+    #[serde(deserialize_with = "value_enum")]
+    pub display: Option<DisplayType>,
+    #[serde(deserialize_with = "value_enum")]
+    pub team_colors: Option<TeamColorPreset>,
+    #[serde(deserialize_with = "some_hex_color")]
+    pub orange_color: Option<[f32; 4]>,
+    #[serde(deserialize_with = "some_hex_color")]
+    pub blue_color: Option<[f32; 4]>,
+    pub keys: KeyBindings,
+    pub colors: Theme,
+    /// Rotation rules to warn about in the window and list with `--violations`.
+    pub rules: Vec<Rule>,
+}
+
+impl Config {
+    /// Reads the config file at `path`, or the one in the user's config directory when no path is
+    /// given. Having no config file there is the same as an empty one.
+    pub fn load(path: Option<&Path>) -> Result<Self, Box<dyn error::Error>> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("couldn't read {}: {e}", path.display()))?;
+        toml::from_str(&text).map_err(|e| format!("couldn't read {}: {e}", path.display()).into())
+    }
+}
+
+/// Where the config file lives when `--config` isn't given, like
+/// `~/.config/rl-replay-viz/config.toml` on Linux.
+fn default_path() -> Option<PathBuf> {
+    Some(
+        dirs::config_dir()?
+            .join("rl-replay-viz")
+            .join("config.toml"),
+    )
+}
+
+/// Something a key does in the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Pause,
+    Back,
+    Forward,
+    StepBack,
+    StepForward,
+    LoopStart,
+    LoopEnd,
+    ClearLoop,
+    GoTo,
+    Bookmark,
+    NextBookmark,
+    Faster,
+    Slower,
+    Goal,
+    Shot,
+    Labels,
+    Roles,
+    Stats,
+    SideView,
+    Velocity,
+    Prediction,
+    ResetView,
+    Presentation,
+    Fullscreen,
+    Controls,
+    Info,
+    /// Play at one of the preset speeds, by its place in the list.
+    Speed(usize),
+}
+
+/// Which key does what in the window, by the names piston gives keys, like `Space`, `Left`, `G` or
+/// `D0` for the 0 key. `speeds` are the keys for 0.25x, 0.5x, 1x, 2x and 4x. Holding shift with the goal, shot and bookmark keys goes backwards.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    pub pause: Key,
+    pub back: Key,
+    pub forward: Key,
+    pub step_back: Key,
+    pub step_forward: Key,
+    pub loop_start: Key,
+    pub loop_end: Key,
+    pub clear_loop: Key,
+    pub go_to: Key,
+    pub bookmark: Key,
+    pub next_bookmark: Key,
+    pub faster: Key,
+    pub slower: Key,
+    pub goal: Key,
+    pub shot: Key,
+    pub labels: Key,
+    pub roles: Key,
+    pub stats: Key,
+    pub side_view: Key,
+    pub velocity: Key,
+    pub prediction: Key,
+    pub reset_view: Key,
+    pub presentation: Key,
+    pub fullscreen: Key,
+    pub controls: Key,
+    pub info: Key,
+    pub speeds: [Key; 5],
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            pause: Key::Space,
+            back: Key::Left,
+            forward: Key::Right,
+            step_back: Key::Comma,
+            step_forward: Key::Period,
+            loop_start: Key::LeftBracket,
+            loop_end: Key::RightBracket,
+            clear_loop: Key::Backslash,
+            go_to: Key::T,
+            bookmark: Key::B,
+            next_bookmark: Key::N,
+            faster: Key::Up,
+            slower: Key::Down,
+            goal: Key::G,
+            shot: Key::H,
+            labels: Key::L,
+            roles: Key::R,
+            stats: Key::S,
+            side_view: Key::Z,
+            velocity: Key::V,
+            prediction: Key::C,
+            reset_view: Key::D0,
+            presentation: Key::P,
+            fullscreen: Key::F11,
+            controls: Key::Tab,
+            info: Key::I,
+            speeds: [Key::D1, Key::D2, Key::D3, Key::D4, Key::D5],
+        }
+    }
+}
+
+impl KeyBindings {
+    /// What pressing `key` does, if anything.
+    pub fn action(&self, key: Key) -> Option<Action> {
+        [
+            (self.pause, Action::Pause),
+            (self.back, Action::Back),
+            (self.forward, Action::Forward),
+            (self.step_back, Action::StepBack),
+            (self.step_forward, Action::StepForward),
+            (self.loop_start, Action::LoopStart),
+            (self.loop_end, Action::LoopEnd),
+            (self.clear_loop, Action::ClearLoop),
+            (self.go_to, Action::GoTo),
+            (self.bookmark, Action::Bookmark),
+            (self.next_bookmark, Action::NextBookmark),
+            (self.faster, Action::Faster),
+            (self.slower, Action::Slower),
+            (self.goal, Action::Goal),
+            (self.shot, Action::Shot),
+            (self.labels, Action::Labels),
+            (self.roles, Action::Roles),
+            (self.stats, Action::Stats),
+            (self.side_view, Action::SideView),
+            (self.velocity, Action::Velocity),
+            (self.prediction, Action::Prediction),
+            (self.reset_view, Action::ResetView),
+            (self.presentation, Action::Presentation),
+            (self.fullscreen, Action::Fullscreen),
+            (self.controls, Action::Controls),
+            (self.info, Action::Info),
+        ]
+        .into_iter()
+        .chain((0..self.speeds.len()).map(|i| (self.speeds[i], Action::Speed(i))))
+        .find(|(bound, _)| *bound == key)
+        .map(|(_, action)| action)
+    }
+}
+
+/// Reads one of a `ValueEnum`'s values by the name it goes by on the command line.
+fn value_enum<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: ValueEnum,
+{
+    let value = String::deserialize(deserializer)?;
+    T::from_str(&value, true)
+        .map(Some)
+        .map_err(de::Error::custom)
+}
+
+/// Parses a color written as `#rrggbb`, or `#rrggbbaa` to make it see-through.
+pub fn parse_color(value: &str) -> Result<[f32; 4], String> {
+    let invalid = || format!("`{value}` is not a color like `#ff8800`");
+    let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return Err(invalid());
+    }
+
+    let mut color = [1.0; 4];
+    for (channel, pair) in color.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+        *channel = u8::from_str_radix(pair, 16).map_err(|_| invalid())? as f32 / 255.0;
+    }
+    Ok(color)
+}
+
+/// Parses a playback speed like `0.5` or `2x`, which has to be more than zero.
+pub fn parse_speed(value: &str) -> Result<f64, String> {
+    let speed: f64 = value
+        .strip_suffix('x')
+        .unwrap_or(value)
+        .parse()
+        .map_err(|_| format!("`{value}` is not a speed like `0.5` or `2x`"))?;
+    if !(speed > 0.0 && speed.is_finite()) {
+        return Err(format!("the speed has to be more than zero, not `{value}`"));
+    }
+    Ok(speed)
+}
+
+/// Reads a color the way `parse_color` does.
+pub fn hex_color<'de, D>(deserializer: D) -> Result<[f32; 4], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    parse_color(&value).map_err(de::Error::custom)
+}
+
+fn some_speed<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let speed = f64::deserialize(deserializer)?;
+    parse_speed(&speed.to_string())
+        .map(Some)
+        .map_err(de::Error::custom)
+}
+
+fn some_hex_color<'de, D>(deserializer: D) -> Result<Option<[f32; 4]>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    hex_color(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_are_read_with_or_without_alpha() {
+        assert_eq!(parse_color("#ff8800"), Ok([1.0, 136.0 / 255.0, 0.0, 1.0]));
+        assert_eq!(
+            parse_color("#FF880080"),
+            Ok([1.0, 136.0 / 255.0, 0.0, 128.0 / 255.0])
+        );
+    }
+
+    #[test]
+    fn colors_without_a_hash_or_six_or_eight_digits_are_refused() {
+        for value in [
+            "ff8800", "0xff8800", "#f80", "#ff88001", "#ff88zz", "#ff88é", "",
+        ] {
+            assert!(parse_color(value).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn speeds_are_read_with_or_without_an_x() {
+        assert_eq!(parse_speed("0.5"), Ok(0.5));
+        assert_eq!(parse_speed("2x"), Ok(2.0));
+    }
+
+    #[test]
+    fn speeds_that_are_not_more_than_zero_are_refused() {
+        for value in ["0", "0x", "-1x", "inf", "NaN", "x", "fast"] {
+            assert!(parse_speed(value).is_err(), "{value}");
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use clap::ValueEnum;
+use graphics::character::CharacterCache;
+use graphics::{Context, Graphics};
+
+use crate::app::render;
+use crate::app::{DisplayType, SPEEDS};
+use crate::arena::Arena;
+
+const WIDTH: f64 = 200.0;
+const ROW_HEIGHT: f64 = 20.0;
+/// Space around the panel's edge and between widgets in a row.
+const MARGIN: f64 = 6.0;
+const TEXT_SIZE: u32 = 12;
+const PLAY_BUTTON_WIDTH: f64 = 52.0;
+const BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.7];
+const WIDGET: [f32; 4] = [1.0, 1.0, 1.0, 0.15];
+const TEXT: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+const DISABLED: [f32; 4] = [1.0, 1.0, 1.0, 0.35];
+
+/// One player's line in the panel.
+pub struct PlayerRow {
+    pub name: String,
+    pub color: [f32; 4],
+    pub shown: bool,
+}
+
+/// What the panel shows, read off the window each time it's drawn or clicked.
+pub struct Status {
+    pub paused: bool,
+    pub speed: f64,
+    pub display: DisplayType,
+    /// Whether there's a player's heatmap to show, which takes `--player`.
+    pub player_heatmap: bool,
+    /// Whether the window's renderer can draw the 3d display.
+    pub three_d: bool,
+    pub players: Vec<PlayerRow>,
+}
+
+/// Something a click or drag on the panel asks the window to do.
+pub enum Control {
+    PlayPause,
+    Speed(f64),
+    Display(DisplayType),
+    /// Show or hide the player by this name.
+    TogglePlayer(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Widget {
+    PlayPause,
+    Speed,
+    /// The button that opens the list of displays.
+    DisplayMenu,
+    Display(DisplayType),
+    /// A player's checkbox, by their place in `Status::players`.
+    Player(usize),
+}
+
+/// Buttons and a slider for the things otherwise done with keys, drawn in the bottom left corner
+/// of the field. Nothing is kept between frames besides what's open and held, so the panel always
+/// shows the window as it is.
+pub struct ControlPanel {
+    pub open: bool,
+    /// Whether the list of displays is dropped down.
+    menu_open: bool,
+    /// Whether the speed slider is being dragged.
+    sliding: bool,
+}
+
+impl ControlPanel {
+    pub fn new(open: bool) -> Self {
+        Self {
+            open,
+            menu_open: false,
+            sliding: false,
+        }
+    }
+
+    /// Whether `point` is over the panel, so a click there shouldn't reach the field.
+    pub fn contains(&self, arena: &Arena, status: &Status, point: [f64; 2]) -> bool {
+        self.open && inside(&self.bounds(arena, status), point)
+    }
+
+    /// Clicks the widget under `point`. Clicking anywhere besides the display list closes it.
+    pub fn press(&mut self, arena: &Arena, status: &Status, point: [f64; 2]) -> Option<Control> {
+        if !self.open {
+            return None;
+        }
+        let widget = self
+            .layout(arena, status)
+            .into_iter()
+            .find(|(_, rect)| inside(rect, point))
+            .map(|(widget, _)| widget);
+        if widget != Some(Widget::DisplayMenu) {
+            self.menu_open = false;
+        }
+
+        match widget? {
+            Widget::PlayPause => Some(Control::PlayPause),
+            Widget::Speed => {
+                self.sliding = true;
+                self.slide(arena, status, point)
+            }
+            Widget::DisplayMenu => {
+                self.menu_open = !self.menu_open;
+                None
+            }
+            Widget::Display(DisplayType::PLAYER_HEATMAP) if !status.player_heatmap => None,
+            Widget::Display(DisplayType::THREE_D) if !status.three_d => None,
+            Widget::Display(display) => Some(Control::Display(display)),
+            Widget::Player(i) => Some(Control::TogglePlayer(status.players[i].name.clone())),
+        }
+    }
+
+    /// Moves the speed slider along with the mouse while it's held on it.
+    pub fn slide(&self, arena: &Arena, status: &Status, point: [f64; 2]) -> Option<Control> {
+        if !self.sliding {
+            return None;
+        }
+        let (_, [x, _, width, _]) = self
+            .layout(arena, status)
+            .into_iter()
+            .find(|(widget, _)| *widget == Widget::Speed)?;
+        let along = ((point[0] - x) / width).clamp(0.0, 1.0);
+        // The slider runs in doublings so slow speeds get as much room as fast ones
+        let [slowest, fastest] = [SPEEDS[0].log2(), SPEEDS[SPEEDS.len() - 1].log2()];
+        let speed = (slowest + along * (fastest - slowest)).exp2();
+        // Steps of 0.05 still reach every preset, like 1x
+        Some(Control::Speed(
+            ((speed * 20.0).round() / 20.0).max(SPEEDS[0]),
+        ))
+    }
+
+    pub fn release(&mut self) {
+        self.sliding = false;
+    }
+
+    pub fn draw<C, G>(
+        &self,
+        arena: &Arena,
+        status: &Status,
+        glyphs: &mut C,
+        c: &Context,
+        gl: &mut G,
+    ) where
+        C: CharacterCache,
+        G: Graphics<Texture = C::Texture>,
+    {
+        use graphics::*;
+
+        if !self.open {
+            return;
+        }
+        rectangle(BACKGROUND, self.bounds(arena, status), c.transform, gl);
+
+        let [slowest, fastest] = [SPEEDS[0].log2(), SPEEDS[SPEEDS.len() - 1].log2()];
+        for (widget, [x, y, width, height]) in self.layout(arena, status) {
+            let label = match widget {
+                Widget::PlayPause => {
+                    rectangle(WIDGET, [x, y, width, height], c.transform, gl);
+                    let line = if status.paused { "Play" } else { "Pause" };
+                    Some((TEXT, line.to_string(), x + MARGIN))
+                }
+                Widget::Speed => {
+                    let along = (status.speed.log2() - slowest) / (fastest - slowest);
+                    let knob = x + along.clamp(0.0, 1.0) * width;
+                    let middle = y + height / 2.0;
+                    rectangle(WIDGET, [x, middle - 1.5, width, 3.0], c.transform, gl);
+                    rectangle(
+                        TEXT,
+                        [knob - 3.0, y + 3.0, 6.0, height - 6.0],
+                        c.transform,
+                        gl,
+                    );
+                    None
+                }
+                Widget::DisplayMenu => {
+                    rectangle(WIDGET, [x, y, width, height], c.transform, gl);
+                    let line = format!("Display: {} \u{25be}", display_name(status.display));
+                    Some((TEXT, line, x + MARGIN))
+                }
+                Widget::Display(display) => {
+                    if display == status.display {
+                        rectangle(WIDGET, [x, y, width, height], c.transform, gl);
+                    }
+                    let usable = match display {
+                        DisplayType::PLAYER_HEATMAP => status.player_heatmap,
+                        DisplayType::THREE_D => status.three_d,
+                        _ => true,
+                    };
+                    let color = if usable { TEXT } else { DISABLED };
+                    Some((color, display_name(display), x + MARGIN * 2.0))
+                }
+                Widget::Player(i) => {
+                    let player = &status.players[i];
+                    let size = height - 8.0;
+                    let top = y + 4.0;
+                    Rectangle::new_border(TEXT, 1.0).draw(
+                        [x, top, size, size],
+                        &c.draw_state,
+                        c.transform,
+                        gl,
+                    );
+                    if player.shown {
+                        let check = [x + 2.0, top + 2.0, size - 4.0, size - 4.0];
+                        rectangle(player.color, check, c.transform, gl);
+                    }
+                    let color = if player.shown { player.color } else { DISABLED };
+                    Some((color, player.name.clone(), x + size + MARGIN))
+                }
+            };
+
+            if let Some((color, line, left)) = label {
+                let transform = c
+                    .transform
+                    .trans(left, y + height / 2.0 + TEXT_SIZE as f64 * 0.35);
+                // A glyph that fails to load just leaves the label out
+                let _ = text(color, TEXT_SIZE, &line, glyphs, transform, gl);
+            }
+        }
+    }
+
+    /// Where each widget goes, stacked up from just above the seek bar.
+    fn layout(&self, arena: &Arena, status: &Status) -> Vec<(Widget, [f64; 4])> {
+        let mut rows = vec![
+            vec![Widget::PlayPause, Widget::Speed],
+            vec![Widget::DisplayMenu],
+        ];
+        if self.menu_open {
+            rows.extend(
+                DisplayType::value_variants()
+                    .iter()
+                    .map(|display| vec![Widget::Display(*display)]),
+            );
+        }
+        rows.extend((0..status.players.len()).map(|i| vec![Widget::Player(i)]));
+
+        let [left, top, width, _] = self.bounds(arena, status);
+        let inner = width - MARGIN * 2.0;
+        let mut widgets = vec![];
+        for (i, row) in rows.into_iter().enumerate() {
+            let y = top + MARGIN + ROW_HEIGHT * i as f64;
+            let mut x = left + MARGIN;
+            for widget in row {
+                let width = match widget {
+                    Widget::PlayPause => PLAY_BUTTON_WIDTH,
+                    Widget::Speed => inner - PLAY_BUTTON_WIDTH - MARGIN,
+                    _ => inner,
+                };
+                widgets.push((widget, [x, y + 1.0, width, ROW_HEIGHT - 2.0]));
+                x += width + MARGIN;
+            }
+        }
+        widgets
+    }
+
+    fn bounds(&self, arena: &Arena, status: &Status) -> [f64; 4] {
+        let displays = if self.menu_open {
+            DisplayType::value_variants().len()
+        } else {
+            0
+        };
+        let rows = 2 + displays + status.players.len();
+        let height = ROW_HEIGHT * rows as f64 + MARGIN * 2.0;
+        let [_, bottom, _, _] = render::timeline_rect(arena);
+        [MARGIN, bottom - MARGIN - height, WIDTH, height]
+    }
+}
+
+/// The name a display goes by with `--display`, like `team-voronoi`.
+fn display_name(display: DisplayType) -> String {
+    display
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+fn inside(rect: &[f64; 4], point: [f64; 2]) -> bool {
+    let [x, y, width, height] = *rect;
+    (x..x + width).contains(&point[0]) && (y..y + height).contains(&point[1])
+}
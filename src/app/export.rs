@@ -0,0 +1,231 @@
+use std::error;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use graphics::{Context, Transformed};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+use crate::app::canvas::{save_png, Canvas};
+use crate::app::render::{self, RenderState};
+use crate::app::svg;
+use crate::arena::Arena;
+use crate::timeline::Timeline;
+use crate::{create, ReplayEngine};
+
+/// Size in pixels of an exported frame, matching the default window size, with room below the
+/// field for the spread chart if it's drawn.
+pub fn frame_size(arena: &Arena, state: &RenderState) -> (u32, u32) {
+    let [width, mut height] = render::screen_size(arena);
+    if state.has_spread() {
+        height += render::SPREAD_HEIGHT;
+    }
+    (width as u32, height as u32)
+}
+
+/// Portion of the replay to export, in seconds since the first network frame.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TimeRange {
+    pub from: Option<f32>,
+    pub to: Option<f32>,
+}
+
+/// Jumps `engine` with `timeline`, if there is one, to as far before `time` seconds into the replay
+/// as anything drawn looks back, so the frames before that aren't stepped through.
+fn skip_towards(
+    engine: &mut ReplayEngine,
+    state: &mut RenderState,
+    timeline: Option<&Timeline>,
+    time: f32,
+) {
+    let Some(timeline) = timeline else {
+        return;
+    };
+    let frames = timeline.frames();
+    let start = frames.first().map_or(0.0, |entry| entry.time);
+    let lead_in = time - state.lookback();
+    let skipped = frames.partition_point(|entry| entry.time - start < lead_in);
+    if let Some(entry) = frames
+        .get(skipped)
+        .filter(|entry| entry.frame > engine.frame_index())
+    {
+        timeline.seek(engine, entry.frame);
+        state.jump(engine, timeline);
+    }
+}
+
+/// Steps through the frames inside `range`, calling `f` after each one is processed. Frames
+/// before the range are still observed so accumulated displays include them, or with a timeline,
+/// counted from it.
+fn for_each_frame_in(
+    engine: &mut ReplayEngine,
+    state: &mut RenderState,
+    range: TimeRange,
+    timeline: Option<&Timeline>,
+    mut f: impl FnMut(&ReplayEngine, &RenderState) -> Result<(), Box<dyn error::Error>>,
+) -> Result<(), Box<dyn error::Error>> {
+    if let Some(from) = range.from {
+        skip_towards(engine, state, timeline, from);
+    }
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+        state.observe(engine);
+        if range.from.is_some_and(|from| engine.elapsed() < from) {
+            continue;
+        }
+        if range.to.is_some_and(|to| engine.elapsed() > to) {
+            break;
+        }
+        f(engine, state)?;
+    }
+
+    Ok(())
+}
+
+/// Renders the engine's current frame onto an off-screen canvas.
+pub fn render_canvas(engine: &ReplayEngine, state: &RenderState) -> Canvas {
+    let (width, height) = frame_size(engine.arena(), state);
+    let mut canvas = Canvas::new(width, height);
+    let c = Context::new_abs(width as f64, height as f64);
+    state.draw(engine, &c, &mut canvas);
+    if state.has_spread() {
+        let [_, field_height] = render::screen_size(engine.arena());
+        render::draw_spread(engine, state, &c.trans(0.0, field_height), &mut canvas);
+    }
+    canvas
+}
+
+/// Renders every network frame and pipes them into `ffmpeg`, which picks the container and codec
+/// from the output extension (e.g. `.mp4` or `.webm`).
+pub fn export_video(
+    mut engine: ReplayEngine,
+    mut state: RenderState,
+    range: TimeRange,
+    timeline: Option<&Timeline>,
+    path: &Path,
+) -> Result<(), Box<dyn error::Error>> {
+    let (width, height) = frame_size(engine.arena(), &state);
+    let mut ffmpeg = Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-loglevel",
+            "error",
+            "-f",
+            "rawvideo",
+            "-pix_fmt",
+            "rgba",
+        ])
+        .args(["-s", &format!("{width}x{height}")])
+        .args(["-r", &engine.record_fps().to_string()])
+        .args(["-i", "-", "-pix_fmt", "yuv420p"])
+        .args(metadata(&engine))
+        .arg(path)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start ffmpeg, is it installed and on your PATH? {e}"))?;
+
+    let mut stdin = ffmpeg.stdin.take().ok_or("failed to open ffmpeg's stdin")?;
+    for_each_frame_in(&mut engine, &mut state, range, timeline, |engine, state| {
+        let canvas = render_canvas(engine, state);
+        stdin.write_all(canvas.pixels())?;
+        Ok(())
+    })?;
+    drop(stdin);
+
+    let status = ffmpeg.wait()?;
+    if !status.success() {
+        return Err(format!("ffmpeg exited with {status}").into());
+    }
+
+    Ok(())
+}
+
+/// `ffmpeg` arguments that tag the video with the match it's from, for containers that keep them.
+fn metadata(engine: &ReplayEngine) -> Vec<String> {
+    let info = engine.match_info();
+    let mut tags = vec![format!("comment={}", info.description())];
+    tags.extend(info.name.map(|name| format!("title={name}")));
+    tags.extend(info.date.map(|date| format!("date={date}")));
+    tags.into_iter()
+        .flat_map(|tag| ["-metadata".to_string(), tag])
+        .collect()
+}
+
+/// Renders the frames inside `range` into a looping animated GIF.
+pub fn export_gif(
+    mut engine: ReplayEngine,
+    mut state: RenderState,
+    range: TimeRange,
+    timeline: Option<&Timeline>,
+    path: &Path,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut encoder = GifEncoder::new(BufWriter::new(create(path)?));
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let delay = Delay::from_numer_denom_ms(1000, engine.record_fps().round() as u32);
+    for_each_frame_in(&mut engine, &mut state, range, timeline, |engine, state| {
+        let image = render_canvas(engine, state).into_image();
+        encoder.encode_frame(Frame::from_parts(image, 0, 0, delay))?;
+        Ok(())
+    })
+}
+
+/// Advances to `time` seconds into the replay and writes that frame as a PNG.
+pub fn export_snapshot(
+    engine: ReplayEngine,
+    state: RenderState,
+    time: f32,
+    timeline: Option<&Timeline>,
+    path: &Path,
+) -> Result<(), Box<dyn error::Error>> {
+    save_png(&snapshot(engine, state, time, timeline)?, path)
+}
+
+/// Advances to `time` seconds into the replay and renders that frame.
+pub fn snapshot(
+    mut engine: ReplayEngine,
+    mut state: RenderState,
+    time: f32,
+    timeline: Option<&Timeline>,
+) -> Result<RgbaImage, Box<dyn error::Error>> {
+    play_until(&mut engine, &mut state, time, timeline)?;
+    Ok(render_canvas(&engine, &state).into_image())
+}
+
+/// Writes the field `time` seconds into the replay to an SVG, with the cars' names beside them if
+/// `labels` is set.
+pub fn export_svg(
+    mut engine: ReplayEngine,
+    mut state: RenderState,
+    time: f32,
+    labels: bool,
+    timeline: Option<&Timeline>,
+    path: &Path,
+) -> Result<(), Box<dyn error::Error>> {
+    play_until(&mut engine, &mut state, time, timeline)?;
+    svg::write_frame(&engine, &state, labels, path)
+}
+
+/// Steps through the replay until `time` seconds in, observing every frame on the way, or with a
+/// timeline, every frame anything drawn still shows.
+fn play_until(
+    engine: &mut ReplayEngine,
+    state: &mut RenderState,
+    time: f32,
+    timeline: Option<&Timeline>,
+) -> Result<(), Box<dyn error::Error>> {
+    skip_towards(engine, state, timeline, time);
+    while engine.frame_index() < engine.frame_count() && engine.elapsed() < time {
+        engine.step();
+        state.observe(engine);
+    }
+    if engine.elapsed() < time {
+        return Err(format!(
+            "the replay is only {:.1} seconds long, can't export a frame at {time} seconds",
+            engine.elapsed()
+        )
+        .into());
+    }
+    Ok(())
+}
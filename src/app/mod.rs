@@ -0,0 +1,2017 @@
+use std::collections::BTreeMap;
+use std::error;
+use std::io::{self, BufWriter, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+
+use boxcars::ActorId;
+use clap::builder::PossibleValuesParser;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use graphics::{Context, Transformed};
+use piston::{
+    Button, ButtonEvent, ButtonState, EventLoop, EventSettings, Events, Key, MouseButton,
+    MouseCursorEvent, MouseScrollEvent, RenderArgs, RenderEvent, TextEvent, UpdateArgs,
+    UpdateEvent, WindowSettings,
+};
+use serde::Serialize;
+use winit::window::{Fullscreen, WindowLevel};
+
+use bookmarks::Bookmarks;
+use compare::Comparison;
+use config::{Action, Config};
+use controls::{Control, ControlPanel, PlayerRow};
+use render::{Camera, Letterbox, RenderState, Theme, View};
+use renderer::{AppWindow, GlRenderer, Renderer};
+use scene::Orbit;
+use script::Script;
+use watch::ReplayWatcher;
+
+use crate::arena::Arena;
+use crate::events::{self, Event};
+use crate::heatmap::PositionHeatmap;
+use crate::overlay::OverlayRegistry;
+use crate::rotation::{self, RoleShares, Rule};
+use crate::shots::{self, Shot, ShotKind};
+use crate::situations::{self, Situation};
+use crate::timeline::Timeline;
+use crate::touches;
+use crate::{
+    create, dump, dump_json, parse_replay, parse_replay_lenient, team_shades, DemolitionRecord,
+    FrameSummary, GamePhase, Goal, ReplayEngine, Team, TeamColors,
+};
+
+mod ballchasing;
+mod batch;
+mod bench;
+mod bookmarks;
+mod canvas;
+mod chart;
+mod compare;
+mod config;
+mod controls;
+mod export;
+mod render;
+mod renderer;
+mod report;
+mod scene;
+mod script;
+mod serve;
+mod shot_chart;
+mod svg;
+mod tui;
+mod watch;
+#[cfg(feature = "wgpu")]
+mod wgpu_renderer;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    visualize: Option<Args>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Visualize a replay, which is also what happens when no subcommand is given
+    Visualize(Box<Args>),
+    /// Write out a replay's network frames with actor and object names resolved
+    Dump(DumpArgs),
+    /// Answer HTTP requests for a replay's frames, events and zone control, to build web dashboards on
+    Serve(ServeArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct DumpArgs {
+    /// Path to replay file to dump.
+    #[arg(short, long)]
+    replay: PathBuf,
+
+    /// File to write the dump to
+    #[arg(short, long)]
+    out: PathBuf,
+
+    /// Whether to write a readable text listing of every frame, versioned JSON, or (with the `parquet` feature) a Parquet table of every player's position on every frame
+    #[arg(value_enum, short, long, default_value_t = DumpFormat::TEXT)]
+    format: DumpFormat,
+}
+
+#[derive(clap::Args, Debug)]
+struct ServeArgs {
+    /// Path to replay file to serve
+    #[arg(short, long)]
+    replay: PathBuf,
+
+    /// Address to listen on. The default only takes requests from this machine; `0.0.0.0:8080` takes them from anywhere on the network
+    #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1:8080")]
+    listen: String,
+
+    /// What kind of display `/frame.png` renders, out of those `--display` takes apart from player-heatmap and 3d
+    #[arg(value_enum, short, long, default_value_t = DisplayType::POINTS)]
+    display: DisplayType,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+#[allow(clippy::upper_case_acronyms)]
+enum DumpFormat {
+    TEXT,
+    JSON,
+    #[cfg(feature = "parquet")]
+    PARQUET,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+#[allow(clippy::upper_case_acronyms)]
+enum Backend {
+    OPENGL,
+    #[cfg(feature = "wgpu")]
+    WGPU,
+}
+
+impl Backend {
+    /// Whether the window can show the 3d display when it draws with this.
+    fn draws_scenes(self) -> bool {
+        match self {
+            Backend::OPENGL => GlRenderer::DRAWS_SCENES,
+            #[cfg(feature = "wgpu")]
+            Backend::WGPU => wgpu_renderer::WgpuRenderer::DRAWS_SCENES,
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+struct Args {
+    /// Path to replay file to visualize, or a directory of replays to process into `--output` in one go. Give it twice to play two replays side by side in the window, kept at the same game clock
+    #[arg(short, long, required_unless_present_any = ["ballchasing", "watch"])]
+    replay: Vec<PathBuf>,
+
+    /// Draw the second `--replay`'s cars and ball see-through over the first one's field, instead of side by side
+    #[arg(long, requires = "replay")]
+    overlay: bool,
+
+    /// Draw just this player from the second `--replay` see-through over the first one's field, lined up by kickoffs rather than the game clock, to compare their positioning across games
+    #[arg(long, value_name = "NAME", requires = "replay")]
+    ghost: Option<String>,
+
+    /// Download a replay from ballchasing.com by its id or URL and visualize it. Needs an API token in `BALLCHASING_TOKEN`, and downloads are cached so they only happen once
+    #[arg(long, value_name = "ID_OR_URL", conflicts_with = "replay")]
+    ballchasing: Option<String>,
+
+    /// Watch a folder, like Rocket League's `Demos` folder, opening its newest replay and switching to each new one as soon as it's saved
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["replay", "ballchasing", "headless", "export", "export_gif", "snapshot", "export_svg"])]
+    watch: Option<PathBuf>,
+
+    /// How fast to play the replay, as a multiple of real time like 0.5 or 2. Defaults to 1. Change it while playing with `Up` and `Down`, or pick 0.25x to 4x with `1` to `5`
+    #[arg(long, value_name = "RATE", value_parser = config::parse_speed)]
+    speed: Option<f64>,
+
+    /// Count of network frames to process per second, as another way to give `--speed`
+    #[arg(short, long, conflicts_with = "speed")]
+    ups: Option<u64>,
+
+    /// Begin playback where the game clock shows this much time left, as `m:ss` or seconds. Jump to a clock time while playing with `T`
+    #[arg(long, value_name = "CLOCK", value_parser = parse_timestamp, conflicts_with_all = ["watch", "headless", "export", "export_gif", "snapshot", "export_svg"])]
+    start_at: Option<f32>,
+
+    /// What kind of display to show, defaulting to points, whether it's points to show a point for each player, voronoi to show a voronoi diagram, weighted-voronoi to grow each player's cell with their speed and boost, team-voronoi to merge each team's cells into one region, passing-lanes to show how open the lanes between teammates are, team-shape to shade the area between each team's cars with a mark at its middle, heatmap to show which team has controlled each part of the field so far, player-heatmap to show where `--player` spent the whole replay, or 3d to show the field in 3D, with the ball and cars at their true height and turned the way they are, from a camera that's turned by dragging and moved in and out by scrolling. 3d only opens in the window, and needs `--renderer wgpu`
+    #[arg(value_enum, short, long)]
+    display: Option<DisplayType>,
+
+    /// Name of the player to show with the player-heatmap display
+    #[arg(long, required_if_eq("display", "player-heatmap"))]
+    player: Option<String>,
+
+    /// Show each player's name next to their dot in the window. Toggle with `L`
+    #[arg(long)]
+    labels: bool,
+
+    /// Show each player's place in their team's rotation, as first, second or third man, on their dot in the window. Toggle with `R`
+    #[arg(long)]
+    roles: bool,
+
+    /// Show how much of the field each team and player controls, and each team's share of possession, in the window. Toggle with `S`
+    #[arg(long)]
+    stats: bool,
+
+    /// Show the field from behind a goal below the seek bar, with the ball and cars at their true height between the floor and the ceiling, to follow aerial play. Toggle with `Z`
+    #[arg(long)]
+    side_view: bool,
+
+    /// Open the window fullscreen. Toggle with `F11`
+    #[arg(long)]
+    fullscreen: bool,
+
+    /// Draw the field with nothing behind it, in a borderless window kept on top of the others, so streaming software like OBS can capture the zones and cars over gameplay footage. Snapshots get a transparent background too
+    #[arg(long, conflicts_with = "chroma_key")]
+    transparent: bool,
+
+    /// Like `--transparent`, but fill behind the field with this color, written as `#rrggbb`, for capture software that keys out a color instead of reading transparency
+    #[arg(long, value_name = "COLOR", value_parser = config::parse_color)]
+    chroma_key: Option<[f32; 4]>,
+
+    /// What the window draws with: OpenGL, or (with the `wgpu` feature) wgpu, which uses Vulkan, Metal or DX12 on machines where OpenGL is missing or broken
+    #[arg(value_enum, long, default_value_t = Backend::OPENGL)]
+    renderer: Backend,
+
+    /// Hide the seek bar, the player inspector and the mouse cursor so the field fills the window, for showing replays on a projector or shared screen. Toggle with `P`
+    #[arg(long)]
+    presentation: bool,
+
+    /// Skip past the goal replay after each goal, leaving it out of playback, exports and everything counted over the replay
+    #[arg(long)]
+    skip_goal_replays: bool,
+
+    /// Open replays that fail their checksum or stop partway through their network data, playing whatever frames could be read and saying how much of the replay that is, instead of refusing to open them
+    #[arg(long)]
+    lenient: bool,
+
+    /// How many replays to process at once when `--replay` is a directory, for batch output and `--focus-player` reports. Defaults to one per core
+    #[arg(long, value_name = "N")]
+    jobs: Option<NonZeroUsize>,
+
+    /// Step through the whole replay once before starting, keeping every frame's positions, possession and zone control in a table, and say how long that took and how much memory it uses. Headless output and the player heatmap are then read from the table, and seeking in the window and exports starting partway through jump straight to the frame from it
+    #[arg(long)]
+    preprocess: bool,
+
+    /// Pause playback as each kickoff countdown starts, with every car in its kickoff spot. Resume with `Space`
+    #[arg(long)]
+    pause_on_kickoff: bool,
+
+    /// Draw an arrow showing where each car and the ball are heading. Toggle with `V`
+    #[arg(long)]
+    velocity: bool,
+
+    /// Draw a dotted line where the ball will go over the next 3 seconds if nobody touches it, bouncing off the floor, ceiling and walls. Toggle with `C`
+    #[arg(long)]
+    prediction: bool,
+
+    /// Draw a fading line behind each car showing where it went over this many seconds
+    #[arg(long, value_name = "SECONDS")]
+    trail: Option<f32>,
+
+    /// Draw a fading line behind the ball over this many seconds, marking where it bounced
+    #[arg(long, value_name = "SECONDS")]
+    ball_trail: Option<f32>,
+
+    /// Chart how spread out each team has been over this many seconds in a strip below the field, in the window and in exported videos, GIFs and snapshots. Solid lines are the average distance between teammates and faint ones the average distance to the ball
+    #[arg(long, value_name = "SECONDS")]
+    spread: Option<f32>,
+
+    /// Keep the field zoomed in on and following the ball, one player as `player:NAME`, or the action, centered between the ball and every car and zoomed out far enough to show them all. Handy for exporting zoomed clips, and takes over from zooming and panning in the window
+    #[arg(long, value_name = "MODE", value_parser = parse_camera)]
+    camera: Option<Camera>,
+
+    /// Draw this layer on top of the field, in the window and in exported videos, GIFs and snapshots. `team-centers` marks the middle of each team's cars, `nearest-to-ball` joins the ball to each team's closest car, `goal-coverage` shades the shadow each defender casts from the ball over their goal mouth, and `shot-chart` marks where every shot so far was taken from and every save made. Repeat to draw more than one
+    #[arg(long, value_name = "NAME")]
+    layer: Vec<String>,
+
+    /// Colors to draw the teams in, either classic for the game's own orange and blue, or colorblind for amber and blue shades that stay apart from each other and the ball with red-green colorblindness
+    #[arg(value_enum, long, value_name = "PRESET")]
+    team_colors: Option<TeamColorPreset>,
+
+    /// Draw the orange team in shades of this color instead, written as `#rrggbb`
+    #[arg(long, value_name = "COLOR", value_parser = config::parse_color)]
+    orange_color: Option<[f32; 4]>,
+
+    /// Draw the blue team in shades of this color instead, written as `#rrggbb`
+    #[arg(long, value_name = "COLOR", value_parser = config::parse_color)]
+    blue_color: Option<[f32; 4]>,
+
+    /// Process the replay without opening a window, writing one JSON line per frame with player positions, zone control, how spread out each team is, the score and the game phase
+    #[arg(long)]
+    headless: bool,
+
+    /// Deprecated spelling of `dump --format json`, kept for scripts written before the subcommand
+    #[arg(long, value_name = "FILE", hide = true, conflicts_with_all = ["headless", "export", "export_gif", "snapshot"])]
+    dump_json: Option<PathBuf>,
+
+    /// Deprecated spelling of `dump --format parquet`, kept for scripts written before the subcommand
+    #[cfg(feature = "parquet")]
+    #[arg(long, value_name = "FILE", hide = true, conflicts_with_all = ["headless", "export", "export_gif", "snapshot", "dump_json"])]
+    dump_parquet: Option<PathBuf>,
+
+    /// Play the replay in the terminal instead of opening a window, for a quick look over SSH. Each player's share of the field is shaded in their color with block characters, beside the score and zone percentages. `Space` pauses, `Left` and `Right` skip and `q` quits
+    #[arg(long, conflicts_with_all = ["headless", "export", "export_gif", "snapshot", "export_svg", "start_at"])]
+    tui: bool,
+
+    /// Time how many network frames a second the replay is stepped through, with zone control worked out on each, and preprocessed as with `--preprocess`, and print the results. Build with `--release` for numbers worth comparing
+    #[arg(long, conflicts_with_all = ["headless", "tui", "export", "export_gif", "snapshot", "export_svg", "preprocess"])]
+    bench_replay: bool,
+
+    /// Run this Rhai script on every frame played. Its `on_frame(frame)` function gets the frame as `--headless` writes it and can call `pause()` to pause playback in the window, or `metric(name, value)` to show a value beside the field and add it to each `--headless` line
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["tui", "export", "export_gif", "snapshot", "export_svg", "bench_replay"])]
+    script: Option<PathBuf>,
+
+    /// Write every time a player touched the ball to this CSV file instead of opening a window, with where the ball was and how fast it was going before and after
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "export", "export_gif", "snapshot", "export_svg"])]
+    touch_log: Option<PathBuf>,
+
+    /// Write the replay's goals, shots, saves, demolitions, open nets and outnumbered defenses to this JSON file instead of opening a window, with each shot's quality as a rough chance of it going in, and each player's average speed, time supersonic, time in the air and distance driven
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "export", "export_gif", "snapshot", "export_svg", "touch_log"])]
+    events: Option<PathBuf>,
+
+    /// Write a chart of which team was pressing over the course of the replay to this file instead of opening a window, from where the ball was and how much of the field each team controlled, with a line at each goal. Written as an SVG when the file ends in `.svg`, otherwise as a PNG
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "export", "export_gif", "snapshot", "export_svg", "touch_log", "events"])]
+    pressure_chart: Option<PathBuf>,
+
+    /// Write how much of the replay's live play each player spent as first, second and third man in their team's rotation to this CSV file instead of opening a window
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "export", "export_gif", "snapshot", "export_svg", "touch_log", "events", "pressure_chart"])]
+    role_shares: Option<PathBuf>,
+
+    /// Write how many big and small boost pads each player picked up over the replay, how much boost they collected and used during live play, and how long they spent with an empty or full tank to this CSV file instead of opening a window
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "export", "export_gif", "snapshot", "export_svg", "touch_log", "events", "pressure_chart", "role_shares"])]
+    boost_stats: Option<PathBuf>,
+
+    /// Write a report of the whole game to this HTML file instead of opening a window, with the score, zone control and heatmaps, each player's boost and movement stats, every kickoff and each player's kickoff win rate, and every shot and save. Images are embedded so the file can be shared on its own
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "export", "export_gif", "snapshot", "export_svg", "touch_log", "events", "pressure_chart", "role_shares", "boost_stats"])]
+    report: Option<PathBuf>,
+
+    /// Write every time a team broke one of the rotation rules in the config file for half a second or more to this CSV file instead of opening a window
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "export", "export_gif", "snapshot", "export_svg", "touch_log", "events", "pressure_chart", "role_shares", "boost_stats", "report"])]
+    violations: Option<PathBuf>,
+
+    /// Write where every shot in the replay was taken from and every save was made to this file instead of opening a window, as a PNG of the field with a dot for each shot, bigger the better its chance of going in, and a ring for each save. Written as CSV rows when the file ends in `.csv`
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "export", "export_gif", "snapshot", "export_svg", "touch_log", "events", "pressure_chart", "role_shares", "boost_stats", "report", "violations"])]
+    shot_chart: Option<PathBuf>,
+
+    /// Write the `--report` on this one player instead, matched by name or online id, over every game they played in when `--replay` is a directory
+    #[arg(long, value_name = "NAME|ID", requires = "report")]
+    focus_player: Option<String>,
+
+    /// File to write headless output, a snapshot or an SVG to, or the directory to write results to when `--replay` is a directory. Headless output defaults to stdout
+    #[arg(short, long, visible_alias = "out")]
+    output: Option<PathBuf>,
+
+    /// Read default settings, key bindings, colors and rotation rules from this TOML file instead of `rl-replay-viz/config.toml` in your config directory
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// What was read from the config file, filled in after parsing
+    #[arg(skip)]
+    settings: Config,
+
+    /// The layers `--layer` can pick from, filled in after parsing
+    #[arg(skip)]
+    overlays: OverlayRegistry,
+
+    /// Render every frame off-screen and encode them into a video with ffmpeg, e.g. `replay.mp4` or `replay.webm`
+    #[arg(long, conflicts_with = "headless")]
+    export: Option<PathBuf>,
+
+    /// Render the replay off-screen into an animated GIF
+    #[arg(long, conflicts_with_all = ["headless", "export"])]
+    export_gif: Option<PathBuf>,
+
+    /// Render a single PNG of the field at this time into the replay, as `m:ss` or seconds, and write it to `--output`
+    #[arg(long, value_parser = parse_timestamp, requires = "output", conflicts_with_all = ["headless", "export", "export_gif"])]
+    snapshot: Option<f32>,
+
+    /// Write the field at this time into the replay to `--output` as an SVG, as `m:ss` or seconds, with the voronoi cells, field markings, cars and ball drawn as shapes that stay sharp at any size. The cells follow `--display`, and `--labels` names the players
+    #[arg(long, value_name = "TIME", value_parser = parse_timestamp, requires = "output", conflicts_with_all = ["headless", "export", "export_gif", "snapshot"])]
+    export_svg: Option<f32>,
+
+    /// Start exporting at this time into the replay, as `m:ss` or seconds
+    #[arg(long, value_parser = parse_timestamp)]
+    from: Option<f32>,
+
+    /// Stop exporting at this time into the replay, as `m:ss` or seconds
+    #[arg(long, value_parser = parse_timestamp)]
+    to: Option<f32>,
+}
+
+impl Args {
+    /// What `--dump-json` or `--dump-parquet` asked to dump, the way `dump` takes it.
+    fn legacy_dump(&self) -> Result<Option<DumpArgs>, Box<dyn error::Error>> {
+        #[cfg(feature = "parquet")]
+        let parquet = self
+            .dump_parquet
+            .clone()
+            .map(|out| (out, DumpFormat::PARQUET));
+        #[cfg(not(feature = "parquet"))]
+        let parquet = None;
+        let json = self.dump_json.clone().map(|out| (out, DumpFormat::JSON));
+        let Some((out, format)) = parquet.or(json) else {
+            return Ok(None);
+        };
+        let [replay] = self.replay.as_slice() else {
+            return Err("--dump-json and --dump-parquet dump a single --replay".into());
+        };
+        Ok(Some(DumpArgs {
+            replay: replay.clone(),
+            out,
+            format,
+        }))
+    }
+
+    fn render_state(
+        &self,
+        engine: &ReplayEngine,
+        timeline: Option<&Timeline>,
+    ) -> Result<RenderState, Box<dyn error::Error>> {
+        let display = self.display.or(self.settings.display).unwrap_or_default();
+        if display == DisplayType::THREE_D
+            && (self.writes_output() || !self.renderer.draws_scenes())
+        {
+            return Err("the 3d display only opens in the window, with --renderer wgpu".into());
+        }
+        let mut state = RenderState::new(display, engine.arena()).with_theme(self.theme());
+        state.velocity = self.velocity;
+        state.prediction = self.prediction;
+        if let Some(seconds) = self.trail {
+            state = state.with_trail(seconds);
+        }
+        if let Some(seconds) = self.ball_trail {
+            state = state.with_ball_trail(seconds);
+        }
+        if let Some(seconds) = self.spread {
+            state = state.with_spread(seconds);
+        }
+        if let Some(camera) = &self.camera {
+            if let Camera::Player(name) = camera {
+                find_player(engine, name)?;
+            }
+            state = state.with_camera(camera.clone());
+        }
+        for name in &self.layer {
+            let overlay = self
+                .overlays
+                .create(name)
+                .ok_or_else(|| format!("no layer called {name}"))?;
+            state = state.with_overlay(overlay);
+        }
+        if display == DisplayType::PLAYER_HEATMAP {
+            // The display can come from the config file, where clap can't require --player
+            let name = self
+                .player
+                .as_ref()
+                .ok_or("--player is needed for the player-heatmap display")?;
+            let heatmap = match timeline {
+                Some(timeline) => PositionHeatmap::from_timeline(timeline, name)?,
+                None => PositionHeatmap::for_player(engine, name)?,
+            };
+            state = state.with_player_heatmap(heatmap);
+        }
+        Ok(state)
+    }
+
+    /// How fast to play a replay recorded at `record_fps` to begin with, as a multiple of real time.
+    fn speed(&self, record_fps: f32) -> f64 {
+        let from_ups = |ups: u64| ups as f64 / record_fps as f64;
+        self.speed
+            .or(self.ups.map(from_ups))
+            .or(self.settings.speed)
+            .or(self.settings.ups.map(from_ups))
+            .unwrap_or(1.0)
+    }
+
+    /// Whether these args write something out instead of opening the window.
+    fn writes_output(&self) -> bool {
+        self.focus_player.is_some()
+            || self.touch_log.is_some()
+            || self.events.is_some()
+            || self.role_shares.is_some()
+            || self.boost_stats.is_some()
+            || self.report.is_some()
+            || self.violations.is_some()
+            || self.shot_chart.is_some()
+            || self.pressure_chart.is_some()
+            || self.headless
+            || self.bench_replay
+            || self.export.is_some()
+            || self.export_gif.is_some()
+            || self.snapshot.is_some()
+            || self.export_svg.is_some()
+    }
+
+    /// Opens the replay at `path` to play along with `engine`'s.
+    fn comparison(
+        &self,
+        engine: &ReplayEngine,
+        path: &Path,
+    ) -> Result<Comparison, Box<dyn error::Error>> {
+        let other = self.engine(path)?;
+        if other.arena() != engine.arena() {
+            return Err("only replays played on the same shape of arena can be compared".into());
+        }
+        let overlay = self.overlay || self.ghost.is_some();
+        if !overlay && self.camera.is_some() {
+            return Err("--camera can't follow two replays side by side, try --overlay".into());
+        }
+        let state = self.render_state(&other, None)?;
+        let comparison = Comparison::new(other, state, self.overlay);
+        match &self.ghost {
+            Some(name) => {
+                find_player(&comparison.engine, name)?;
+                Ok(comparison.with_ghost(engine, name.clone()))
+            }
+            None => Ok(comparison),
+        }
+    }
+
+    /// Parses a replay, ready to play back the way these args ask for.
+    fn engine(&self, path: &Path) -> Result<ReplayEngine, Box<dyn error::Error>> {
+        let replay = if self.lenient {
+            let (replay, recovery) = parse_replay_lenient(path)?;
+            match recovery.expected {
+                Some(expected) => eprintln!(
+                    "recovered {} of {expected} network frames ({:.0}%) from {}",
+                    recovery.frames,
+                    recovery.frames as f64 / expected.max(1) as f64 * 100.0,
+                    path.display()
+                ),
+                None => eprintln!(
+                    "recovered {} network frames from {}",
+                    recovery.frames,
+                    path.display()
+                ),
+            }
+            replay
+        } else {
+            parse_replay(path)?
+        };
+        let mut engine = ReplayEngine::new(replay);
+        warn_unknown_map(&engine);
+        engine.skip_goal_replays(self.skip_goal_replays);
+        engine.set_team_colors(self.team_colors());
+        Ok(engine)
+    }
+
+    /// The `--script` to run on every frame, compiled and ready to go.
+    fn script(&self) -> Result<Option<Script>, Box<dyn error::Error>> {
+        self.script.as_deref().map(Script::load).transpose()
+    }
+
+    /// The replay stepped through into a timeline up front, if `--preprocess` asks for one.
+    fn timeline(&self, engine: &ReplayEngine) -> Option<Timeline> {
+        if !self.preprocess {
+            return None;
+        }
+        let start = Instant::now();
+        let timeline = Timeline::build(engine);
+        eprintln!(
+            "preprocessed {} frames in {:.2}s into {:.1} MB",
+            timeline.len(),
+            start.elapsed().as_secs_f64(),
+            timeline.heap_size() as f64 / 1_000_000.0
+        );
+        Some(timeline)
+    }
+
+    /// The config file's colors, with the background and field cleared away for `--transparent` or
+    /// `--chroma-key`.
+    fn theme(&self) -> Theme {
+        let mut theme = self.settings.colors;
+        let behind = match self.chroma_key {
+            Some(color) => color,
+            None if self.transparent => [0.0; 4],
+            None => return theme,
+        };
+        theme.background = behind;
+        theme.field = behind;
+        theme
+    }
+
+    /// Whether the window is for capturing over other footage, without a border and above
+    /// everything else.
+    fn captured(&self) -> bool {
+        self.transparent || self.chroma_key.is_some()
+    }
+
+    /// The team colors picked on the command line or in the config file.
+    fn team_colors(&self) -> TeamColors {
+        let preset = self.team_colors.or(self.settings.team_colors);
+        let mut colors = match preset.unwrap_or_default() {
+            TeamColorPreset::CLASSIC => TeamColors::default(),
+            TeamColorPreset::COLORBLIND => TeamColors::COLORBLIND,
+        };
+        if let Some(color) = self.orange_color.or(self.settings.orange_color) {
+            colors.orange = team_shades(color);
+        }
+        if let Some(color) = self.blue_color.or(self.settings.blue_color) {
+            colors.blue = team_shades(color);
+        }
+        colors
+    }
+
+    /// The replay file or directory to process, downloading it first if it's on ballchasing.com.
+    fn replay_path(&self) -> Result<PathBuf, Box<dyn error::Error>> {
+        match (self.replay.first(), &self.ballchasing) {
+            (Some(path), _) => Ok(path.to_path_buf()),
+            (None, Some(id_or_url)) => ballchasing::fetch(id_or_url),
+            (None, None) => unreachable!("clap requires --replay or --ballchasing without --watch"),
+        }
+    }
+
+    fn time_range(&self) -> export::TimeRange {
+        export::TimeRange {
+            from: self.from,
+            to: self.to,
+        }
+    }
+}
+
+/// Parses `m:ss` (fractional seconds allowed) or a plain number of seconds.
+fn parse_timestamp(value: &str) -> Result<f32, String> {
+    let seconds = match value.split_once(':') {
+        Some((minutes, seconds)) => {
+            let minutes: u32 = minutes
+                .parse()
+                .map_err(|_| format!("invalid minutes in `{value}`"))?;
+            let seconds: f32 = seconds
+                .parse()
+                .map_err(|_| format!("invalid seconds in `{value}`"))?;
+            if !(0.0..60.0).contains(&seconds) {
+                return Err(format!("seconds in `{value}` aren't between 0 and 60"));
+            }
+            minutes as f32 * 60.0 + seconds
+        }
+        None => value
+            .parse()
+            .map_err(|_| format!("`{value}` is not a time, expected `m:ss` or seconds"))?,
+    };
+
+    if !seconds.is_finite() {
+        return Err(format!(
+            "`{value}` is not a time, expected `m:ss` or seconds"
+        ));
+    }
+    if seconds < 0.0 {
+        return Err(format!("`{value}` is negative"));
+    }
+    Ok(seconds)
+}
+
+/// Parses `ball`, `action` or `player:NAME`.
+fn parse_camera(value: &str) -> Result<Camera, String> {
+    match value.split_once(':') {
+        Some(("player", name)) if !name.is_empty() => Ok(Camera::Player(name.to_string())),
+        None if value == "ball" => Ok(Camera::Ball),
+        None if value == "action" => Ok(Camera::Action),
+        _ => Err(format!(
+            "`{value}` is not a camera, expected `ball`, `player:NAME` or `action`"
+        )),
+    }
+}
+
+/// Checks that a player by this name drives in the replay, naming everyone who does if not.
+fn find_player(engine: &ReplayEngine, name: &str) -> Result<(), Box<dyn error::Error>> {
+    let mut engine = engine.clone();
+    let mut seen_names: Vec<String> = vec![];
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+        for (player, _) in engine.player_positions() {
+            if player.name.eq_ignore_ascii_case(name) {
+                return Ok(());
+            } else if !seen_names.contains(&player.name) {
+                seen_names.push(player.name.clone());
+            }
+        }
+    }
+    Err(format!(
+        "no player named `{name}` in this replay, players are: {}",
+        seen_names.join(", ")
+    )
+    .into())
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, ValueEnum)]
+#[allow(clippy::upper_case_acronyms)]
+enum TeamColorPreset {
+    #[default]
+    CLASSIC,
+    COLORBLIND,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, ValueEnum)]
+// Variants keep the screaming style of the originals even when they span several words
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+enum DisplayType {
+    #[default]
+    POINTS,
+    VORONOI,
+    WEIGHTED_VORONOI,
+    TEAM_VORONOI,
+    PASSING_LANES,
+    TEAM_SHAPE,
+    HEATMAP,
+    PLAYER_HEATMAP,
+    #[value(name = "3d")]
+    THREE_D,
+}
+
+const FONT: &[u8] = include_bytes!("../../assets/DejaVuSans.ttf");
+
+/// Seconds of play shown before a goal when jumping to it.
+const GOAL_LEAD_IN: f32 = 3.0;
+/// Seconds of play shown before a shot when jumping to it.
+const SHOT_LEAD_IN: f32 = 1.5;
+/// Seconds of play shown before an event clicked on the seek bar.
+const EVENT_LEAD_IN: f32 = 1.0;
+/// How much one notch of the mouse wheel zooms the field.
+const ZOOM_STEP: f64 = 1.25;
+/// Pixels the mouse can move while held before a click on the field turns into a pan.
+const CLICK_SLOP: f64 = 4.0;
+/// A clock time being typed in to jump to.
+#[derive(Default)]
+struct TimePrompt {
+    text: String,
+    /// Whether the time entered was unreadable or never shows on the clock.
+    invalid: bool,
+}
+
+impl TimePrompt {
+    fn label(&self) -> String {
+        if self.invalid {
+            format!("Go to clock time: {} isn't in this replay", self.text)
+        } else {
+            format!("Go to clock time: {}_", self.text)
+        }
+    }
+}
+
+/// The space everything in the window is laid out in before being fit to the window's actual size:
+/// the field, the seek bar below it unless in presentation mode, the spread chart and the side view
+/// below that, and another replay beside it when comparing side by side.
+fn layout(
+    arena: &Arena,
+    presentation: bool,
+    side_by_side: bool,
+    spread: bool,
+    side_view: bool,
+) -> [f64; 2] {
+    let [width, mut height] = match presentation {
+        true => render::screen_size(arena),
+        false => render::window_size(arena),
+    };
+    if spread {
+        height += render::SPREAD_HEIGHT;
+    }
+    if side_view {
+        height += render::SIDE_VIEW_HEIGHT;
+    }
+    match side_by_side {
+        true => [width * 2.0, height],
+        false => [width, height],
+    }
+}
+
+/// How often the window plays the replay on, however fast it's playing.
+const UPDATES_PER_SECOND: u64 = 120;
+/// The speeds `Up` and `Down` step through, and the number keys pick from.
+const SPEEDS: [f64; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+struct ReplayVis<R: Renderer> {
+    renderer: R,
+    engine: ReplayEngine,
+    /// The replay preprocessed with `--preprocess`, which seeking jumps through.
+    timeline: Option<Timeline>,
+    state: RenderState,
+    goals: Vec<Goal>,
+    shots: Vec<Shot>,
+    /// Everything marked on the seek bar, to show tooltips for and jump to.
+    events: Vec<Event>,
+    /// Every open net and team outnumbered at the back, marked on the seek bar.
+    situations: Vec<Situation>,
+    /// The rotation rules from the config file, warned about as they're broken.
+    rules: Vec<Rule>,
+    labels: bool,
+    roles: bool,
+    stats: bool,
+    /// Whether the match info from the replay's header is showing.
+    info: bool,
+    /// Whether the window is showing just the field, without the seek bar or player inspector.
+    presentation: bool,
+    side_view: bool,
+
+    /// The player clicked on to inspect, by the actor id of their player info.
+    inspected: Option<ActorId>,
+
+    /// How the field fits in the window as it was last drawn.
+    letterbox: Letterbox,
+    /// Where the mouse is, in the layout the field is drawn in rather than in the window.
+    cursor: [f64; 2],
+    seeking: bool,
+    /// How fast the replay plays, as a multiple of real time.
+    speed: f64,
+    paused: bool,
+    /// Game time owed to playback that's less than the next frame's delta, in seconds.
+    seconds_due: f64,
+    /// Where playback cycles back to from `loop_end`, by network frame.
+    loop_start: Option<usize>,
+    loop_end: Option<usize>,
+    /// The clock time typed into the go to time prompt, while it's open.
+    prompt: Option<TimePrompt>,
+    bookmarks: Bookmarks,
+    /// A second replay to play along with this one.
+    compare: Option<Comparison>,
+    panel: ControlPanel,
+    /// Where the mouse was pressed on the field, while it's held down.
+    drag_start: Option<[f64; 2]>,
+    /// Whether the held mouse has moved far enough to pan instead of click.
+    panning: bool,
+    script: Option<Script>,
+    /// Whether the script asked to pause on the frame just stepped to.
+    script_paused: bool,
+}
+
+impl<R: Renderer> ReplayVis<R> {
+    fn new(
+        renderer: R,
+        engine: ReplayEngine,
+        timeline: Option<Timeline>,
+        state: RenderState,
+        bookmarks: Bookmarks,
+        args: &Args,
+    ) -> Self {
+        let letterbox = Letterbox::new(
+            render::window_size(engine.arena()),
+            render::window_size(engine.arena()),
+        );
+        let goals = engine.goals();
+        let shots = shots::shots(&engine);
+        Self {
+            renderer,
+            events: events::events(&engine, &goals, &shots),
+            situations: situations::situation_log(&engine),
+            rules: args.settings.rules.clone(),
+            goals,
+            shots,
+            engine,
+            timeline,
+            state,
+            labels: args.labels,
+            roles: args.roles,
+            stats: args.stats,
+            side_view: args.side_view,
+            info: false,
+            presentation: args.presentation,
+            inspected: None,
+            letterbox,
+            cursor: [0.0; 2],
+            seeking: false,
+            speed: 1.0,
+            paused: false,
+            seconds_due: 0.0,
+            loop_start: None,
+            loop_end: None,
+            prompt: None,
+            bookmarks,
+            compare: None,
+            panel: ControlPanel::new(true),
+            drag_start: None,
+            panning: false,
+            script: None,
+            script_paused: false,
+        }
+    }
+
+    /// Swaps in another replay, keeping the window's toggles as they are.
+    fn load(
+        &mut self,
+        engine: ReplayEngine,
+        timeline: Option<Timeline>,
+        mut state: RenderState,
+        bookmarks: Bookmarks,
+    ) {
+        state.velocity = self.state.velocity;
+        state.prediction = self.state.prediction;
+        self.goals = engine.goals();
+        self.shots = shots::shots(&engine);
+        self.events = events::events(&engine, &self.goals, &self.shots);
+        self.situations = situations::situation_log(&engine);
+        self.engine = engine;
+        self.timeline = timeline;
+        self.state = state;
+        self.inspected = None;
+        self.bookmarks = bookmarks;
+        self.clear_loop();
+    }
+
+    fn render(&mut self, args: &RenderArgs) {
+        // Draw the cars and ball as far on to the next frame as playback is
+        let seconds = if self.paused { 0.0 } else { self.seconds_due };
+        self.engine.interpolate(seconds as f32);
+        let side_by_side = self.side_by_side();
+        let status = self.panel_status();
+        let engine = &self.engine;
+        let state = &self.state;
+        let goals = &self.goals;
+        let shots = &self.shots;
+        let situations = &self.situations;
+        let rules = &self.rules;
+        let labels = self.labels;
+        let roles = self.roles;
+        let stats = self.stats;
+        let info = self.info;
+        let presentation = self.presentation;
+        let side_view = self.side_view;
+        let speed = self.speed;
+        let paused = self.paused;
+        let loop_frames = (self.loop_start, self.loop_end);
+        let prompt = self.prompt.as_ref().map(TimePrompt::label);
+        let bookmarks = self.bookmarks.frames();
+        let events = &self.events;
+        let hovered = render::event_at(engine, events, self.cursor);
+        // An event's tooltip wins over a situation's under it
+        let hovered_situation =
+            render::situation_at(engine, situations, self.cursor).filter(|_| hovered.is_none());
+        let inspected = self.inspected.filter(|_| !presentation);
+        let compare = self.compare.as_ref();
+        let panel = &self.panel;
+        let metrics = self.script.as_ref().map(Script::metrics);
+        // The spread chart and then the side view go under everything else laid out for one replay
+        let spread = state.has_spread();
+        let [replay_width, spread_top] = layout(engine.arena(), presentation, false, false, false);
+        let [_, side_view_top] = layout(engine.arena(), presentation, false, spread, false);
+        let layout = layout(
+            engine.arena(),
+            presentation,
+            side_by_side,
+            spread,
+            side_view,
+        );
+        // A minimized window has nothing to fit the field into
+        if args.window_size[0] > 0.0 && args.window_size[1] > 0.0 {
+            self.letterbox = Letterbox::new(layout, args.window_size);
+        }
+        let letterbox = self.letterbox;
+        let viewport = args.viewport();
+        let mut scenes = vec![];
+        if state.display == DisplayType::THREE_D {
+            let c = letterbox.context(&Context::new_viewport(viewport));
+            scenes.push(scene::scene(engine, state, c.transform));
+            if let Some(compare) = compare.filter(|compare| !compare.overlay) {
+                let [width, _] = render::screen_size(engine.arena());
+                let c = c.trans(width, 0.0);
+                scenes.push(scene::scene(&compare.engine, &compare.state, c.transform));
+            }
+        }
+        self.renderer.draw(viewport, &scenes, |window, gl, glyphs| {
+            // Nothing covers a see-through background, so the last frame has to be wiped first
+            graphics::clear(state.theme().background, gl);
+            let c = letterbox.context(&window);
+            state.draw(engine, &c, gl);
+            match compare {
+                Some(compare) if compare.overlay => {
+                    let ghost = compare.ghost.as_deref();
+                    render::draw_ghost(&compare.engine, ghost, &state.view, state.theme(), &c, gl);
+                }
+                Some(compare) => {
+                    let [width, _] = render::screen_size(engine.arena());
+                    let c = c.trans(width, 0.0);
+                    let (engine, state) = (&compare.engine, &compare.state);
+                    state.draw(engine, &c, gl);
+                    if labels {
+                        render::draw_labels(engine, state, glyphs, &c, gl);
+                    }
+                    if roles {
+                        render::draw_roles(engine, state, glyphs, &c, gl);
+                    }
+                    render::draw_kickoff(engine, glyphs, &c, gl);
+                    render::draw_summary(engine, glyphs, &c, gl);
+                    if !presentation {
+                        let goals = &compare.goals;
+                        render::draw_timeline(engine, goals, &[], state.theme(), glyphs, &c, gl);
+                    }
+                }
+                None => {}
+            }
+            if labels {
+                render::draw_labels(engine, state, glyphs, &c, gl);
+            }
+            if roles {
+                render::draw_roles(engine, state, glyphs, &c, gl);
+            }
+            if stats {
+                render::draw_stats(engine, glyphs, &c, gl);
+            }
+            render::draw_threat(engine, state, glyphs, &c, gl);
+            render::draw_violations(engine, state, rules, glyphs, &c, gl);
+            if info {
+                render::draw_info(engine, glyphs, &c, gl);
+            }
+            if let Some(metrics) = &metrics {
+                render::draw_metrics(engine, metrics, glyphs, &c, gl);
+            }
+            if let Some(player) = inspected {
+                render::draw_inspector(engine, &state.view, player, glyphs, &c, gl);
+            }
+            render::draw_kickoff(engine, glyphs, &c, gl);
+            if let Some(prompt) = &prompt {
+                render::draw_prompt(engine, prompt, glyphs, &c, gl);
+            }
+            render::draw_summary(engine, glyphs, &c, gl);
+            if !presentation {
+                render::draw_speed(engine, speed, paused, glyphs, &c, gl);
+                render::draw_timeline(engine, goals, shots, state.theme(), glyphs, &c, gl);
+                render::draw_situations(engine, situations, hovered_situation, glyphs, &c, gl);
+                render::draw_events(engine, events, hovered, glyphs, &c, gl);
+                render::draw_demolitions(engine, glyphs, &c, gl);
+                render::draw_loop(engine, loop_frames, &c, gl);
+                render::draw_bookmarks(engine, bookmarks, glyphs, &c, gl);
+                panel.draw(engine.arena(), &status, glyphs, &c, gl);
+            }
+            if spread {
+                let c = c.trans(0.0, spread_top);
+                render::draw_spread(engine, state, &c, gl);
+                if let Some(compare) = compare.filter(|compare| !compare.overlay) {
+                    let c = c.trans(replay_width, 0.0);
+                    render::draw_spread(&compare.engine, &compare.state, &c, gl);
+                }
+            }
+            if side_view {
+                let c = c.trans(0.0, side_view_top);
+                render::draw_side_view(engine, state, &c, gl);
+                if let Some(compare) = compare.filter(|compare| !compare.overlay) {
+                    render::draw_side_view(
+                        &compare.engine,
+                        &compare.state,
+                        &c.trans(replay_width, 0.0),
+                        gl,
+                    );
+                }
+            }
+            letterbox.draw_bars(state.theme(), &window, gl);
+        })
+    }
+
+    /// What the control panel has to show for the window as it is.
+    fn panel_status(&self) -> controls::Status {
+        let mut players: Vec<_> = self.engine.players().values().collect();
+        players.sort_by(|a, b| a.name.cmp(&b.name));
+        players.sort_by_key(|player| player.team == Team::Blue);
+        players.dedup_by(|a, b| a.name == b.name);
+        controls::Status {
+            paused: self.paused,
+            speed: self.speed,
+            display: self.state.display,
+            player_heatmap: self.state.has_player_heatmap(),
+            three_d: R::DRAWS_SCENES,
+            players: players
+                .into_iter()
+                .map(|player| PlayerRow {
+                    name: player.name.clone(),
+                    color: player.color,
+                    shown: self.state.shows(player),
+                })
+                .collect(),
+        }
+    }
+
+    /// Does what was clicked on the control panel.
+    fn apply(&mut self, control: Control) {
+        match control {
+            Control::PlayPause => self.paused = !self.paused,
+            Control::Speed(speed) => self.speed = speed,
+            Control::Display(display) => {
+                self.state.display = display;
+                if let Some(compare) = &mut self.compare {
+                    compare.state.display = display;
+                }
+            }
+            Control::TogglePlayer(name) => {
+                if !self.state.hidden.remove(&name) {
+                    self.state.hidden.insert(name);
+                }
+            }
+        }
+    }
+
+    /// Clicks the control panel if the mouse is over it. Otherwise jumps to the event under the
+    /// mouse on the timeline, or starts seeking if the mouse was pressed anywhere else on it, or
+    /// starts a click or drag on the field.
+    fn press(&mut self) {
+        if !self.presentation {
+            let status = self.panel_status();
+            let arena = self.engine.arena();
+            let over_panel = self.panel.contains(arena, &status, self.cursor);
+            if let Some(control) = self.panel.press(arena, &status, self.cursor) {
+                self.apply(control);
+            }
+            if over_panel {
+                return;
+            }
+        }
+
+        let event = render::event_at(&self.engine, &self.events, self.cursor);
+        let situation = render::situation_at(&self.engine, &self.situations, self.cursor);
+        if let Some(frame) = event
+            .map(|event| event.frame)
+            .or(situation.map(|situation| situation.start_frame))
+            .filter(|_| self.on_timeline())
+        {
+            let lead_in = (EVENT_LEAD_IN * self.engine.record_fps()) as usize;
+            let target = frame.saturating_sub(lead_in);
+            self.move_frame(target as i32 - self.engine.frame_index() as i32);
+        } else if self.on_timeline() {
+            self.seeking = true;
+            self.seek_to_cursor();
+        } else {
+            self.drag_start = Some(self.cursor);
+            self.panning = false;
+        }
+    }
+
+    /// Lets go of the mouse, which inspects whoever was clicked on unless the field was dragged.
+    fn release(&mut self) {
+        self.seeking = false;
+        self.panel.release();
+        let three_d = self.state.display == DisplayType::THREE_D;
+        if self.drag_start.take().is_some() && !self.panning && !self.presentation && !three_d {
+            self.inspected = render::player_at(&self.engine, &self.state.view, self.cursor);
+        }
+    }
+
+    fn move_cursor(&mut self, cursor: [f64; 2]) {
+        let cursor = self.letterbox.to_layout(cursor);
+        let delta = [cursor[0] - self.cursor[0], cursor[1] - self.cursor[1]];
+        self.cursor = cursor;
+        if self.seeking {
+            self.seek_to_cursor();
+        }
+        let status = self.panel_status();
+        if let Some(control) = self.panel.slide(self.engine.arena(), &status, cursor) {
+            self.apply(control);
+        }
+        // Side by side, a zoomed field would be drawn over the one beside it
+        if let Some([x, y]) = self.drag_start.filter(|_| !self.side_by_side()) {
+            if self.state.display == DisplayType::THREE_D {
+                self.panning = true;
+                self.state.orbit.rotate(delta);
+            } else if !self.panning && (cursor[0] - x).hypot(cursor[1] - y) > CLICK_SLOP {
+                self.panning = true;
+                // Catch up on the movement that was still in the slop
+                self.state
+                    .view
+                    .pan(self.engine.arena(), [cursor[0] - x, cursor[1] - y]);
+            } else if self.panning {
+                self.state.view.pan(self.engine.arena(), delta);
+            }
+        }
+    }
+
+    /// Zooms the field in or out around the cursor by a number of mouse wheel notches.
+    fn scroll(&mut self, notches: f64) {
+        if self.on_timeline() || self.side_by_side() {
+            return;
+        }
+        if self.state.display == DisplayType::THREE_D {
+            self.state.orbit.zoom(ZOOM_STEP.powf(notches));
+            return;
+        }
+        self.state
+            .view
+            .zoom_at(self.engine.arena(), self.cursor, ZOOM_STEP.powf(notches));
+    }
+
+    /// Whether a second replay is being drawn beside this one.
+    fn side_by_side(&self) -> bool {
+        self.compare
+            .as_ref()
+            .is_some_and(|compare| !compare.overlay)
+    }
+
+    /// Whether the mouse is over the seek bar, which presentation mode hides.
+    fn on_timeline(&self) -> bool {
+        !self.presentation
+            && render::rect_contains(render::timeline_rect(self.engine.arena()), self.cursor)
+    }
+
+    fn seek_to_cursor(&mut self) {
+        let target = render::timeline_frame(&self.engine, self.cursor[0]);
+        self.move_frame(target as i32 - self.engine.frame_index() as i32);
+    }
+
+    /// Jumps to shortly before the next goal, or the previous one when going backwards.
+    fn jump_to_goal(&mut self, forward: bool) {
+        let frames: Vec<usize> = self.goals.iter().map(|goal| goal.frame).collect();
+        self.jump_to(&frames, GOAL_LEAD_IN, forward);
+    }
+
+    /// Jumps to shortly before the next shot on goal, or the previous one when going backwards.
+    fn jump_to_shot(&mut self, forward: bool) {
+        let frames: Vec<usize> = self
+            .shots
+            .iter()
+            .filter(|shot| shot.kind == ShotKind::Shot)
+            .map(|shot| shot.frame)
+            .collect();
+        self.jump_to(&frames, SHOT_LEAD_IN, forward);
+    }
+
+    /// Jumps to `lead_in` seconds before the next of `frames`, or the previous one when going
+    /// backwards.
+    fn jump_to(&mut self, frames: &[usize], lead_in: f32, forward: bool) {
+        let lead_in = (lead_in * self.engine.record_fps()) as usize;
+        let current = self.engine.frame_index();
+        let mut targets = frames.iter().map(|frame| frame.saturating_sub(lead_in));
+        let target = if forward {
+            targets.find(|&target| target > current)
+        } else {
+            targets.rev().find(|&target| target < current)
+        };
+
+        if let Some(target) = target {
+            self.move_frame(target as i32 - current as i32);
+        }
+    }
+
+    fn move_frame(&mut self, frame: i32) {
+        // Stepping on a frame plays through it, as does moving ahead without a timeline to jump on
+        if frame == 1 || (frame > 0 && self.timeline.is_none()) {
+            for _ in 0..frame {
+                self.step();
+            }
+            // Only frames played through pause for the script, not ones skipped over
+            self.script_paused = false;
+            return;
+        }
+
+        match &self.timeline {
+            Some(timeline) => {
+                let frames = self.engine.frame_count() as i64;
+                let target = self.engine.frame_index() as i64 + frame as i64;
+                // Going back past the start wraps around to the end, the same as without one
+                let target = if target < 0 {
+                    target.rem_euclid(frames.max(1))
+                } else {
+                    target.min(frames)
+                };
+                timeline.seek(&mut self.engine, target as usize);
+                self.state.jump(&self.engine, timeline);
+            }
+            None => {
+                self.engine.move_frame(frame);
+                // Nothing steps while paused, so catch the camera and trails up now
+                self.state.observe(&self.engine);
+            }
+        }
+        if let Some(compare) = &mut self.compare {
+            compare.follow(&self.engine);
+        }
+    }
+
+    /// Plays the replay on by however many frames `args.dt` is worth at the current speed, stopping
+    /// early and returning true if a kickoff countdown starts.
+    fn update(&mut self, args: &UpdateArgs) -> bool {
+        if self.paused {
+            return false;
+        }
+
+        self.seconds_due += args.dt * self.speed;
+        loop {
+            // The end of the replay is held for a frame before playback starts over
+            let delta = self
+                .engine
+                .next_frame_delta()
+                .unwrap_or(1.0 / self.engine.record_fps()) as f64;
+            if self.seconds_due < delta {
+                break;
+            }
+            self.seconds_due -= delta;
+            if self.step() {
+                return true;
+            }
+            if std::mem::take(&mut self.script_paused) {
+                self.paused = true;
+                return false;
+            }
+            if let (Some(start), Some(end)) = (self.loop_start, self.loop_end) {
+                if self.engine.frame_index() >= end {
+                    self.move_frame(start as i32 - self.engine.frame_index() as i32);
+                }
+            }
+        }
+        false
+    }
+
+    /// Bookmarks the current frame, or takes its bookmark away if it has one.
+    fn toggle_bookmark(&mut self) {
+        if let Err(e) = self.bookmarks.toggle(self.engine.frame_index()) {
+            eprintln!("{e}");
+        }
+    }
+
+    /// Jumps to the next bookmark, or the previous one when going backwards.
+    fn jump_to_bookmark(&mut self, forward: bool) {
+        let frames = self.bookmarks.frames().to_vec();
+        self.jump_to(&frames, 0.0, forward);
+    }
+
+    /// Jumps to where the game clock shows `seconds` left, returning false if it never does.
+    fn go_to_clock(&mut self, seconds: f32) -> bool {
+        let Some(frame) = self.engine.clock_frame(seconds) else {
+            return false;
+        };
+        // Stepping through the frame itself puts everything where it is at that time
+        self.move_frame(frame as i32 + 1 - self.engine.frame_index() as i32);
+        true
+    }
+
+    /// Types into the go to time prompt while it's open, returning whether it took the key.
+    fn prompt_key(&mut self, key: Key) -> bool {
+        let Some(prompt) = &mut self.prompt else {
+            return false;
+        };
+        match key {
+            Key::Return => {
+                let seconds = parse_timestamp(&prompt.text).ok();
+                if seconds.is_some_and(|seconds| self.go_to_clock(seconds)) {
+                    self.prompt = None;
+                } else if let Some(prompt) = &mut self.prompt {
+                    prompt.invalid = true;
+                }
+            }
+            Key::Backspace => {
+                prompt.text.pop();
+                prompt.invalid = false;
+            }
+            Key::Escape => self.prompt = None,
+            _ => {}
+        }
+        true
+    }
+
+    /// Starts the loop at the current frame, dropping its end if that's no longer after the start.
+    fn set_loop_start(&mut self) {
+        let frame = self.engine.frame_index();
+        self.loop_start = Some(frame);
+        if self.loop_end.is_some_and(|end| end <= frame) {
+            self.loop_end = None;
+        }
+    }
+
+    /// Ends the loop at the current frame, looping from the beginning of the replay if it has no
+    /// start yet. Does nothing before the start.
+    fn set_loop_end(&mut self) {
+        let frame = self.engine.frame_index();
+        let start = self.loop_start.unwrap_or(0);
+        if frame > start {
+            self.loop_start = Some(start);
+            self.loop_end = Some(frame);
+        }
+    }
+
+    fn clear_loop(&mut self) {
+        self.loop_start = None;
+        self.loop_end = None;
+    }
+
+    /// Steps the replay forward one frame, returning whether a kickoff countdown just started.
+    fn step(&mut self) -> bool {
+        let waiting = self.engine.kickoff().is_some();
+        self.engine.step();
+        self.state.observe(&self.engine);
+        if let Some(script) = &mut self.script {
+            match script.on_frame(&self.engine) {
+                Ok(pause) => self.script_paused |= pause,
+                Err(e) => {
+                    // Playback carries on without a script that's failed
+                    eprintln!("{e}, stopping the script");
+                    self.script = None;
+                }
+            }
+        }
+        if let Some(compare) = &mut self.compare {
+            compare.follow(&self.engine);
+        }
+        !waiting && self.engine.kickoff().is_some()
+    }
+
+    /// Moves to the next preset speed up, or down when `faster` is false, staying put at the ends.
+    fn change_speed(&mut self, faster: bool) {
+        let next = if faster {
+            SPEEDS.iter().find(|&&speed| speed > self.speed)
+        } else {
+            SPEEDS.iter().rev().find(|&&speed| speed < self.speed)
+        };
+        if let Some(&speed) = next {
+            self.speed = speed;
+        }
+    }
+}
+
+fn run(
+    args: &Args,
+    engine: ReplayEngine,
+    timeline: Option<Timeline>,
+    state: RenderState,
+    bookmarks: Bookmarks,
+    compare: Option<Comparison>,
+    watcher: Option<ReplayWatcher>,
+) -> Result<(), Box<dyn error::Error>> {
+    let start_at = match args.start_at {
+        Some(seconds) => Some(
+            engine
+                .clock_frame(seconds)
+                .ok_or("the game clock never shows the --start-at time")?,
+        ),
+        None => None,
+    };
+
+    let side_by_side = compare.as_ref().is_some_and(|compare| !compare.overlay);
+    let size = layout(
+        engine.arena(),
+        false,
+        side_by_side,
+        state.has_spread(),
+        args.side_view,
+    );
+    let settings = WindowSettings::new("Replay", size)
+        // Escape closes the go to time prompt before it closes the window
+        .exit_on_esc(false)
+        .fullscreen(args.fullscreen)
+        .decorated(!args.captured())
+        .transparent(args.transparent);
+    match args.renderer {
+        Backend::OPENGL => {
+            let (window, renderer) = GlRenderer::open(&settings)?;
+            let viz = ReplayVis::new(renderer, engine, timeline, state, bookmarks, args);
+            play(args, window, viz, compare, watcher, start_at)
+        }
+        #[cfg(feature = "wgpu")]
+        Backend::WGPU => {
+            let (window, renderer) = wgpu_renderer::WgpuRenderer::open(&settings)?;
+            let viz = ReplayVis::new(renderer, engine, timeline, state, bookmarks, args);
+            play(args, window, viz, compare, watcher, start_at)
+        }
+    }
+}
+
+/// Plays the replay in `window`, drawing it with `viz`'s renderer, until the window's closed.
+fn play<W: AppWindow, R: Renderer>(
+    args: &Args,
+    mut window: W,
+    mut viz: ReplayVis<R>,
+    compare: Option<Comparison>,
+    mut watcher: Option<ReplayWatcher>,
+    start_at: Option<usize>,
+) -> Result<(), Box<dyn error::Error>> {
+    if args.captured() {
+        window.winit().set_window_level(WindowLevel::AlwaysOnTop);
+    }
+    window.winit().set_cursor_visible(!args.presentation);
+
+    viz.compare = compare;
+    viz.script = args.script()?;
+    viz.speed = args.speed(viz.engine.record_fps());
+    if let Some(frame) = start_at {
+        viz.move_frame(frame as i32 + 1);
+    }
+
+    let keys = args.settings.keys;
+    let mut shift = false;
+    let mut events = Events::new(EventSettings::new().max_fps(60).ups(UPDATES_PER_SECOND));
+    while let Some(e) = events.next(&mut window) {
+        if let Some(args) = e.render_args() {
+            viz.render(&args);
+        }
+
+        if let Some(update) = e.update_args() {
+            if viz.update(&update) && args.pause_on_kickoff {
+                viz.paused = true;
+            }
+        }
+
+        if let Some(path) = watcher.as_mut().and_then(ReplayWatcher::poll) {
+            match open_replay(args, &path) {
+                Ok((engine, timeline, state, bookmarks)) => {
+                    // Replays from another mode are played on a differently shaped arena
+                    let resize = engine.arena() != viz.engine.arena();
+                    viz.load(engine, timeline, state, bookmarks);
+                    if resize {
+                        window.set_size(layout(
+                            viz.engine.arena(),
+                            false,
+                            viz.side_by_side(),
+                            viz.state.has_spread(),
+                            viz.side_view,
+                        ));
+                    }
+                }
+                Err(e) => eprintln!("couldn't open {}: {e}", path.display()),
+            }
+        }
+
+        if let Some(text) = e.text_args() {
+            if let Some(prompt) = &mut viz.prompt {
+                let typed = text
+                    .chars()
+                    .filter(|c| c.is_ascii_digit() || matches!(c, ':' | '.'));
+                prompt.text.extend(typed);
+                prompt.invalid = false;
+            }
+        }
+
+        if let Some(cursor) = e.mouse_cursor_args() {
+            viz.move_cursor(cursor);
+        }
+
+        if let Some([_, notches]) = e.mouse_scroll_args() {
+            viz.scroll(notches);
+        }
+
+        if let Some(args) = e.button_args() {
+            if args.button == Button::Mouse(MouseButton::Left) {
+                match args.state {
+                    ButtonState::Press => viz.press(),
+                    ButtonState::Release => viz.release(),
+                }
+                continue;
+            }
+
+            if let Button::Keyboard(Key::LShift | Key::RShift) = args.button {
+                shift = args.state == ButtonState::Press;
+            }
+
+            if args.state != ButtonState::Press {
+                continue;
+            }
+
+            let Button::Keyboard(key) = args.button else {
+                continue;
+            };
+            if viz.prompt_key(key) {
+                continue;
+            }
+            if key == Key::Escape {
+                window.set_should_close(true);
+                continue;
+            }
+            match keys.action(key) {
+                Some(Action::GoTo) => viz.prompt = Some(TimePrompt::default()),
+                Some(Action::Pause) => viz.paused = !viz.paused,
+                Some(Action::Back) => viz.move_frame(-150),
+                Some(Action::Forward) => viz.move_frame(150),
+                // Playing steps on by itself, so these only do anything while paused
+                Some(Action::StepBack | Action::StepForward) if !viz.paused => {}
+                Some(Action::StepBack) => viz.move_frame(-1),
+                Some(Action::StepForward) => viz.move_frame(1),
+                Some(Action::LoopStart) => viz.set_loop_start(),
+                Some(Action::LoopEnd) => viz.set_loop_end(),
+                Some(Action::ClearLoop) => viz.clear_loop(),
+                Some(Action::Bookmark) => viz.toggle_bookmark(),
+                Some(Action::NextBookmark) => viz.jump_to_bookmark(!shift),
+                Some(Action::Goal) => viz.jump_to_goal(!shift),
+                Some(Action::Shot) => viz.jump_to_shot(!shift),
+                Some(Action::Labels) => viz.labels = !viz.labels,
+                Some(Action::Roles) => viz.roles = !viz.roles,
+                Some(Action::Stats) => viz.stats = !viz.stats,
+                Some(Action::SideView) => viz.side_view = !viz.side_view,
+                Some(Action::Velocity) => viz.state.velocity = !viz.state.velocity,
+                Some(Action::Prediction) => viz.state.prediction = !viz.state.prediction,
+                Some(Action::ResetView) => {
+                    viz.state.view = View::default();
+                    viz.state.orbit = Orbit::default();
+                }
+                Some(Action::Presentation) => {
+                    viz.presentation = !viz.presentation;
+                    window.winit().set_cursor_visible(!viz.presentation);
+                }
+                Some(Action::Fullscreen) => {
+                    let fullscreen = match window.winit().fullscreen() {
+                        Some(_) => None,
+                        None => Some(Fullscreen::Borderless(None)),
+                    };
+                    window.winit().set_fullscreen(fullscreen);
+                }
+                Some(Action::Controls) => viz.panel.open = !viz.panel.open,
+                Some(Action::Info) => viz.info = !viz.info,
+                Some(Action::Faster) => viz.change_speed(true),
+                Some(Action::Slower) => viz.change_speed(false),
+                Some(Action::Speed(preset)) => viz.speed = SPEEDS[preset],
+                None => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_touch_log(engine: &ReplayEngine, path: &Path) -> Result<(), Box<dyn error::Error>> {
+    let mut out = BufWriter::new(create(path)?);
+    writeln!(out, "frame,time,player,team,x,y,z,speed_before,speed_after")?;
+    for touch in touches::touches(engine) {
+        writeln!(
+            out,
+            "{},{},{},{:?},{},{},{},{:.0},{:.0}",
+            touch.frame,
+            touch.time,
+            batch::csv_field(&touch.player),
+            touch.team,
+            touch.location.x,
+            touch.location.y,
+            touch.location.z,
+            touch.speed_before(),
+            touch.speed_after(),
+        )?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Adds up each player's time in each rotation role over the replay's live play and writes the
+/// shares as a CSV.
+fn write_role_shares(mut engine: ReplayEngine, path: &Path) -> Result<(), Box<dyn error::Error>> {
+    let mut shares = RoleShares::default();
+    let mut last_time = None;
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+        let seconds = last_time.map_or(0.0, |last| engine.time() - last);
+        last_time = Some(engine.time());
+        if matches!(engine.phase(), GamePhase::Regulation | GamePhase::Overtime) {
+            shares.observe(&engine, seconds);
+        }
+    }
+
+    let mut out = BufWriter::new(create(path)?);
+    writeln!(out, "player,team,first,second,third")?;
+    for (player, team, [first, second, third]) in shares.shares() {
+        writeln!(
+            out,
+            "{},{team:?},{first:.3},{second:.3},{third:.3}",
+            batch::csv_field(player),
+        )?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Writes every stretch of the replay a team spent breaking one of `rules` as a CSV.
+fn write_violations(
+    engine: &ReplayEngine,
+    rules: &[Rule],
+    path: &Path,
+) -> Result<(), Box<dyn error::Error>> {
+    if rules.is_empty() {
+        return Err("--violations needs [[rules]] in the config file to check".into());
+    }
+    let mut out = BufWriter::new(create(path)?);
+    writeln!(out, "start_frame,end_frame,time,seconds,team,rule,players")?;
+    for span in rotation::violation_log(engine, rules) {
+        writeln!(
+            out,
+            "{},{},{},{:.2},{:?},{},{}",
+            span.start_frame,
+            span.end_frame,
+            span.time,
+            span.seconds,
+            span.team,
+            batch::csv_field(&rules[span.rule].describe()),
+            batch::csv_field(&span.players.join("; ")),
+        )?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Plays the replay through and writes each player's boost stats as a CSV.
+fn write_boost_stats(mut engine: ReplayEngine, path: &Path) -> Result<(), Box<dyn error::Error>> {
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+    }
+
+    let mut out = BufWriter::new(create(path)?);
+    batch::write_boost_stats(&engine, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Everything notable that happened in a replay, as written by `--events`.
+#[derive(Serialize)]
+struct ReplayEvents {
+    goals: Vec<Goal>,
+    shots: Vec<Shot>,
+    demolitions: Vec<DemolitionRecord>,
+    situations: Vec<Situation>,
+    players: Vec<PlayerTotals>,
+}
+
+/// How a player moved over the whole replay's live play, in unreal units and seconds.
+#[derive(Serialize)]
+struct PlayerTotals {
+    name: String,
+    team: Team,
+    average_speed: f32,
+    seconds_supersonic: f32,
+    seconds_airborne: f32,
+    distance: f32,
+}
+
+fn write_events(engine: &ReplayEngine, path: &Path) -> Result<(), Box<dyn error::Error>> {
+    let mut finished = engine.clone();
+    while finished.frame_index() < finished.frame_count() {
+        finished.step();
+    }
+    let players = finished
+        .movement()
+        .players()
+        .into_iter()
+        .map(|(name, team, movement)| PlayerTotals {
+            name: name.to_string(),
+            team,
+            average_speed: movement.average_speed(),
+            seconds_supersonic: movement.seconds_supersonic,
+            seconds_airborne: movement.seconds_airborne,
+            distance: movement.distance,
+        })
+        .collect();
+    let events = ReplayEvents {
+        goals: engine.goals(),
+        shots: shots::shots(engine),
+        demolitions: finished.demolition_log().to_vec(),
+        situations: situations::situation_log(engine),
+        players,
+    };
+    let mut out = BufWriter::new(create(path)?);
+    serde_json::to_writer_pretty(&mut out, &events)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// A line of `--headless` output with the metrics a `--script` has set so far.
+#[derive(Serialize)]
+struct ScriptedFrame {
+    #[serde(flatten)]
+    summary: FrameSummary,
+    metrics: BTreeMap<String, rhai::Dynamic>,
+}
+
+fn run_headless(
+    args: &Args,
+    mut engine: ReplayEngine,
+    timeline: Option<&Timeline>,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    // The script needs the engine at each frame, so it can't go by the timeline
+    if let Some(mut script) = args.script()? {
+        while engine.frame_index() < engine.frame_count() {
+            engine.step();
+            script.on_frame(&engine)?;
+            let frame = ScriptedFrame {
+                summary: engine.summary(),
+                metrics: script.metrics(),
+            };
+            serde_json::to_writer(&mut out, &frame)?;
+            out.write_all(b"\n")?;
+        }
+    } else if let Some(timeline) = timeline {
+        for entry in timeline.frames() {
+            serde_json::to_writer(&mut out, &timeline.summary(entry))?;
+            out.write_all(b"\n")?;
+        }
+    } else {
+        while engine.frame_index() < engine.frame_count() {
+            engine.step();
+            serde_json::to_writer(&mut out, &engine.summary())?;
+            out.write_all(b"\n")?;
+        }
+    }
+    out.flush()?;
+
+    Ok(())
+}
+
+fn run_dump(args: &DumpArgs) -> Result<(), Box<dyn error::Error>> {
+    let replay = parse_replay(&args.replay)?;
+    match args.format {
+        DumpFormat::TEXT => dump(&replay, &args.out)?,
+        DumpFormat::JSON => dump_json(&replay, &args.out)?,
+        #[cfg(feature = "parquet")]
+        DumpFormat::PARQUET => crate::parquet_dump::dump_parquet(&replay, &args.out)?,
+    }
+    Ok(())
+}
+
+fn run_serve(args: &ServeArgs) -> Result<(), Box<dyn error::Error>> {
+    if args.display == DisplayType::PLAYER_HEATMAP {
+        return Err("serve can't render the player-heatmap display, which needs a player".into());
+    }
+    if args.display == DisplayType::THREE_D {
+        return Err("serve can't render the 3d display, which only opens in the window".into());
+    }
+    let engine = ReplayEngine::new(parse_replay(&args.replay)?);
+    warn_unknown_map(&engine);
+    serve::serve(engine, args.display, &args.listen)
+}
+
+/// Says when a replay's labs map has no arena of its own and a standard pitch stands in for it.
+fn warn_unknown_map(engine: &ReplayEngine) {
+    if let Some(map) = engine.unknown_map() {
+        eprintln!("no arena known for the labs map {map}, assuming a standard pitch");
+    }
+}
+
+/// A replay opened to play in the window, with its timeline if it was preprocessed.
+type OpenReplay = (ReplayEngine, Option<Timeline>, RenderState, Bookmarks);
+
+fn open_replay(args: &Args, path: &Path) -> Result<OpenReplay, Box<dyn error::Error>> {
+    let engine = args.engine(path)?;
+    let timeline = args.timeline(&engine);
+    let state = args.render_state(&engine, timeline.as_ref())?;
+    Ok((engine, timeline, state, Bookmarks::load(path)?))
+}
+
+/// Opens the newest replay in `dir`, or waits for one to be saved, then keeps switching to each
+/// replay saved there after.
+fn run_watching(args: &Args, dir: &Path) -> Result<(), Box<dyn error::Error>> {
+    // Watch first so a replay saved while looking for the newest one isn't missed
+    let mut watcher = ReplayWatcher::new(dir)?;
+    let path = match watch::newest_replay(dir)? {
+        Some(path) => path,
+        None => {
+            eprintln!("waiting for a replay to be saved in {}", dir.display());
+            watcher.wait()
+        }
+    };
+
+    let (engine, timeline, state, bookmarks) = open_replay(args, &path)?;
+    run(
+        args,
+        engine,
+        timeline,
+        state,
+        bookmarks,
+        None,
+        Some(watcher),
+    )
+}
+
+fn visualize(args: &Args) -> Result<(), Box<dyn error::Error>> {
+    if let Some(dir) = &args.watch {
+        return run_watching(args, dir);
+    }
+
+    if let Some(jobs) = args.jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs.get())
+            .build_global()?;
+    }
+
+    let path = args.replay_path()?;
+    match args.replay.len() {
+        0 | 1 if args.overlay || args.ghost.is_some() => {
+            return Err("--overlay and --ghost need a second --replay to draw".into());
+        }
+        0 | 1 => {}
+        2 if args.writes_output() || args.tui || path.is_dir() => {
+            return Err("two replays can only be compared in the window".into());
+        }
+        2 => {}
+        _ => return Err("--replay can only be given twice, to compare two replays".into()),
+    }
+    if let Some(player) = &args.focus_player {
+        let paths = if path.is_dir() {
+            batch::replay_paths(&path)?
+        } else {
+            vec![path]
+        };
+        let report = args.report.as_ref().expect("clap requires --report");
+        return report::write_player_report(&paths, player, args.skip_goal_replays, report);
+    }
+    if path.is_dir() {
+        let out = args
+            .output
+            .as_ref()
+            .ok_or("--output must name a directory to write to when --replay is a directory")?;
+        return batch::run_batch(&path, out, args.skip_goal_replays);
+    }
+
+    let engine = args.engine(&path)?;
+    if args.bench_replay {
+        return bench::run_bench(&engine);
+    }
+    if let Some(path) = &args.touch_log {
+        return write_touch_log(&engine, path);
+    }
+    if let Some(path) = &args.events {
+        return write_events(&engine, path);
+    }
+    if let Some(path) = &args.role_shares {
+        return write_role_shares(engine, path);
+    }
+    if let Some(path) = &args.boost_stats {
+        return write_boost_stats(engine, path);
+    }
+    if let Some(path) = &args.report {
+        return report::write_report(engine, path);
+    }
+    if let Some(path) = &args.violations {
+        return write_violations(&engine, &args.settings.rules, path);
+    }
+    if let Some(path) = &args.shot_chart {
+        return shot_chart::write_shot_chart(&engine, path);
+    }
+    if let Some(path) = &args.pressure_chart {
+        return chart::write_pressure_chart(engine, path);
+    }
+    let timeline = args.timeline(&engine);
+    if args.headless {
+        return run_headless(args, engine, timeline.as_ref());
+    }
+    if args.tui {
+        let speed = args.speed(engine.record_fps());
+        return tui::run_tui(engine, speed);
+    }
+
+    let state = args.render_state(&engine, timeline.as_ref())?;
+    if let Some(path) = &args.export {
+        export::export_video(engine, state, args.time_range(), timeline.as_ref(), path)?;
+    } else if let Some(path) = &args.export_gif {
+        export::export_gif(engine, state, args.time_range(), timeline.as_ref(), path)?;
+    } else if let Some(time) = args.snapshot {
+        let path = args
+            .output
+            .as_ref()
+            .expect("clap requires --output with --snapshot");
+        export::export_snapshot(engine, state, time, timeline.as_ref(), path)?;
+    } else if let Some(time) = args.export_svg {
+        let path = args
+            .output
+            .as_ref()
+            .expect("clap requires --output with --export-svg");
+        export::export_svg(engine, state, time, args.labels, timeline.as_ref(), path)?;
+    } else {
+        let compare = match args.replay.get(1) {
+            Some(path) => Some(args.comparison(&engine, path)?),
+            None => None,
+        };
+        let bookmarks = Bookmarks::load(&path)?;
+        run(args, engine, timeline, state, bookmarks, compare, None)?;
+    }
+
+    Ok(())
+}
+
+/// Runs the visualizer on the process's command line, offering `--layer` every overlay in
+/// `overlays`. The binary calls this with [`OverlayRegistry::builtin`]; register your own layers
+/// on that registry and call it from your own `main` to ship them.
+pub fn run_with(overlays: OverlayRegistry) -> ExitCode {
+    match try_main(overlays) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parses the command line, offering `--layer` every overlay in `overlays`. Register an overlay
+/// before calling this and it can be picked like the built-in ones.
+fn parse_cli(overlays: &OverlayRegistry) -> Cli {
+    let layers = || PossibleValuesParser::new(overlays.names());
+    let mut command = Cli::command()
+        .mut_arg("layer", |arg| arg.value_parser(layers()))
+        .mut_subcommand("visualize", |visualize| {
+            visualize.mut_arg("layer", |arg| arg.value_parser(layers()))
+        });
+    let matches = command.get_matches_mut();
+    Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.format(&mut command).exit())
+}
+
+fn try_main(overlays: OverlayRegistry) -> Result<(), Box<dyn error::Error>> {
+    let cli = parse_cli(&overlays);
+    let mut args = match cli.command {
+        Some(Command::Visualize(args)) => *args,
+        Some(Command::Dump(args)) => return run_dump(&args),
+        Some(Command::Serve(args)) => return run_serve(&args),
+        None => cli
+            .visualize
+            .expect("clap requires a replay to open without a subcommand"),
+    };
+    if let Some(dump) = args.legacy_dump()? {
+        eprintln!("--dump-json and --dump-parquet are deprecated, use the dump subcommand instead");
+        return run_dump(&dump);
+    }
+    args.settings = Config::load(args.config.as_deref())?;
+    args.overlays = overlays;
+    visualize(&args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_are_read_as_minutes_and_seconds_or_seconds() {
+        assert_eq!(parse_timestamp("3:45"), Ok(225.0));
+        assert_eq!(parse_timestamp("0:07.5"), Ok(7.5));
+        assert_eq!(parse_timestamp("90"), Ok(90.0));
+    }
+
+    #[test]
+    fn timestamps_that_are_not_a_time_on_the_clock_are_refused() {
+        for value in [
+            "NaN", "inf", "-inf", "1:NaN", "1:inf", "-5", "1:-5", "1:60", "1:75", "-1:30", "",
+        ] {
+            assert!(parse_timestamp(value).is_err(), "{value}");
+        }
+    }
+}
@@ -0,0 +1,2959 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use boxcars::{ActorId, RigidBody, Vector3f};
+use graphics::character::CharacterCache;
+use graphics::ellipse::circle;
+use graphics::{Context, Graphics};
+use rhai::Dynamic;
+use serde::Deserialize;
+
+use crate::app::scene::{Orbit, Projection};
+use crate::app::{config, DisplayType};
+use crate::arena::{Arena, ArenaKind, TileState};
+use crate::coverage::{self, THREAT_MARGIN};
+use crate::events::{Event, EventKind};
+use crate::heatmap::{PositionHeatmap, ZoneHeatmap, CELL_SIZE};
+use crate::movement::{AirState, SUPERSONIC_SPEED};
+use crate::overlay::{OverlayPlugin, Painter};
+use crate::prediction;
+use crate::rotation::{self, Rule};
+use crate::shots::{Shot, ShotKind};
+use crate::situations::{Situation, SituationKind};
+use crate::thirds::Third;
+use crate::timeline::Timeline;
+use crate::zones::{self, TeamSpread};
+use crate::{Goal, PlayerDetails, ReplayEngine, Team, TeamColors, GREY, PURPLE, RESPAWN_SECONDS};
+
+pub const SCALE_FACTOR: f64 = 10.;
+/// Height of the seek bar shown under the field in the window.
+pub const TIMELINE_HEIGHT: f64 = 24.;
+
+const LABEL_SIZE: u32 = 12;
+const BANNER_SIZE: u32 = 24;
+/// Size of the shot quality written beside each shot on the timeline, small enough to fit in the
+/// top half of the bar.
+const QUALITY_SIZE: u32 = 9;
+/// Size of the rotation role written over each player's dot.
+const ROLE_SIZE: u32 = 10;
+/// Space between the edge of the end-of-replay panel and its text.
+const PANEL_PADDING: f64 = 12.0;
+/// Half the size of a player's dot.
+pub const DOT_RADIUS: f64 = 6.0;
+/// How big the minimap is next to the unzoomed field.
+const MINIMAP_SCALE: f64 = 0.2;
+const BOOST: [f32; 4] = [1.0, 0.84, 0.0, 1.0];
+const BOOST_BAR_WIDTH: f64 = 16.0;
+/// Boost pads are drawn a bit bigger than they are so small ones are still visible.
+const BIG_PAD_RADIUS: f64 = 7.0;
+const SMALL_PAD_RADIUS: f64 = 3.5;
+pub const BALL_RADIUS: f64 = 92.75;
+pub const CEILING_HEIGHT: f64 = 2044.0;
+/// How high the crossbar of a soccar goal is.
+pub const GOAL_HEIGHT: f64 = 642.775;
+/// Height in pixels of the side view, floor to ceiling.
+pub const SIDE_VIEW_HEIGHT: f64 = CEILING_HEIGHT / SCALE_FACTOR;
+/// Height in pixels of the strip charting how spread out each team is.
+pub const SPREAD_HEIGHT: f64 = 60.0;
+/// Outline around the dots of supersonic cars.
+const SUPERSONIC_OUTLINE: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+/// How far back a powersliding car's skid marks reach, in seconds of its travel.
+const SKID_SECONDS: f64 = 0.2;
+const SKID: [f32; 4] = [0.05, 0.05, 0.05, 0.7];
+/// How far ahead, in seconds of travel, velocity arrows reach.
+const VELOCITY_SECONDS: f64 = 0.5;
+/// How far ahead the ball's predicted path reaches, in seconds.
+const PREDICTION_SECONDS: f64 = 3.0;
+/// How many of the predictor's steps are skipped between the dots of the ball's predicted path.
+const PREDICTION_DOT_STEPS: usize = 6;
+/// How see-through the cars and ball of a replay drawn over another one are.
+const GHOST_ALPHA: f32 = 0.45;
+/// How long a cross flashes where a car was demolished, in seconds.
+const DEMOLITION_FLASH_SECONDS: f32 = 0.5;
+/// How many of the latest demolitions the log lists.
+const DEMOLITION_LOG_LINES: usize = 4;
+/// Color of the rings around players breaking a rotation rule, and of the warning about it.
+const VIOLATION: [f32; 4] = [1.0, 0.25, 0.2, 1.0];
+/// Width and height of the demolition and aerial icons on the seek bar.
+const EVENT_ICON_SIZE: f64 = 6.0;
+/// Colors of the strips along the bottom of the seek bar marking open nets and teams outnumbered
+/// at the back.
+const OPEN_NET: [f32; 4] = [1.0, 0.25, 0.2, 0.9];
+const OUTNUMBERED: [f32; 4] = [1.0, 0.7, 0.0, 0.9];
+/// How tall those strips are.
+const SITUATION_HEIGHT: f64 = 4.0;
+/// Color of bookmarks on the seek bar, and of the one playback is at in the list.
+const BOOKMARK: [f32; 4] = [0.3, 0.8, 1.0, 1.0];
+/// Color of the lines on the seek bar where a playback loop starts and ends.
+const LOOP_MARKER: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+pub const BACKGROUND: [f32; 4] = [0.1, 0.1, 0.1, 1.0];
+const FIELD_LINES: [f32; 4] = [1.0, 1.0, 1.0, 0.6];
+pub const CENTER_CIRCLE_RADIUS: f64 = 1000.0;
+pub const GOAL_BOX_WIDTH: f64 = 3400.0;
+pub const GOAL_BOX_DEPTH: f64 = 1400.0;
+/// Radius of a Hoops rim, which sits right up against its backboard.
+const HOOP_RADIUS: f64 = 716.0;
+const BACKBOARD_WIDTH: f64 = 2.0 * 1040.0;
+/// Distance from the center of a Dropshot tile to each of its corners.
+const TILE_RADIUS: f64 = 443.0;
+/// How far the camera zooms in when following the ball or a player.
+const CAMERA_ZOOM: f64 = 3.0;
+/// Room the action camera leaves around the ball and cars when zooming to fit them.
+const ACTION_MARGIN: f64 = 1500.0;
+/// Seconds of play the camera takes to catch up most of the way to what it follows.
+const CAMERA_EASE: f64 = 0.25;
+
+/// What the view follows around the field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Camera {
+    Ball,
+    /// A player, by name.
+    Player(String),
+    /// The middle of the ball and every car, zoomed out far enough to show them all.
+    Action,
+}
+
+impl Camera {
+    /// The field point to center on and how far to zoom, if what's followed is on the field.
+    fn target(&self, engine: &ReplayEngine) -> Option<([f64; 2], f64)> {
+        let location = |body: &RigidBody| [body.location.x as f64, body.location.y as f64];
+        match self {
+            Camera::Ball => Some((location(&engine.ball()?), CAMERA_ZOOM)),
+            Camera::Player(name) => {
+                let (_, body) = engine
+                    .player_positions()
+                    .find(|(player, _)| player.name.eq_ignore_ascii_case(name))?;
+                Some((location(body), CAMERA_ZOOM))
+            }
+            Camera::Action => {
+                let mut points: Vec<_> = engine
+                    .player_positions()
+                    .map(|(_, body)| location(body))
+                    .collect();
+                points.extend(engine.ball().as_ref().map(location));
+                if points.is_empty() {
+                    return None;
+                }
+
+                let count = points.len() as f64;
+                let center = [
+                    points.iter().map(|[x, _]| x).sum::<f64>() / count,
+                    points.iter().map(|[_, y]| y).sum::<f64>() / count,
+                ];
+                let reach = |axis: usize| {
+                    points
+                        .iter()
+                        .map(|point| (point[axis] - center[axis]).abs())
+                        .fold(0.0, f64::max)
+                        + ACTION_MARGIN
+                };
+                let arena = engine.arena();
+                let zoom = (arena.width / (2.0 * reach(0)))
+                    .min((arena.height + 2.0 * arena.goal_depth) / (2.0 * reach(1)))
+                    .clamp(1.0, CAMERA_ZOOM);
+                Some((center, zoom))
+            }
+        }
+    }
+}
+
+/// Colors for the parts of the field that don't belong to either team, which the config file can
+/// change.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Behind the field, and in the seek bar and any bars around the field in the window.
+    #[serde(deserialize_with = "config::hex_color")]
+    pub background: [f32; 4],
+    /// The playing surface.
+    #[serde(deserialize_with = "config::hex_color")]
+    pub field: [f32; 4],
+    /// The walls and painted lines.
+    #[serde(deserialize_with = "config::hex_color")]
+    pub lines: [f32; 4],
+    /// The ball and its trail.
+    #[serde(deserialize_with = "config::hex_color")]
+    pub ball: [f32; 4],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            background: BACKGROUND,
+            field: GREY,
+            lines: FIELD_LINES,
+            ball: PURPLE,
+        }
+    }
+}
+
+/// Recent screen positions of something moving, oldest first, with the replay time of each.
+struct Trail {
+    color: [f32; 4],
+    points: VecDeque<(f32, [f64; 2])>,
+}
+
+impl Trail {
+    fn new(color: [f32; 4]) -> Self {
+        Self {
+            color,
+            points: VecDeque::new(),
+        }
+    }
+
+    /// Drops every point older than `seconds` before `now`.
+    fn expire(&mut self, now: f32, seconds: f32) {
+        while let Some((time, _)) = self.points.front() {
+            if now - time <= seconds {
+                break;
+            }
+            self.points.pop_front();
+        }
+    }
+}
+
+/// What gets drawn, plus anything the display builds up across frames.
+pub struct RenderState {
+    pub display: DisplayType,
+    pub velocity: bool,
+    /// Whether to draw where the ball is headed if nobody touches it.
+    pub prediction: bool,
+    pub view: View,
+    /// Where the 3D display looks from.
+    pub orbit: Orbit,
+    /// Names of the players left off the field.
+    pub hidden: HashSet<String>,
+    theme: Theme,
+    heatmap: ZoneHeatmap,
+    player_heatmap: Option<PositionHeatmap>,
+    trail_seconds: Option<f32>,
+    trails: HashMap<ActorId, Trail>,
+    ball_trail_seconds: Option<f32>,
+    ball_trail: Trail,
+    bounces: VecDeque<(f32, [f64; 2])>,
+    last_ball_velocity: Option<Vector3f>,
+    spread_seconds: Option<f32>,
+    spread: VecDeque<(f32, TeamSpread)>,
+    last_time: f32,
+    camera: Option<Camera>,
+    /// Where the camera is centered and how far it's zoomed, on its way to what it follows.
+    camera_focus: Option<([f64; 2], f64)>,
+    overlays: Vec<Box<dyn OverlayPlugin>>,
+}
+
+impl RenderState {
+    pub fn new(display: DisplayType, arena: &Arena) -> Self {
+        Self {
+            display,
+            velocity: false,
+            prediction: false,
+            view: View::default(),
+            orbit: Orbit::default(),
+            hidden: HashSet::new(),
+            theme: Theme::default(),
+            heatmap: ZoneHeatmap::new(arena),
+            player_heatmap: None,
+            trail_seconds: None,
+            trails: HashMap::new(),
+            ball_trail_seconds: None,
+            ball_trail: Trail::new(PURPLE),
+            bounces: VecDeque::new(),
+            last_ball_velocity: None,
+            spread_seconds: None,
+            spread: VecDeque::new(),
+            last_time: 0.0,
+            camera: None,
+            camera_focus: None,
+            overlays: vec![],
+        }
+    }
+
+    /// Draws everything that doesn't belong to a team in these colors.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.ball_trail.color = theme.ball;
+        self.theme = theme;
+        self
+    }
+
+    pub fn has_player_heatmap(&self) -> bool {
+        self.player_heatmap.is_some()
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Draws a line behind every car covering where it was over the last `seconds`.
+    pub fn with_trail(mut self, seconds: f32) -> Self {
+        self.trail_seconds = Some(seconds);
+        self
+    }
+
+    /// Shows where a single player spent their time over the whole replay.
+    pub fn with_player_heatmap(mut self, heatmap: PositionHeatmap) -> Self {
+        self.player_heatmap = Some(heatmap);
+        self
+    }
+
+    /// Draws a line behind the ball covering the last `seconds`, with a marker wherever it bounced.
+    pub fn with_ball_trail(mut self, seconds: f32) -> Self {
+        self.ball_trail_seconds = Some(seconds);
+        self
+    }
+
+    /// Charts how spread out each team has been over the last `seconds`, with `draw_spread`.
+    pub fn with_spread(mut self, seconds: f32) -> Self {
+        self.spread_seconds = Some(seconds);
+        self
+    }
+
+    pub fn has_spread(&self) -> bool {
+        self.spread_seconds.is_some()
+    }
+
+    /// Keeps the view zoomed in on and following `camera` instead of showing the whole field.
+    pub fn with_camera(mut self, camera: Camera) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    /// Draws `overlay` over the field, on top of anything added before it.
+    pub fn with_overlay(mut self, overlay: Box<dyn OverlayPlugin>) -> Self {
+        self.overlays.push(overlay);
+        self
+    }
+
+    /// How many seconds back the trails and spread chart reach, so a frame drawn after a jump
+    /// needs to have been played up to from this far before it to show them whole.
+    pub fn lookback(&self) -> f32 {
+        [
+            self.trail_seconds,
+            self.ball_trail_seconds,
+            self.spread_seconds,
+        ]
+        .into_iter()
+        .flatten()
+        .fold(0.0, f32::max)
+    }
+
+    /// Records the frame the engine just processed. Call after every `ReplayEngine::step`.
+    pub fn observe(&mut self, engine: &ReplayEngine) {
+        if self.display == DisplayType::HEATMAP {
+            self.heatmap.accumulate(engine);
+        }
+
+        // Seeking backwards or looping leaves trails that don't belong to this part of the replay
+        let now = engine.time();
+        if now < self.last_time {
+            self.forget_recent();
+        }
+        let seconds = now - self.last_time;
+        self.last_time = now;
+
+        self.follow(engine, seconds);
+        for overlay in &mut self.overlays {
+            overlay.observe(engine);
+        }
+
+        if let Some(seconds) = self.ball_trail_seconds {
+            self.record_ball(engine, seconds);
+        }
+
+        if let Some(seconds) = self.spread_seconds {
+            if let Some(spread) = zones::team_spread(engine) {
+                self.spread.push_back((now, spread));
+            }
+            while self
+                .spread
+                .front()
+                .is_some_and(|(time, _)| now - time > seconds)
+            {
+                self.spread.pop_front();
+            }
+        }
+
+        if let Some(seconds) = self.trail_seconds {
+            for (player, body) in engine.player_positions() {
+                let Some(car) = player.car_actor_id else {
+                    continue;
+                };
+                let point = to_screen(
+                    engine.arena(),
+                    body.location.x as f64,
+                    body.location.y as f64,
+                );
+                self.trails
+                    .entry(car)
+                    .or_insert_with(|| Trail::new(player.color))
+                    .points
+                    .push_back((now, point));
+            }
+
+            for trail in self.trails.values_mut() {
+                trail.expire(now, seconds);
+            }
+            self.trails.retain(|_, trail| !trail.points.is_empty());
+        }
+    }
+
+    /// Catches up with `engine` having jumped to its frame with `Timeline::seek` rather than stepping
+    /// there. The heatmap is counted again from the timeline up to the frame, and the trails and
+    /// spread chart start over from it. Carry on with `observe` after the next step.
+    pub fn jump(&mut self, engine: &ReplayEngine, timeline: &Timeline) {
+        if self.display == DisplayType::HEATMAP {
+            self.heatmap = ZoneHeatmap::from_timeline(timeline, engine.frame_index());
+        }
+        self.forget_recent();
+        self.last_time = engine.time();
+        self.follow(engine, 0.0);
+    }
+
+    /// Drops what was recorded over the last few seconds, once it's no longer the part of the
+    /// replay leading up to the frame.
+    fn forget_recent(&mut self) {
+        self.trails.clear();
+        self.ball_trail.points.clear();
+        self.bounces.clear();
+        self.last_ball_velocity = None;
+        self.spread.clear();
+        self.camera_focus = None;
+    }
+
+    /// Moves the view toward whatever the camera follows, `seconds` after it last moved.
+    fn follow(&mut self, engine: &ReplayEngine, seconds: f32) {
+        let Some(target) = self
+            .camera
+            .as_ref()
+            .and_then(|camera| camera.target(engine))
+        else {
+            return;
+        };
+        let ([x, y], zoom) = target;
+        let focus = match self.camera_focus {
+            // Easing toward the target glides over jumps like a car respawning
+            Some(([from_x, from_y], from_zoom)) => {
+                let t = 1.0 - (-seconds as f64 / CAMERA_EASE).exp();
+                (
+                    [from_x + (x - from_x) * t, from_y + (y - from_y) * t],
+                    from_zoom + (zoom - from_zoom) * t,
+                )
+            }
+            None => target,
+        };
+        self.camera_focus = Some(focus);
+        self.view = View::centered(engine.arena(), focus.0, focus.1);
+    }
+
+    fn record_ball(&mut self, engine: &ReplayEngine, seconds: f32) {
+        let now = engine.time();
+        let arena = engine.arena();
+        if let Some(ball) = engine.ball() {
+            let point = to_screen(arena, ball.location.x as f64, ball.location.y as f64);
+            self.ball_trail.points.push_back((now, point));
+
+            if let (Some(before), Some(after)) = (self.last_ball_velocity, ball.linear_velocity) {
+                if bounced(arena, &ball.location, &before, &after) {
+                    self.bounces.push_back((now, point));
+                }
+            }
+            self.last_ball_velocity = ball.linear_velocity;
+        }
+
+        self.ball_trail.expire(now, seconds);
+        while let Some((time, _)) = self.bounces.front() {
+            if now - time <= seconds {
+                break;
+            }
+            self.bounces.pop_front();
+        }
+    }
+
+    /// Whether `player` is drawn rather than hidden.
+    pub fn shows(&self, player: &PlayerDetails) -> bool {
+        !self.hidden.contains(&player.name)
+    }
+
+    /// Draws the whole field for the engine's current frame.
+    pub fn draw<G: Graphics>(&self, engine: &ReplayEngine, c: &Context, gl: &mut G) {
+        draw_frame(engine, self, c, gl);
+        if self.view.is_zoomed() && self.display != DisplayType::THREE_D {
+            draw_minimap(engine, self, c, gl);
+        }
+    }
+
+    /// Where something at `location` is drawn, whether from above or in 3D, or `None` if it's
+    /// behind the 3D display's camera.
+    pub fn place(&self, arena: &Arena, location: Vector3f) -> Option<[f64; 2]> {
+        let [x, y, z] = [location.x, location.y, location.z].map(f64::from);
+        match self.display {
+            DisplayType::THREE_D => {
+                Projection::new(&self.orbit, screen_size(arena)).project([x, y, z])
+            }
+            _ => Some(self.view.to_window(arena, x, y)),
+        }
+    }
+}
+
+fn draw_frame<G: Graphics>(engine: &ReplayEngine, state: &RenderState, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    let display = state.display;
+    // The renderer draws the 3D display's scene under everything else, with its own depth buffer
+    if display == DisplayType::THREE_D {
+        return;
+    }
+    let arena = engine.arena();
+    let player_actors = engine.players();
+    let ball = engine.ball();
+    // Hidden players still claim their share of the field, they just aren't drawn
+    let shown: HashMap<ActorId, PlayerDetails> = player_actors
+        .iter()
+        .filter(|(_, player)| state.shows(player))
+        .map(|(id, player)| (*id, player.clone()))
+        .collect();
+
+    let theme = &state.theme;
+    // Only the field's own space, since a second replay can be drawn beside it
+    let [width, height] = screen_size(arena);
+    rectangle(theme.background, [0.0, 0.0, width, height], c.transform, gl);
+
+    let c = &state.view.field_context(arena, c);
+    render_pitch(arena, theme, engine.team_colors(), c, gl);
+
+    match display {
+        DisplayType::POINTS | DisplayType::THREE_D => {}
+        DisplayType::VORONOI => {
+            render_voronoi(engine, c, gl);
+        }
+        DisplayType::WEIGHTED_VORONOI => {
+            render_weighted_voronoi(engine, c, gl);
+        }
+        DisplayType::TEAM_VORONOI => {
+            render_team_voronoi(engine, c, gl);
+        }
+        DisplayType::PASSING_LANES => {
+            render_passing_lanes(engine, c, gl);
+        }
+        DisplayType::TEAM_SHAPE => {
+            render_team_shapes(engine, c, gl);
+        }
+        DisplayType::HEATMAP => {
+            render_zone_heatmap(arena, &state.heatmap, engine.team_colors(), c, gl);
+        }
+        DisplayType::PLAYER_HEATMAP => {
+            if let Some(heatmap) = &state.player_heatmap {
+                render_position_heatmap(arena, heatmap, c, gl);
+            }
+        }
+    }
+    // Dots on top of colored cells get a black ring so they stand out from their own cell
+    let ringed = matches!(
+        display,
+        DisplayType::VORONOI | DisplayType::WEIGHTED_VORONOI
+    );
+    render_dots(engine, &shown, ringed, c, gl);
+    if let Some(seconds) = state.trail_seconds {
+        let hidden_cars: HashSet<ActorId> = player_actors
+            .values()
+            .filter(|player| !state.shows(player))
+            .filter_map(|player| player.car_actor_id)
+            .collect();
+        for (car, trail) in &state.trails {
+            if !hidden_cars.contains(car) {
+                render_trail(trail, engine.time(), seconds, c, gl);
+            }
+        }
+    }
+    if let Some(seconds) = state.ball_trail_seconds {
+        render_trail(&state.ball_trail, engine.time(), seconds, c, gl);
+        for (time, [x, y]) in &state.bounces {
+            let alpha = 1.0 - (engine.time() - time) / seconds;
+            Ellipse::new_border([1.0, 1.0, 1.0, alpha], 0.75).draw(
+                circle(*x, *y, 5.0),
+                &c.draw_state,
+                c.transform,
+                gl,
+            );
+        }
+    }
+    render_possession(engine, c, gl);
+    render_demolitions(engine, c, gl);
+    if arena.kind == ArenaKind::Dropshot {
+        render_tiles(engine, theme, c, gl);
+    }
+    render_markings(arena, theme, engine.team_colors(), c, gl);
+    render_boost_pads(engine, c, gl);
+    render_boost(engine, state, c, gl);
+
+    if let Some(ball) = ball {
+        // 0 with the ball resting on the ground, up to 1 when it touches the ceiling
+        let height = ((ball.location.z as f64 - BALL_RADIUS) / (CEILING_HEIGHT - BALL_RADIUS))
+            .clamp(0.0, 1.0);
+        let size = 6.0 + 6.0 * height;
+        let [x, y] = to_screen(arena, ball.location.x as f64, ball.location.y as f64);
+
+        // The shadow falls further from the ball the higher it goes
+        let shadow = 20.0 * height;
+        rectangle(
+            [0.0, 0.0, 0.0, 0.4],
+            circle(x + shadow, y + shadow, 6.0),
+            c.transform,
+            gl,
+        );
+
+        if matches!(
+            display,
+            DisplayType::VORONOI | DisplayType::WEIGHTED_VORONOI | DisplayType::TEAM_VORONOI
+        ) {
+            let entity_background = circle(x, y, size + 4.0);
+            rectangle([0.0, 0.0, 0.0, 1.0], entity_background, c.transform, gl);
+        }
+
+        rectangle(theme.ball, circle(x, y, size), c.transform, gl);
+    }
+
+    if state.prediction {
+        render_prediction(engine, state, c, gl);
+    }
+    if state.velocity {
+        render_velocity(engine, state, c, gl);
+    }
+
+    let mut painter = FieldPainter::new(arena, c, gl);
+    for overlay in &state.overlays {
+        overlay.draw(engine, &mut painter);
+    }
+}
+
+/// Draws the cars and ball of another replay see-through over the field, in the same view, or just
+/// `player`'s car.
+pub fn draw_ghost<G: Graphics>(
+    engine: &ReplayEngine,
+    player: Option<&str>,
+    view: &View,
+    theme: &Theme,
+    c: &Context,
+    gl: &mut G,
+) {
+    use graphics::*;
+
+    let arena = engine.arena();
+    let c = &view.field_context(arena, c);
+    let faded = |[r, g, b, _]: [f32; 4]| [r, g, b, GHOST_ALPHA];
+    let ghosted = |name: &str| player.is_none_or(|player| player.eq_ignore_ascii_case(name));
+    for (details, car) in engine.player_positions() {
+        if !ghosted(&details.name) {
+            continue;
+        }
+        let [x, y] = to_screen(arena, car.location.x as f64, car.location.y as f64);
+        rectangle(
+            faded(details.color),
+            circle(x, y, DOT_RADIUS),
+            c.transform,
+            gl,
+        );
+    }
+    if let Some(ball) = engine.ball().filter(|_| player.is_none()) {
+        let [x, y] = to_screen(arena, ball.location.x as f64, ball.location.y as f64);
+        rectangle(faded(theme.ball), circle(x, y, 6.0), c.transform, gl);
+    }
+}
+
+/// Draws the field seen from behind a goal, with the side walls at either edge and the ceiling at
+/// the top, to show how high the ball and cars are. It shares its x axis with the field above it.
+pub fn draw_side_view<G: Graphics>(
+    engine: &ReplayEngine,
+    state: &RenderState,
+    c: &Context,
+    gl: &mut G,
+) {
+    use graphics::*;
+
+    let arena = engine.arena();
+    let theme = &state.theme;
+    let [width, _] = screen_size(arena);
+    let to_side = |x: f32, z: f32| {
+        [
+            to_pixels(x as f64 + arena.width / 2.0),
+            to_pixels(CEILING_HEIGHT - z as f64),
+        ]
+    };
+
+    let bounds = [0.0, 0.0, width, SIDE_VIEW_HEIGHT];
+    rectangle(theme.background, bounds, c.transform, gl);
+    Rectangle::new_border(theme.lines, 0.5).draw(bounds, &c.draw_state, c.transform, gl);
+    if arena.goal_width > 0.0 {
+        let [left, top] = to_side(-arena.goal_width as f32 / 2.0, GOAL_HEIGHT as f32);
+        Rectangle::new_border(theme.lines, 0.5).draw(
+            [
+                left,
+                top,
+                to_pixels(arena.goal_width),
+                to_pixels(GOAL_HEIGHT),
+            ],
+            &c.draw_state,
+            c.transform,
+            gl,
+        );
+    }
+
+    for (player, car) in engine.player_positions() {
+        if !state.shows(player) {
+            continue;
+        }
+        let [x, y] = to_side(car.location.x, car.location.z);
+        rectangle(player.color, circle(x, y, DOT_RADIUS), c.transform, gl);
+    }
+    if let Some(ball) = engine.ball() {
+        let [x, y] = to_side(ball.location.x, ball.location.z);
+        let radius = to_pixels(BALL_RADIUS);
+        rectangle(theme.ball, circle(x, y, radius), c.transform, gl);
+    }
+}
+
+/// Draws the whole field small in the bottom right corner, with the cars and ball and a box around
+/// the part the zoomed view is showing, so it's clear where on the field that is.
+fn draw_minimap<G: Graphics>(engine: &ReplayEngine, state: &RenderState, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    let arena = engine.arena();
+    let theme = &state.theme;
+    let [width, height] = screen_size(arena);
+    let [left, top] = [
+        width * (1.0 - MINIMAP_SCALE) - PANEL_PADDING,
+        height * (1.0 - MINIMAP_SCALE) - PANEL_PADDING,
+    ];
+    let bounds = [left, top, width * MINIMAP_SCALE, height * MINIMAP_SCALE];
+    rectangle(theme.background, bounds, c.transform, gl);
+    Rectangle::new_border(theme.lines, 0.5).draw(bounds, &c.draw_state, c.transform, gl);
+
+    let c = c
+        .trans(left, top)
+        .zoom(MINIMAP_SCALE)
+        .trans(0.0, to_pixels(arena.goal_depth));
+    // Everything from here is shrunk, so sizes are scaled back up to stay readable
+    let pixel = 1.0 / MINIMAP_SCALE;
+    render_pitch(arena, theme, engine.team_colors(), &c, gl);
+    for (player, car) in engine.player_positions() {
+        if state.shows(player) {
+            let [x, y] = to_screen(arena, car.location.x as f64, car.location.y as f64);
+            rectangle(player.color, circle(x, y, 2.0 * pixel), c.transform, gl);
+        }
+    }
+    if let Some(ball) = engine.ball() {
+        let [x, y] = to_screen(arena, ball.location.x as f64, ball.location.y as f64);
+        rectangle(theme.ball, circle(x, y, 2.0 * pixel), c.transform, gl);
+    }
+
+    let [x1, y1] = state.view.to_field(arena, [0.0, 0.0]);
+    let [x2, y2] = state.view.to_field(arena, [width, height]);
+    let [x1, y1] = to_screen(arena, x1, y1);
+    let [x2, y2] = to_screen(arena, x2, y2);
+    Rectangle::new_border(theme.lines, pixel).draw(
+        [x1, y1, x2 - x1, y2 - y1],
+        &c.draw_state,
+        c.transform,
+        gl,
+    );
+}
+
+/// Charts how far apart each team's cars have been from each other, and from the ball, over the
+/// last few seconds, the newest at the right. Solid lines are the distance between teammates and
+/// faint ones the distance to the ball, going up to as far apart as the side walls at the top.
+pub fn draw_spread<G: Graphics>(
+    engine: &ReplayEngine,
+    state: &RenderState,
+    c: &Context,
+    gl: &mut G,
+) {
+    use graphics::*;
+
+    let Some(seconds) = state.spread_seconds else {
+        return;
+    };
+    let arena = engine.arena();
+    let theme = &state.theme;
+    let colors = engine.team_colors();
+    let [width, _] = screen_size(arena);
+    let now = engine.time();
+    let to_strip = |time: f32, distance: f64| {
+        [
+            width * (1.0 - ((now - time) / seconds) as f64),
+            SPREAD_HEIGHT * (1.0 - (distance / arena.width).min(1.0)),
+        ]
+    };
+
+    let bounds = [0.0, 0.0, width, SPREAD_HEIGHT];
+    rectangle(theme.background, bounds, c.transform, gl);
+    Rectangle::new_border(theme.lines, 0.5).draw(bounds, &c.draw_state, c.transform, gl);
+    for team in [Team::Orange, Team::Blue] {
+        let [r, g, b, _] = colors.palette(team)[1];
+        for (to_ball, alpha) in [(true, 0.4), (false, 1.0)] {
+            let points: Vec<_> = state
+                .spread
+                .iter()
+                .map(|(time, spread)| {
+                    let spacing = spread.team(team);
+                    let distance = match to_ball {
+                        true => spacing.ball,
+                        false => spacing.teammates,
+                    };
+                    to_strip(*time, distance)
+                })
+                .collect();
+            for pair in points.windows(2) {
+                let [[x1, y1], [x2, y2]] = [pair[0], pair[1]];
+                line([r, g, b, alpha], 0.6, [x1, y1, x2, y2], c.transform, gl);
+            }
+        }
+    }
+}
+
+/// Size in pixels of the field and both goals, without the seek bar.
+pub fn screen_size(arena: &Arena) -> [f64; 2] {
+    [
+        to_pixels(arena.width),
+        to_pixels(arena.height + 2.0 * arena.goal_depth),
+    ]
+}
+
+/// Size of the window needed to show the whole arena and the seek bar below it, which is the space
+/// everything in the window is laid out in before being fit to the window's actual size.
+pub fn window_size(arena: &Arena) -> [f64; 2] {
+    let [width, height] = screen_size(arena);
+    [width, height + TIMELINE_HEIGHT]
+}
+
+/// How the field and seek bar are scaled and centered to fit a window of any size, leaving bars
+/// at the sides or at the top and bottom when the window is a different shape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Letterbox {
+    scale: f64,
+    offset: [f64; 2],
+    window: [f64; 2],
+}
+
+impl Letterbox {
+    /// Fits a layout of `size`, usually `window_size`, into `window`.
+    pub fn new(size: [f64; 2], window: [f64; 2]) -> Self {
+        let [width, height] = size;
+        let scale = (window[0] / width).min(window[1] / height);
+        Self {
+            scale,
+            offset: [
+                (window[0] - width * scale) / 2.0,
+                (window[1] - height * scale) / 2.0,
+            ],
+            window,
+        }
+    }
+
+    /// Moves a context covering the window to the layout's.
+    pub fn context(&self, c: &Context) -> Context {
+        use graphics::Transformed;
+
+        c.trans(self.offset[0], self.offset[1])
+            .scale(self.scale, self.scale)
+    }
+
+    /// Where a point in the window is in the layout, undoing `context`.
+    pub fn to_layout(self, [x, y]: [f64; 2]) -> [f64; 2] {
+        [
+            (x - self.offset[0]) / self.scale,
+            (y - self.offset[1]) / self.scale,
+        ]
+    }
+
+    /// Fills the bars around the layout with the background, covering anything drawn past its
+    /// edges like a zoomed in field. Takes a context covering the whole window.
+    pub fn draw_bars<G: Graphics>(&self, theme: &Theme, c: &Context, gl: &mut G) {
+        use graphics::*;
+
+        let [x, y] = self.offset;
+        let [width, height] = self.window;
+        for bar in [
+            [0.0, 0.0, x, height],
+            [width - x, 0.0, x, height],
+            [0.0, 0.0, width, y],
+            [0.0, height - y, width, y],
+        ] {
+            if bar[2] > 0.0 && bar[3] > 0.0 {
+                rectangle(theme.background, bar, c.transform, gl);
+            }
+        }
+    }
+}
+
+/// Where the seek bar sits in the window, right below the field.
+pub fn timeline_rect(arena: &Arena) -> [f64; 4] {
+    let [width, height] = screen_size(arena);
+    [0.0, height, width, TIMELINE_HEIGHT]
+}
+
+/// Whether a window point falls inside an `[x, y, width, height]` rectangle.
+pub fn rect_contains([x, y, width, height]: [f64; 4], [px, py]: [f64; 2]) -> bool {
+    px >= x && px < x + width && py >= y && py < y + height
+}
+
+/// Network frame under a window x coordinate on the seek bar.
+pub fn timeline_frame(engine: &ReplayEngine, x: f64) -> usize {
+    let [left, _, width, _] = timeline_rect(engine.arena());
+    let progress = ((x - left) / width).clamp(0.0, 1.0);
+    ((progress * engine.frame_count() as f64) as usize).min(engine.frame_count() - 1)
+}
+
+/// Draws the seek bar with the replay's progress and a marker for every goal.
+pub fn draw_timeline<C, G>(
+    engine: &ReplayEngine,
+    goals: &[Goal],
+    shots: &[Shot],
+    theme: &Theme,
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let colors = engine.team_colors();
+    let [x, y, width, height] = timeline_rect(engine.arena());
+    let frame_x = |frame: usize| x + width * frame as f64 / engine.frame_count() as f64;
+
+    rectangle(theme.background, [x, y, width, height], c.transform, gl);
+    rectangle(
+        [1.0, 1.0, 1.0, 0.25],
+        [x, y, frame_x(engine.frame_index()) - x, height],
+        c.transform,
+        gl,
+    );
+
+    // Shots are marked along the top half of the bar and saves along the bottom half
+    for shot in shots {
+        let color = match shot.team {
+            Team::Orange => colors.orange[1],
+            Team::Blue => colors.blue[1],
+        };
+        let top = match shot.kind {
+            ShotKind::Shot => y,
+            ShotKind::Save => y + height / 2.0,
+        };
+        rectangle(
+            color,
+            [frame_x(shot.frame) - 1.0, top, 2.0, height / 2.0],
+            c.transform,
+            gl,
+        );
+
+        if let Some(quality) = shot.quality {
+            let transform = c
+                .transform
+                .trans(frame_x(shot.frame) + 3.0, top + height / 2.0 - 2.0);
+            // A glyph that fails to load just leaves the shot's quality out
+            let _ = text(
+                FIELD_LINES,
+                QUALITY_SIZE,
+                &format!("{:.0}%", quality * 100.0),
+                glyphs,
+                transform,
+                gl,
+            );
+        }
+    }
+
+    for goal in goals {
+        let color = colors.team(goal.team);
+        rectangle(
+            color,
+            [frame_x(goal.frame) - 1.5, y, 3.0, height],
+            c.transform,
+            gl,
+        );
+    }
+
+    rectangle(
+        [1.0, 1.0, 1.0, 1.0],
+        [frame_x(engine.frame_index()) - 1.0, y, 2.0, height],
+        c.transform,
+        gl,
+    );
+}
+
+/// Writes every player's name beside their dot.
+pub fn draw_labels<C, G>(
+    engine: &ReplayEngine,
+    state: &RenderState,
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let arena = engine.arena();
+    for (player, body) in engine.player_positions() {
+        if !state.shows(player) {
+            continue;
+        }
+        let Some([x, y]) = state.place(arena, body.location) else {
+            continue;
+        };
+        let transform = c.transform.trans(x + 8.0, y + 4.0);
+        // A glyph that fails to load just leaves the label out
+        let _ = text(FIELD_LINES, LABEL_SIZE, &player.name, glyphs, transform, gl);
+    }
+}
+
+/// Writes each player's place in their team's rotation, 1 to 3, over their dot.
+pub fn draw_roles<C, G>(
+    engine: &ReplayEngine,
+    state: &RenderState,
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let arena = engine.arena();
+    for (player, role) in rotation::roles(engine) {
+        if !state.shows(player) {
+            continue;
+        }
+        let Some(Some(body)) = player.car_actor_id.and_then(|car| engine.cars().get(&car)) else {
+            continue;
+        };
+        let number = role.number().to_string();
+        let Some([x, y]) = state.place(arena, body.location) else {
+            continue;
+        };
+        let width = glyphs.width(ROLE_SIZE, &number).unwrap_or_default();
+        let transform = c
+            .transform
+            .trans(x - width / 2.0, y + ROLE_SIZE as f64 * 0.35);
+        // A glyph that fails to load just leaves the role out
+        let _ = text(
+            [0.0, 0.0, 0.0, 1.0],
+            ROLE_SIZE,
+            &number,
+            glyphs,
+            transform,
+            gl,
+        );
+    }
+}
+
+/// Once the replay has finished, shows a panel in the middle of the field with how much of the
+/// live play each player spent in their defensive, middle and offensive thirds, how they got and
+/// spent their boost, and how fast and far they drove.
+pub fn draw_summary<C, G>(engine: &ReplayEngine, glyphs: &mut C, c: &Context, gl: &mut G)
+where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    if engine.frame_index() < engine.frame_count() {
+        return;
+    }
+    let shares = engine.occupancy().shares();
+    if shares.is_empty() {
+        return;
+    }
+    let colors = engine.team_colors();
+
+    let mut lines = vec![(
+        FIELD_LINES,
+        "Time in each third: defensive / middle / offensive".to_string(),
+    )];
+    for (player, team, [defensive, middle, offensive]) in shares {
+        lines.push((
+            colors.team(team),
+            format!(
+                "{player}  {:.0}% / {:.0}% / {:.0}%",
+                defensive * 100.0,
+                middle * 100.0,
+                offensive * 100.0
+            ),
+        ));
+    }
+
+    let boost = engine.boost_stats().players();
+    if !boost.is_empty() {
+        lines.push((FIELD_LINES, String::new()));
+        lines.push((
+            FIELD_LINES,
+            "Boost: collected / used / time empty / time full".to_string(),
+        ));
+        for (player, team, boost) in boost {
+            lines.push((
+                colors.team(team),
+                format!(
+                    "{player}  {:.0} / {:.0} / {:.0}s / {:.0}s",
+                    boost.collected, boost.used, boost.seconds_empty, boost.seconds_full
+                ),
+            ));
+        }
+    }
+
+    let movement = engine.movement().players();
+    if !movement.is_empty() {
+        lines.push((FIELD_LINES, String::new()));
+        lines.push((
+            FIELD_LINES,
+            "Movement: average speed / time supersonic / time in the air / distance".to_string(),
+        ));
+        for (player, team, movement) in movement {
+            // Unreal units are centimeters
+            lines.push((
+                colors.team(team),
+                format!(
+                    "{player}  {:.0} km/h / {:.0}s / {:.0}s / {:.1} km",
+                    movement.average_speed() * 0.036,
+                    movement.seconds_supersonic,
+                    movement.seconds_airborne,
+                    movement.distance / 100_000.0
+                ),
+            ));
+        }
+    }
+
+    let demolitions = engine.demolition_log();
+    if !demolitions.is_empty() {
+        lines.push((FIELD_LINES, String::new()));
+        lines.push((FIELD_LINES, "Demolitions: inflicted / taken".to_string()));
+        for (player, team, _) in engine.occupancy().shares() {
+            let inflicted = demolitions
+                .iter()
+                .filter(|demo| demo.attacker.as_deref() == Some(player))
+                .count();
+            let taken = demolitions
+                .iter()
+                .filter(|demo| demo.victim == player)
+                .count();
+            lines.push((
+                colors.team(team),
+                format!("{player}  {inflicted} / {taken}"),
+            ));
+        }
+    }
+
+    let [screen_width, screen_height] = screen_size(engine.arena());
+    draw_panel(
+        &lines,
+        |[width, height]| [(screen_width - width) / 2.0, (screen_height - height) / 2.0],
+        glyphs,
+        c,
+        gl,
+    );
+}
+
+/// Draws lines of text on a dark panel sized to fit them, placed by `position`, which is given the
+/// panel's size and returns where its top left corner goes.
+fn draw_panel<C, G>(
+    lines: &[([f32; 4], String)],
+    position: impl FnOnce([f64; 2]) -> [f64; 2],
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let line_height = LABEL_SIZE as f64 + 4.0;
+    let width = lines
+        .iter()
+        .map(|(_, line)| glyphs.width(LABEL_SIZE, line).unwrap_or_default())
+        .fold(0.0, f64::max);
+    let size = [
+        width + PANEL_PADDING * 2.0,
+        line_height * lines.len() as f64 + PANEL_PADDING * 2.0,
+    ];
+    let [x, y] = position(size);
+    rectangle(
+        [0.0, 0.0, 0.0, 0.75],
+        [x, y, size[0], size[1]],
+        c.transform,
+        gl,
+    );
+    for (i, (color, line)) in lines.iter().enumerate() {
+        let transform = c.transform.trans(
+            x + PANEL_PADDING,
+            y + PANEL_PADDING + line_height * (i as f64 + 0.8),
+        );
+        // A glyph that fails to load just leaves the line out
+        let _ = text(*color, LABEL_SIZE, line, glyphs, transform, gl);
+    }
+}
+
+/// The player whose dot is under a point in the window, if any, by the actor id of their player
+/// info.
+pub fn player_at(engine: &ReplayEngine, view: &View, window: [f64; 2]) -> Option<ActorId> {
+    let arena = engine.arena();
+    let [x, y] = view.to_field(arena, window);
+    // A little off the dot still counts, since they're small and moving
+    let reach = DOT_RADIUS * 2.0 * SCALE_FACTOR;
+    engine
+        .players()
+        .iter()
+        .filter_map(|(id, player)| {
+            let car = engine.cars().get(&player.car_actor_id?)?.as_ref()?;
+            let distance = (car.location.x as f64 - x).hypot(car.location.y as f64 - y);
+            Some((*id, distance))
+        })
+        .filter(|(_, distance)| *distance <= reach)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(id, _)| id)
+}
+
+/// Outlines a player's voronoi cell and shows a panel in the top right corner with who they are,
+/// how they're doing right now and their stats so far.
+pub fn draw_inspector<C, G>(
+    engine: &ReplayEngine,
+    view: &View,
+    player: ActorId,
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let Some(details) = engine.players().get(&player) else {
+        return;
+    };
+    let arena = engine.arena();
+    let car = details.car_actor_id;
+    let body = car.and_then(|car| engine.cars().get(&car)?.as_ref());
+    let colors = engine.team_colors();
+    let color = colors.team(details.team);
+
+    let field = view.field_context(arena, c);
+    let cell = zones::player_cells(engine)
+        .into_iter()
+        .find(|(cell_player, _)| car.is_some() && cell_player.car_actor_id == car);
+    if let Some((_, vertices)) = cell {
+        let outline: Vec<_> = vertices
+            .iter()
+            .map(|[x, y]| to_screen(arena, *x, *y))
+            .collect();
+        polygon([1.0, 1.0, 1.0, 0.2], &outline, field.transform, gl);
+        for (i, a) in outline.iter().enumerate() {
+            let b = outline[(i + 1) % outline.len()];
+            line_from_to([1.0, 1.0, 1.0, 0.9], 1.5, *a, b, field.transform, gl);
+        }
+    }
+
+    let mut lines = vec![(color, details.name.clone())];
+    match (details.platform(), details.online_id()) {
+        (Some(platform), Some(id)) => lines.push((FIELD_LINES, format!("{platform} {id}"))),
+        (Some(platform), None) => lines.push((FIELD_LINES, platform.to_string())),
+        _ => {}
+    }
+    lines.push((color, format!("{:?}", details.team)));
+    lines.push((FIELD_LINES, String::new()));
+
+    match body {
+        Some(body) => {
+            let speed = body
+                .linear_velocity
+                .map_or(0.0, |v| (v.x * v.x + v.y * v.y + v.z * v.z).sqrt());
+            // Unreal units are centimeters
+            let supersonic = if speed >= SUPERSONIC_SPEED {
+                ", supersonic"
+            } else {
+                ""
+            };
+            lines.push((
+                FIELD_LINES,
+                format!("Speed: {:.0} km/h{supersonic}", speed * 0.036),
+            ));
+            if let Some(state) = car.and_then(|car| engine.air_state(car)) {
+                let state = match state {
+                    AirState::Grounded => "On the ground",
+                    AirState::Jumping => "In the air, jumping",
+                    AirState::DoubleJumping => "In the air, double jumping",
+                    AirState::Dodging => "In the air, dodging",
+                    AirState::Airborne => "In the air",
+                };
+                lines.push((FIELD_LINES, state.to_string()));
+            }
+            if let Some(boost) = car.and_then(|car| engine.boost(car)) {
+                lines.push((FIELD_LINES, format!("Boost: {:.0}", boost * 100.0)));
+            }
+            let third = Third::at(arena, details.team, body.location.y);
+            lines.push((FIELD_LINES, format!("In their {third:?} third")));
+        }
+        None => lines.push((FIELD_LINES, "Not on the field".to_string())),
+    }
+    if let Some((_, role)) = rotation::roles(engine)
+        .into_iter()
+        .find(|(role_player, _)| role_player.car_actor_id == car)
+    {
+        lines.push((FIELD_LINES, format!("Rotation: {:?} man", role)));
+    }
+    if let Some((_, share)) = zones::player_control(engine)
+        .into_iter()
+        .find(|(control_player, _)| control_player.car_actor_id == car)
+    {
+        lines.push((
+            FIELD_LINES,
+            format!("Controls {:.0}% of the field", share * 100.0),
+        ));
+    }
+
+    lines.push((FIELD_LINES, String::new()));
+    lines.push((FIELD_LINES, "So far:".to_string()));
+    let name = details.name.as_str();
+    if let Some((.., shares)) = engine
+        .occupancy()
+        .shares()
+        .into_iter()
+        .find(|(player, ..)| *player == name)
+    {
+        lines.push((
+            FIELD_LINES,
+            format!(
+                "Thirds: {:.0}% / {:.0}% / {:.0}%",
+                shares[0] * 100.0,
+                shares[1] * 100.0,
+                shares[2] * 100.0
+            ),
+        ));
+    }
+    if let Some((.., boost)) = engine
+        .boost_stats()
+        .players()
+        .into_iter()
+        .find(|(player, ..)| *player == name)
+    {
+        lines.push((
+            FIELD_LINES,
+            format!(
+                "Boost collected / used: {:.0} / {:.0}",
+                boost.collected, boost.used
+            ),
+        ));
+        lines.push((
+            FIELD_LINES,
+            format!("Pads: {} big, {} small", boost.big_pads, boost.small_pads),
+        ));
+    }
+    if let Some((.., movement)) = engine
+        .movement()
+        .players()
+        .into_iter()
+        .find(|(player, ..)| *player == name)
+    {
+        lines.push((
+            FIELD_LINES,
+            format!(
+                "Average speed: {:.0} km/h",
+                movement.average_speed() * 0.036
+            ),
+        ));
+        lines.push((
+            FIELD_LINES,
+            format!("Time in the air: {:.0}s", movement.seconds_airborne),
+        ));
+        lines.push((
+            FIELD_LINES,
+            format!("Distance: {:.1} km", movement.distance / 100_000.0),
+        ));
+    }
+
+    let [screen_width, _] = screen_size(arena);
+    draw_panel(
+        &lines,
+        |[width, _]| [screen_width - width - PANEL_PADDING, PANEL_PADDING],
+        glyphs,
+        c,
+        gl,
+    );
+}
+
+/// Writes how much of the field each team and player controls, and how long each team has had the
+/// ball, into the space left of the top goal.
+pub fn draw_stats<C, G>(engine: &ReplayEngine, glyphs: &mut C, c: &Context, gl: &mut G)
+where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let Some(control) = zones::zone_control(engine) else {
+        return;
+    };
+    let colors = engine.team_colors();
+    let (leader, share) = if control.orange > control.blue {
+        (Team::Orange, control.orange)
+    } else {
+        (Team::Blue, control.blue)
+    };
+
+    let mut lines = vec![(
+        colors.team(leader),
+        format!("{leader:?} controls {:.0}% of the field", share * 100.0),
+    )];
+    if let Some((orange, blue)) = engine.possession().shares() {
+        lines.push((
+            FIELD_LINES,
+            format!(
+                "Possession: Orange {:.0}% / Blue {:.0}%",
+                orange * 100.0,
+                blue * 100.0
+            ),
+        ));
+    }
+    let mut players = zones::player_control(engine);
+    // Biggest share first, grouped by team
+    players.sort_by(|a, b| b.1.total_cmp(&a.1));
+    players.sort_by_key(|(player, _)| player.team == Team::Blue);
+    for (player, share) in players {
+        lines.push((
+            colors.team(player.team),
+            format!("{} {:.0}%", player.name, share * 100.0),
+        ));
+    }
+
+    let line_height = LABEL_SIZE as f64 + 2.0;
+    for (i, (color, line)) in lines.iter().enumerate() {
+        let transform = c.transform.trans(6.0, line_height * (i + 1) as f64);
+        // A glyph that fails to load just leaves the line out
+        let _ = text(*color, LABEL_SIZE, line, glyphs, transform, gl);
+    }
+}
+
+/// Writes the latest demolitions so far into the space left of the bottom goal, oldest first, with
+/// the game clock, whose car did it and whose car blew up.
+pub fn draw_demolitions<C, G>(engine: &ReplayEngine, glyphs: &mut C, c: &Context, gl: &mut G)
+where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let log = engine.demolition_log();
+    if log.is_empty() {
+        return;
+    }
+    let colors = engine.team_colors();
+    let mut lines = vec![(FIELD_LINES, "Demolitions".to_string())];
+    for demo in &log[log.len().saturating_sub(DEMOLITION_LOG_LINES)..] {
+        let clock = match engine.clock_at(demo.frame) {
+            Some(seconds) => format!("{}:{:02}", seconds / 60, seconds % 60),
+            None => format!("frame {}", demo.frame),
+        };
+        let victim = &demo.victim;
+        let line = match &demo.attacker {
+            Some(attacker) => format!("{clock}  {attacker} demolished {victim}"),
+            None => format!("{clock}  {victim} demolished"),
+        };
+        // In the demolished player's color, like their cross on the seek bar
+        let color = colors.team(demo.team);
+        lines.push((color, line));
+    }
+
+    let arena = engine.arena();
+    let [_, height] = screen_size(arena);
+    let line_height = LABEL_SIZE as f64 + 2.0;
+    let top = height - to_pixels(arena.goal_depth);
+    for (i, (color, line)) in lines.iter().enumerate() {
+        let transform = c.transform.trans(6.0, top + line_height * (i + 1) as f64);
+        // A glyph that fails to load just leaves the line out
+        let _ = text(*color, LABEL_SIZE, line, glyphs, transform, gl);
+    }
+}
+
+/// Rings the players breaking any of `rules` in the current frame, and lists the broken rules in a
+/// panel at the top right of the field.
+pub fn draw_violations<C, G>(
+    engine: &ReplayEngine,
+    state: &RenderState,
+    rules: &[Rule],
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let violations = rotation::violations(engine, rules);
+    if violations.is_empty() {
+        return;
+    }
+    let arena = engine.arena();
+    let colors = engine.team_colors();
+    let mut lines = vec![(VIOLATION, "Rotation".to_string())];
+    for violation in &violations {
+        let color = colors.team(violation.team);
+        let mut line = format!("{:?}: {}", violation.team, rules[violation.rule].describe());
+        if !violation.players.is_empty() {
+            line += &format!(" ({})", violation.players.join(", "));
+        }
+        lines.push((color, line));
+
+        for (player, body) in engine.player_positions() {
+            if !violation.players.contains(&player.name) || !state.shows(player) {
+                continue;
+            }
+            if let Some([x, y]) = state.place(arena, body.location) {
+                Ellipse::new_border(VIOLATION, 1.5).draw(
+                    circle(x, y, DOT_RADIUS + 6.0),
+                    &c.draw_state,
+                    c.transform,
+                    gl,
+                );
+            }
+        }
+    }
+
+    let [screen_width, _] = screen_size(arena);
+    let top = to_pixels(arena.goal_depth) + PANEL_PADDING;
+    draw_panel(
+        &lines,
+        |[width, _]| [screen_width - width - PANEL_PADDING, top],
+        glyphs,
+        c,
+        gl,
+    );
+}
+
+/// Shows a panel in the middle of the field with what the replay's header says about the match:
+/// its name, map, date and type, and each team's final scoreboard.
+pub fn draw_info<C, G>(engine: &ReplayEngine, glyphs: &mut C, c: &Context, gl: &mut G)
+where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    let info = engine.match_info();
+    let colors = engine.team_colors();
+
+    let mut lines = vec![];
+    if let Some(name) = &info.name {
+        lines.push((FIELD_LINES, name.clone()));
+    }
+    let mut kind = vec![];
+    if let Some(size) = info.team_size {
+        kind.push(format!("{size}v{size}"));
+    }
+    kind.extend(info.match_type.clone());
+    kind.extend(info.map.clone());
+    if !kind.is_empty() {
+        lines.push((FIELD_LINES, kind.join(", ")));
+    }
+    if let Some(date) = &info.date {
+        lines.push((FIELD_LINES, date.clone()));
+    }
+
+    for (team, score) in [(Team::Blue, info.score.0), (Team::Orange, info.score.1)] {
+        let color = colors.team(team);
+        lines.push((FIELD_LINES, String::new()));
+        let name = info.team_name(team);
+        lines.push((color, format!("{name} {}", score.unwrap_or(0))));
+        for player in info.players.iter().filter(|player| player.team == team) {
+            lines.push((
+                color,
+                format!(
+                    "{}{}: {} points, {} goals, {} assists, {} saves, {} shots",
+                    player.name,
+                    if player.bot { " (bot)" } else { "" },
+                    player.score,
+                    player.goals,
+                    player.assists,
+                    player.saves,
+                    player.shots
+                ),
+            ));
+        }
+    }
+
+    let [screen_width, screen_height] = screen_size(engine.arena());
+    draw_panel(
+        &lines,
+        |[width, height]| [(screen_width - width) / 2.0, (screen_height - height) / 2.0],
+        glyphs,
+        c,
+        gl,
+    );
+}
+
+/// Lists the metrics a `--script` has set in the bottom left corner.
+pub fn draw_metrics<C, G>(
+    engine: &ReplayEngine,
+    metrics: &BTreeMap<String, Dynamic>,
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    if metrics.is_empty() {
+        return;
+    }
+    let lines: Vec<_> = metrics
+        .iter()
+        .map(|(name, value)| {
+            let value = match value.as_float() {
+                Ok(value) => format!("{value:.2}"),
+                Err(_) => value.to_string(),
+            };
+            (FIELD_LINES, format!("{name}: {value}"))
+        })
+        .collect();
+
+    let [_, screen_height] = screen_size(engine.arena());
+    draw_panel(
+        &lines,
+        |[_, height]| [PANEL_PADDING, screen_height - height - PANEL_PADDING],
+        glyphs,
+        c,
+        gl,
+    );
+}
+
+/// While the ball is heading at a goal fast enough to be a threat, shows that goal's face in the
+/// space beside it, as the ball sees it: where the ball would cross the line, how soon, and the
+/// part of the goal each defender in the way covers.
+pub fn draw_threat<C, G>(
+    engine: &ReplayEngine,
+    state: &RenderState,
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let Some(threat) = coverage::threat(engine) else {
+        return;
+    };
+    let arena = engine.arena();
+    let theme = &state.theme;
+    let [width, height] = screen_size(arena);
+    let [_, right_post] = goal_posts(arena);
+    let depth = to_pixels(arena.goal_depth);
+
+    // Fit the goal and the margin around it a threat can still be in, between the goal and the
+    // side of the window, in the goal's own strip of background
+    let half_span = arena.goal_width / 2.0 + THREAT_MARGIN as f64;
+    let span_height = GOAL_HEIGHT + THREAT_MARGIN as f64;
+    let room = [
+        width - right_post - 2.0 * PANEL_PADDING,
+        depth - PANEL_PADDING,
+    ];
+    let scale = (room[0] / (2.0 * half_span)).min(room[1] / span_height);
+    let [panel_width, panel_height] = [2.0 * half_span * scale, span_height * scale];
+    let left = right_post + (width - right_post - panel_width) / 2.0;
+    // Team 0 defends the goal at the top
+    let top = match threat.team {
+        Team::Blue => (depth - panel_height) / 2.0,
+        Team::Orange => height - (depth + panel_height) / 2.0,
+    };
+    let bounds = [left, top, panel_width, panel_height];
+    let to_face = |[x, z]: [f64; 2]| {
+        [
+            (left + (x + half_span) * scale).clamp(left, left + panel_width),
+            (top + panel_height - z * scale).clamp(top, top + panel_height),
+        ]
+    };
+
+    rectangle(theme.background, bounds, c.transform, gl);
+    Rectangle::new_border(theme.lines, 0.5).draw(bounds, &c.draw_state, c.transform, gl);
+    let [r, g, b, _] = engine.team_colors().palette(threat.team)[1];
+    for (center, radius) in &threat.blockers {
+        // Pinned to the panel, so cars off to the side show as the part of them over the goal
+        let outline: Vec<_> = (0..24)
+            .map(|i| {
+                let angle = i as f64 * std::f64::consts::TAU / 24.0;
+                to_face([
+                    center[0] + radius * angle.cos(),
+                    center[1] + radius * angle.sin(),
+                ])
+            })
+            .collect();
+        polygon([r, g, b, 0.5], &outline, c.transform, gl);
+    }
+    let half_goal = arena.goal_width / 2.0;
+    let posts = [
+        to_face([-half_goal, 0.0]),
+        to_face([-half_goal, GOAL_HEIGHT]),
+        to_face([half_goal, GOAL_HEIGHT]),
+        to_face([half_goal, 0.0]),
+    ];
+    for pair in posts.windows(2) {
+        let [[x1, y1], [x2, y2]] = [pair[0], pair[1]];
+        line(theme.lines, 1.0, [x1, y1, x2, y2], c.transform, gl);
+    }
+
+    // Under the floor, the ball would have bounced on its way in
+    let [x, z] = threat.crossing;
+    let [x, y] = to_face([x, z.max(BALL_RADIUS)]);
+    let radius = BALL_RADIUS * scale;
+    rectangle(theme.ball, circle(x, y, radius), c.transform, gl);
+
+    let label = format!("{:.1}s", threat.seconds);
+    let transform = c
+        .transform
+        .trans(left + 3.0, top + QUALITY_SIZE as f64 + 2.0);
+    // A glyph that fails to load just leaves the label empty
+    let _ = text(theme.lines, QUALITY_SIZE, &label, glyphs, transform, gl);
+}
+
+/// Writes a banner across the middle of the field while waiting for a kickoff, with the countdown.
+pub fn draw_kickoff<C, G>(engine: &ReplayEngine, glyphs: &mut C, c: &Context, gl: &mut G)
+where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let Some(countdown) = engine.kickoff() else {
+        return;
+    };
+    let banner = match countdown {
+        0 => "KICKOFF".to_string(),
+        countdown => format!("KICKOFF {countdown}"),
+    };
+
+    let [width, height] = screen_size(engine.arena());
+    let strip = BANNER_SIZE as f64 * 2.0;
+    rectangle(
+        [0.0, 0.0, 0.0, 0.5],
+        [0.0, (height - strip) / 2.0, width, strip],
+        c.transform,
+        gl,
+    );
+    let text_width = glyphs.width(BANNER_SIZE, &banner).unwrap_or_default();
+    let transform = c.transform.trans(
+        (width - text_width) / 2.0,
+        (height + BANNER_SIZE as f64 * 0.7) / 2.0,
+    );
+    // A glyph that fails to load just leaves the banner empty
+    let _ = text(
+        [1.0, 1.0, 1.0, 1.0],
+        BANNER_SIZE,
+        &banner,
+        glyphs,
+        transform,
+        gl,
+    );
+}
+
+/// Marks demolitions and aerial touches on the seek bar, alongside the goals, shots and saves
+/// `draw_timeline` marks, and describes `hovered` in a tooltip over the bar.
+pub fn draw_events<C, G>(
+    engine: &ReplayEngine,
+    events: &[Event],
+    hovered: Option<&Event>,
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let colors = engine.team_colors();
+    let [x, y, width, height] = timeline_rect(engine.arena());
+    let frame_x = |frame: usize| x + width * frame as f64 / engine.frame_count() as f64;
+    let size = EVENT_ICON_SIZE;
+    for event in events {
+        let color = colors.team(event.team);
+        let center = frame_x(event.frame);
+        match event.kind {
+            // A cross in the middle of the bar for a car blowing up
+            EventKind::Demolition => {
+                let middle = y + height / 2.0;
+                let [left, right] = [center - size / 2.0, center + size / 2.0];
+                let [top, bottom] = [middle - size / 2.0, middle + size / 2.0];
+                line_from_to(color, 1.0, [left, top], [right, bottom], c.transform, gl);
+                line_from_to(color, 1.0, [left, bottom], [right, top], c.transform, gl);
+            }
+            // A triangle pointing up along the top of the bar for a touch in the air
+            EventKind::Aerial => polygon(
+                color,
+                &[
+                    [center - size / 2.0, y + size],
+                    [center + size / 2.0, y + size],
+                    [center, y],
+                ],
+                c.transform,
+                gl,
+            ),
+            EventKind::Goal | EventKind::Shot | EventKind::Save => {}
+        }
+    }
+
+    let Some(event) = hovered else {
+        return;
+    };
+    let player = &event.player;
+    let mut tooltip = match event.kind {
+        EventKind::Goal => format!("Goal by {player}"),
+        EventKind::Shot => format!("Shot by {player}"),
+        EventKind::Save => format!("Save by {player}"),
+        EventKind::Demolition => format!("{player} demolished"),
+        EventKind::Aerial => format!("Aerial by {player}"),
+    };
+    if let Some(seconds) = engine.clock_at(event.frame) {
+        tooltip += &format!(" at {}:{:02}", seconds / 60, seconds % 60);
+    }
+    draw_tooltip(engine, &tooltip, frame_x(event.frame), glyphs, c, gl);
+}
+
+/// Writes `tooltip` in a box over the seek bar, centered on `center` as far as the window allows.
+fn draw_tooltip<C, G>(
+    engine: &ReplayEngine,
+    tooltip: &str,
+    center: f64,
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let [_, y, width, _] = timeline_rect(engine.arena());
+    let text_width = glyphs.width(LABEL_SIZE, tooltip).unwrap_or_default();
+    let box_width = text_width + PANEL_PADDING;
+    let box_height = LABEL_SIZE as f64 + PANEL_PADDING / 2.0;
+    // Keep the tooltip over the marker but inside the window
+    let left = (center - box_width / 2.0).clamp(0.0, (width - box_width).max(0.0));
+    let top = y - box_height - 2.0;
+    rectangle(
+        [0.0, 0.0, 0.0, 0.8],
+        [left, top, box_width, box_height],
+        c.transform,
+        gl,
+    );
+    let transform = c.transform.trans(
+        left + PANEL_PADDING / 2.0,
+        top + (box_height + LABEL_SIZE as f64 * 0.7) / 2.0,
+    );
+    // A glyph that fails to load just leaves the tooltip empty
+    let _ = text(
+        [1.0, 1.0, 1.0, 1.0],
+        LABEL_SIZE,
+        tooltip,
+        glyphs,
+        transform,
+        gl,
+    );
+}
+
+/// The event marked on the seek bar closest to `point`, if it's close enough to be pointing at it.
+pub fn event_at<'a>(
+    engine: &ReplayEngine,
+    events: &'a [Event],
+    point: [f64; 2],
+) -> Option<&'a Event> {
+    let [x, _, width, _] = timeline_rect(engine.arena());
+    if !rect_contains(timeline_rect(engine.arena()), point) {
+        return None;
+    }
+    let distance = |event: &Event| {
+        (x + width * event.frame as f64 / engine.frame_count() as f64 - point[0]).abs()
+    };
+    events
+        .iter()
+        .filter(|event| distance(event) <= EVENT_ICON_SIZE / 2.0 + 1.0)
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+}
+
+/// Marks every open net and every team outnumbered at the back with a strip along the bottom of the
+/// seek bar, as long as it lasted, and describes `hovered` in a tooltip over the bar. Open nets are
+/// drawn over the rest.
+pub fn draw_situations<C, G>(
+    engine: &ReplayEngine,
+    situations: &[Situation],
+    hovered: Option<&Situation>,
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let [x, y, width, height] = timeline_rect(engine.arena());
+    let frame_x = |frame: usize| x + width * frame as f64 / engine.frame_count() as f64;
+    for kind in [SituationKind::Outnumbered, SituationKind::OpenNet] {
+        let color = match kind {
+            SituationKind::OpenNet => OPEN_NET,
+            SituationKind::Outnumbered => OUTNUMBERED,
+        };
+        for situation in situations.iter().filter(|situation| situation.kind == kind) {
+            let [start, end] = [situation.start_frame, situation.end_frame + 1].map(frame_x);
+            rectangle(
+                color,
+                [
+                    start,
+                    y + height - SITUATION_HEIGHT,
+                    (end - start).max(1.0),
+                    SITUATION_HEIGHT,
+                ],
+                c.transform,
+                gl,
+            );
+        }
+    }
+
+    let Some(situation) = hovered else {
+        return;
+    };
+    let mut tooltip = format!("{} for {:?}", situation.kind.name(), situation.team);
+    if let Some(seconds) = engine.clock_at(situation.start_frame) {
+        tooltip += &format!(" at {}:{:02}", seconds / 60, seconds % 60);
+    }
+    tooltip += &format!(", {:.1}s", situation.seconds);
+    let center = (frame_x(situation.start_frame) + frame_x(situation.end_frame)) / 2.0;
+    draw_tooltip(engine, &tooltip, center, glyphs, c, gl);
+}
+
+/// The situation marked along the bottom of the seek bar under `point`, if there is one. Open nets
+/// win over the rest, the way they're drawn.
+pub fn situation_at<'a>(
+    engine: &ReplayEngine,
+    situations: &'a [Situation],
+    point: [f64; 2],
+) -> Option<&'a Situation> {
+    let [x, y, width, height] = timeline_rect(engine.arena());
+    let strip = [
+        x,
+        y + height - SITUATION_HEIGHT * 2.0,
+        width,
+        SITUATION_HEIGHT * 2.0,
+    ];
+    if !rect_contains(strip, point) {
+        return None;
+    }
+    let frame = timeline_frame(engine, point[0]);
+    situations
+        .iter()
+        .filter(|situation| (situation.start_frame..=situation.end_frame).contains(&frame))
+        .max_by_key(|situation| situation.kind == SituationKind::OpenNet)
+}
+
+/// Shades the part of the seek bar that playback is looping over, or marks where the loop starts
+/// while it has no end.
+pub fn draw_loop<G>(
+    engine: &ReplayEngine,
+    (start, end): (Option<usize>, Option<usize>),
+    c: &Context,
+    gl: &mut G,
+) where
+    G: Graphics,
+{
+    use graphics::*;
+
+    let Some(start) = start else {
+        return;
+    };
+    let [x, y, width, height] = timeline_rect(engine.arena());
+    let frame_x = |frame: usize| x + width * frame as f64 / engine.frame_count() as f64;
+
+    rectangle(
+        LOOP_MARKER,
+        [frame_x(start) - 1.0, y, 2.0, height],
+        c.transform,
+        gl,
+    );
+    if let Some(end) = end {
+        rectangle(
+            [1.0, 1.0, 1.0, 0.15],
+            [frame_x(start), y, frame_x(end) - frame_x(start), height],
+            c.transform,
+            gl,
+        );
+        rectangle(
+            LOOP_MARKER,
+            [frame_x(end) - 1.0, y, 2.0, height],
+            c.transform,
+            gl,
+        );
+    }
+}
+
+/// Shows a line of typed input in a box across the top of the field.
+pub fn draw_prompt<C, G>(
+    engine: &ReplayEngine,
+    label: &str,
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let [width, _] = screen_size(engine.arena());
+    let text_width = glyphs.width(LABEL_SIZE, label).unwrap_or_default();
+    let box_width = text_width + PANEL_PADDING * 2.0;
+    let box_height = LABEL_SIZE as f64 + PANEL_PADDING;
+    let left = (width - box_width) / 2.0;
+    rectangle(
+        [0.0, 0.0, 0.0, 0.7],
+        [left, PANEL_PADDING, box_width, box_height],
+        c.transform,
+        gl,
+    );
+    let transform = c.transform.trans(
+        left + PANEL_PADDING,
+        PANEL_PADDING + (box_height + LABEL_SIZE as f64 * 0.7) / 2.0,
+    );
+    // A glyph that fails to load just leaves the prompt empty
+    let _ = text(
+        [1.0, 1.0, 1.0, 1.0],
+        LABEL_SIZE,
+        label,
+        glyphs,
+        transform,
+        gl,
+    );
+}
+
+/// Lists the bookmarked moments by game clock down the right side of the field, below the speed,
+/// and marks them on the seek bar.
+pub fn draw_bookmarks<C, G>(
+    engine: &ReplayEngine,
+    frames: &[usize],
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    if frames.is_empty() {
+        return;
+    }
+
+    let [x, y, width, height] = timeline_rect(engine.arena());
+    for &frame in frames {
+        let frame_x = x + width * frame as f64 / engine.frame_count() as f64;
+        rectangle(
+            BOOKMARK,
+            [frame_x - 1.0, y, 2.0, height / 3.0],
+            c.transform,
+            gl,
+        );
+    }
+
+    let [width, _] = screen_size(engine.arena());
+    let line_height = LABEL_SIZE as f64 + 2.0;
+    let mut lines = vec!["Bookmarks".to_string()];
+    lines.extend(frames.iter().map(|&frame| match engine.clock_at(frame) {
+        Some(seconds) => format!("{}:{:02}", seconds / 60, seconds % 60),
+        None => format!("frame {frame}"),
+    }));
+    // The first line sits below the speed
+    for (i, line) in lines.iter().enumerate() {
+        let color = match i.checked_sub(1).map(|i| frames[i]) {
+            Some(frame) if frame == engine.frame_index() => BOOKMARK,
+            _ => FIELD_LINES,
+        };
+        let text_width = glyphs.width(LABEL_SIZE, line).unwrap_or_default();
+        let transform = c
+            .transform
+            .trans(width - text_width - 6.0, line_height * (i + 2) as f64);
+        // A glyph that fails to load just leaves the line out
+        let _ = text(color, LABEL_SIZE, line, glyphs, transform, gl);
+    }
+}
+
+/// Writes how fast the replay is playing in the top right corner, like `2x`, or that it's paused.
+pub fn draw_speed<C, G>(
+    engine: &ReplayEngine,
+    speed: f64,
+    paused: bool,
+    glyphs: &mut C,
+    c: &Context,
+    gl: &mut G,
+) where
+    C: CharacterCache,
+    G: Graphics<Texture = C::Texture>,
+{
+    use graphics::*;
+
+    let label = if paused {
+        "Paused".to_string()
+    } else {
+        // Speeds from --ups can be any fraction, so keep to two places
+        format!("{}x", (speed * 100.0).round() / 100.0)
+    };
+    let [width, _] = screen_size(engine.arena());
+    let text_width = glyphs.width(LABEL_SIZE, &label).unwrap_or_default();
+    let transform = c
+        .transform
+        .trans(width - text_width - 6.0, LABEL_SIZE as f64 + 2.0);
+    // A glyph that fails to load just leaves the label empty
+    let _ = text(FIELD_LINES, LABEL_SIZE, &label, glyphs, transform, gl);
+}
+
+/// How far in the field view can zoom.
+const MAX_ZOOM: f64 = 8.0;
+
+/// How much of the field is shown and where: zoomed in around some point and panned around, or
+/// the whole field when left alone. Everything placed on the field goes through here, so drawing
+/// and clicking agree on where things are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct View {
+    zoom: f64,
+    /// Where the top left corner of the field and goals ends up in the window.
+    offset: [f64; 2],
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            offset: [0.0, 0.0],
+        }
+    }
+}
+
+impl View {
+    /// Zoomed in by `zoom` with the field point `[x, y]` in the middle, or as near the middle as it
+    /// gets without showing past the field.
+    fn centered(arena: &Arena, [x, y]: [f64; 2], zoom: f64) -> Self {
+        let zoom = zoom.clamp(1.0, MAX_ZOOM);
+        let [width, height] = screen_size(arena);
+        let [x, y] = to_screen(arena, x, y);
+        let mut view = Self {
+            zoom,
+            offset: [
+                width / 2.0 - x * zoom,
+                height / 2.0 - (y + to_pixels(arena.goal_depth)) * zoom,
+            ],
+        };
+        view.clamp(arena);
+        view
+    }
+
+    /// Moves the context to field coordinates, which start at the back wall, leaving room above it
+    /// for the top goal.
+    fn field_context(&self, arena: &Arena, c: &Context) -> Context {
+        use graphics::Transformed;
+
+        c.trans(self.offset[0], self.offset[1])
+            .zoom(self.zoom)
+            .trans(0.0, to_pixels(arena.goal_depth))
+    }
+
+    /// Whether only part of the field is shown, from zooming in or following the camera.
+    pub fn is_zoomed(self) -> bool {
+        self.zoom > 1.0
+    }
+
+    /// Where a point in field coordinates ends up in the window.
+    pub fn to_window(self, arena: &Arena, x: f64, y: f64) -> [f64; 2] {
+        let [x, y] = to_screen(arena, x, y);
+        [
+            self.offset[0] + x * self.zoom,
+            self.offset[1] + (y + to_pixels(arena.goal_depth)) * self.zoom,
+        ]
+    }
+
+    /// Field coordinates under a point in the window, undoing `to_window`.
+    pub fn to_field(self, arena: &Arena, [x, y]: [f64; 2]) -> [f64; 2] {
+        let x = (x - self.offset[0]) / self.zoom;
+        let y = (y - self.offset[1]) / self.zoom - to_pixels(arena.goal_depth);
+        [
+            x * SCALE_FACTOR - arena.width / 2.0,
+            y * SCALE_FACTOR - arena.height / 2.0,
+        ]
+    }
+
+    /// Zooms in by `factor`, or out when it's below 1, keeping the point under `window` where it
+    /// is. Never zooms out past the whole field.
+    pub fn zoom_at(&mut self, arena: &Arena, window: [f64; 2], factor: f64) {
+        let zoom = (self.zoom * factor).clamp(1.0, MAX_ZOOM);
+        for (offset, window) in self.offset.iter_mut().zip(window) {
+            *offset = window - (window - *offset) * zoom / self.zoom;
+        }
+        self.zoom = zoom;
+        self.clamp(arena);
+    }
+
+    /// Drags the field along by `delta` pixels.
+    pub fn pan(&mut self, arena: &Arena, delta: [f64; 2]) {
+        self.offset[0] += delta[0];
+        self.offset[1] += delta[1];
+        self.clamp(arena);
+    }
+
+    /// Keeps the field covering the whole of its part of the window.
+    fn clamp(&mut self, arena: &Arena) {
+        for (offset, size) in self.offset.iter_mut().zip(screen_size(arena)) {
+            *offset = offset.clamp(size * (1.0 - self.zoom), 0.0);
+        }
+    }
+}
+
+/// Paints onto the field as `RenderState::draw` lays it out.
+pub struct FieldPainter<'a, G> {
+    arena: &'a Arena,
+    c: &'a Context,
+    gl: &'a mut G,
+}
+
+impl<'a, G: Graphics> FieldPainter<'a, G> {
+    pub fn new(arena: &'a Arena, c: &'a Context, gl: &'a mut G) -> Self {
+        Self { arena, c, gl }
+    }
+
+    fn to_screen(&self, [x, y]: [f64; 2]) -> [f64; 2] {
+        to_screen(self.arena, x, y)
+    }
+}
+
+impl<G: Graphics> Painter for FieldPainter<'_, G> {
+    fn circle(&mut self, center: [f64; 2], radius: f64, color: [f32; 4]) {
+        let [x, y] = self.to_screen(center);
+        graphics::ellipse(
+            color,
+            circle(x, y, to_pixels(radius)),
+            self.c.transform,
+            self.gl,
+        );
+    }
+
+    fn ring(&mut self, center: [f64; 2], radius: f64, width: f64, color: [f32; 4]) {
+        let [x, y] = self.to_screen(center);
+        graphics::Ellipse::new_border(color, to_pixels(width) / 2.0).draw(
+            circle(x, y, to_pixels(radius)),
+            &self.c.draw_state,
+            self.c.transform,
+            self.gl,
+        );
+    }
+
+    fn line(&mut self, from: [f64; 2], to: [f64; 2], width: f64, color: [f32; 4]) {
+        let [x1, y1] = self.to_screen(from);
+        let [x2, y2] = self.to_screen(to);
+        graphics::line(
+            color,
+            to_pixels(width) / 2.0,
+            [x1, y1, x2, y2],
+            self.c.transform,
+            self.gl,
+        );
+    }
+
+    fn polygon(&mut self, points: &[[f64; 2]], color: [f32; 4]) {
+        let points: Vec<_> = points.iter().map(|point| self.to_screen(*point)).collect();
+        graphics::polygon(color, &points, self.c.transform, self.gl);
+    }
+}
+
+/// Scales a length in unreal units to pixels on an unzoomed field.
+pub fn to_pixels(length: f64) -> f64 {
+    length / SCALE_FACTOR
+}
+
+/// Scales a point in field coordinates to the screen.
+pub fn to_screen(arena: &Arena, x: f64, y: f64) -> [f64; 2] {
+    [
+        to_pixels(x + arena.width / 2.0),
+        to_pixels(y + arena.height / 2.0),
+    ]
+}
+
+/// Corners of the field, on screen, going around the walls and their diagonal corner cuts.
+pub fn wall_outline(arena: &Arena) -> [[f64; 2]; 8] {
+    arena.wall_corners().map(|[x, y]| to_screen(arena, x, y))
+}
+
+/// Screen x of the left and right goal posts.
+pub fn goal_posts(arena: &Arena) -> [f64; 2] {
+    [
+        to_screen(arena, -arena.goal_width / 2.0, 0.0)[0],
+        to_screen(arena, arena.goal_width / 2.0, 0.0)[0],
+    ]
+}
+
+/// Draws the field with nothing on it: the background, the playing surface, the goals and the
+/// painted lines, laid out as `RenderState::draw` lays out an unzoomed field.
+pub fn draw_field<G: Graphics>(
+    arena: &Arena,
+    theme: &Theme,
+    colors: &TeamColors,
+    c: &Context,
+    gl: &mut G,
+) {
+    use graphics::Transformed;
+
+    let [width, height] = screen_size(arena);
+    graphics::rectangle(theme.background, [0.0, 0.0, width, height], c.transform, gl);
+    let c = &c.trans(0.0, to_pixels(arena.goal_depth));
+    render_pitch(arena, theme, colors, c, gl);
+    render_markings(arena, theme, colors, c, gl);
+}
+
+/// Fills in the playing surface and both goals.
+fn render_pitch<G: Graphics>(
+    arena: &Arena,
+    theme: &Theme,
+    colors: &TeamColors,
+    c: &Context,
+    gl: &mut G,
+) {
+    use graphics::*;
+
+    polygon(theme.field, &wall_outline(arena), c.transform, gl);
+
+    // Team 0 defends the goal at negative y
+    let [goal_left, goal_right] = goal_posts(arena);
+    let depth = to_pixels(arena.goal_depth);
+    let top = to_screen(arena, 0.0, -arena.height / 2.0)[1];
+    let bottom = to_screen(arena, 0.0, arena.height / 2.0)[1];
+    for (y, color) in [(top - depth, colors.blue[0]), (bottom, colors.orange[1])] {
+        rectangle(
+            color,
+            [goal_left, y, goal_right - goal_left, depth],
+            c.transform,
+            gl,
+        );
+    }
+}
+
+/// Draws the walls and painted lines over whatever the display put on the field, covering anything
+/// that spilled past the corner cuts.
+fn render_markings<G: Graphics>(
+    arena: &Arena,
+    theme: &Theme,
+    colors: &TeamColors,
+    c: &Context,
+    gl: &mut G,
+) {
+    use graphics::*;
+
+    let walls = wall_outline(arena);
+    let [left, top] = to_screen(arena, -arena.width / 2.0, -arena.height / 2.0);
+    let [right, bottom] = to_screen(arena, arena.width / 2.0, arena.height / 2.0);
+    for (corner, cut) in [
+        ([right, top], [walls[1], walls[2]]),
+        ([right, bottom], [walls[3], walls[4]]),
+        ([left, bottom], [walls[5], walls[6]]),
+        ([left, top], [walls[7], walls[0]]),
+    ] {
+        polygon(theme.background, &[corner, cut[0], cut[1]], c.transform, gl);
+    }
+
+    let line = Line::new(theme.lines, 1.0);
+    for i in 0..walls.len() {
+        let [x1, y1] = walls[i];
+        let [x2, y2] = walls[(i + 1) % walls.len()];
+        line.draw([x1, y1, x2, y2], &c.draw_state, c.transform, gl);
+    }
+
+    let [center_x, center_y] = to_screen(arena, 0.0, 0.0);
+    line.draw(
+        [left, center_y, right, center_y],
+        &c.draw_state,
+        c.transform,
+        gl,
+    );
+
+    // Dropshot's floor is all tiles, which are drawn along with their damage
+    if arena.kind == ArenaKind::Dropshot {
+        return;
+    }
+    Ellipse::new_border(theme.lines, 0.5).draw(
+        circle(center_x, center_y, to_pixels(CENTER_CIRCLE_RADIUS)),
+        &c.draw_state,
+        c.transform,
+        gl,
+    );
+
+    if arena.kind == ArenaKind::Hoops {
+        render_hoops(arena, colors, c, gl);
+        return;
+    }
+    for y in [-arena.height / 2.0, arena.height / 2.0 - GOAL_BOX_DEPTH] {
+        let [x, y] = to_screen(arena, -GOAL_BOX_WIDTH / 2.0, y);
+        Rectangle::new_border(theme.lines, 0.5).draw(
+            [x, y, to_pixels(GOAL_BOX_WIDTH), to_pixels(GOAL_BOX_DEPTH)],
+            &c.draw_state,
+            c.transform,
+            gl,
+        );
+    }
+}
+
+/// Draws each Hoops backboard along its back wall with the rim hanging in front of it, in the
+/// color of the team defending it.
+fn render_hoops<G: Graphics>(arena: &Arena, colors: &TeamColors, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    // Team 0 defends the hoop at negative y
+    let half_height = arena.height / 2.0;
+    for (wall, rim, color) in [
+        (
+            -half_height + 10.0,
+            -half_height + HOOP_RADIUS,
+            colors.blue[0],
+        ),
+        (
+            half_height - 10.0,
+            half_height - HOOP_RADIUS,
+            colors.orange[1],
+        ),
+    ] {
+        let [left, y] = to_screen(arena, -BACKBOARD_WIDTH / 2.0, wall);
+        let [right, _] = to_screen(arena, BACKBOARD_WIDTH / 2.0, wall);
+        Line::new(color, 2.0).draw([left, y, right, y], &c.draw_state, c.transform, gl);
+
+        let [x, y] = to_screen(arena, 0.0, rim);
+        Ellipse::new_border(color, 1.5).draw(
+            circle(x, y, to_pixels(HOOP_RADIUS)),
+            &c.draw_state,
+            c.transform,
+            gl,
+        );
+    }
+}
+
+/// Outlines every Dropshot tile, tinting damaged tiles with the color of the team whose side they
+/// are on and blacking out the ones that have been broken through.
+fn render_tiles<G: Graphics>(engine: &ReplayEngine, theme: &Theme, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    let arena = engine.arena();
+    let outline = Line::new(theme.lines, 0.5);
+    for tile in engine.tiles() {
+        let [x, y] = tile.location;
+        let corners: Vec<_> = (0..6)
+            .map(|i| {
+                let angle = std::f64::consts::FRAC_PI_3 * i as f64 + std::f64::consts::FRAC_PI_6;
+                to_screen(
+                    arena,
+                    x + TILE_RADIUS * angle.cos(),
+                    y + TILE_RADIUS * angle.sin(),
+                )
+            })
+            .collect();
+
+        // Team 0 defends the tiles at negative y
+        let colors = engine.team_colors();
+        let mut side = if y < 0.0 {
+            colors.blue[0]
+        } else {
+            colors.orange[1]
+        };
+        match tile.state {
+            TileState::Intact => {}
+            TileState::Damaged => {
+                side[3] = 0.5;
+                polygon(side, &corners, c.transform, gl);
+            }
+            TileState::Destroyed => polygon(theme.background, &corners, c.transform, gl),
+        }
+        for i in 0..corners.len() {
+            let [x1, y1] = corners[i];
+            let [x2, y2] = corners[(i + 1) % corners.len()];
+            outline.draw([x1, y1, x2, y2], &c.draw_state, c.transform, gl);
+        }
+    }
+}
+
+/// Tints the field faintly with the color of the team that has the ball.
+fn render_possession<G: Graphics>(engine: &ReplayEngine, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    let Some(team) = engine.possession().team() else {
+        return;
+    };
+    let colors = engine.team_colors();
+    let mut tint = colors.team(team);
+    tint[3] = 0.12;
+    polygon(tint, &wall_outline(engine.arena()), c.transform, gl);
+}
+
+/// Flashes a cross where each demolished car blew up, then leaves a greyed out marker there ringed
+/// by a countdown that runs out when the car respawns.
+fn render_demolitions<G: Graphics>(engine: &ReplayEngine, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    for demo in engine.demolitions() {
+        let Some(player) = engine.players().get(&demo.player) else {
+            continue;
+        };
+        let [x, y] = to_screen(
+            engine.arena(),
+            demo.location.x as f64,
+            demo.location.y as f64,
+        );
+        let [r, g, b, _] = player.color;
+        let grey = (r + g + b) / 3.0;
+        rectangle(
+            [grey, grey, grey, 0.6],
+            circle(x, y, DOT_RADIUS),
+            c.transform,
+            gl,
+        );
+
+        let remaining = 1.0 - (engine.time() - demo.time) / RESPAWN_SECONDS;
+        let start = -std::f64::consts::FRAC_PI_2;
+        CircleArc::new(
+            player.color,
+            1.0,
+            start,
+            start + std::f64::consts::TAU * remaining as f64,
+        )
+        .draw(circle(x, y, 10.0), &c.draw_state, c.transform, gl);
+
+        let flash = 1.0 - (engine.time() - demo.time) / DEMOLITION_FLASH_SECONDS;
+        if flash > 0.0 {
+            let size = 8.0 + 8.0 * flash as f64;
+            let color = [1.0, 1.0, 1.0, flash];
+            let [left, right, top, bottom] = [x - size, x + size, y - size, y + size];
+            line_from_to(color, 2.0, [left, top], [right, bottom], c.transform, gl);
+            line_from_to(color, 2.0, [left, bottom], [right, top], c.transform, gl);
+        }
+    }
+}
+
+/// Draws a dot for every car in `player_actors`, turned on its corner into a diamond while the car
+/// is in the air and outlined while it's supersonic. A powersliding car leaves skid marks behind it.
+fn render_dots<G: Graphics>(
+    engine: &ReplayEngine,
+    player_actors: &HashMap<ActorId, PlayerDetails>,
+    ringed: bool,
+    c: &Context,
+    gl: &mut G,
+) {
+    use graphics::*;
+
+    let arena = engine.arena();
+    let car_actors = engine.cars();
+    for player in player_actors.values() {
+        if let Some(car) = player.car_actor_id {
+            if let Some(Some(r)) = car_actors.get(&car) {
+                let [x, y] = to_screen(arena, r.location.x as f64, r.location.y as f64);
+                let airborne = engine.air_state(car).is_some_and(AirState::in_air);
+                let velocity = r.linear_velocity.unwrap_or(Vector3f {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                });
+
+                if engine.powersliding(car) {
+                    render_skid([x, y], &velocity, c, gl);
+                }
+
+                // A square, or a diamond as wide as the square is across its diagonal
+                let shape = |radius: f64| match airborne {
+                    true => {
+                        let reach = radius * std::f64::consts::SQRT_2;
+                        [
+                            [x, y - reach],
+                            [x + reach, y],
+                            [x, y + reach],
+                            [x - reach, y],
+                        ]
+                    }
+                    false => [
+                        [x - radius, y - radius],
+                        [x + radius, y - radius],
+                        [x + radius, y + radius],
+                        [x - radius, y + radius],
+                    ],
+                };
+                if ringed {
+                    polygon([0.0, 0.0, 0.0, 1.0], &shape(10.0), c.transform, gl);
+                }
+                polygon(player.color, &shape(DOT_RADIUS), c.transform, gl);
+
+                let speed = (velocity.x.powi(2) + velocity.y.powi(2) + velocity.z.powi(2)).sqrt();
+                if speed >= SUPERSONIC_SPEED {
+                    let outline = shape(DOT_RADIUS + 2.5);
+                    for (i, from) in outline.iter().enumerate() {
+                        let to = outline[(i + 1) % outline.len()];
+                        line_from_to(SUPERSONIC_OUTLINE, 1.0, *from, to, c.transform, gl);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Draws a pair of skid marks trailing back from a car at `center` the way it came.
+fn render_skid<G: Graphics>(center: [f64; 2], velocity: &Vector3f, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    let speed = (velocity.x as f64).hypot(velocity.y as f64);
+    if speed < 1.0 {
+        return;
+    }
+    let [x, y] = center;
+    let back = [-velocity.x as f64 / speed, -velocity.y as f64 / speed];
+    let length = to_pixels(speed * SKID_SECONDS);
+    // One mark under each side of the car
+    for side in [-1.0, 1.0] {
+        let [offset_x, offset_y] = [-back[1] * side * 4.0, back[0] * side * 4.0];
+        line_from_to(
+            SKID,
+            1.5,
+            [x + offset_x, y + offset_y],
+            [
+                x + offset_x + back[0] * length,
+                y + offset_y + back[1] * length,
+            ],
+            c.transform,
+            gl,
+        );
+    }
+}
+
+/// Whether the ball bounced off the ground, ceiling or a wall between two velocity readings, going
+/// by which way it was moving and whether it is close enough to that surface.
+fn bounced(arena: &Arena, location: &Vector3f, before: &Vector3f, after: &Vector3f) -> bool {
+    // How close the ball's center has to be to a surface to count as touching it
+    const REACH: f32 = BALL_RADIUS as f32 + 60.0;
+    let side_wall = arena.width as f32 / 2.0 - REACH;
+    let back_wall = arena.height as f32 / 2.0 - REACH;
+
+    let ground = before.z < 0.0 && after.z >= 0.0 && location.z < REACH;
+    let ceiling = before.z > 0.0 && after.z <= 0.0 && location.z > CEILING_HEIGHT as f32 - REACH;
+    let side = before.x.signum() != after.x.signum() && location.x.abs() > side_wall;
+    let back = before.y.signum() != after.y.signum() && location.y.abs() > back_wall;
+    ground || ceiling || side || back
+}
+
+/// Draws a trail as a line that fades out towards its oldest point.
+fn render_trail<G: Graphics>(trail: &Trail, now: f32, seconds: f32, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    for ((_, from), (time, to)) in trail.points.iter().zip(trail.points.iter().skip(1)) {
+        let mut color = trail.color;
+        color[3] = 1.0 - (now - time) / seconds;
+        Line::new(color, 1.0).draw(
+            [from[0], from[1], to[0], to[1]],
+            &c.draw_state,
+            c.transform,
+            gl,
+        );
+    }
+}
+
+/// Draws every boost pad, filled while it's there to be picked up and as a grey outline while it
+/// respawns.
+fn render_boost_pads<G: Graphics>(engine: &ReplayEngine, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    let arena = engine.arena();
+    for (pad, available) in engine.boost_pads() {
+        let [x, y] = to_screen(arena, pad.location[0], pad.location[1]);
+        let radius = if pad.big {
+            BIG_PAD_RADIUS
+        } else {
+            SMALL_PAD_RADIUS
+        };
+        if available {
+            Ellipse::new([BOOST[0], BOOST[1], BOOST[2], 0.8]).draw(
+                circle(x, y, radius),
+                &c.draw_state,
+                c.transform,
+                gl,
+            );
+        } else {
+            Ellipse::new_border([0.5, 0.5, 0.5, 0.8], 0.75).draw(
+                circle(x, y, radius),
+                &c.draw_state,
+                c.transform,
+                gl,
+            );
+        }
+    }
+}
+
+/// Draws a small bar under every car showing how much boost it has.
+fn render_boost<G: Graphics>(engine: &ReplayEngine, state: &RenderState, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    for (player, body) in engine.player_positions() {
+        if !state.shows(player) {
+            continue;
+        }
+        let Some(boost) = player.car_actor_id.and_then(|car| engine.boost(car)) else {
+            continue;
+        };
+
+        let [x, y] = to_screen(
+            engine.arena(),
+            body.location.x as f64,
+            body.location.y as f64,
+        );
+        let [left, top] = [x - BOOST_BAR_WIDTH / 2.0, y + 9.0];
+        rectangle(
+            [0.0, 0.0, 0.0, 0.6],
+            [left, top, BOOST_BAR_WIDTH, 3.0],
+            c.transform,
+            gl,
+        );
+        rectangle(
+            BOOST,
+            [left, top, BOOST_BAR_WIDTH * boost as f64, 3.0],
+            c.transform,
+            gl,
+        );
+    }
+}
+
+/// Draws an arrow from every car and the ball to where it would be in `VELOCITY_SECONDS`.
+fn render_velocity<G: Graphics>(
+    engine: &ReplayEngine,
+    state: &RenderState,
+    c: &Context,
+    gl: &mut G,
+) {
+    use graphics::*;
+
+    let bodies = engine
+        .player_positions()
+        .filter(|(player, _)| state.shows(player))
+        .map(|(_, body)| *body)
+        .chain(engine.ball());
+    for body in bodies {
+        let Some(velocity) = body.linear_velocity else {
+            continue;
+        };
+
+        let [x, y] = to_screen(
+            engine.arena(),
+            body.location.x as f64,
+            body.location.y as f64,
+        );
+        let dx = to_pixels(velocity.x as f64 * VELOCITY_SECONDS);
+        let dy = to_pixels(velocity.y as f64 * VELOCITY_SECONDS);
+        if dx.hypot(dy) < 1.0 {
+            continue;
+        }
+
+        Line::new([1.0, 1.0, 1.0, 0.9], 0.75).draw_arrow(
+            [x, y, x + dx, y + dy],
+            4.0,
+            &c.draw_state,
+            c.transform,
+            gl,
+        );
+    }
+}
+
+/// Draws a dotted line along where the ball goes over the next `PREDICTION_SECONDS` if nobody
+/// touches it, fading out the further ahead it is.
+fn render_prediction<G: Graphics>(
+    engine: &ReplayEngine,
+    state: &RenderState,
+    c: &Context,
+    gl: &mut G,
+) {
+    use graphics::*;
+
+    let Some(ball) = engine.ball() else {
+        return;
+    };
+    let Some(velocity) = ball.linear_velocity else {
+        return;
+    };
+    let arena = engine.arena();
+    let path = prediction::predict(arena, &ball.location, &velocity, PREDICTION_SECONDS);
+    let steps = (PREDICTION_SECONDS / prediction::STEP) as f32;
+    let [r, g, b, _] = state.theme().ball;
+    for (i, [x, y, _]) in path
+        .into_iter()
+        .enumerate()
+        .skip(PREDICTION_DOT_STEPS)
+        .step_by(PREDICTION_DOT_STEPS)
+    {
+        let [x, y] = to_screen(arena, x, y);
+        let alpha = 0.9 - 0.7 * i as f32 / steps;
+        rectangle([r, g, b, alpha], circle(x, y, 1.5), c.transform, gl);
+    }
+}
+
+/// Colors each player's cell of the voronoi diagram in their own color.
+fn render_voronoi<G: Graphics>(engine: &ReplayEngine, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    let arena = engine.arena();
+    for (player, cell) in zones::player_cells(engine) {
+        let vertices: Vec<_> = cell.iter().map(|p| to_screen(arena, p[0], p[1])).collect();
+        polygon(player.color, &vertices, c.transform, gl);
+    }
+}
+
+/// Screen rectangle covered by a heatmap cell, cut off at the back wall.
+fn cell_rect(arena: &Arena, column: usize, row: usize) -> [f64; 4] {
+    let size = to_pixels(CELL_SIZE);
+    let y = row as f64 * size;
+    let height = size.min(to_pixels(arena.height) - y);
+    [column as f64 * size, y, size, height]
+}
+
+fn render_weighted_voronoi<G: Graphics>(engine: &ReplayEngine, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    let arena = engine.arena();
+    for (player, cell) in zones::weighted_player_cells(engine) {
+        let vertices: Vec<_> = cell.iter().map(|p| to_screen(arena, p[0], p[1])).collect();
+        polygon(player.color, &vertices, c.transform, gl);
+    }
+}
+
+/// Colors each team's space as one region, with a line along the boundary between them.
+fn render_team_voronoi<G: Graphics>(engine: &ReplayEngine, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    let arena = engine.arena();
+    let colors = engine.team_colors();
+    for (player, cell) in zones::player_cells(engine) {
+        let color = colors.team(player.team);
+        let vertices: Vec<_> = cell.iter().map(|p| to_screen(arena, p[0], p[1])).collect();
+        polygon(color, &vertices, c.transform, gl);
+    }
+
+    let boundary = Line::new([1.0, 1.0, 1.0, 1.0], 1.5);
+    for [a, b] in zones::contested_edges(engine) {
+        let [x1, y1] = to_screen(arena, a[0], a[1]);
+        let [x2, y2] = to_screen(arena, b[0], b[1]);
+        boundary.draw([x1, y1, x2, y2], &c.draw_state, c.transform, gl);
+    }
+}
+
+/// Draws a line between every pair of teammates, thicker and brighter the less of it runs through
+/// the other team's space, so the passes nobody can cut off stand out.
+fn render_passing_lanes<G: Graphics>(engine: &ReplayEngine, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    let arena = engine.arena();
+    let colors = engine.team_colors();
+    for lane in zones::passing_lanes(engine) {
+        let color = colors.team(lane.team);
+        let alpha = 0.15 + 0.85 * lane.open as f32;
+        let [x1, y1] = to_screen(arena, lane.from[0], lane.from[1]);
+        let [x2, y2] = to_screen(arena, lane.to[0], lane.to[1]);
+        Line::new([color[0], color[1], color[2], alpha], 0.5 + 2.0 * lane.open).draw(
+            [x1, y1, x2, y2],
+            &c.draw_state,
+            c.transform,
+            gl,
+        );
+    }
+}
+
+/// Shades the area between each team's cars, outlined in the team's color, with a cross at its
+/// middle. A tight triangle is a team holding its shape, and a long thin one is a team strung out.
+fn render_team_shapes<G: Graphics>(engine: &ReplayEngine, c: &Context, gl: &mut G) {
+    use graphics::*;
+
+    let arena = engine.arena();
+    let colors = engine.team_colors();
+    for shape in zones::team_shapes(engine) {
+        let [r, g, b, _] = colors.palette(shape.team)[1];
+        let hull: Vec<_> = shape
+            .hull
+            .iter()
+            .map(|&[x, y]| to_screen(arena, x, y))
+            .collect();
+        if hull.len() >= 3 {
+            polygon([r, g, b, 0.25], &hull, c.transform, gl);
+        }
+        // Two cars have no area between them, so their outline is just the line joining them
+        if hull.len() >= 2 {
+            for (i, &[x1, y1]) in hull.iter().enumerate() {
+                let [x2, y2] = hull[(i + 1) % hull.len()];
+                line([r, g, b, 0.8], 0.75, [x1, y1, x2, y2], c.transform, gl);
+            }
+        }
+        let [x, y] = to_screen(arena, shape.center[0], shape.center[1]);
+        let arm = 6.0;
+        line(
+            [r, g, b, 1.0],
+            1.0,
+            [x - arm, y - arm, x + arm, y + arm],
+            c.transform,
+            gl,
+        );
+        line(
+            [r, g, b, 1.0],
+            1.0,
+            [x - arm, y + arm, x + arm, y - arm],
+            c.transform,
+            gl,
+        );
+    }
+}
+
+fn render_zone_heatmap<G: Graphics>(
+    arena: &Arena,
+    heatmap: &ZoneHeatmap,
+    colors: &TeamColors,
+    c: &Context,
+    gl: &mut G,
+) {
+    use graphics::*;
+
+    for row in 0..heatmap.rows() {
+        for column in 0..heatmap.columns() {
+            let Some(orange_share) = heatmap.orange_share(column, row) else {
+                continue;
+            };
+
+            let orange_share = orange_share as f32;
+            let mut color = [0.0; 4];
+            for (channel, value) in color.iter_mut().enumerate() {
+                *value = colors.orange[0][channel] * orange_share
+                    + colors.blue[0][channel] * (1.0 - orange_share);
+            }
+
+            rectangle(color, cell_rect(arena, column, row), c.transform, gl);
+        }
+    }
+}
+
+fn render_position_heatmap<G: Graphics>(
+    arena: &Arena,
+    heatmap: &PositionHeatmap,
+    c: &Context,
+    gl: &mut G,
+) {
+    use graphics::*;
+
+    for row in 0..heatmap.rows() {
+        for column in 0..heatmap.columns() {
+            let density = heatmap.density(column, row) as f32;
+            if density == 0.0 {
+                continue;
+            }
+
+            // Fades from a faint yellow for rarely visited cells to solid red for the most visited
+            rectangle(
+                [1.0, 1.0 - density, 0.0, 0.25 + 0.75 * density],
+                cell_rect(arena, column, row),
+                c.transform,
+                gl,
+            );
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::error;
+
+use glutin_window::{GlutinWindow, OpenGL};
+use graphics::{CharacterCache, Context, Graphics, ImageSize, Viewport};
+use opengl_graphics::{GlGraphics, GlyphCache, TextureSettings};
+use piston::{AdvancedWindow, Window, WindowSettings};
+
+use crate::app::scene::Scene;
+use crate::app::FONT;
+
+/// What the window draws each frame with. Everything on screen is drawn through piston's
+/// `Graphics`, so a renderer only has to hand one out along with a glyph cache whose textures it
+/// can draw.
+pub trait Renderer {
+    type Texture: ImageSize;
+    type Graphics: Graphics<Texture = Self::Texture>;
+    type Glyphs: CharacterCache<Texture = Self::Texture>;
+
+    /// Whether `draw` draws the 3D display's scenes. Only a renderer with a depth buffer can.
+    const DRAWS_SCENES: bool = false;
+
+    /// Draws one frame into `viewport`: `scenes` first, if it draws them, then `f` over them,
+    /// showing it once `f` returns.
+    fn draw<F>(&mut self, viewport: Viewport, scenes: &[Scene], f: F)
+    where
+        F: FnOnce(Context, &mut Self::Graphics, &mut Self::Glyphs);
+}
+
+/// A window a `Renderer` draws into, with the winit window under it for what piston doesn't
+/// cover: going fullscreen, staying on top of other windows and hiding the cursor.
+pub trait AppWindow: Window + AdvancedWindow {
+    fn winit(&self) -> &winit::window::Window;
+}
+
+impl AppWindow for GlutinWindow {
+    fn winit(&self) -> &winit::window::Window {
+        &self.window
+    }
+}
+
+/// OpenGL versions the window tries, in order. Nothing drawn needs more than 3.2, and 2.1 still
+/// opens on old integrated graphics and in virtual machines that have nothing newer.
+const OPENGL_VERSIONS: [OpenGL; 2] = [OpenGL::V3_2, OpenGL::V2_1];
+
+/// Draws with OpenGL through `opengl_graphics`.
+pub struct GlRenderer {
+    gl: GlGraphics,
+    glyphs: GlyphCache<'static>,
+}
+
+impl GlRenderer {
+    /// Opens a window with the newest OpenGL in `OPENGL_VERSIONS` the machine has, and a renderer
+    /// for it.
+    pub fn open(settings: &WindowSettings) -> Result<(GlutinWindow, Self), Box<dyn error::Error>> {
+        let mut failure = None;
+        for opengl in OPENGL_VERSIONS {
+            match settings.clone().graphics_api(opengl).build() {
+                Ok(window) => {
+                    let glyphs = GlyphCache::from_bytes(FONT, (), TextureSettings::new())
+                        .expect("the bundled font is valid");
+                    let renderer = Self {
+                        gl: GlGraphics::new(opengl),
+                        glyphs,
+                    };
+                    return Ok((window, renderer));
+                }
+                Err(e) => failure = Some(e),
+            }
+        }
+        let e = failure.map(|e| e.to_string()).unwrap_or_default();
+        Err(format!("couldn't open a window with OpenGL 2.1 or newer: {e}").into())
+    }
+}
+
+impl Renderer for GlRenderer {
+    type Texture = opengl_graphics::Texture;
+    type Graphics = GlGraphics;
+    type Glyphs = GlyphCache<'static>;
+
+    fn draw<F>(&mut self, viewport: Viewport, _scenes: &[Scene], f: F)
+    where
+        F: FnOnce(Context, &mut GlGraphics, &mut GlyphCache<'static>),
+    {
+        let glyphs = &mut self.glyphs;
+        self.gl.draw(viewport, |c, gl| f(c, gl, glyphs));
+    }
+}
@@ -0,0 +1,566 @@
+use std::cmp::Ordering;
+use std::error;
+use std::fmt::{self, Write as _};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use image::ImageFormat;
+use rayon::prelude::*;
+
+use crate::app::export::render_canvas;
+use crate::app::render::RenderState;
+use crate::app::DisplayType;
+use crate::boost::PlayerBoost;
+use crate::heatmap::PositionHeatmap;
+use crate::kickoffs;
+use crate::movement::PlayerMovement;
+use crate::rotation::RoleShares;
+use crate::shots::{self, ShotKind};
+use crate::timeline::Timeline;
+use crate::{parse_replay, write, zones, GamePhase, PlayerDetails, ReplayEngine, Team};
+
+const STYLE: &str = "
+body { background: #1a1a1a; color: #eee; font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { padding: 0.3em 0.8em; text-align: right; }
+th:first-child, td:first-child { text-align: left; }
+tr:nth-child(even) { background: #262626; }
+.orange { color: #ff9c33; }
+.blue { color: #4d9eff; }
+.heatmaps { display: flex; flex-wrap: wrap; gap: 1em; }
+figure { margin: 0; }
+img { max-height: 480px; }
+";
+
+/// Escapes text so it can be put in HTML as is.
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Formats seconds as `m:ss`.
+fn clock(seconds: f32) -> String {
+    let seconds = seconds.max(0.0) as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn team_class(team: Team) -> &'static str {
+    match team {
+        Team::Orange => "orange",
+        Team::Blue => "blue",
+    }
+}
+
+/// Renders the state at the engine's current frame as a PNG, ready to put in an `<img>`.
+fn image_data(engine: &ReplayEngine, state: &RenderState) -> Result<String, Box<dyn error::Error>> {
+    let mut png = vec![];
+    render_canvas(engine, state)
+        .into_image()
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(format!("data:image/png;base64,{}", BASE64.encode(png)))
+}
+
+/// Plays the replay through and writes a single HTML file covering the whole game: the score,
+/// each team's average zone control, the zone control heatmap and every player's position heatmap,
+/// boost and movement stats, how each kickoff went with every player's kickoff win rate, and the
+/// list of shots and saves. Images are embedded so the file can be shared on its own.
+pub fn write_report(mut engine: ReplayEngine, path: &Path) -> Result<(), Box<dyn error::Error>> {
+    let start = engine.clone();
+    let shots = shots::shots(&engine);
+    let kickoffs = kickoffs::kickoffs(&engine);
+
+    let mut state = RenderState::new(DisplayType::HEATMAP, engine.arena());
+    let (mut orange_control, mut blue_control, mut control_frames) = (0.0, 0.0, 0);
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+        state.observe(&engine);
+        if let Some(control) = zones::zone_control(&engine) {
+            orange_control += control.orange;
+            blue_control += control.blue;
+            control_frames += 1;
+        }
+    }
+    let first_time = engine.time() - engine.elapsed();
+    let score = engine.score();
+
+    let mut html = String::new();
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Replay report</title>\n<style>{STYLE}</style>\n</head>\n<body>"
+    )?;
+    writeln!(
+        html,
+        "<h1><span class=\"orange\">Orange {}</span> - <span class=\"blue\">{} Blue</span></h1>",
+        score.orange, score.blue
+    )?;
+    writeln!(html, "<p>Replay length {}</p>", clock(engine.elapsed()))?;
+    let info = engine.match_info();
+    let about: Vec<_> = [
+        info.name.clone(),
+        info.date.clone(),
+        Some(info.description()),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|line| escape(&line))
+    .collect();
+    writeln!(html, "<p>{}</p>", about.join("<br>"))?;
+
+    if !info.players.is_empty() {
+        writeln!(html, "<h2>Scoreboard</h2>\n<table>")?;
+        writeln!(
+            html,
+            "<tr><th>Player</th><th>Score</th><th>Goals</th><th>Assists</th><th>Saves</th><th>Shots</th></tr>"
+        )?;
+        for player in &info.players {
+            writeln!(
+                html,
+                "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                team_class(player.team),
+                escape(&player.name),
+                player.score,
+                player.goals,
+                player.assists,
+                player.saves,
+                player.shots,
+            )?;
+        }
+        writeln!(html, "</table>")?;
+    }
+
+    writeln!(html, "<h2>Zone Control</h2>")?;
+    if control_frames > 0 {
+        writeln!(
+            html,
+            "<p><span class=\"orange\">Orange {:.1}%</span> / <span class=\"blue\">Blue {:.1}%</span> of the field on average</p>",
+            orange_control / control_frames as f64 * 100.0,
+            blue_control / control_frames as f64 * 100.0,
+        )?;
+    }
+    writeln!(html, "<div class=\"heatmaps\">")?;
+    writeln!(
+        html,
+        "<figure><img src=\"{}\"><figcaption>Zone control</figcaption></figure>",
+        image_data(&engine, &state)?
+    )?;
+    let timeline = Timeline::build(&start);
+    for (player, team, _) in engine.occupancy().shares() {
+        let heatmap = PositionHeatmap::from_timeline(&timeline, player)?;
+        let state = RenderState::new(DisplayType::PLAYER_HEATMAP, engine.arena())
+            .with_player_heatmap(heatmap);
+        writeln!(
+            html,
+            "<figure><img src=\"{}\"><figcaption class=\"{}\">{}</figcaption></figure>",
+            image_data(&engine, &state)?,
+            team_class(team),
+            escape(player)
+        )?;
+    }
+    writeln!(html, "</div>")?;
+
+    writeln!(html, "<h2>Boost</h2>\n<table>")?;
+    writeln!(
+        html,
+        "<tr><th>Player</th><th>Big pads</th><th>Small pads</th><th>Collected</th><th>Used</th><th>Time empty</th><th>Time full</th></tr>"
+    )?;
+    for (player, team, boost) in engine.boost_stats().players() {
+        writeln!(
+            html,
+            "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{:.0}</td><td>{:.0}</td><td>{:.0}s</td><td>{:.0}s</td></tr>",
+            team_class(team),
+            escape(player),
+            boost.big_pads,
+            boost.small_pads,
+            boost.collected,
+            boost.used,
+            boost.seconds_empty,
+            boost.seconds_full,
+        )?;
+    }
+    writeln!(html, "</table>")?;
+
+    writeln!(html, "<h2>Movement</h2>\n<table>")?;
+    writeln!(
+        html,
+        "<tr><th>Player</th><th>Average speed</th><th>Time supersonic</th><th>Time in the air</th><th>Distance</th></tr>"
+    )?;
+    for (player, team, movement) in engine.movement().players() {
+        // Unreal units are centimeters
+        writeln!(
+            html,
+            "<tr class=\"{}\"><td>{}</td><td>{:.0} km/h</td><td>{:.0}s</td><td>{:.0}s</td><td>{:.1} km</td></tr>",
+            team_class(team),
+            escape(player),
+            movement.average_speed() * 0.036,
+            movement.seconds_supersonic,
+            movement.seconds_airborne,
+            movement.distance / 100_000.0,
+        )?;
+    }
+    writeln!(html, "</table>")?;
+
+    writeln!(html, "<h2>Kickoffs</h2>\n<table>")?;
+    writeln!(
+        html,
+        "<tr><th>Time</th><th>Went for the ball</th><th>First touch</th><th>Time to touch</th><th>Won by</th></tr>"
+    )?;
+    for kickoff in &kickoffs {
+        let went: Vec<_> = kickoff
+            .players
+            .iter()
+            .filter(|player| player.went_for_ball)
+            .map(|player| {
+                format!(
+                    "<span class=\"{}\">{}</span> ({})",
+                    team_class(player.team),
+                    escape(&player.name),
+                    player.spot.name()
+                )
+            })
+            .collect();
+        let (touch, seconds) = match &kickoff.first_touch {
+            Some(touch) => (
+                format!(
+                    "<span class=\"{}\">{}</span>",
+                    team_class(touch.team),
+                    escape(&touch.player)
+                ),
+                format!("{:.1}s", touch.seconds),
+            ),
+            None => Default::default(),
+        };
+        let winner = match kickoff.winner {
+            Some(Team::Orange) => "<span class=\"orange\">Orange</span>",
+            Some(Team::Blue) => "<span class=\"blue\">Blue</span>",
+            None => "",
+        };
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{touch}</td><td>{seconds}</td><td>{winner}</td></tr>",
+            clock(kickoff.time - first_time),
+            went.join(", "),
+        )?;
+    }
+    writeln!(html, "</table>\n<table>")?;
+    writeln!(
+        html,
+        "<tr><th>Player</th><th>Kickoffs</th><th>Went for the ball</th><th>First touches</th><th>Won</th><th>Win rate</th></tr>"
+    )?;
+    for player in kickoffs::player_kickoffs(&kickoffs) {
+        let win_rate = player
+            .win_rate()
+            .map(|rate| format!("{:.0}%", rate * 100.0))
+            .unwrap_or_default();
+        writeln!(
+            html,
+            "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{win_rate}</td></tr>",
+            team_class(player.team),
+            escape(&player.player),
+            player.kickoffs,
+            player.went_for_ball,
+            player.first_touches,
+            player.won,
+        )?;
+    }
+    writeln!(html, "</table>")?;
+
+    writeln!(html, "<h2>Shots and Saves</h2>\n<table>")?;
+    writeln!(
+        html,
+        "<tr><th>Time</th><th>Player</th><th>Kind</th><th>Speed</th><th>Quality</th></tr>"
+    )?;
+    for shot in &shots {
+        let kind = match shot.kind {
+            ShotKind::Shot => "Shot",
+            ShotKind::Save => "Save",
+        };
+        let velocity = shot.velocity;
+        let speed = (velocity.x * velocity.x + velocity.y * velocity.y + velocity.z * velocity.z)
+            .sqrt()
+            * 0.036;
+        let quality = shot
+            .quality
+            .map(|quality| format!("{:.0}%", quality * 100.0))
+            .unwrap_or_default();
+        writeln!(
+            html,
+            "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{kind}</td><td>{speed:.0} km/h</td><td>{quality}</td></tr>",
+            team_class(shot.team),
+            clock(shot.time - first_time),
+            escape(&shot.player),
+        )?;
+    }
+    writeln!(html, "</table>\n</body>\n</html>")?;
+
+    write(path, html)?;
+    Ok(())
+}
+
+/// The focused player's stats over one game, or added up over several.
+#[derive(Clone)]
+struct PlayerGame {
+    /// What the player was called, since players can change their names between games.
+    name: String,
+    team: Team,
+    /// Whether the player's team won, lost or drew.
+    result: Ordering,
+    control: f64,
+    control_frames: usize,
+    boost: PlayerBoost,
+    movement: PlayerMovement,
+    thirds: [f64; 3],
+    roles: [f64; 3],
+    /// Where the player was, turned so their own goal is at the bottom whichever team they were on.
+    heatmap: PositionHeatmap,
+}
+
+impl PlayerGame {
+    /// Adds another game's stats to these, weighting shares by how long the player was on the
+    /// field in each game. Returns `false` if the other game's heatmap was left out for being on a
+    /// different size arena.
+    fn add(&mut self, other: &PlayerGame) -> bool {
+        let seconds = self.movement.seconds as f64;
+        let other_seconds = other.movement.seconds as f64;
+        let total_seconds = (seconds + other_seconds).max(f64::EPSILON);
+        for i in 0..3 {
+            self.thirds[i] =
+                (self.thirds[i] * seconds + other.thirds[i] * other_seconds) / total_seconds;
+            self.roles[i] =
+                (self.roles[i] * seconds + other.roles[i] * other_seconds) / total_seconds;
+        }
+
+        self.control += other.control;
+        self.control_frames += other.control_frames;
+        self.boost.big_pads += other.boost.big_pads;
+        self.boost.small_pads += other.boost.small_pads;
+        self.boost.collected += other.boost.collected;
+        self.boost.used += other.boost.used;
+        self.boost.seconds_empty += other.boost.seconds_empty;
+        self.boost.seconds_full += other.boost.seconds_full;
+        self.movement.distance += other.movement.distance;
+        self.movement.seconds += other.movement.seconds;
+        self.movement.seconds_supersonic += other.movement.seconds_supersonic;
+        self.movement.seconds_airborne += other.movement.seconds_airborne;
+        self.heatmap.merge(&other.heatmap)
+    }
+
+    /// Writes the stats as a row of the player report's table.
+    fn write_row(&self, html: &mut String, label: &str, class: &str) -> fmt::Result {
+        let percent = |shares: [f64; 3]| {
+            shares
+                .map(|share| format!("{:.0}%", share * 100.0))
+                .join(" / ")
+        };
+        writeln!(
+            html,
+            "<tr class=\"{class}\"><td>{label}</td><td>{:.1}%</td><td>{:.0}</td><td>{:.0}</td><td>{:.0}s</td><td>{:.0} km/h</td><td>{:.0}s</td><td>{:.0}s</td><td>{}</td><td>{}</td></tr>",
+            self.control / self.control_frames.max(1) as f64 * 100.0,
+            self.boost.collected,
+            self.boost.used,
+            self.boost.seconds_empty,
+            self.movement.average_speed() * 0.036,
+            self.movement.seconds_supersonic,
+            self.movement.seconds_airborne,
+            percent(self.thirds),
+            percent(self.roles),
+        )
+    }
+}
+
+fn is_focus(player: &PlayerDetails, focus: &str) -> bool {
+    player.name.eq_ignore_ascii_case(focus) || player.online_id().is_some_and(|id| id == focus)
+}
+
+/// Plays a replay through and picks out the focused player's stats, or `None` if they weren't in
+/// it.
+fn player_game(
+    start: &ReplayEngine,
+    focus: &str,
+) -> Result<Option<PlayerGame>, Box<dyn error::Error>> {
+    let mut engine = start.clone();
+    let mut player = None;
+    let mut roles = RoleShares::default();
+    let (mut control, mut control_frames) = (0.0, 0);
+    let mut last_time = None;
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+        let seconds = last_time.map_or(0.0, |last| engine.time() - last);
+        last_time = Some(engine.time());
+
+        // Only counts once they're on the field, since players who leave early can linger
+        let Some((details, _)) = engine.player_positions().find(|(p, _)| is_focus(p, focus)) else {
+            continue;
+        };
+        player = Some((details.name.clone(), details.team));
+        if !matches!(engine.phase(), GamePhase::Regulation | GamePhase::Overtime) {
+            continue;
+        }
+        roles.observe(&engine, seconds);
+        let share = zones::player_control(&engine)
+            .into_iter()
+            .find(|(player, _)| is_focus(player, focus));
+        if let Some((_, share)) = share {
+            control += share;
+            control_frames += 1;
+        }
+    }
+    let Some((name, team)) = player else {
+        return Ok(None);
+    };
+
+    let score = engine.score();
+    let result = match team {
+        Team::Orange => score.orange.cmp(&score.blue),
+        Team::Blue => score.blue.cmp(&score.orange),
+    };
+    // Blue defends the goal drawn at the top
+    let heatmap = PositionHeatmap::for_player(start, &name)?;
+    let heatmap = match team {
+        Team::Blue => heatmap.rotated(),
+        Team::Orange => heatmap,
+    };
+    let shares = |shares: Vec<(&str, Team, [f64; 3])>| {
+        shares
+            .into_iter()
+            .find(|(player, ..)| *player == name)
+            .map_or([0.0; 3], |(.., shares)| shares)
+    };
+    Ok(Some(PlayerGame {
+        result,
+        control,
+        control_frames,
+        boost: engine
+            .boost_stats()
+            .players()
+            .into_iter()
+            .find(|(player, ..)| *player == name)
+            .map(|(.., boost)| boost)
+            .unwrap_or_default(),
+        movement: engine
+            .movement()
+            .players()
+            .into_iter()
+            .find(|(player, ..)| *player == name)
+            .map(|(.., movement)| movement)
+            .unwrap_or_default(),
+        thirds: shares(engine.occupancy().shares()),
+        roles: shares(roles.shares()),
+        heatmap,
+        name,
+        team,
+    }))
+}
+
+/// Writes an HTML report on one player across every replay in `paths` they played in, matched by
+/// name or by online id: their position heatmap over all of the games, and their zone control,
+/// boost, movement, thirds and rotation stats both in total and game by game. Replays that fail to
+/// parse or that the player wasn't in are reported and left out.
+pub fn write_player_report(
+    paths: &[PathBuf],
+    focus: &str,
+    skip_goal_replays: bool,
+    path: &Path,
+) -> Result<(), Box<dyn error::Error>> {
+    let open = |replay: &Path| -> Result<ReplayEngine, Box<dyn error::Error>> {
+        let mut engine = ReplayEngine::new(parse_replay(replay)?);
+        engine.skip_goal_replays(skip_goal_replays);
+        Ok(engine)
+    };
+    // Games are read across every thread in rayon's pool, keeping the order they were given in.
+    // Boxed errors can't be sent between threads, so only their messages come back.
+    let results: Vec<_> = paths
+        .par_iter()
+        .map(|replay| {
+            eprintln!("processing {}", replay.display());
+            open(replay)
+                .and_then(|engine| player_game(&engine, focus))
+                .map_err(|e| e.to_string())
+        })
+        .collect();
+    let mut games = vec![];
+    let mut background = None;
+    for (replay, game) in paths.iter().zip(results) {
+        match game {
+            Ok(Some(game)) => {
+                let name = replay
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                games.push((name, game));
+                // The heatmap is drawn over the first game's first frame, before any cars are out
+                background.get_or_insert(replay);
+            }
+            Ok(None) => eprintln!("skipping {}: no player `{focus}` in it", replay.display()),
+            Err(e) => eprintln!("skipping {}: {e}", replay.display()),
+        }
+    }
+    let (Some((_, first)), Some(background)) = (games.first(), background) else {
+        return Err(format!("no replays with a player `{focus}` in them").into());
+    };
+    // Only the first game's engine is opened again, rather than keeping every game's in memory
+    let background = open(background)?;
+
+    let mut total = first.clone();
+    for (name, game) in &games[1..] {
+        if !total.add(game) {
+            eprintln!("leaving {name} out of the heatmap: it's on a different size arena");
+        }
+    }
+    let wins = games
+        .iter()
+        .filter(|(_, game)| game.result == Ordering::Greater)
+        .count();
+    let losses = games
+        .iter()
+        .filter(|(_, game)| game.result == Ordering::Less)
+        .count();
+    let state = RenderState::new(DisplayType::PLAYER_HEATMAP, background.arena())
+        .with_player_heatmap(total.heatmap.clone());
+
+    let mut html = String::new();
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Player report</title>\n<style>{STYLE}</style>\n</head>\n<body>"
+    )?;
+    writeln!(html, "<h1>{}</h1>", escape(&first.name))?;
+    writeln!(
+        html,
+        "<p>{} games, {wins} won and {losses} lost</p>",
+        games.len()
+    )?;
+    writeln!(
+        html,
+        "<figure><img src=\"{}\"><figcaption>Where they were over every game, with their own goal at the bottom</figcaption></figure>",
+        image_data(&background, &state)?
+    )?;
+
+    writeln!(html, "<h2>Stats</h2>\n<table>")?;
+    writeln!(
+        html,
+        "<tr><th>Game</th><th>Zone control</th><th>Boost collected</th><th>Boost used</th><th>Time empty</th><th>Average speed</th><th>Time supersonic</th><th>Time in the air</th><th>Defensive / middle / offensive third</th><th>First / second / third man</th></tr>"
+    )?;
+    total.write_row(&mut html, "All games", "")?;
+    for (name, game) in &games {
+        let result = match game.result {
+            Ordering::Greater => "won",
+            Ordering::Less => "lost",
+            Ordering::Equal => "drawn",
+        };
+        let label = format!("{} ({result}, as {})", escape(name), escape(&game.name));
+        game.write_row(&mut html, &label, team_class(game.team))?;
+    }
+    writeln!(html, "</table>\n</body>\n</html>")?;
+
+    write(path, html)?;
+    eprintln!(
+        "wrote a report on {} games to {}",
+        games.len(),
+        path.display()
+    );
+    Ok(())
+}
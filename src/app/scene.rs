@@ -0,0 +1,424 @@
+use std::f64::consts::{PI, TAU};
+
+use boxcars::{Quaternion, RigidBody};
+use graphics::math::Matrix2d;
+
+use crate::app::render::{screen_size, RenderState, BALL_RADIUS, CEILING_HEIGHT, GOAL_HEIGHT};
+use crate::ReplayEngine;
+
+/// How much of the scene the 3D display takes in from top to bottom, about 50 degrees.
+const FIELD_OF_VIEW: f64 = 0.87;
+/// How close to the camera something can be and still be drawn. Anything nearer is cut off, like
+/// a wall the camera is orbiting behind.
+const NEAR: f64 = 100.0;
+/// How far from the camera something can be and still be drawn, past the far corner of the field
+/// from the camera pulled all the way out.
+const FAR: f64 = 60000.0;
+/// Radians the camera turns for each pixel the mouse is dragged.
+const ORBIT_SPEED: f64 = 0.01;
+/// How far above the floor the camera can orbit, from nearly level with it to nearly straight down.
+const MIN_PITCH: f64 = 0.05;
+const MAX_PITCH: f64 = 1.55;
+const MIN_DISTANCE: f64 = 2000.0;
+const MAX_DISTANCE: f64 = 40000.0;
+/// The Octane's hitbox, the one most players use: how long, wide and tall it is.
+const CAR_SIZE: [f64; 3] = [118.0, 84.2, 36.2];
+/// How round the ball is drawn: bands from top to bottom, each cut into this many segments.
+const BALL_BANDS: usize = 8;
+const BALL_SEGMENTS: usize = 16;
+/// Shadows sit just above the floor so they aren't lost in it.
+const SHADOW_HEIGHT: f64 = 2.0;
+
+/// Where the 3D display looks at the field from: somewhere on a sphere around the middle of the
+/// field, turned around it by dragging and moved in and out by scrolling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Orbit {
+    /// Radians around the field, starting from behind the goal at the bottom of the top-down view.
+    yaw: f64,
+    /// Radians above the floor.
+    pitch: f64,
+    /// Unreal units from the middle of the field.
+    distance: f64,
+}
+
+impl Default for Orbit {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.6,
+            distance: 20000.0,
+        }
+    }
+}
+
+impl Orbit {
+    /// Turns the camera around the field for a mouse drag of `delta` pixels.
+    pub fn rotate(&mut self, [x, y]: [f64; 2]) {
+        self.yaw -= x * ORBIT_SPEED;
+        self.pitch = (self.pitch + y * ORBIT_SPEED).clamp(MIN_PITCH, MAX_PITCH);
+    }
+
+    /// Moves the camera in by `factor`, or out when it's below 1.
+    pub fn zoom(&mut self, factor: f64) {
+        self.distance = (self.distance / factor).clamp(MIN_DISTANCE, MAX_DISTANCE);
+    }
+}
+
+/// Takes points in the replay's coordinates to the screen as the orbit camera sees them.
+pub struct Projection {
+    eye: [f64; 3],
+    right: [f64; 3],
+    up: [f64; 3],
+    forward: [f64; 3],
+    center: [f64; 2],
+    focal_length: f64,
+}
+
+impl Projection {
+    /// The camera at `orbit`, drawing into a space `size` pixels across.
+    pub fn new(orbit: &Orbit, [width, height]: [f64; 2]) -> Self {
+        let (sin_yaw, cos_yaw) = orbit.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = orbit.pitch.sin_cos();
+        // The replay's y is flipped so the field isn't mirrored from the top-down view, which puts
+        // its x to the right and y down the screen
+        let forward = [-cos_pitch * sin_yaw, cos_pitch * cos_yaw, -sin_pitch];
+        let right = normalize(cross(forward, [0.0, 0.0, 1.0]));
+        Self {
+            eye: forward.map(|axis| -axis * orbit.distance),
+            right,
+            up: cross(right, forward),
+            forward,
+            center: [width / 2.0, height / 2.0],
+            focal_length: height / 2.0 / (FIELD_OF_VIEW / 2.0).tan(),
+        }
+    }
+
+    /// Where a point ends up on screen, or `None` if it's behind the camera.
+    pub fn project(&self, point: [f64; 3]) -> Option<[f64; 2]> {
+        let [x, y, depth] = self.to_camera(point);
+        (depth >= NEAR).then(|| {
+            [
+                self.center[0] + self.focal_length * x / depth,
+                self.center[1] - self.focal_length * y / depth,
+            ]
+        })
+    }
+
+    /// A point relative to the camera: how far to its right, above it and in front of it.
+    fn to_camera(&self, [x, y, z]: [f64; 3]) -> [f64; 3] {
+        let offset = [x - self.eye[0], -y - self.eye[1], z - self.eye[2]];
+        [
+            dot(offset, self.right),
+            dot(offset, self.up),
+            dot(offset, self.forward),
+        ]
+    }
+
+    /// The matrix a renderer takes points in the replay's coordinates to clip space with, so they
+    /// land where `project` puts them once `transform` takes the screen to the window. Its rows are
+    /// each the same as `to_camera` along one axis, with the flip of y and the move from the eye
+    /// folded into the last column. Depth runs from 0 at `NEAR` to 1 at `FAR`.
+    pub fn clip_matrix(&self, transform: Matrix2d) -> [[f32; 4]; 4] {
+        let along = |axis: [f64; 3]| [axis[0], -axis[1], axis[2], -dot(axis, self.eye)];
+        let [right, up, forward] = [along(self.right), along(self.up), along(self.forward)];
+        let [[a, b, c], [d, e, f]] = transform;
+        let focal = self.focal_length;
+        let [center_x, center_y] = self.center;
+        let rows = [
+            [a * focal, -b * focal, a * center_x + b * center_y + c],
+            [d * focal, -e * focal, d * center_x + e * center_y + f],
+        ]
+        .map(|[x, y, depth]| [0, 1, 2, 3].map(|i| x * right[i] + y * up[i] + depth * forward[i]));
+        let scale = FAR / (FAR - NEAR);
+        let depth =
+            [0, 1, 2, 3].map(|i| scale * forward[i] - if i == 3 { NEAR * scale } else { 0.0 });
+        let rows = [rows[0], rows[1], depth, forward];
+        // Column by column, the way WGSL lays out a mat4x4
+        [0, 1, 2, 3].map(|column| rows.map(|row| row[column] as f32))
+    }
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(v: [f64; 3]) -> [f64; 3] {
+    let length = dot(v, v).sqrt();
+    v.map(|axis| axis / length)
+}
+
+/// `v` turned by `rotation`, the way a car's rigid body is turned from facing along x.
+fn rotate(rotation: &Quaternion, v: [f64; 3]) -> [f64; 3] {
+    let [x, y, z, w] = [rotation.x, rotation.y, rotation.z, rotation.w].map(f64::from);
+    let q = [x, y, z];
+    // v + 2w(q × v) + 2q × (q × v)
+    let t = cross(q, v).map(|axis| axis * 2.0);
+    let u = cross(q, t);
+    [0, 1, 2].map(|i| v[i] + w * t[i] + u[i])
+}
+
+/// A corner of one of the 3D display's triangles or lines, in the replay's coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+/// One field's worth of the 3D display, for a renderer with a depth buffer to draw under
+/// everything else in the window.
+// Only the wgpu renderer reads it
+#[cfg_attr(not(feature = "wgpu"), allow(dead_code))]
+pub struct Scene {
+    /// Takes the replay's coordinates to clip space, from `Projection::clip_matrix`.
+    pub transform: [[f32; 4]; 4],
+    /// The part of the window the field takes up, in normalized device coordinates: left, top,
+    /// right and bottom. Nothing in the scene is drawn outside it.
+    pub bounds: [f32; 4],
+    /// Three vertices for each triangle.
+    pub triangles: Vec<Vertex>,
+    /// Two vertices for each line.
+    pub lines: Vec<Vertex>,
+}
+
+impl Scene {
+    fn triangle(&mut self, corners: [[f64; 3]; 3], color: [f32; 4]) {
+        self.triangles
+            .extend(corners.map(|corner| vertex(corner, color)));
+    }
+
+    fn line(&mut self, from: [f64; 3], to: [f64; 3], color: [f32; 4]) {
+        self.lines.extend([vertex(from, color), vertex(to, color)]);
+    }
+
+    /// A flat disc on the floor under something at `[x, y]`.
+    fn shadow(&mut self, [x, y]: [f64; 2], radius: f64) {
+        let at = |angle: f64| {
+            let (sin, cos) = angle.sin_cos();
+            [x + radius * cos, y + radius * sin, SHADOW_HEIGHT]
+        };
+        let center = [x, y, SHADOW_HEIGHT];
+        for i in 0..BALL_SEGMENTS {
+            let angle = TAU * i as f64 / BALL_SEGMENTS as f64;
+            let next = TAU * (i + 1) as f64 / BALL_SEGMENTS as f64;
+            self.triangle([center, at(angle), at(next)], [0.0, 0.0, 0.0, 0.4]);
+        }
+    }
+
+    /// A car's hitbox, turned the way its rigid body is, with the sides facing up lit the most.
+    fn car(&mut self, body: &RigidBody, color: [f32; 4]) {
+        let location = [body.location.x, body.location.y, body.location.z].map(f64::from);
+        let corner = |[x, y, z]: [f64; 3]| {
+            let offset = rotate(
+                &body.rotation,
+                [0, 1, 2].map(|i| [x, y, z][i] * CAR_SIZE[i] / 2.0),
+            );
+            [0, 1, 2].map(|i| location[i] + offset[i])
+        };
+        // Each face as its four corners going around it, and which way it faces
+        let faces = [
+            ([0, 1, 2], 1.0),
+            ([0, 1, 2], -1.0),
+            ([1, 2, 0], 1.0),
+            ([1, 2, 0], -1.0),
+            ([2, 0, 1], 1.0),
+            ([2, 0, 1], -1.0),
+        ];
+        for ([normal, along, across], side) in faces {
+            let point = |a: f64, b: f64| {
+                let mut unit = [0.0; 3];
+                unit[normal] = side;
+                unit[along] = a;
+                unit[across] = b;
+                corner(unit)
+            };
+            let mut facing = [0.0; 3];
+            facing[normal] = side;
+            let color = shade(color, rotate(&body.rotation, facing)[2]);
+            let [a, b, c, d] = [
+                point(-1.0, -1.0),
+                point(1.0, -1.0),
+                point(1.0, 1.0),
+                point(-1.0, 1.0),
+            ];
+            self.triangle([a, b, c], color);
+            self.triangle([a, c, d], color);
+        }
+    }
+
+    /// The ball, lit from above.
+    fn ball(&mut self, [x, y, z]: [f64; 3], color: [f32; 4]) {
+        let at = |band: usize, segment: usize| {
+            let (sin_pitch, cos_pitch) =
+                (PI * band as f64 / BALL_BANDS as f64 - PI / 2.0).sin_cos();
+            let (sin_yaw, cos_yaw) = (TAU * segment as f64 / BALL_SEGMENTS as f64).sin_cos();
+            let normal = [cos_pitch * cos_yaw, cos_pitch * sin_yaw, sin_pitch];
+            let center = [x, y, z];
+            let point = [0, 1, 2].map(|i| center[i] + normal[i] * BALL_RADIUS);
+            (point, normal[2])
+        };
+        for band in 0..BALL_BANDS {
+            for segment in 0..BALL_SEGMENTS {
+                let corners = [
+                    at(band, segment),
+                    at(band, segment + 1),
+                    at(band + 1, segment + 1),
+                    at(band + 1, segment),
+                ];
+                let up = corners.iter().map(|(_, up)| up).sum::<f64>() / 4.0;
+                let color = shade(color, up);
+                let [a, b, c, d] = corners.map(|(point, _)| point);
+                self.triangle([a, b, c], color);
+                self.triangle([a, c, d], color);
+            }
+        }
+    }
+}
+
+fn vertex(position: [f64; 3], color: [f32; 4]) -> Vertex {
+    Vertex {
+        position: position.map(|axis| axis as f32),
+        color,
+    }
+}
+
+/// `color` lit from straight above, for a surface facing `up` of the way up, from -1 to 1.
+fn shade([r, g, b, a]: [f32; 4], up: f64) -> [f32; 4] {
+    let light = 0.6 + 0.4 * up as f32;
+    [r * light, g * light, b * light, a]
+}
+
+/// The arena in 3D from the orbit camera, with the ball and cars at their true height and turned
+/// the way they are in the replay. Each stands on a line down to its shadow on the floor, so how
+/// high it is reads even looking from the side. `transform` takes the field's screen space to the
+/// window, the same as the context the rest of the field is drawn with.
+pub fn scene(engine: &ReplayEngine, state: &RenderState, transform: Matrix2d) -> Scene {
+    let arena = engine.arena();
+    let theme = state.theme();
+    let colors = engine.team_colors();
+    let [width, height] = screen_size(arena);
+    let projection = Projection::new(&state.orbit, [width, height]);
+
+    let [[a, b, c], [d, e, f]] = transform;
+    let ndc = |x: f64, y: f64| [a * x + b * y + c, d * x + e * y + f];
+    let [left, top] = ndc(0.0, 0.0);
+    let [right, bottom] = ndc(width, height);
+    let mut scene = Scene {
+        transform: projection.clip_matrix(transform),
+        bounds: [left, top, right, bottom].map(|edge| edge as f32),
+        triangles: vec![],
+        lines: vec![],
+    };
+
+    let corners = arena.wall_corners();
+    for (i, &[x, y]) in corners.iter().enumerate() {
+        let [next_x, next_y] = corners[(i + 1) % corners.len()];
+        scene.triangle([[0.0; 3], [x, y, 0.0], [next_x, next_y, 0.0]], theme.field);
+        scene.line([x, y, 0.0], [next_x, next_y, 0.0], theme.lines);
+        scene.line(
+            [x, y, CEILING_HEIGHT],
+            [next_x, next_y, CEILING_HEIGHT],
+            theme.lines,
+        );
+        scene.line([x, y, 0.0], [x, y, CEILING_HEIGHT], theme.lines);
+    }
+    let half_width = arena.width / 2.0;
+    scene.line(
+        [-half_width, 0.0, SHADOW_HEIGHT],
+        [half_width, 0.0, SHADOW_HEIGHT],
+        theme.lines,
+    );
+
+    // Team 0 defends the goal at negative y
+    if arena.goal_width > 0.0 {
+        let half = arena.goal_width / 2.0;
+        for (y, color) in [
+            (-arena.height / 2.0, colors.blue[0]),
+            (arena.height / 2.0, colors.orange[1]),
+        ] {
+            scene.line([-half, y, 0.0], [-half, y, GOAL_HEIGHT], color);
+            scene.line([half, y, 0.0], [half, y, GOAL_HEIGHT], color);
+            scene.line([-half, y, GOAL_HEIGHT], [half, y, GOAL_HEIGHT], color);
+        }
+    }
+
+    let stand = |scene: &mut Scene, [x, y, z]: [f64; 3], radius: f64, [r, g, b, _]: [f32; 4]| {
+        scene.shadow([x, y], radius);
+        scene.line([x, y, SHADOW_HEIGHT], [x, y, z], [r, g, b, 0.5]);
+    };
+    for (player, car) in engine
+        .player_positions()
+        .filter(|(player, _)| state.shows(player))
+    {
+        let location = [car.location.x, car.location.y, car.location.z].map(f64::from);
+        stand(&mut scene, location, CAR_SIZE[0] / 2.0, player.color);
+        scene.car(car, player.color);
+    }
+    if let Some(ball) = engine.ball() {
+        let location = [ball.location.x, ball.location.y, ball.location.z].map(f64::from);
+        stand(&mut scene, location, BALL_RADIUS, theme.ball);
+        scene.ball(location, theme.ball);
+    }
+    scene
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_clip_matrix_puts_points_where_they_are_projected() {
+        let orbit = Orbit {
+            yaw: 0.7,
+            pitch: 0.4,
+            distance: 9000.0,
+        };
+        let projection = Projection::new(&orbit, [800.0, 1000.0]);
+        // Into a window twice as big, offset the way the letterbox does it
+        let transform = [[0.002, 0.0, -0.9], [0.0, -0.0015, 0.8]];
+        let matrix = projection.clip_matrix(transform);
+        for point in [
+            [0.0, 0.0, 0.0],
+            [1500.0, -3000.0, 400.0],
+            [-4000.0, 5000.0, 1800.0],
+        ] {
+            let [x, y] = projection.project(point).unwrap();
+            let clip: Vec<f64> = (0..4)
+                .map(|row| {
+                    (0..4)
+                        .map(|column| {
+                            let coordinate = [point[0], point[1], point[2], 1.0][column];
+                            matrix[column][row] as f64 * coordinate
+                        })
+                        .sum()
+                })
+                .collect();
+            let [[a, b, c], [d, e, f]] = transform;
+            let expected = [a * x + b * y + c, d * x + e * y + f];
+            assert!((clip[0] / clip[3] - expected[0]).abs() < 1e-4, "{point:?}");
+            assert!((clip[1] / clip[3] - expected[1]).abs() < 1e-4, "{point:?}");
+            assert!((0.0..1.0).contains(&(clip[2] / clip[3])), "{point:?}");
+        }
+    }
+
+    #[test]
+    fn cars_are_turned_the_way_their_rigid_body_is() {
+        // A quarter turn about z takes a car facing along x to facing along y
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let turn = Quaternion {
+            x: 0.0,
+            y: 0.0,
+            z: half,
+            w: half,
+        };
+        let [x, y, z] = rotate(&turn, [1.0, 0.0, 0.0]);
+        assert!(x.abs() < 1e-6 && (y - 1.0).abs() < 1e-6 && z.abs() < 1e-6);
+    }
+}
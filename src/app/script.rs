@@ -0,0 +1,95 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::error;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+use rhai::{CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+
+use crate::ReplayEngine;
+
+/// How much work a script gets to do on each frame before it's stopped, so one stuck in a loop
+/// doesn't hang playback.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// What a script asked for through the functions it's given.
+#[derive(Default)]
+struct Calls {
+    pause: bool,
+    metrics: BTreeMap<String, Dynamic>,
+}
+
+/// A Rhai script from `--script`, whose `on_frame(frame)` function runs on every frame played.
+///
+/// `frame` is the frame as `--headless` writes it. The script can call `pause()` to pause playback
+/// in the window and `metric(name, value)` to show a value beside the field and add it to
+/// `--headless` output. `this` is a map kept from one frame to the next for running totals.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    memory: Dynamic,
+    calls: Rc<RefCell<Calls>>,
+}
+
+impl Script {
+    /// Compiles the script at `path` and runs anything outside its functions.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn error::Error>> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read {}: {e}", path.display()))?;
+
+        let calls = Rc::new(RefCell::new(Calls::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        // Headless output goes to stdout, so anything the script prints can't
+        engine.on_print(|text| eprintln!("{text}"));
+        let pause = calls.clone();
+        engine.register_fn("pause", move || pause.borrow_mut().pause = true);
+        let metric = calls.clone();
+        engine.register_fn("metric", move |name: &str, value: Dynamic| {
+            metric.borrow_mut().metrics.insert(name.to_string(), value);
+        });
+
+        let ast = engine
+            .compile(source)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        if !ast
+            .iter_functions()
+            .any(|function| function.name == "on_frame" && function.params.len() == 1)
+        {
+            return Err(format!("{} has no on_frame(frame) function", path.display()).into());
+        }
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+
+        Ok(Self {
+            engine,
+            ast,
+            scope,
+            memory: Map::new().into(),
+            calls,
+        })
+    }
+
+    /// Runs `on_frame` for the frame `engine` last processed, returning whether it asked to pause.
+    pub fn on_frame(&mut self, engine: &ReplayEngine) -> Result<bool, Box<dyn error::Error>> {
+        let frame = rhai::serde::to_dynamic(engine.summary())?;
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.memory);
+        // Whatever on_frame returns is ignored, it only talks back through pause() and metric()
+        let _: Dynamic = self
+            .engine
+            .call_fn_with_options(options, &mut self.scope, &self.ast, "on_frame", (frame,))
+            .map_err(|e| format!("on_frame failed on frame {}: {e}", engine.frame_index() - 1))?;
+        Ok(std::mem::take(&mut self.calls.borrow_mut().pause))
+    }
+
+    /// Every metric the script has set, with the value it last gave each one.
+    pub fn metrics(&self) -> BTreeMap<String, Dynamic> {
+        self.calls.borrow().metrics.clone()
+    }
+}
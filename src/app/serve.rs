@@ -0,0 +1,216 @@
+use std::error;
+use std::io::Cursor;
+
+use image::ImageFormat;
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::app::export;
+use crate::app::render::RenderState;
+use crate::app::{parse_timestamp, DisplayType};
+use crate::events::{self, Event};
+use crate::shots;
+use crate::timeline::{Timeline, TimelineFrame};
+use crate::zones::ZoneControl;
+use crate::ReplayEngine;
+
+/// Zone control on one network frame, an entry of `/zone-control`.
+#[derive(Serialize)]
+struct ZoneControlPoint {
+    frame: usize,
+    /// Seconds into the replay, the same as the `t` the other endpoints take.
+    seconds: f32,
+    #[serde(flatten)]
+    control: ZoneControl,
+}
+
+/// A request that can't be answered, and the status to turn it away with.
+struct Failure {
+    status: u16,
+    message: String,
+}
+
+impl Failure {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: 400,
+            message: message.into(),
+        }
+    }
+}
+
+/// Everything `serve` answers from, worked out once when it starts so requests only look it up.
+struct Served {
+    /// The replay as opened, which `/frame.png` plays a copy of up to the frame asked for.
+    engine: ReplayEngine,
+    display: DisplayType,
+    timeline: Timeline,
+    events: Vec<Event>,
+    zone_control: Vec<ZoneControlPoint>,
+}
+
+impl Served {
+    fn new(engine: ReplayEngine, display: DisplayType) -> Self {
+        let timeline = Timeline::build(&engine);
+        let events = events::events(&engine, &engine.goals(), &shots::shots(&engine));
+        let start = timeline.frames().first().map_or(0.0, |entry| entry.time);
+        let zone_control = timeline
+            .frames()
+            .iter()
+            .filter_map(|entry| {
+                Some(ZoneControlPoint {
+                    frame: entry.frame,
+                    seconds: entry.time - start,
+                    control: entry.zone_control?,
+                })
+            })
+            .collect();
+
+        Self {
+            engine,
+            display,
+            timeline,
+            events,
+            zone_control,
+        }
+    }
+
+    /// The body and content type to answer `request` with.
+    fn respond(&self, request: &Request) -> Result<(Vec<u8>, &'static str), Failure> {
+        if *request.method() != Method::Get {
+            return Err(Failure {
+                status: 405,
+                message: "only GET requests are served".to_string(),
+            });
+        }
+        let url = request.url();
+        let path = url.split('?').next().unwrap_or_default();
+        match path {
+            "/frame" => {
+                let entry = self.frame_at(time(url)?)?;
+                json(&self.timeline.summary(entry))
+            }
+            "/frame.png" => {
+                let time = time(url)?;
+                let state = RenderState::new(self.display, self.engine.arena());
+                let image =
+                    export::snapshot(self.engine.clone(), state, time, Some(&self.timeline))
+                        .map_err(|e| Failure::bad_request(e.to_string()))?;
+                let mut png = Cursor::new(vec![]);
+                image
+                    .write_to(&mut png, ImageFormat::Png)
+                    .map_err(|e| Failure {
+                        status: 500,
+                        message: format!("couldn't encode the frame: {e}"),
+                    })?;
+                Ok((png.into_inner(), "image/png"))
+            }
+            "/events" => json(&self.events),
+            "/zone-control" => json(&self.zone_control),
+            _ => Err(Failure {
+                status: 404,
+                message: format!(
+                    "nothing at {path}, try /frame, /frame.png, /events or /zone-control"
+                ),
+            }),
+        }
+    }
+
+    /// The first frame `time` seconds or more into the replay, the one `--snapshot` would render.
+    fn frame_at(&self, time: f32) -> Result<&TimelineFrame, Failure> {
+        let frames = self.timeline.frames();
+        let start = frames.first().map_or(0.0, |entry| entry.time);
+        frames
+            .iter()
+            .find(|entry| entry.time - start >= time)
+            .ok_or_else(|| {
+                let length = frames.last().map_or(0.0, |entry| entry.time - start);
+                Failure::bad_request(format!(
+                    "the replay is only {length:.1} seconds long, there's no frame at {time} seconds"
+                ))
+            })
+    }
+}
+
+/// The `t` query parameter, as `m:ss` or seconds into the replay.
+fn time(url: &str) -> Result<f32, Failure> {
+    let value = url
+        .split_once('?')
+        .and_then(|(_, query)| {
+            form_urlencoded::parse(query.as_bytes())
+                .find_map(|(key, value)| (key == "t").then_some(value))
+        })
+        .ok_or_else(|| {
+            Failure::bad_request("missing t, the time into the replay as m:ss or seconds")
+        })?;
+    parse_timestamp(&value).map_err(Failure::bad_request)
+}
+
+fn json(value: &impl Serialize) -> Result<(Vec<u8>, &'static str), Failure> {
+    let body = serde_json::to_vec(value).map_err(|e| Failure {
+        status: 500,
+        message: e.to_string(),
+    })?;
+    Ok((body, "application/json"))
+}
+
+/// Answers HTTP requests about `engine`'s replay on `address` until the process is stopped.
+pub fn serve(
+    engine: ReplayEngine,
+    display: DisplayType,
+    address: &str,
+) -> Result<(), Box<dyn error::Error>> {
+    let served = Served::new(engine, display);
+    let server = Server::http(address).map_err(|e| format!("couldn't listen on {address}: {e}"))?;
+    eprintln!("serving on http://{}", server.server_addr());
+
+    // Dashboards are usually served from somewhere else, so any page is allowed to ask
+    let cors = Header::from_bytes("Access-Control-Allow-Origin", "*").expect("valid header");
+    for request in server.incoming_requests() {
+        let (body, content_type, status) = match served.respond(&request) {
+            Ok((body, content_type)) => (body, content_type, 200),
+            Err(failure) => {
+                let body = serde_json::json!({ "error": failure.message }).to_string();
+                (body.into_bytes(), "application/json", failure.status)
+            }
+        };
+        let content_type = Header::from_bytes("Content-Type", content_type).expect("valid header");
+        let response = Response::from_data(body)
+            .with_status_code(status)
+            .with_header(content_type)
+            .with_header(cors.clone());
+        if let Err(e) = request.respond(response) {
+            eprintln!("couldn't answer a request: {e}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn times_are_read_however_the_colon_is_encoded() {
+        for url in [
+            "/frame?t=1:30",
+            "/frame?t=1%3A30",
+            "/frame?t=1%3a30",
+            "/frame?x=1&t=1%3a30",
+        ] {
+            assert_eq!(time(url).ok(), Some(90.0), "{url}");
+        }
+        assert_eq!(time("/frame?t=12.5").ok(), Some(12.5));
+    }
+
+    #[test]
+    fn a_missing_or_unreadable_time_is_a_bad_request() {
+        for url in ["/frame", "/frame?x=1", "/frame?t=1%3", "/frame?t=soon"] {
+            assert_eq!(
+                time(url).err().map(|failure| failure.status),
+                Some(400),
+                "{url}"
+            );
+        }
+    }
+}
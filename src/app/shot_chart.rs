@@ -0,0 +1,74 @@
+use std::error;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use graphics::{Context, Transformed};
+
+use crate::app::batch::csv_field;
+use crate::app::canvas::{save_png, Canvas};
+use crate::app::render::FieldPainter;
+use crate::app::render::{self, Theme};
+use crate::arena::Arena;
+use crate::overlay;
+use crate::shots::{self, Shot, ShotKind};
+use crate::{create, ReplayEngine, TeamColors};
+
+/// Draws an empty field with a mark where every one of `shots` was taken from or made, the same
+/// marks the `shot-chart` layer builds up over a replay.
+pub fn render_shot_chart(arena: &Arena, colors: &TeamColors, shots: &[Shot]) -> Canvas {
+    let [width, height] = render::screen_size(arena);
+    let mut canvas = Canvas::new(width as u32, height as u32);
+    let c = Context::new_abs(width, height);
+    render::draw_field(arena, &Theme::default(), colors, &c, &mut canvas);
+    let c = c.trans(0.0, render::to_pixels(arena.goal_depth));
+    overlay::paint_shots(
+        shots,
+        colors,
+        &mut FieldPainter::new(arena, &c, &mut canvas),
+    );
+    canvas
+}
+
+/// Writes every one of `shots` as a CSV row, with where the ball was when it was hit.
+pub fn write_shots(shots: &[Shot], out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "kind,frame,time,player,team,x,y,z,quality")?;
+    for shot in shots {
+        let kind = match shot.kind {
+            ShotKind::Shot => "shot",
+            ShotKind::Save => "save",
+        };
+        writeln!(
+            out,
+            "{kind},{},{:.2},{},{:?},{:.0},{:.0},{:.0},{}",
+            shot.frame,
+            shot.time,
+            csv_field(&shot.player),
+            shot.team,
+            shot.location.x,
+            shot.location.y,
+            shot.location.z,
+            shot.quality
+                .map(|quality| format!("{quality:.3}"))
+                .unwrap_or_default(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes where every shot in the replay was taken from and every save was made. Paths ending in
+/// `.csv` get one row for each, and anything else a PNG of the field with them marked on it.
+pub fn write_shot_chart(engine: &ReplayEngine, path: &Path) -> Result<(), Box<dyn error::Error>> {
+    let shots = shots::shots(engine);
+    let csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    if csv {
+        let mut out = BufWriter::new(create(path)?);
+        write_shots(&shots, &mut out)?;
+        out.flush()?;
+    } else {
+        let chart = render_shot_chart(engine.arena(), engine.team_colors(), &shots);
+        save_png(&chart.into_image(), path)?;
+    }
+    Ok(())
+}
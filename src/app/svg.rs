@@ -0,0 +1,210 @@
+use std::error;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::arena::ArenaKind;
+use crate::{create, zones, ReplayEngine};
+
+use crate::app::render::{self, RenderState};
+use crate::app::DisplayType;
+
+/// Writes the engine's current frame to `path` as an SVG, with the voronoi cells, the field's
+/// markings and a dot for every car and the ball drawn as shapes, so it stays sharp at any size in
+/// an article or on a slide. The cells follow the display: weighted and team voronoi are drawn as
+/// such, and every other display gets the plain voronoi cells.
+pub fn write_frame(
+    engine: &ReplayEngine,
+    state: &RenderState,
+    labels: bool,
+    path: &Path,
+) -> Result<(), Box<dyn error::Error>> {
+    let arena = engine.arena();
+    let theme = state.theme();
+    let colors = engine.team_colors();
+    let [width, height] = render::screen_size(arena);
+    let mut out = BufWriter::new(create(path)?);
+
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )?;
+    let info = engine.match_info();
+    if let Some(name) = &info.name {
+        writeln!(out, "<title>{}</title>", escape(name))?;
+    }
+    writeln!(out, "<desc>{}</desc>", escape(&info.description()))?;
+    writeln!(
+        out,
+        r#"<rect width="{width}" height="{height}" {}/>"#,
+        fill(theme.background)
+    )?;
+    // Everything below is placed the way the window places it, below the top goal
+    writeln!(
+        out,
+        r#"<g transform="translate(0 {})">"#,
+        render::to_pixels(arena.goal_depth)
+    )?;
+
+    let walls = points(&render::wall_outline(arena));
+    writeln!(
+        out,
+        r#"<clipPath id="field"><polygon points="{walls}"/></clipPath>"#
+    )?;
+    writeln!(out, r#"<polygon points="{walls}" {}/>"#, fill(theme.field))?;
+
+    // Team 0 defends the goal at negative y
+    let [goal_left, goal_right] = render::goal_posts(arena);
+    let depth = render::to_pixels(arena.goal_depth);
+    let top = render::to_screen(arena, 0.0, -arena.height / 2.0)[1];
+    let bottom = render::to_screen(arena, 0.0, arena.height / 2.0)[1];
+    for (y, color) in [(top - depth, colors.blue[0]), (bottom, colors.orange[1])] {
+        writeln!(
+            out,
+            r#"<rect x="{goal_left}" y="{y}" width="{}" height="{depth}" {}/>"#,
+            goal_right - goal_left,
+            fill(color)
+        )?;
+    }
+
+    // The cells reach past the corner cuts, so they're clipped to the walls
+    writeln!(out, r#"<g clip-path="url(#field)">"#)?;
+    let cells = match state.display {
+        DisplayType::WEIGHTED_VORONOI => zones::weighted_player_cells(engine)
+            .into_iter()
+            .map(|(player, cell)| (player.color, cell))
+            .collect(),
+        DisplayType::TEAM_VORONOI => zones::player_cells(engine)
+            .into_iter()
+            .map(|(player, cell)| (colors.team(player.team), cell))
+            .collect(),
+        _ => zones::player_cells(engine)
+            .into_iter()
+            .map(|(player, cell)| (player.color, cell))
+            .collect::<Vec<_>>(),
+    };
+    for (color, cell) in cells {
+        let cell: Vec<_> = cell
+            .iter()
+            .map(|p| render::to_screen(arena, p[0], p[1]))
+            .collect();
+        writeln!(
+            out,
+            r#"<polygon points="{}" {}/>"#,
+            points(&cell),
+            fill(color)
+        )?;
+    }
+    if state.display == DisplayType::TEAM_VORONOI {
+        for [a, b] in zones::contested_edges(engine) {
+            let [x1, y1] = render::to_screen(arena, a[0], a[1]);
+            let [x2, y2] = render::to_screen(arena, b[0], b[1]);
+            writeln!(
+                out,
+                r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="white" stroke-width="1.5"/>"#
+            )?;
+        }
+    }
+    writeln!(out, "</g>")?;
+
+    let lines = stroke(theme.lines);
+    writeln!(
+        out,
+        r#"<polygon points="{walls}" fill="none" {lines} stroke-width="1"/>"#
+    )?;
+    let [left, _] = render::to_screen(arena, -arena.width / 2.0, 0.0);
+    let [right, _] = render::to_screen(arena, arena.width / 2.0, 0.0);
+    let [center_x, center_y] = render::to_screen(arena, 0.0, 0.0);
+    writeln!(
+        out,
+        r#"<line x1="{left}" y1="{center_y}" x2="{right}" y2="{center_y}" {lines} stroke-width="1"/>"#
+    )?;
+    // Dropshot's floor is all tiles, with no painted lines besides the halfway line
+    if arena.kind != ArenaKind::Dropshot {
+        writeln!(
+            out,
+            r#"<circle cx="{center_x}" cy="{center_y}" r="{}" fill="none" {lines} stroke-width="0.5"/>"#,
+            render::to_pixels(render::CENTER_CIRCLE_RADIUS)
+        )?;
+    }
+    if arena.kind == ArenaKind::Soccar {
+        for y in [
+            -arena.height / 2.0,
+            arena.height / 2.0 - render::GOAL_BOX_DEPTH,
+        ] {
+            let [x, y] = render::to_screen(arena, -render::GOAL_BOX_WIDTH / 2.0, y);
+            writeln!(
+                out,
+                r#"<rect x="{x}" y="{y}" width="{}" height="{}" fill="none" {lines} stroke-width="0.5"/>"#,
+                render::to_pixels(render::GOAL_BOX_WIDTH),
+                render::to_pixels(render::GOAL_BOX_DEPTH)
+            )?;
+        }
+    }
+
+    for (player, body) in engine.player_positions() {
+        let [x, y] = render::to_screen(arena, body.location.x as f64, body.location.y as f64);
+        writeln!(out, r#"<circle cx="{x}" cy="{y}" r="10" fill="black"/>"#)?;
+        writeln!(
+            out,
+            r#"<circle cx="{x}" cy="{y}" r="{}" {}/>"#,
+            render::DOT_RADIUS,
+            fill(player.color)
+        )?;
+        if labels {
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="12" {}>{}</text>"#,
+                x + 12.0,
+                y + 4.0,
+                fill(theme.lines),
+                escape(&player.name)
+            )?;
+        }
+    }
+    if let Some(ball) = engine.ball() {
+        let [x, y] = render::to_screen(arena, ball.location.x as f64, ball.location.y as f64);
+        writeln!(
+            out,
+            r#"<circle cx="{x}" cy="{y}" r="6" {}/>"#,
+            fill(theme.ball)
+        )?;
+    }
+
+    writeln!(out, "</g>")?;
+    writeln!(out, "</svg>")?;
+    out.flush()?;
+    Ok(())
+}
+
+fn points(points: &[[f64; 2]]) -> String {
+    points
+        .iter()
+        .map(|[x, y]| format!("{x:.2},{y:.2}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn fill(color: [f32; 4]) -> String {
+    let (rgb, alpha) = rgb(color);
+    format!(r#"fill="{rgb}" fill-opacity="{alpha}""#)
+}
+
+fn stroke(color: [f32; 4]) -> String {
+    let (rgb, alpha) = rgb(color);
+    format!(r#"stroke="{rgb}" stroke-opacity="{alpha}""#)
+}
+
+fn rgb([r, g, b, a]: [f32; 4]) -> (String, f32) {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    (
+        format!("rgb({},{},{})", channel(r), channel(g), channel(b)),
+        a,
+    )
+}
+
+/// Escapes a player's name, or anything else from the replay, for use as SVG text.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
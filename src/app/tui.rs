@@ -0,0 +1,310 @@
+use std::error;
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::canvas::{self, Canvas, Painter, Points, Shape};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::arena::Arena;
+use crate::{zones, ReplayEngine, Team};
+
+/// How long to wait for a key before drawing the next frame, which keeps to about 30 a second.
+const FRAME_TIME: Duration = Duration::from_millis(33);
+/// Network frames skipped by the arrow keys, like in the window.
+const SKIP_FRAMES: usize = 150;
+/// Columns given to the zone percentages beside the field.
+const PANEL_WIDTH: u16 = 32;
+/// How much of each player's color their share of the field is shaded with, so their dot stands
+/// out on top of it.
+const ZONE_SHADE: f32 = 0.35;
+const BALL: Color = Color::Rgb(204, 102, 255);
+
+/// Plays the replay in the terminal with block characters, until `q` or `Esc` is pressed.
+pub fn run_tui(mut engine: ReplayEngine, speed: f64) -> Result<(), Box<dyn error::Error>> {
+    let mut terminal = ratatui::init();
+    let result = play(&mut terminal, &mut engine, speed);
+    // Put the terminal back the way it was even if drawing failed partway
+    ratatui::restore();
+    result
+}
+
+fn play(
+    terminal: &mut DefaultTerminal,
+    engine: &mut ReplayEngine,
+    speed: f64,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut paused = false;
+    let mut seconds_due = 0.0;
+    let mut last = Instant::now();
+    loop {
+        engine.interpolate(if paused { 0.0 } else { seconds_due as f32 });
+        terminal.draw(|frame| draw(frame, engine, speed, paused))?;
+
+        if event::poll(FRAME_TIME)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                        KeyCode::Char(' ') => paused = !paused,
+                        KeyCode::Left => {
+                            engine.seek(engine.frame_index().saturating_sub(SKIP_FRAMES))
+                        }
+                        KeyCode::Right => {
+                            let frame =
+                                (engine.frame_index() + SKIP_FRAMES).min(engine.frame_count());
+                            engine.seek(frame);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let now = Instant::now();
+        if !paused {
+            seconds_due += (now - last).as_secs_f64() * speed;
+            while let Some(delta) = engine.next_frame_delta() {
+                if seconds_due < delta as f64 {
+                    break;
+                }
+                engine.step();
+                seconds_due -= delta as f64;
+            }
+        }
+        last = now;
+    }
+}
+
+fn draw(frame: &mut Frame, engine: &ReplayEngine, speed: f64, paused: bool) {
+    let [field, panel] = Layout::horizontal([Constraint::Min(0), Constraint::Length(PANEL_WIDTH)])
+        .areas(frame.area());
+    let field = fit_field(engine.arena(), field);
+    frame.render_widget(field_canvas(engine, field), field);
+    frame.render_widget(side_panel(engine, speed, paused), panel);
+}
+
+/// The largest part of `area` the whole field fits in without stretching. Each character is
+/// about twice as tall as it is wide and holds two half blocks, so its halves are about square.
+fn fit_field(arena: &Arena, area: Rect) -> Rect {
+    let [width, height] = field_size(arena);
+    let aspect = width / height;
+    let columns = (area.height as f64 * 2.0 * aspect).min(area.width as f64) as u16;
+    let rows = ((columns as f64 / aspect / 2.0) as u16).min(area.height);
+    Rect {
+        x: area.x + (area.width - columns) / 2,
+        y: area.y,
+        width: columns,
+        height: rows,
+    }
+}
+
+/// Size of the field and both goals, in unreal units.
+fn field_size(arena: &Arena) -> [f64; 2] {
+    [arena.width, arena.height + 2.0 * arena.goal_depth]
+}
+
+/// The field with every player's share of it shaded in their color, the walls, goals and
+/// halfway line, and a dot for each car and the ball.
+fn field_canvas(engine: &ReplayEngine, area: Rect) -> impl ratatui::widgets::Widget + '_ {
+    let arena = engine.arena();
+    let [width, height] = field_size(arena);
+    Canvas::default()
+        .marker(Marker::HalfBlock)
+        .x_bounds([-width / 2.0, width / 2.0])
+        .y_bounds([-height / 2.0, height / 2.0])
+        .paint(move |ctx| {
+            ctx.draw(&Zones {
+                engine,
+                resolution: [area.width as usize, area.height as usize * 2],
+            });
+            ctx.layer();
+
+            let outline = wall_outline(arena);
+            for (from, to) in outline.iter().zip(outline.iter().cycle().skip(1)) {
+                ctx.draw(&line(*from, *to, Color::White));
+            }
+            ctx.draw(&line(
+                [-arena.width / 2.0, 0.0],
+                [arena.width / 2.0, 0.0],
+                Color::Gray,
+            ));
+            for side in [-1.0, 1.0] {
+                let back = side * arena.height / 2.0;
+                let depth = side * (arena.height / 2.0 + arena.goal_depth);
+                let post = arena.goal_width / 2.0;
+                ctx.draw(&line([-post, back], [-post, depth], Color::White));
+                ctx.draw(&line([-post, depth], [post, depth], Color::White));
+                ctx.draw(&line([post, depth], [post, back], Color::White));
+            }
+
+            // A dot a couple of half blocks across, so it isn't lost among the shading
+            let pixel = width / area.width.max(1) as f64;
+            let dot = |x: f64, y: f64| {
+                [
+                    (0.0, 0.0),
+                    (pixel, 0.0),
+                    (-pixel, 0.0),
+                    (0.0, pixel),
+                    (0.0, -pixel),
+                ]
+                .map(|(dx, dy)| (x + dx, -y + dy))
+            };
+            for (player, body) in engine.player_positions() {
+                ctx.draw(&Points {
+                    coords: &dot(body.location.x as f64, body.location.y as f64),
+                    color: terminal_color(player.color, 1.0),
+                });
+            }
+            if let Some(ball) = engine.ball() {
+                ctx.draw(&Points {
+                    coords: &dot(ball.location.x as f64, ball.location.y as f64),
+                    color: BALL,
+                });
+            }
+        })
+}
+
+/// Shades each half block with the color of the closest player, which is the same split of the
+/// field as the voronoi display.
+struct Zones<'a> {
+    engine: &'a ReplayEngine,
+    /// Half blocks across and down the field.
+    resolution: [usize; 2],
+}
+
+impl Shape for Zones<'_> {
+    fn draw(&self, painter: &mut Painter) {
+        let arena = self.engine.arena();
+        let players: Vec<_> = self.engine.player_positions().collect();
+        if players.is_empty() {
+            return;
+        }
+
+        let [width, height] = field_size(arena);
+        let [columns, rows] = self.resolution;
+        for row in 0..rows {
+            // Field y runs down the screen, the same way up as in the window
+            let y = (row as f64 + 0.5) / rows as f64 * height - height / 2.0;
+            if y.abs() > arena.height / 2.0 {
+                continue;
+            }
+            for column in 0..columns {
+                let x = (column as f64 + 0.5) / columns as f64 * width - width / 2.0;
+                if cut_off(arena, x, y) {
+                    continue;
+                }
+                let Some((player, _)) = players.iter().min_by(|(_, a), (_, b)| {
+                    let a = (a.location.x as f64 - x).hypot(a.location.y as f64 - y);
+                    let b = (b.location.x as f64 - x).hypot(b.location.y as f64 - y);
+                    a.total_cmp(&b)
+                }) else {
+                    continue;
+                };
+                if let Some((x, y)) = painter.get_point(x, -y) {
+                    painter.paint(x, y, terminal_color(player.color, ZONE_SHADE));
+                }
+            }
+        }
+    }
+}
+
+/// The game clock, the score, how much of the field each team and player controls, and the keys.
+fn side_panel(engine: &ReplayEngine, speed: f64, paused: bool) -> Paragraph<'_> {
+    let colors = engine.team_colors();
+    let team_color = |team| terminal_color(colors.team(team), 1.0);
+
+    let clock = match engine.clock_at(engine.frame_index().saturating_sub(1)) {
+        Some(seconds) => format!("{}:{:02}", seconds / 60, seconds % 60),
+        None => "-:--".to_string(),
+    };
+    let score = engine.score();
+    let mut lines = vec![
+        Line::from(vec![
+            Span::raw(format!("{clock}  ")),
+            Span::styled(
+                format!("Orange {}", score.orange),
+                Style::new().fg(team_color(Team::Orange)),
+            ),
+            Span::raw(" - "),
+            Span::styled(
+                format!("{} Blue", score.blue),
+                Style::new().fg(team_color(Team::Blue)),
+            ),
+        ]),
+        Line::raw(""),
+    ];
+
+    if let Some(control) = zones::zone_control(engine) {
+        for (team, share) in [(Team::Orange, control.orange), (Team::Blue, control.blue)] {
+            lines.push(Line::styled(
+                format!("{team:?} controls {:.0}%", share * 100.0),
+                Style::new().fg(team_color(team)),
+            ));
+        }
+        lines.push(Line::raw(""));
+    }
+    let mut players = zones::player_control(engine);
+    // Biggest share first, grouped by team, like the stats in the window
+    players.sort_by(|a, b| b.1.total_cmp(&a.1));
+    players.sort_by_key(|(player, _)| player.team == Team::Blue);
+    for (player, share) in players {
+        lines.push(Line::styled(
+            format!("{} {:.0}%", player.name, share * 100.0),
+            Style::new().fg(team_color(player.team)),
+        ));
+    }
+
+    let status = if paused {
+        "Paused".to_string()
+    } else {
+        format!("{}x", (speed * 100.0).round() / 100.0)
+    };
+    lines.extend([
+        Line::raw(""),
+        Line::raw(status),
+        Line::raw("Space pause, q quit"),
+        Line::raw("Left/Right skip"),
+    ]);
+    Paragraph::new(lines).block(Block::bordered().title("Zones"))
+}
+
+fn line([x1, y1]: [f64; 2], [x2, y2]: [f64; 2], color: Color) -> canvas::Line {
+    // The canvas counts y up the screen, so it's flipped to match the window
+    canvas::Line::new(x1, -y1, x2, -y2, color)
+}
+
+/// Corners of the field going around the walls and their diagonal corner cuts.
+fn wall_outline(arena: &Arena) -> [[f64; 2]; 8] {
+    let half_width = arena.width / 2.0;
+    let half_height = arena.height / 2.0;
+    let [along_back, along_side] = arena.corner;
+    [
+        [-half_width + along_back, -half_height],
+        [half_width - along_back, -half_height],
+        [half_width, -half_height + along_side],
+        [half_width, half_height - along_side],
+        [half_width - along_back, half_height],
+        [-half_width + along_back, half_height],
+        [-half_width, half_height - along_side],
+        [-half_width, -half_height + along_side],
+    ]
+}
+
+/// Whether a point inside the side and back walls is behind one of the diagonal corner walls.
+fn cut_off(arena: &Arena, x: f64, y: f64) -> bool {
+    let [along_back, along_side] = arena.corner;
+    let past_back = x.abs() - (arena.width / 2.0 - along_back);
+    let past_side = y.abs() - (arena.height / 2.0 - along_side);
+    past_back > 0.0 && past_side > 0.0 && past_back / along_back + past_side / along_side > 1.0
+}
+
+/// A terminal color for one of the window's colors, darkened to `brightness` of it.
+fn terminal_color(color: [f32; 4], brightness: f32) -> Color {
+    let channel = |value: f32| (value * brightness * 255.0).round() as u8;
+    Color::Rgb(channel(color[0]), channel(color[1]), channel(color[2]))
+}
@@ -0,0 +1,89 @@
+use std::error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+/// How long a replay has to go without changing before it's treated as finished. Rocket League
+/// can write a replay in more than one go when a match ends.
+const SETTLE: Duration = Duration::from_secs(2);
+
+fn is_replay(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "replay")
+}
+
+/// The most recently modified replay in `dir`, if it has any.
+pub fn newest_replay(dir: &Path) -> Result<Option<PathBuf>, Box<dyn error::Error>> {
+    let mut replays = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if is_replay(&path) {
+            replays.push((fs::metadata(&path)?.modified()?, path));
+        }
+    }
+    Ok(replays
+        .into_iter()
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path))
+}
+
+/// Watches a directory, such as Rocket League's demos folder, for replays being saved.
+pub struct ReplayWatcher {
+    // Events stop once the watcher is dropped
+    _watcher: RecommendedWatcher,
+    changes: Receiver<PathBuf>,
+    pending: Option<(PathBuf, Instant)>,
+}
+
+impl ReplayWatcher {
+    pub fn new(dir: &Path) -> Result<Self, Box<dyn error::Error>> {
+        let (tx, changes) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if !event.kind.is_create() && !event.kind.is_modify() {
+                    return;
+                }
+                for path in event.paths.into_iter().filter(|path| is_replay(path)) {
+                    // The receiver only goes away along with the watcher
+                    let _ = tx.send(path);
+                }
+            })?;
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+            pending: None,
+        })
+    }
+
+    /// Returns a replay once it has finished being written, without blocking.
+    pub fn poll(&mut self) -> Option<PathBuf> {
+        while let Ok(path) = self.changes.try_recv() {
+            self.pending = Some((path, Instant::now()));
+        }
+
+        match &self.pending {
+            Some((_, changed)) if changed.elapsed() >= SETTLE => {
+                self.pending.take().map(|(path, _)| path)
+            }
+            _ => None,
+        }
+    }
+
+    /// Blocks until a replay has finished being written.
+    pub fn wait(&mut self) -> PathBuf {
+        loop {
+            if let Some(path) = self.poll() {
+                return path;
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+}
@@ -0,0 +1,1144 @@
+use std::collections::VecDeque;
+use std::error;
+use std::num::{NonZeroIsize, NonZeroU32};
+use std::ops::Range;
+use std::ptr::NonNull;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use graphics::glyph_cache::rusttype::GlyphCache;
+use graphics::{Context, DrawState, Graphics, ImageSize, Viewport};
+use opengl_graphics::{CreateTexture, Format, TextureOp, TextureSettings, UpdateTexture};
+use piston::{
+    AdvancedWindow, Button, ButtonArgs, ButtonState, CloseArgs, Event, Input, Key, Motion,
+    Position, ResizeArgs, Size, Window, WindowSettings,
+};
+use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
+use wgpu::rwh;
+use wgpu::util::DeviceExt;
+use winit::event::{ElementState, KeyboardInput, MouseScrollDelta, StartCause, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop, EventLoopBuilder};
+use winit::platform::run_return::EventLoopExtRunReturn;
+
+use crate::app::renderer::{AppWindow, Renderer};
+use crate::app::scene::{Scene, Vertex};
+use crate::app::FONT;
+
+const SHADER: &str = r#"
+struct Out {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@location(0) position: vec2<f32>, @location(1) uv: vec2<f32>, @location(2) color: vec4<f32>) -> Out {
+    return Out(vec4<f32>(position, 0.0, 1.0), uv, color);
+}
+
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+@fragment
+fn fs_main(in: Out) -> @location(0) vec4<f32> {
+    return in.color * textureSample(texture, texture_sampler, in.uv);
+}
+"#;
+
+/// Floats in each vertex: its position in normalized device coordinates, where it is on its
+/// texture, and its color.
+const VERTEX_FLOATS: usize = 8;
+
+/// Draws the 3D display's scenes, taking each vertex from the replay's coordinates to clip space
+/// with the scene's transform.
+const SCENE_SHADER: &str = r#"
+struct Out {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> transform: mat4x4<f32>;
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>, @location(1) color: vec4<f32>) -> Out {
+    return Out(transform * vec4<f32>(position, 1.0), color);
+}
+
+@fragment
+fn fs_main(in: Out) -> @location(0) vec4<f32> {
+    return in.color;
+}
+"#;
+
+/// Floats in each of a scene's vertices: its position in the replay's coordinates and its color.
+const SCENE_VERTEX_FLOATS: usize = 7;
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Woken up to stop waiting on the window's events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WakeUp;
+
+/// A plain winit window, with no OpenGL context behind it, turned into piston events.
+pub struct WgpuWindow {
+    window: Arc<winit::window::Window>,
+    event_loop: EventLoop<WakeUp>,
+    events: VecDeque<WindowEvent<'static>>,
+    title: String,
+    exit_on_esc: bool,
+    automatic_close: bool,
+    should_close: bool,
+    /// The last key pressed, to leave out the repeats the system sends while it's held.
+    last_key: Option<Key>,
+}
+
+impl WgpuWindow {
+    fn new(settings: &WindowSettings) -> Result<Self, Box<dyn error::Error>> {
+        let event_loop = EventLoopBuilder::with_user_event().build();
+        let Size { width, height } = settings.get_size();
+        let mut builder = winit::window::WindowBuilder::new()
+            .with_inner_size(winit::dpi::LogicalSize { width, height })
+            .with_title(settings.get_title())
+            .with_decorations(settings.get_decorated())
+            .with_resizable(settings.get_resizable())
+            .with_transparent(settings.get_transparent());
+        if settings.get_fullscreen() {
+            let monitor = event_loop.primary_monitor();
+            builder = builder.with_fullscreen(Some(winit::window::Fullscreen::Borderless(monitor)));
+        }
+        Ok(Self {
+            window: Arc::new(builder.build(&event_loop)?),
+            event_loop,
+            events: VecDeque::new(),
+            title: settings.get_title(),
+            exit_on_esc: settings.get_exit_on_esc(),
+            automatic_close: settings.get_automatic_close(),
+            should_close: false,
+            last_key: None,
+        })
+    }
+
+    /// Runs the event loop until it runs out of events, or with `wait`, until at least one comes
+    /// in or the wait it gives, like `ControlFlow::WaitUntil`, runs out.
+    fn pump(&mut self, wait: Option<ControlFlow>) {
+        if wait.is_none() {
+            self.event_loop.create_proxy().send_event(WakeUp).ok();
+        }
+        let events = &mut self.events;
+        self.event_loop.run_return(|event, _, control_flow| {
+            *control_flow = wait.unwrap_or(ControlFlow::Wait);
+            match event {
+                winit::event::Event::WindowEvent { event, .. } => {
+                    if let Some(event) = event.to_static() {
+                        events.push_back(event);
+                    }
+                    if wait.is_some() {
+                        *control_flow = ControlFlow::Exit;
+                    }
+                }
+                winit::event::Event::NewEvents(StartCause::ResumeTimeReached { .. })
+                | winit::event::Event::UserEvent(WakeUp) => *control_flow = ControlFlow::Exit,
+                _ => {}
+            }
+        });
+    }
+
+    /// The next event already pumped that piston has an input for.
+    fn next_input(&mut self) -> Option<Input> {
+        while let Some(event) = self.events.pop_front() {
+            if let Some(input) = self.input(event) {
+                return Some(input);
+            }
+        }
+        None
+    }
+
+    fn input(&mut self, event: WindowEvent<'static>) -> Option<Input> {
+        let scale = self.window.scale_factor();
+        match event {
+            WindowEvent::Resized(draw_size) => Some(Input::Resize(ResizeArgs {
+                window_size: self.size().into(),
+                draw_size: draw_size.into(),
+            })),
+            WindowEvent::ReceivedCharacter(ch) if !ch.is_control() => {
+                Some(Input::Text(ch.to_string()))
+            }
+            WindowEvent::Focused(focused) => Some(Input::Focus(focused)),
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(keycode),
+                        scancode,
+                        ..
+                    },
+                ..
+            } => {
+                let key = glutin_window::map_key(keycode);
+                let state = match state {
+                    ElementState::Pressed if self.last_key == Some(key) => return None,
+                    ElementState::Pressed => {
+                        self.last_key = Some(key);
+                        if self.exit_on_esc && key == Key::Escape {
+                            self.should_close = true;
+                        }
+                        ButtonState::Press
+                    }
+                    ElementState::Released => {
+                        if self.last_key == Some(key) {
+                            self.last_key = None;
+                        }
+                        ButtonState::Release
+                    }
+                };
+                Some(Input::Button(ButtonArgs {
+                    state,
+                    button: Button::Keyboard(key),
+                    scancode: Some(scancode as i32),
+                }))
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = position.to_logical::<f64>(scale);
+                Some(Input::Move(Motion::MouseCursor([position.x, position.y])))
+            }
+            WindowEvent::CursorEntered { .. } => Some(Input::Cursor(true)),
+            WindowEvent::CursorLeft { .. } => Some(Input::Cursor(false)),
+            WindowEvent::MouseWheel { delta, .. } => {
+                let [x, y] = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => [x as f64, y as f64],
+                    MouseScrollDelta::PixelDelta(position) => {
+                        let position = position.to_logical::<f64>(scale);
+                        [position.x, position.y]
+                    }
+                };
+                Some(Input::Move(Motion::MouseScroll([x, y])))
+            }
+            WindowEvent::MouseInput { state, button, .. } => Some(Input::Button(ButtonArgs {
+                state: match state {
+                    ElementState::Pressed => ButtonState::Press,
+                    ElementState::Released => ButtonState::Release,
+                },
+                button: Button::Mouse(glutin_window::map_mouse(button)),
+                scancode: None,
+            })),
+            WindowEvent::CloseRequested => {
+                if self.automatic_close {
+                    self.should_close = true;
+                }
+                Some(Input::Close(CloseArgs))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Window for WgpuWindow {
+    fn set_should_close(&mut self, value: bool) {
+        self.should_close = value;
+    }
+
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn size(&self) -> Size {
+        let size = self.window.inner_size();
+        let size = size.to_logical::<u32>(self.window.scale_factor());
+        (size.width, size.height).into()
+    }
+
+    /// Frames are shown by `WgpuRenderer::draw` as soon as they're drawn, so there's nothing left
+    /// to swap.
+    fn swap_buffers(&mut self) {}
+
+    fn wait_event(&mut self) -> Event {
+        loop {
+            if let Some(input) = self.next_input() {
+                return Event::Input(input, None);
+            }
+            self.pump(Some(ControlFlow::Wait));
+        }
+    }
+
+    fn wait_event_timeout(&mut self, timeout: Duration) -> Option<Event> {
+        if self.events.is_empty() {
+            self.pump(Some(ControlFlow::WaitUntil(Instant::now() + timeout)));
+        }
+        self.poll_event()
+    }
+
+    fn poll_event(&mut self) -> Option<Event> {
+        if self.events.is_empty() {
+            self.pump(None);
+        }
+        self.next_input().map(|input| Event::Input(input, None))
+    }
+
+    fn draw_size(&self) -> Size {
+        let size = self.window.inner_size();
+        (size.width, size.height).into()
+    }
+}
+
+impl AdvancedWindow for WgpuWindow {
+    fn get_title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn set_title(&mut self, value: String) {
+        self.window.set_title(&value);
+        self.title = value;
+    }
+
+    fn get_exit_on_esc(&self) -> bool {
+        self.exit_on_esc
+    }
+
+    fn set_exit_on_esc(&mut self, value: bool) {
+        self.exit_on_esc = value;
+    }
+
+    fn get_automatic_close(&self) -> bool {
+        self.automatic_close
+    }
+
+    fn set_automatic_close(&mut self, value: bool) {
+        self.automatic_close = value;
+    }
+
+    /// Only hides the cursor, since nothing in the window needs relative mouse movement.
+    fn set_capture_cursor(&mut self, value: bool) {
+        self.window.set_cursor_visible(!value);
+    }
+
+    fn show(&mut self) {
+        self.window.set_visible(true);
+    }
+
+    fn hide(&mut self) {
+        self.window.set_visible(false);
+    }
+
+    fn get_position(&self) -> Option<Position> {
+        let position = self.window.outer_position().ok()?;
+        let position = position.to_logical::<i32>(self.window.scale_factor());
+        Some((position.x, position.y).into())
+    }
+
+    fn set_position<P: Into<Position>>(&mut self, position: P) {
+        let Position { x, y } = position.into();
+        self.window
+            .set_outer_position(winit::dpi::LogicalPosition { x, y });
+    }
+
+    fn set_size<S: Into<Size>>(&mut self, size: S) {
+        let Size { width, height } = size.into();
+        self.window
+            .set_inner_size(winit::dpi::LogicalSize { width, height });
+    }
+}
+
+impl AppWindow for WgpuWindow {
+    fn winit(&self) -> &winit::window::Window {
+        &self.window
+    }
+}
+
+/// The window wgpu draws into. The surface made from it holds on to it, so the window can't be
+/// closed while the surface still draws into it.
+struct SurfaceWindow(Arc<winit::window::Window>);
+
+// winit 0.28 still hands out raw-window-handle 0.5 handles, so they're copied over to the 0.6 ones
+// wgpu takes
+impl rwh::HasWindowHandle for SurfaceWindow {
+    fn window_handle(&self) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+        let handle = window_handle(self.0.raw_window_handle())?;
+        // Safety: the handle is `self.0`'s, which stays open for as long as it's borrowed
+        Ok(unsafe { rwh::WindowHandle::borrow_raw(handle) })
+    }
+}
+
+impl rwh::HasDisplayHandle for SurfaceWindow {
+    fn display_handle(&self) -> Result<rwh::DisplayHandle<'_>, rwh::HandleError> {
+        let handle = display_handle(self.0.raw_display_handle())?;
+        // Safety: the display is the one `self.0` is open on, which it doesn't outlive
+        Ok(unsafe { rwh::DisplayHandle::borrow_raw(handle) })
+    }
+}
+
+fn window_handle(
+    handle: raw_window_handle::RawWindowHandle,
+) -> Result<rwh::RawWindowHandle, rwh::HandleError> {
+    use raw_window_handle::RawWindowHandle;
+
+    let unsupported = rwh::HandleError::NotSupported;
+    Ok(match handle {
+        RawWindowHandle::Xlib(handle) => rwh::XlibWindowHandle::new(handle.window).into(),
+        RawWindowHandle::Xcb(handle) => {
+            rwh::XcbWindowHandle::new(NonZeroU32::new(handle.window).ok_or(unsupported)?).into()
+        }
+        RawWindowHandle::Wayland(handle) => {
+            rwh::WaylandWindowHandle::new(NonNull::new(handle.surface).ok_or(unsupported)?).into()
+        }
+        RawWindowHandle::Win32(handle) => {
+            let hwnd = NonZeroIsize::new(handle.hwnd as isize).ok_or(unsupported)?;
+            let mut win32 = rwh::Win32WindowHandle::new(hwnd);
+            win32.hinstance = NonZeroIsize::new(handle.hinstance as isize);
+            win32.into()
+        }
+        RawWindowHandle::AppKit(handle) => {
+            rwh::AppKitWindowHandle::new(NonNull::new(handle.ns_view).ok_or(unsupported)?).into()
+        }
+        _ => return Err(unsupported),
+    })
+}
+
+fn display_handle(
+    handle: raw_window_handle::RawDisplayHandle,
+) -> Result<rwh::RawDisplayHandle, rwh::HandleError> {
+    use raw_window_handle::RawDisplayHandle;
+
+    let unsupported = rwh::HandleError::NotSupported;
+    Ok(match handle {
+        RawDisplayHandle::Xlib(handle) => {
+            rwh::XlibDisplayHandle::new(NonNull::new(handle.display), handle.screen).into()
+        }
+        RawDisplayHandle::Xcb(handle) => {
+            rwh::XcbDisplayHandle::new(NonNull::new(handle.connection), handle.screen).into()
+        }
+        RawDisplayHandle::Wayland(handle) => {
+            rwh::WaylandDisplayHandle::new(NonNull::new(handle.display).ok_or(unsupported)?).into()
+        }
+        RawDisplayHandle::Windows(_) => rwh::WindowsDisplayHandle::new().into(),
+        RawDisplayHandle::AppKit(_) => rwh::AppKitDisplayHandle::new().into(),
+        _ => return Err(unsupported),
+    })
+}
+
+/// What the glyph cache makes its textures with.
+pub struct Factory {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    layout: Arc<wgpu::BindGroupLayout>,
+    sampler: Arc<wgpu::Sampler>,
+}
+
+impl Factory {
+    /// The bind group the shader samples `texture` through.
+    fn bind_group(&self, texture: &wgpu::Texture) -> Arc<wgpu::BindGroup> {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Arc::new(self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        }))
+    }
+}
+
+/// An RGBA texture on the GPU, like a glyph from the glyph cache.
+pub struct WgpuTexture {
+    texture: wgpu::Texture,
+    bind_group: Arc<wgpu::BindGroup>,
+    size: [u32; 2],
+}
+
+impl WgpuTexture {
+    fn write(
+        &self,
+        queue: &wgpu::Queue,
+        memory: &[u8],
+        [x, y]: [u32; 2],
+        [width, height]: [u32; 2],
+    ) {
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            memory,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
+
+impl ImageSize for WgpuTexture {
+    fn get_size(&self) -> (u32, u32) {
+        (self.size[0], self.size[1])
+    }
+}
+
+impl TextureOp<Factory> for WgpuTexture {
+    type Error = String;
+}
+
+impl CreateTexture<Factory> for WgpuTexture {
+    fn create<S: Into<[u32; 2]>>(
+        factory: &mut Factory,
+        _format: Format,
+        memory: &[u8],
+        size: S,
+        _settings: &TextureSettings,
+    ) -> Result<Self, String> {
+        let [width, height] = size.into();
+        let texture = factory.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let texture = WgpuTexture {
+            bind_group: factory.bind_group(&texture),
+            texture,
+            size: [width, height],
+        };
+        if width > 0 && height > 0 {
+            texture.write(&factory.queue, memory, [0, 0], [width, height]);
+        }
+        Ok(texture)
+    }
+}
+
+impl UpdateTexture<Factory> for WgpuTexture {
+    fn update<O, S>(
+        &mut self,
+        factory: &mut Factory,
+        _format: Format,
+        memory: &[u8],
+        offset: O,
+        size: S,
+    ) -> Result<(), String>
+    where
+        O: Into<[u32; 2]>,
+        S: Into<[u32; 2]>,
+    {
+        let size = size.into();
+        if size[0] > 0 && size[1] > 0 {
+            self.write(&factory.queue, memory, offset.into(), size);
+        }
+        Ok(())
+    }
+}
+
+/// Collects a frame's triangles to draw in one render pass, in runs that share a texture.
+/// Stencils and scissors from the draw state are ignored, since nothing in the window clips.
+pub struct WgpuGraphics {
+    vertices: Vec<f32>,
+    /// Runs of vertices drawn with the same texture, in the order they were drawn.
+    batches: Vec<(Arc<wgpu::BindGroup>, Range<u32>)>,
+    /// What the frame was last cleared to.
+    clear: wgpu::Color,
+    /// A single white pixel, sampled by everything drawn without a texture.
+    white: Arc<wgpu::BindGroup>,
+}
+
+impl WgpuGraphics {
+    fn push(&mut self, bind_group: &Arc<wgpu::BindGroup>, vertex: ([f32; 2], [f32; 2], [f32; 4])) {
+        let index = (self.vertices.len() / VERTEX_FLOATS) as u32;
+        let (position, uv, color) = vertex;
+        self.vertices
+            .extend(position.into_iter().chain(uv).chain(color));
+        match self.batches.last_mut() {
+            Some((last, range)) if Arc::ptr_eq(last, bind_group) => range.end = index + 1,
+            _ => self.batches.push((bind_group.clone(), index..index + 1)),
+        }
+    }
+}
+
+impl Graphics for WgpuGraphics {
+    type Texture = WgpuTexture;
+
+    /// Throws away everything drawn so far in the frame, since the clear would cover it anyway.
+    fn clear_color(&mut self, [r, g, b, a]: [f32; 4]) {
+        self.vertices.clear();
+        self.batches.clear();
+        self.clear = wgpu::Color {
+            r: r as f64,
+            g: g as f64,
+            b: b as f64,
+            a: a as f64,
+        };
+    }
+
+    fn clear_stencil(&mut self, _value: u8) {}
+
+    fn tri_list<F>(&mut self, _draw_state: &DrawState, color: &[f32; 4], mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+    {
+        let white = self.white.clone();
+        f(&mut |positions| {
+            for position in positions {
+                self.push(&white, (*position, [0.0; 2], *color));
+            }
+        });
+    }
+
+    fn tri_list_c<F>(&mut self, _draw_state: &DrawState, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 4]])),
+    {
+        let white = self.white.clone();
+        f(&mut |positions, colors| {
+            for (position, color) in positions.iter().zip(colors) {
+                self.push(&white, (*position, [0.0; 2], *color));
+            }
+        });
+    }
+
+    fn tri_list_uv<F>(
+        &mut self,
+        _draw_state: &DrawState,
+        color: &[f32; 4],
+        texture: &WgpuTexture,
+        mut f: F,
+    ) where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]])),
+    {
+        f(&mut |positions, uvs| {
+            for (position, uv) in positions.iter().zip(uvs) {
+                self.push(&texture.bind_group, (*position, *uv, *color));
+            }
+        });
+    }
+
+    fn tri_list_uv_c<F>(&mut self, _draw_state: &DrawState, texture: &WgpuTexture, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]])),
+    {
+        f(&mut |positions, uvs, colors| {
+            for ((position, uv), color) in positions.iter().zip(uvs).zip(colors) {
+                self.push(&texture.bind_group, (*position, *uv, *color));
+            }
+        });
+    }
+}
+
+/// Draws with wgpu, which picks Vulkan, Metal or DX12 for the machine it's on, so the window
+/// opens without OpenGL.
+pub struct WgpuRenderer {
+    surface: wgpu::Surface<'static>,
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    config: wgpu::SurfaceConfiguration,
+    pipeline: wgpu::RenderPipeline,
+    /// Scenes' triangles, then their lines, both hidden behind whatever's nearer the camera.
+    scene_pipelines: [wgpu::RenderPipeline; 2],
+    scene_layout: wgpu::BindGroupLayout,
+    /// How far from the camera what's been drawn so far in each pixel is, sized to the surface.
+    depth: wgpu::TextureView,
+    graphics: WgpuGraphics,
+    glyphs: GlyphCache<'static, Factory, WgpuTexture>,
+}
+
+impl WgpuRenderer {
+    /// Opens a window with no OpenGL context, and a renderer for it.
+    pub fn open(settings: &WindowSettings) -> Result<(WgpuWindow, Self), Box<dyn error::Error>> {
+        let window = WgpuWindow::new(settings)?;
+        let instance = wgpu::Instance::default();
+        let surface = instance.create_surface(SurfaceWindow(window.window.clone()))?;
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            compatible_surface: Some(&surface),
+            ..Default::default()
+        }))
+        .ok_or("couldn't find a graphics adapter wgpu can draw to the window with")?;
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                required_features: wgpu::Features::empty(),
+                required_limits:
+                    wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
+            },
+            None,
+        ))?;
+        let (device, queue) = (Arc::new(device), Arc::new(queue));
+
+        // Colors are drawn as they're given, the same as with OpenGL, so the surface can't be sRGB
+        let capabilities = surface.get_capabilities(&adapter);
+        let format = capabilities
+            .formats
+            .iter()
+            .copied()
+            .find(|format| !format.is_srgb())
+            .or(capabilities.formats.first().copied())
+            .ok_or("the window's surface can't be drawn to")?;
+        let see_through = [
+            wgpu::CompositeAlphaMode::PostMultiplied,
+            wgpu::CompositeAlphaMode::PreMultiplied,
+        ];
+        let alpha_mode = capabilities
+            .alpha_modes
+            .iter()
+            .copied()
+            .find(|mode| settings.get_transparent() && see_through.contains(mode))
+            .unwrap_or(capabilities.alpha_modes[0]);
+        let size = window.window.inner_size();
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &config);
+
+        let layout = Arc::new(
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            }),
+        );
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: (VERTEX_FLOATS * std::mem::size_of::<f32>()) as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float32x2,
+                        1 => Float32x2,
+                        2 => Float32x4,
+                    ],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // Everything but the scenes is drawn over them, in the order it's drawn in
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let (scene_layout, scene_pipelines) = scene_pipelines(&device, format);
+        let depth = depth_view(&device, &config);
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let mut factory = Factory {
+            device: device.clone(),
+            queue: queue.clone(),
+            layout,
+            sampler: Arc::new(sampler),
+        };
+        let white = WgpuTexture::create(
+            &mut factory,
+            Format::Rgba8,
+            &[255; 4],
+            [1, 1],
+            &TextureSettings::new(),
+        )?;
+        let glyphs = GlyphCache::from_bytes(FONT, factory, TextureSettings::new())
+            .expect("the bundled font is valid");
+
+        let renderer = Self {
+            surface,
+            device,
+            queue,
+            config,
+            pipeline,
+            scene_pipelines,
+            scene_layout,
+            depth,
+            graphics: WgpuGraphics {
+                vertices: vec![],
+                batches: vec![],
+                clear: wgpu::Color::TRANSPARENT,
+                white: white.bind_group,
+            },
+            glyphs,
+        };
+        Ok((window, renderer))
+    }
+
+    /// The vertex buffers and transform `scene` is drawn with, and the pixels it's kept within.
+    fn upload(&self, scene: &Scene) -> Option<SceneBuffers> {
+        let scissor = scissor(scene.bounds, [self.config.width, self.config.height])?;
+        let vertices = |vertices: &[Vertex]| {
+            let bytes: Vec<u8> = vertices
+                .iter()
+                .flat_map(|vertex| vertex.position.into_iter().chain(vertex.color))
+                .flat_map(|float| float.to_ne_bytes())
+                .collect();
+            let buffer = self
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: None,
+                    contents: &bytes,
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+            (buffer, vertices.len() as u32)
+        };
+        let transform: Vec<u8> = scene
+            .transform
+            .iter()
+            .flatten()
+            .flat_map(|float| float.to_ne_bytes())
+            .collect();
+        let transform = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &transform,
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.scene_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: transform.as_entire_binding(),
+            }],
+        });
+        Some(SceneBuffers {
+            bind_group,
+            scissor,
+            vertices: [vertices(&scene.triangles), vertices(&scene.lines)],
+        })
+    }
+}
+
+/// What one scene is drawn with for a frame.
+struct SceneBuffers {
+    bind_group: wgpu::BindGroup,
+    /// Left, top, width and height, in pixels.
+    scissor: [u32; 4],
+    /// The triangles' and the lines', with how many vertices each holds.
+    vertices: [(wgpu::Buffer, u32); 2],
+}
+
+/// The layout of a scene's transform and the pipelines its triangles and lines are drawn with,
+/// into a surface of `format` with a depth buffer.
+fn scene_pipelines(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+) -> (wgpu::BindGroupLayout, [wgpu::RenderPipeline; 2]) {
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: None,
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }],
+    });
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: None,
+        source: wgpu::ShaderSource::Wgsl(SCENE_SHADER.into()),
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: None,
+        bind_group_layouts: &[&layout],
+        push_constant_ranges: &[],
+    });
+    let pipelines = [
+        wgpu::PrimitiveTopology::TriangleList,
+        wgpu::PrimitiveTopology::LineList,
+    ]
+    .map(|topology| {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: (SCENE_VERTEX_FLOATS * std::mem::size_of::<f32>()) as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float32x3,
+                        1 => Float32x4,
+                    ],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+    });
+    (layout, pipelines)
+}
+
+fn depth_view(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::TextureView {
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// The pixels of a surface `size` pixels across that `bounds`, left, top, right and bottom in
+/// normalized device coordinates, cover, or `None` if that's none of them.
+fn scissor([left, top, right, bottom]: [f32; 4], [width, height]: [u32; 2]) -> Option<[u32; 4]> {
+    let x = |ndc: f32| {
+        ((ndc + 1.0) / 2.0 * width as f32)
+            .round()
+            .clamp(0.0, width as f32) as u32
+    };
+    let y = |ndc: f32| {
+        ((1.0 - ndc) / 2.0 * height as f32)
+            .round()
+            .clamp(0.0, height as f32) as u32
+    };
+    let [left, right] = [x(left.min(right)), x(left.max(right))];
+    let [top, bottom] = [y(top.max(bottom)), y(top.min(bottom))];
+    (right > left && bottom > top).then_some([left, top, right - left, bottom - top])
+}
+
+impl Renderer for WgpuRenderer {
+    type Texture = WgpuTexture;
+    type Graphics = WgpuGraphics;
+    type Glyphs = GlyphCache<'static, Factory, WgpuTexture>;
+
+    const DRAWS_SCENES: bool = true;
+
+    fn draw<F>(&mut self, viewport: Viewport, scenes: &[Scene], f: F)
+    where
+        F: FnOnce(Context, &mut WgpuGraphics, &mut Self::Glyphs),
+    {
+        let [width, height] = viewport.draw_size;
+        if width == 0 || height == 0 {
+            return;
+        }
+        if [self.config.width, self.config.height] != [width, height] {
+            self.config.width = width;
+            self.config.height = height;
+            self.surface.configure(&self.device, &self.config);
+            self.depth = depth_view(&self.device, &self.config);
+        }
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            // Lost or outdated after the window changed, so set it up again for the next frame
+            Err(_) => {
+                self.surface.configure(&self.device, &self.config);
+                return;
+            }
+        };
+
+        let scenes: Vec<_> = scenes
+            .iter()
+            .filter_map(|scene| self.upload(scene))
+            .collect();
+        let graphics = &mut self.graphics;
+        graphics.vertices.clear();
+        graphics.batches.clear();
+        graphics.clear = wgpu::Color::TRANSPARENT;
+        f(Context::new_viewport(viewport), graphics, &mut self.glyphs);
+
+        let bytes: Vec<u8> = graphics
+            .vertices
+            .iter()
+            .flat_map(|float| float.to_ne_bytes())
+            .collect();
+        let buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &bytes,
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(graphics.clear),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            for scene in &scenes {
+                let [x, y, width, height] = scene.scissor;
+                pass.set_scissor_rect(x, y, width, height);
+                pass.set_bind_group(0, &scene.bind_group, &[]);
+                for (pipeline, (buffer, count)) in self.scene_pipelines.iter().zip(&scene.vertices)
+                {
+                    if *count > 0 {
+                        pass.set_pipeline(pipeline);
+                        pass.set_vertex_buffer(0, buffer.slice(..));
+                        pass.draw(0..*count, 0..1);
+                    }
+                }
+            }
+            pass.set_scissor_rect(0, 0, self.config.width, self.config.height);
+            if !bytes.is_empty() {
+                pass.set_pipeline(&self.pipeline);
+                pass.set_vertex_buffer(0, buffer.slice(..));
+                for (bind_group, range) in &graphics.batches {
+                    pass.set_bind_group(0, bind_group, &[]);
+                    pass.draw(range.clone(), 0..1);
+                }
+            }
+        }
+        self.queue.submit([encoder.finish()]);
+        frame.present();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_handles_are_copied_over_to_the_ones_wgpu_takes() {
+        let mut xlib = raw_window_handle::XlibWindowHandle::empty();
+        xlib.window = 7;
+        let handle = window_handle(xlib.into()).unwrap();
+        assert_eq!(handle, rwh::XlibWindowHandle::new(7).into());
+
+        let mut xcb = raw_window_handle::XcbDisplayHandle::empty();
+        xcb.screen = 1;
+        let handle = display_handle(xcb.into()).unwrap();
+        assert_eq!(handle, rwh::XcbDisplayHandle::new(None, 1).into());
+    }
+
+    #[test]
+    fn windows_wgpu_cant_draw_to_are_refused() {
+        // A window id of 0 is no window at all
+        let xcb = raw_window_handle::XcbWindowHandle::empty();
+        assert!(window_handle(xcb.into()).is_err());
+        let web = raw_window_handle::WebWindowHandle::empty();
+        assert!(window_handle(web.into()).is_err());
+    }
+
+    #[test]
+    fn scenes_are_kept_to_the_pixels_their_field_covers() {
+        // The right half of the window, drawn past its bottom edge
+        let pixels = scissor([0.0, 1.0, 1.0, -1.5], [800, 600]);
+        assert_eq!(pixels, Some([400, 0, 400, 600]));
+        assert_eq!(scissor([2.0, 1.0, 3.0, -1.0], [800, 600]), None);
+    }
+
+    #[test]
+    fn surfaces_can_hold_on_to_their_window() {
+        fn surface_target<T: Into<wgpu::SurfaceTarget<'static>>>() {}
+        surface_target::<SurfaceWindow>();
+    }
+}
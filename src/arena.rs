@@ -0,0 +1,239 @@
+use serde::Serialize;
+
+use crate::{STANDARD_GOAL_SIZE, STANDARD_GOAL_WIDTH, STANDARD_MAP_HEIGHT, STANDARD_MAP_WIDTH};
+
+/// Which game mode an arena is built for, since Hoops and Dropshot fields look nothing like a
+/// soccar pitch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ArenaKind {
+    Soccar,
+    Hoops,
+    Dropshot,
+}
+
+/// The floor a replay was played on, in unreal units, centered on the kickoff spot.
+///
+/// Every arena is modeled as a rectangle with its corners cut off diagonally, which covers the
+/// standard octagonal pitch as well as Dropshot's hexagon.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Arena {
+    pub kind: ArenaKind,
+    /// Distance between the side walls.
+    pub width: f64,
+    /// Distance between the back walls.
+    pub height: f64,
+    /// How far the diagonal corner walls reach along the back walls, then along the side walls.
+    pub corner: [f64; 2],
+    /// Width of the goal mouths in the back walls, or 0 if there aren't any.
+    pub goal_width: f64,
+    /// How far each goal reaches behind its back wall.
+    pub goal_depth: f64,
+    /// Where the boost pads are, or empty if the layout isn't known.
+    pub boost_pads: &'static [BoostPad],
+}
+
+impl Arena {
+    /// Shared by every regular soccar map, including the Snow Day variants.
+    pub const STANDARD: Arena = Arena {
+        kind: ArenaKind::Soccar,
+        width: STANDARD_MAP_WIDTH,
+        height: STANDARD_MAP_HEIGHT,
+        corner: [1152.0, 1152.0],
+        goal_width: STANDARD_GOAL_WIDTH,
+        goal_depth: STANDARD_GOAL_SIZE / 2.0,
+        boost_pads: &STANDARD_BOOST_PADS,
+    };
+
+    pub const THROWBACK: Arena = Arena {
+        height: 11520.0,
+        boost_pads: &[],
+        ..Arena::STANDARD
+    };
+
+    /// The Octagon labs map, whose corner walls are long enough to make the pitch a true octagon.
+    pub const OCTAGON: Arena = Arena {
+        corner: [2800.0, 2800.0],
+        boost_pads: &[],
+        ..Arena::STANDARD
+    };
+
+    /// The labs maps played on a standard-size pitch, like Pillars, Underpass or Cosmic. Their
+    /// obstacles aren't modeled, and their boost pads don't follow the standard layout.
+    pub const LABS: Arena = Arena {
+        boost_pads: &[],
+        ..Arena::STANDARD
+    };
+
+    /// Hoops' floor has rounded corners and its hoops float above the floor instead of sitting in
+    /// the back walls.
+    pub const HOOPS: Arena = Arena {
+        kind: ArenaKind::Hoops,
+        width: 5920.0,
+        height: 7168.0,
+        corner: [700.0, 700.0],
+        goal_width: 0.0,
+        goal_depth: 0.0,
+        boost_pads: &[],
+    };
+
+    /// Dropshot's floor is a hexagon with the back walls as two of its sides, and no goals.
+    pub const DROPSHOT: Arena = Arena {
+        kind: ArenaKind::Dropshot,
+        width: 10520.0,
+        height: 9110.0,
+        corner: [2630.0, 4555.0],
+        goal_width: 0.0,
+        goal_depth: 0.0,
+        boost_pads: &[],
+    };
+
+    /// Looks up the arena for the `MapName` in a replay's header, like `Stadium_P` or
+    /// `HoopsStadium_P`. Maps without an entry of their own are assumed to be standard, except
+    /// labs maps, which are too different from each other to guess at and give `None`.
+    pub fn for_map(map: &str) -> Option<Arena> {
+        let map = map.to_ascii_lowercase();
+        let arena = if map.starts_with("hoops") {
+            Arena::HOOPS
+        } else if map.starts_with("shattershot") {
+            Arena::DROPSHOT
+        } else if map.starts_with("throwback") {
+            Arena::THROWBACK
+        } else if map.starts_with("labs_octagon") {
+            Arena::OCTAGON
+        } else if STANDARD_SIZE_LABS.iter().any(|labs| map.starts_with(labs)) {
+            Arena::LABS
+        } else if map.starts_with("labs_") {
+            return None;
+        } else {
+            Arena::STANDARD
+        };
+        Some(arena)
+    }
+
+    /// Moves a point onto the floor's bounding rectangle.
+    pub fn clamp(&self, x: f64, y: f64) -> [f64; 2] {
+        [
+            x.clamp(-self.width / 2.0, self.width / 2.0),
+            y.clamp(-self.height / 2.0, self.height / 2.0),
+        ]
+    }
+
+    /// Whether a point is strictly inside the floor's bounding rectangle.
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x.abs() < self.width / 2.0 && y.abs() < self.height / 2.0
+    }
+
+    /// Area of the floor's bounding rectangle.
+    pub fn area(&self) -> f64 {
+        self.width * self.height
+    }
+
+    /// Corners of the floor, going around the walls and their diagonal corner cuts, starting from
+    /// the left end of the back wall at negative y.
+    pub fn wall_corners(&self) -> [[f64; 2]; 8] {
+        let half_width = self.width / 2.0;
+        let half_height = self.height / 2.0;
+        let [along_back, along_side] = self.corner;
+        [
+            [-half_width + along_back, -half_height],
+            [half_width - along_back, -half_height],
+            [half_width, -half_height + along_side],
+            [half_width, half_height - along_side],
+            [half_width - along_back, half_height],
+            [-half_width + along_back, half_height],
+            [-half_width, half_height - along_side],
+            [-half_width, -half_height + along_side],
+        ]
+    }
+}
+
+/// Prefixes of the labs maps built on a standard-size pitch, lowercased. Versions of a map, like
+/// `Labs_Cosmic_V4_P` or `Labs_Galleon_Mast_P`, share their original's prefix.
+const STANDARD_SIZE_LABS: [&str; 9] = [
+    "labs_circlepillars",
+    "labs_cosmic",
+    "labs_doublegoal",
+    "labs_underpass",
+    "labs_utopia",
+    "labs_galleon",
+    "labs_pillarglass",
+    "labs_pillarheat",
+    "labs_pillarwings",
+];
+
+/// How badly a Dropshot tile has been hit by the ball.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum TileState {
+    #[default]
+    Intact,
+    Damaged,
+    Destroyed,
+}
+
+/// One of the hexagonal floor tiles of a Dropshot arena.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Tile {
+    /// Center of the tile on the floor.
+    pub location: [f64; 2],
+    pub state: TileState,
+}
+
+/// A spot on the floor that refills a car's boost when driven over.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BoostPad {
+    pub location: [f64; 2],
+    /// Big pads fill the tank and take 10 seconds to respawn, small ones give 12 boost and take 4.
+    pub big: bool,
+}
+
+const fn big_pad(x: f64, y: f64) -> BoostPad {
+    BoostPad {
+        location: [x, y],
+        big: true,
+    }
+}
+
+const fn small_pad(x: f64, y: f64) -> BoostPad {
+    BoostPad {
+        location: [x, y],
+        big: false,
+    }
+}
+
+/// The 6 big and 28 small pads every standard soccar map shares.
+const STANDARD_BOOST_PADS: [BoostPad; 34] = [
+    big_pad(-3072.0, -4096.0),
+    big_pad(3072.0, -4096.0),
+    big_pad(-3584.0, 0.0),
+    big_pad(3584.0, 0.0),
+    big_pad(-3072.0, 4096.0),
+    big_pad(3072.0, 4096.0),
+    small_pad(0.0, -4240.0),
+    small_pad(-1792.0, -4184.0),
+    small_pad(1792.0, -4184.0),
+    small_pad(-940.0, -3308.0),
+    small_pad(940.0, -3308.0),
+    small_pad(0.0, -2816.0),
+    small_pad(-3584.0, -2484.0),
+    small_pad(3584.0, -2484.0),
+    small_pad(-1788.0, -2300.0),
+    small_pad(1788.0, -2300.0),
+    small_pad(-2048.0, -1036.0),
+    small_pad(0.0, -1024.0),
+    small_pad(2048.0, -1036.0),
+    small_pad(-1024.0, 0.0),
+    small_pad(1024.0, 0.0),
+    small_pad(-2048.0, 1036.0),
+    small_pad(0.0, 1024.0),
+    small_pad(2048.0, 1036.0),
+    small_pad(-1788.0, 2300.0),
+    small_pad(1788.0, 2300.0),
+    small_pad(-3584.0, 2484.0),
+    small_pad(3584.0, 2484.0),
+    small_pad(0.0, 2816.0),
+    small_pad(-940.0, 3310.0),
+    small_pad(940.0, 3308.0),
+    small_pad(-1792.0, 4184.0),
+    small_pad(1792.0, 4184.0),
+    small_pad(0.0, 4240.0),
+];
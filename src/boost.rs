@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::arena::BoostPad;
+use crate::Team;
+
+/// How far a car can be from a pad's center when picking it up, with some slack for car positions
+/// lagging behind.
+pub(crate) const PAD_PICKUP_DISTANCE: f64 = 500.0;
+
+/// How fast boosting drains a car's tank, in the replay's units where a full tank is 255.
+pub(crate) const BOOST_PER_SECOND: f32 = 255.0 / 3.0;
+
+/// How a player picked up and spent their boost. Amounts are in the game's usual units, where a
+/// full tank is 100.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct PlayerBoost {
+    pub big_pads: u32,
+    pub small_pads: u32,
+    pub collected: f32,
+    pub used: f32,
+    /// Seconds spent with an empty tank.
+    pub seconds_empty: f32,
+    /// Seconds spent with a full tank.
+    pub seconds_full: f32,
+}
+
+/// Boost stats for every player seen in the replay so far.
+#[derive(Debug, Default, Clone)]
+pub struct BoostStats {
+    players: HashMap<String, (Team, PlayerBoost)>,
+}
+
+impl BoostStats {
+    /// Every player's boost stats, sorted by team and then by name.
+    pub fn players(&self) -> Vec<(&str, Team, PlayerBoost)> {
+        let mut players: Vec<_> = self
+            .players
+            .iter()
+            .map(|(name, (team, boost))| (name.as_str(), *team, *boost))
+            .collect();
+        players.sort_by_key(|(name, team, _)| (*team == Team::Blue, name.to_string()));
+        players
+    }
+
+    fn player(&mut self, name: &str, team: Team) -> &mut PlayerBoost {
+        let (player_team, boost) = self
+            .players
+            .entry(name.to_string())
+            .or_insert((team, PlayerBoost::default()));
+        *player_team = team;
+        boost
+    }
+
+    pub(crate) fn pick_up(&mut self, name: &str, team: Team, pad: &BoostPad) {
+        let boost = self.player(name, team);
+        if pad.big {
+            boost.big_pads += 1;
+        } else {
+            boost.small_pads += 1;
+        }
+    }
+
+    /// Counts how much each player's tank went up or down over the last `seconds`, and how full it
+    /// ended up, both out of 255.
+    pub(crate) fn add_usage(&mut self, players: Vec<(String, Team, f32, f32)>, seconds: f32) {
+        for (name, team, change, amount) in players {
+            let boost = self.player(&name, team);
+            let change = change * 100.0 / 255.0;
+            if change > 0.0 {
+                boost.collected += change;
+            } else {
+                boost.used -= change;
+            }
+            if amount <= 0.0 {
+                boost.seconds_empty += seconds;
+            } else if amount >= 255.0 {
+                boost.seconds_full += seconds;
+            }
+        }
+    }
+}
+
+/// What's known about one of the boost pads in the replay's level.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct PadState {
+    /// Which of the arena's pads this is, worked out from where the first car to pick it up was.
+    pub pad: Option<usize>,
+    pub taken: bool,
+    /// How many times the pad has been picked up, which newer replays count up with each pickup.
+    pub pickups: u8,
+}
+
+/// The index of the pad in `pads` closest to `location`, if any are close enough for a car there
+/// to have picked it up.
+pub(crate) fn nearest_pad(pads: &[BoostPad], location: [f64; 2]) -> Option<usize> {
+    let distance =
+        |pad: &BoostPad| (pad.location[0] - location[0]).hypot(pad.location[1] - location[1]);
+    pads.iter()
+        .enumerate()
+        .map(|(index, pad)| (index, distance(pad)))
+        .filter(|(_, distance)| *distance <= PAD_PICKUP_DISTANCE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(index, _)| index)
+}
@@ -0,0 +1,204 @@
+use serde::Serialize;
+
+use crate::shots::{self, GOAL_HEIGHT};
+use crate::{ReplayEngine, Team};
+
+/// How far from the middle of a car the ball can pass and still hit it: half an Octane's width
+/// plus the ball's radius.
+pub const BLOCK_RADIUS: f64 = 135.0;
+
+/// How fast, in uu/s, the ball has to be heading at a goal line to be a threat.
+const THREAT_SPEED: f32 = 1000.0;
+
+/// How far wide of the posts or over the crossbar the ball can be heading and still be a threat,
+/// since a touch or a bounce could still turn it in.
+pub const THREAT_MARGIN: f32 = 400.0;
+
+/// The part of the field a defender's car hides from the ball, looking down on it: every straight
+/// shot from the ball into this cone would hit the car on the way.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Shadow {
+    /// Where the edges of the cone touch either side of the car.
+    pub car: [[f64; 2]; 2],
+    /// Where the same edges reach the goal line, in the same order. An edge running away from the
+    /// goal line stops at the side wall instead.
+    pub goal_line: [[f64; 2]; 2],
+}
+
+/// How much of one goal mouth the defending team covers from where the ball is.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GoalCoverage {
+    /// The team defending the goal.
+    pub team: Team,
+    /// The y of the goal line.
+    pub goal_line: f64,
+    /// The x of the left and right posts.
+    pub posts: [f64; 2],
+    /// The shadow of each of the team's cars between the ball and the goal line.
+    pub shadows: Vec<Shadow>,
+    /// Parts of the goal mouth a shot from the ball would be blocked short of, as ranges of x from
+    /// left to right that don't overlap.
+    pub covered: Vec<[f64; 2]>,
+}
+
+impl GoalCoverage {
+    /// Share of the goal mouth, between 0 and 1, left open to a shot from the ball.
+    pub fn open(&self) -> f64 {
+        let [left, right] = self.posts;
+        let covered: f64 = self.covered.iter().map(|[from, to]| to - from).sum();
+        1.0 - covered / (right - left)
+    }
+}
+
+/// The ball heading at a goal fast enough to be dangerous, and how the defenders cover the goal
+/// from it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Threat {
+    /// The team defending the goal.
+    pub team: Team,
+    /// Seconds until the ball reaches the goal line.
+    pub seconds: f64,
+    /// Where the ball would cross the goal line, as `[x, z]`, following it under gravity alone.
+    pub crossing: [f64; 2],
+    /// How each car between the ball and the goal line looks from the ball, flattened onto the
+    /// goal line: where its middle lands as `[x, z]` and how big it looks there.
+    pub blockers: Vec<([f64; 2], f64)>,
+}
+
+/// The goal the ball is heading at right now, if it's going fast enough and close enough to on
+/// target to be a threat.
+pub fn threat(engine: &ReplayEngine) -> Option<Threat> {
+    let arena = engine.arena();
+    let ball = engine.ball()?;
+    let velocity = ball.linear_velocity?;
+    if arena.goal_width <= 0.0 || velocity.y.abs() < THREAT_SPEED {
+        return None;
+    }
+    let team = Team::defending(velocity.y);
+    let (seconds, [x, z]) = shots::crossing(arena, &ball.location, &velocity, team)?;
+    if x.abs() > arena.goal_width as f32 / 2.0 + THREAT_MARGIN || z > GOAL_HEIGHT + THREAT_MARGIN {
+        return None;
+    }
+
+    let goal_line = match team {
+        Team::Blue => -arena.height / 2.0,
+        Team::Orange => arena.height / 2.0,
+    };
+    let ball = ball.location;
+    let [ball_x, ball_y, ball_z] = [ball.x, ball.y, ball.z].map(f64::from);
+    let blockers = engine
+        .player_positions()
+        .filter(|(player, _)| player.team == team)
+        .filter_map(|(_, body)| {
+            let car = body.location;
+            // How much bigger everything about the car gets carried on to the goal line
+            let scale = (goal_line - ball_y) / (car.y as f64 - ball_y);
+            if !scale.is_finite() || scale < 1.0 {
+                return None;
+            }
+            let center = [
+                ball_x + (car.x as f64 - ball_x) * scale,
+                ball_z + (car.z as f64 - ball_z) * scale,
+            ];
+            Some((center, BLOCK_RADIUS * scale))
+        })
+        .collect();
+
+    Some(Threat {
+        team,
+        seconds: seconds as f64,
+        crossing: [x as f64, z as f64],
+        blockers,
+    })
+}
+
+/// Each goal's coverage from where the ball is right now. Empty without a ball, on a field with no
+/// goals, or with the ball past a goal line.
+pub fn goal_coverage(engine: &ReplayEngine) -> Vec<GoalCoverage> {
+    let arena = engine.arena();
+    let Some(ball) = engine.ball() else {
+        return vec![];
+    };
+    let ball = [ball.location.x as f64, ball.location.y as f64];
+    if arena.goal_width <= 0.0 || ball[1].abs() >= arena.height / 2.0 {
+        return vec![];
+    }
+    let posts = [-arena.goal_width / 2.0, arena.goal_width / 2.0];
+
+    // Team 0 defends the goal at negative y
+    [
+        (Team::Blue, -arena.height / 2.0),
+        (Team::Orange, arena.height / 2.0),
+    ]
+    .into_iter()
+    .map(|(team, goal_line)| {
+        let shadows: Vec<_> = engine
+            .player_positions()
+            .filter(|(player, _)| player.team == team)
+            .filter_map(|(_, body)| {
+                let car = [body.location.x as f64, body.location.y as f64];
+                shadow(ball, car, goal_line, arena.width / 2.0)
+            })
+            .collect();
+        let covered = covered(&shadows, posts);
+        GoalCoverage {
+            team,
+            goal_line,
+            posts,
+            shadows,
+            covered,
+        }
+    })
+    .collect()
+}
+
+/// The shadow `car` casts from `ball` onto the goal line at `goal_line`, or `None` if the car
+/// isn't between them or is touching the ball.
+fn shadow(ball: [f64; 2], car: [f64; 2], goal_line: f64, half_width: f64) -> Option<Shadow> {
+    let [dx, dy] = [car[0] - ball[0], car[1] - ball[1]];
+    let toward_goal = (goal_line - ball[1]).signum();
+    let distance = dx.hypot(dy);
+    let between = dy * toward_goal > 0.0 && (goal_line - car[1]) * toward_goal > 0.0;
+    if !between || distance <= BLOCK_RADIUS {
+        return None;
+    }
+
+    let angle = dy.atan2(dx);
+    let spread = (BLOCK_RADIUS / distance).asin();
+    let reach = (distance.powi(2) - BLOCK_RADIUS.powi(2)).sqrt();
+    let edges = [angle - spread, angle + spread].map(|edge| {
+        let (sin, cos) = edge.sin_cos();
+        let touch = [ball[0] + cos * reach, ball[1] + sin * reach];
+        let x = match sin * toward_goal > 0.0 {
+            true => ball[0] + cos * (goal_line - ball[1]) / sin,
+            false => cos.signum() * f64::INFINITY,
+        };
+        (touch, [x.clamp(-half_width, half_width), goal_line])
+    });
+    Some(Shadow {
+        car: edges.map(|(touch, _)| touch),
+        goal_line: edges.map(|(_, end)| end),
+    })
+}
+
+/// The parts of the goal mouth between `posts` inside any of the shadows, merged where they overlap.
+fn covered(shadows: &[Shadow], [left, right]: [f64; 2]) -> Vec<[f64; 2]> {
+    let mut ranges: Vec<_> = shadows
+        .iter()
+        .map(|shadow| {
+            let [a, b] = shadow.goal_line.map(|[x, _]| x);
+            [a.min(b).max(left), a.max(b).min(right)]
+        })
+        .filter(|[from, to]| from < to)
+        .collect();
+    ranges.sort_by(|a, b| a[0].total_cmp(&b[0]));
+
+    let mut merged: Vec<[f64; 2]> = vec![];
+    for [from, to] in ranges {
+        match merged.last_mut() {
+            Some(last) if from <= last[1] => last[1] = last[1].max(to),
+            _ => merged.push([from, to]),
+        }
+    }
+    merged
+}
@@ -0,0 +1,48 @@
+use std::io;
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+/// Everything that can go wrong reading a replay or writing out what's in it, worded so the person
+/// running the visualizer can tell what to do about it.
+#[derive(Debug, Error)]
+pub enum VisError {
+    #[error("couldn't open {}: {source}", path.display())]
+    Open { path: PathBuf, source: io::Error },
+    #[error("couldn't create {}: {source}", path.display())]
+    Create { path: PathBuf, source: io::Error },
+    #[error("couldn't write {}: {source}", path.display())]
+    Write { path: PathBuf, source: io::Error },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(
+        "replay's network data couldn't be read (was it recorded with an old game version?): {0}"
+    )]
+    NetworkData(boxcars::ParseError),
+    #[error("replay couldn't be parsed (is it a complete .replay file?): {0}")]
+    Parse(boxcars::ParseError),
+    #[error("replay has no network data (was it recorded with an old game version?)")]
+    NoNetworkData,
+    /// The replay's frames refer to an object or name past the end of its tables.
+    #[error("replay refers to {kind} {index}, which isn't in its table of {kind}s (is the file corrupt?)")]
+    MissingEntry { kind: &'static str, index: i32 },
+    #[error("no player named `{name}` in this replay, players are: {}", players.join(", "))]
+    NoSuchPlayer { name: String, players: Vec<String> },
+    #[error("couldn't write JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "parquet")]
+    #[error("couldn't write Parquet: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[cfg(feature = "parquet")]
+    #[error("couldn't build the Parquet table: {0}")]
+    Arrow(#[from] arrow_schema::ArrowError),
+}
+
+impl From<boxcars::ParseError> for VisError {
+    fn from(error: boxcars::ParseError) -> Self {
+        match error {
+            boxcars::ParseError::NetworkError(_) => VisError::NetworkData(error),
+            _ => VisError::Parse(error),
+        }
+    }
+}
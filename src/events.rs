@@ -0,0 +1,134 @@
+use boxcars::Vector3f;
+use serde::Serialize;
+
+use crate::arena::Arena;
+use crate::shots::{Shot, ShotKind};
+use crate::touches::{self, Touch};
+use crate::{Goal, ReplayEngine, Team};
+
+/// How high the ball has to be off the floor for a touch to count as an aerial. A ball sitting on
+/// the floor is at its radius of about 93 uu, and a car jumping without boost reaches about this.
+const AERIAL_HEIGHT: f32 = 500.0;
+
+/// How far a high touch has to be from the walls to count as an aerial rather than a wall play.
+const WALL_MARGIN: f32 = 300.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EventKind {
+    Goal,
+    Shot,
+    Save,
+    /// `player` is the one whose car was demolished.
+    Demolition,
+    Aerial,
+}
+
+/// Something worth marking on the seek bar.
+#[derive(Debug, Clone, Serialize)]
+pub struct Event {
+    pub kind: EventKind,
+    /// Network frame it happened on.
+    pub frame: usize,
+    pub player: String,
+    pub team: Team,
+}
+
+/// Something that happens as `ReplayEngine` steps through a replay, passed to the listeners added
+/// with `ReplayEngine::on_event`.
+#[derive(Debug, Clone, Serialize)]
+pub enum ReplayEvent {
+    /// The kickoff countdown started on this network frame.
+    Kickoff {
+        frame: usize,
+    },
+    Touch(Touch),
+    Demolition {
+        frame: usize,
+        /// The player whose car was demolished.
+        player: String,
+        team: Team,
+        /// Where the car was when it blew up.
+        location: Vector3f,
+        /// The player whose car did it, if the replay says.
+        attacker: Option<String>,
+    },
+    Goal(Goal),
+}
+
+/// Every goal, shot, save, demolition and aerial touch in the replay, in the order they happened.
+pub fn events(engine: &ReplayEngine, goals: &[Goal], shots: &[Shot]) -> Vec<Event> {
+    let goals = goals.iter().map(|goal| Event {
+        kind: EventKind::Goal,
+        frame: goal.frame,
+        player: goal.player.clone(),
+        team: goal.team,
+    });
+    let shots = shots.iter().map(|shot| Event {
+        kind: match shot.kind {
+            ShotKind::Shot => EventKind::Shot,
+            ShotKind::Save => EventKind::Save,
+        },
+        frame: shot.frame,
+        player: shot.player.clone(),
+        team: shot.team,
+    });
+    let aerials = touches::touches(engine)
+        .into_iter()
+        .filter(|touch| is_aerial(engine.arena(), touch))
+        .map(|touch| Event {
+            kind: EventKind::Aerial,
+            frame: touch.frame,
+            player: touch.player,
+            team: touch.team,
+        });
+
+    let mut events: Vec<Event> = goals
+        .chain(shots)
+        .chain(aerials)
+        .chain(demolitions(engine))
+        .collect();
+    events.sort_by_key(|event| event.frame);
+    events
+}
+
+fn is_aerial(arena: &Arena, touch: &Touch) -> bool {
+    let location = &touch.location;
+    location.z >= AERIAL_HEIGHT
+        && location.x.abs() < arena.width as f32 / 2.0 - WALL_MARGIN
+        && location.y.abs() < arena.height as f32 / 2.0 - WALL_MARGIN
+}
+
+/// Walks the whole replay, finding the network frame each kickoff countdown started on.
+pub fn kickoffs(engine: &ReplayEngine) -> Vec<usize> {
+    let mut engine = engine.clone();
+    engine.seek(0);
+
+    let mut kickoffs = vec![];
+    while engine.frame_index() < engine.frame_count() {
+        let waiting = engine.kickoff().is_some();
+        engine.step();
+        if !waiting && engine.kickoff().is_some() {
+            kickoffs.push(engine.frame_index() - 1);
+        }
+    }
+    kickoffs
+}
+
+/// Walks the whole replay, finding every car that was demolished.
+fn demolitions(engine: &ReplayEngine) -> Vec<Event> {
+    let mut engine = engine.clone();
+    engine.seek(0);
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+    }
+    engine
+        .demolition_log()
+        .iter()
+        .map(|demo| Event {
+            kind: EventKind::Demolition,
+            frame: demo.frame,
+            player: demo.victim.clone(),
+            team: demo.team,
+        })
+        .collect()
+}
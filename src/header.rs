@@ -0,0 +1,122 @@
+use boxcars::{HeaderProp, Replay};
+use serde::Serialize;
+
+use crate::Team;
+
+/// What a replay's header says about the match, as the game saved it at the end.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct MatchInfo {
+    /// The name the replay was saved under.
+    pub name: Option<String>,
+    /// The map's internal name, like `EuroStadium_Night_P`.
+    pub map: Option<String>,
+    /// When the match was played, as the game writes it, like `2023-09-05 19-36-35`.
+    pub date: Option<String>,
+    /// Like `Online`, `Private` or `Offline`.
+    pub match_type: Option<String>,
+    pub team_size: Option<i32>,
+    /// Team names, which only matches between clubs have, as (blue, orange).
+    pub team_names: (Option<String>, Option<String>),
+    /// Final score, as (blue, orange).
+    pub score: (Option<i32>, Option<i32>),
+    pub players: Vec<PlayerStats>,
+}
+
+/// One player's line on the scoreboard at the end of the match.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerStats {
+    pub name: String,
+    pub team: Team,
+    /// The player's account id for platforms whose ids fit in a number, like Steam. Others, like
+    /// Epic, are saved as 0 and left out.
+    pub online_id: Option<u64>,
+    pub score: i32,
+    pub goals: i32,
+    pub assists: i32,
+    pub saves: i32,
+    pub shots: i32,
+    pub bot: bool,
+}
+
+impl MatchInfo {
+    pub fn from_replay(replay: &Replay) -> Self {
+        let find = |name: &str| {
+            replay
+                .properties
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, prop)| prop)
+        };
+        let string = |name: &str| Some(find(name)?.as_string()?.to_string());
+        let int = |name: &str| find(name)?.as_i32();
+
+        Self {
+            name: string("ReplayName"),
+            map: string("MapName"),
+            date: string("Date"),
+            match_type: string("MatchType"),
+            team_size: int("TeamSize"),
+            team_names: (string("Team0Name"), string("Team1Name")),
+            score: (int("Team0Score"), int("Team1Score")),
+            players: find("PlayerStats")
+                .and_then(HeaderProp::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|stats| player_stats(stats))
+                .collect(),
+        }
+    }
+
+    /// What a team is called, which is its color unless the header names it.
+    pub fn team_name(&self, team: Team) -> String {
+        let name = match team {
+            Team::Blue => &self.team_names.0,
+            Team::Orange => &self.team_names.1,
+        };
+        name.clone().unwrap_or_else(|| format!("{team:?}"))
+    }
+
+    /// The final score and what kind of match it was on which map, in a line, like
+    /// `Blue 4 - 5 Orange, 3v3 Private on EuroStadium_Night_P`.
+    pub fn description(&self) -> String {
+        let mut line = format!(
+            "{} {} - {} {}",
+            self.team_name(Team::Blue),
+            self.score.0.unwrap_or(0),
+            self.score.1.unwrap_or(0),
+            self.team_name(Team::Orange)
+        );
+        if let Some(size) = self.team_size {
+            line += &format!(", {size}v{size}");
+        }
+        if let Some(match_type) = &self.match_type {
+            line += &format!(" {match_type}");
+        }
+        if let Some(map) = &self.map {
+            line += &format!(" on {map}");
+        }
+        line
+    }
+}
+
+fn player_stats(stats: &[(String, HeaderProp)]) -> Option<PlayerStats> {
+    let find = |name: &str| stats.iter().find(|(key, _)| key == name).map(|(_, v)| v);
+    // Players who left before the end aren't given every stat
+    let int = |name: &str| find(name).and_then(HeaderProp::as_i32).unwrap_or(0);
+    Some(PlayerStats {
+        name: find("Name")?.as_string()?.to_string(),
+        team: match find("Team")?.as_i32()? {
+            0 => Team::Blue,
+            _ => Team::Orange,
+        },
+        online_id: find("OnlineID")
+            .and_then(HeaderProp::as_u64)
+            .filter(|id| *id != 0),
+        score: int("Score"),
+        goals: int("Goals"),
+        assists: int("Assists"),
+        saves: int("Saves"),
+        shots: int("Shots"),
+        bot: find("bBot").and_then(HeaderProp::as_bool).unwrap_or(false),
+    })
+}
@@ -0,0 +1,237 @@
+use crate::arena::Arena;
+use crate::timeline::Timeline;
+use crate::{ReplayEngine, Team, VisError};
+
+/// Side length, in unreal units, of one heatmap cell.
+pub const CELL_SIZE: f64 = 160.0;
+
+fn grid_size(arena: &Arena) -> (usize, usize) {
+    (
+        (arena.width / CELL_SIZE).ceil() as usize,
+        (arena.height / CELL_SIZE).ceil() as usize,
+    )
+}
+
+/// Field coordinates of the center of a cell.
+pub fn cell_center(arena: &Arena, column: usize, row: usize) -> [f64; 2] {
+    [
+        (column as f64 + 0.5) * CELL_SIZE - arena.width / 2.0,
+        (row as f64 + 0.5) * CELL_SIZE - arena.height / 2.0,
+    ]
+}
+
+/// Counts, for every cell of a grid laid over the field, how many frames each team owned it.
+///
+/// A cell is owned by the team of the player closest to its center, which is the same ownership
+/// the voronoi view shows for a single frame.
+#[derive(Debug, Clone)]
+pub struct ZoneHeatmap {
+    arena: Arena,
+    columns: usize,
+    rows: usize,
+    orange: Vec<u32>,
+    blue: Vec<u32>,
+}
+
+impl Default for ZoneHeatmap {
+    fn default() -> Self {
+        Self::new(&Arena::STANDARD)
+    }
+}
+
+impl ZoneHeatmap {
+    pub fn new(arena: &Arena) -> Self {
+        let (columns, rows) = grid_size(arena);
+        Self {
+            arena: *arena,
+            columns,
+            rows,
+            orange: vec![0; columns * rows],
+            blue: vec![0; columns * rows],
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Credits every cell to the team of the nearest player in the engine's current frame.
+    pub fn accumulate(&mut self, engine: &ReplayEngine) {
+        let players: Vec<_> = engine
+            .player_positions()
+            .map(|(player, body)| (player.team, body.location.x as f64, body.location.y as f64))
+            .collect();
+        self.credit(&players);
+    }
+
+    /// The same as accumulating every frame on `timeline` before network frame `end`, without
+    /// stepping through them again.
+    pub fn from_timeline(timeline: &Timeline, end: usize) -> Self {
+        let mut heatmap = Self::new(timeline.arena());
+        for entry in timeline
+            .frames()
+            .iter()
+            .take_while(|entry| entry.frame < end)
+        {
+            let players: Vec<_> = entry
+                .cars
+                .iter()
+                .map(|car| {
+                    (
+                        car.team,
+                        car.body.location.x as f64,
+                        car.body.location.y as f64,
+                    )
+                })
+                .collect();
+            heatmap.credit(&players);
+        }
+        heatmap
+    }
+
+    /// Credits every cell to the team of whichever of `players`, as `(team, x, y)`, is nearest.
+    fn credit(&mut self, players: &[(Team, f64, f64)]) {
+        if players.is_empty() {
+            return;
+        }
+
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let [x, y] = cell_center(&self.arena, column, row);
+                let (team, _) = players
+                    .iter()
+                    .map(|(team, px, py)| (*team, (px - x).powi(2) + (py - y).powi(2)))
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .expect("players is not empty");
+
+                let index = row * self.columns + column;
+                match team {
+                    Team::Orange => self.orange[index] += 1,
+                    Team::Blue => self.blue[index] += 1,
+                }
+            }
+        }
+    }
+
+    /// Fraction of observed frames that orange owned a cell, or `None` if it was never observed.
+    pub fn orange_share(&self, column: usize, row: usize) -> Option<f64> {
+        let index = row * self.columns + column;
+        let total = self.orange[index] + self.blue[index];
+        if total == 0 {
+            return None;
+        }
+        Some(self.orange[index] as f64 / total as f64)
+    }
+}
+
+/// How often a single player's car was in each cell of a grid laid over the field.
+#[derive(Debug, Clone)]
+pub struct PositionHeatmap {
+    arena: Arena,
+    columns: usize,
+    rows: usize,
+    counts: Vec<u32>,
+    max: u32,
+}
+
+impl PositionHeatmap {
+    /// Walks the entire replay from where `engine` is, counting every frame the named player's car
+    /// spent in each cell. Names are matched case-insensitively.
+    pub fn for_player(engine: &ReplayEngine, name: &str) -> Result<Self, VisError> {
+        Self::from_timeline(&Timeline::build(engine), name)
+    }
+
+    /// The same as `for_player`, counted from a timeline that's already been built, so heatmaps for
+    /// several players don't each step through the replay again.
+    pub fn from_timeline(timeline: &Timeline, name: &str) -> Result<Self, VisError> {
+        let arena = *timeline.arena();
+        let (columns, rows) = grid_size(&arena);
+        let mut heatmap = Self {
+            arena,
+            columns,
+            rows,
+            counts: vec![0; columns * rows],
+            max: 0,
+        };
+
+        let player = timeline
+            .players()
+            .iter()
+            .position(|player| player.name.eq_ignore_ascii_case(name));
+        for entry in timeline.frames() {
+            for car in entry.cars.iter().filter(|car| Some(car.player) == player) {
+                heatmap.add(car.body.location.x as f64, car.body.location.y as f64);
+            }
+        }
+
+        if heatmap.max == 0 {
+            return Err(VisError::NoSuchPlayer {
+                name: name.to_string(),
+                players: timeline
+                    .players()
+                    .iter()
+                    .map(|player| player.name.clone())
+                    .collect(),
+            });
+        }
+        Ok(heatmap)
+    }
+
+    fn add(&mut self, x: f64, y: f64) {
+        let column = ((x + self.arena.width / 2.0) / CELL_SIZE).floor();
+        let row = ((y + self.arena.height / 2.0) / CELL_SIZE).floor();
+        // Cars in the goals are off the grid
+        if column < 0.0 || row < 0.0 || column as usize >= self.columns || row as usize >= self.rows
+        {
+            return;
+        }
+
+        let index = row as usize * self.columns + column as usize;
+        self.counts[index] += 1;
+        self.max = self.max.max(self.counts[index]);
+    }
+
+    /// The same heatmap turned half way around, as if the teams had swapped ends.
+    pub fn rotated(&self) -> Self {
+        let mut rotated = self.clone();
+        rotated.counts.reverse();
+        rotated
+    }
+
+    /// Adds the counts from another heatmap into this one, so it covers both. Heatmaps over arenas
+    /// of different sizes don't line up, so those are left out, returning `false`.
+    pub fn merge(&mut self, other: &PositionHeatmap) -> bool {
+        if (self.columns, self.rows) != (other.columns, other.rows) {
+            return false;
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+            self.max = self.max.max(*count);
+        }
+        true
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Time spent in a cell relative to the most visited cell, between 0 and 1.
+    ///
+    /// Players sit still on their kickoff spots for seconds at a time, so this is on a log scale to
+    /// keep the cells they only drove through visible.
+    pub fn density(&self, column: usize, row: usize) -> f64 {
+        if self.max == 0 {
+            return 0.0;
+        }
+        let count = self.counts[row * self.columns + column] as f64;
+        count.ln_1p() / (self.max as f64).ln_1p()
+    }
+}
@@ -0,0 +1,261 @@
+use serde::Serialize;
+
+use crate::touches::{TouchDetector, TouchUpdate};
+use crate::{ReplayEngine, Team};
+
+/// How close to the ball a car has to be by the first touch to have gone for it. Cars that hang
+/// back or cheat up are still a couple of thousand uu away when it's hit.
+const GO_DISTANCE: f32 = 1000.0;
+
+/// How long after the first touch to look at where the ball went to decide who won a kickoff.
+const OUTCOME_SECONDS: f32 = 3.0;
+
+/// How far from halfway the ball has to be to count as in one team's half. Closer than this, the
+/// kickoff goes to whoever touched it last.
+const HALFWAY_MARGIN: f32 = 500.0;
+
+/// Where a car starts a kickoff from. Every soccar map has the same five spots on each side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Spot {
+    /// Off to one side of the field, closest to the ball.
+    Diagonal,
+    /// Just off the middle of the field, halfway back.
+    OffCenter,
+    /// Straight back from the ball, in front of the goal.
+    BackCenter,
+}
+
+impl Spot {
+    /// The spot a car at `x` across the field starts from.
+    fn at(x: f32) -> Spot {
+        match x.abs() {
+            x if x > 1000.0 => Spot::Diagonal,
+            x if x > 100.0 => Spot::OffCenter,
+            _ => Spot::BackCenter,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Spot::Diagonal => "diagonal",
+            Spot::OffCenter => "off center",
+            Spot::BackCenter => "back center",
+        }
+    }
+}
+
+/// A player lined up for a kickoff.
+#[derive(Debug, Clone, Serialize)]
+pub struct KickoffPlayer {
+    pub name: String,
+    pub team: Team,
+    pub spot: Spot,
+    /// Where the car was when the countdown ended, as `[x, y]`.
+    pub location: [f32; 2],
+    /// Whether the car was right by the ball when it was first hit.
+    pub went_for_ball: bool,
+}
+
+/// The first player to hit the ball after a kickoff.
+#[derive(Debug, Clone, Serialize)]
+pub struct FirstTouch {
+    pub player: String,
+    pub team: Team,
+    /// Seconds from the end of the countdown to the touch.
+    pub seconds: f32,
+}
+
+/// One kickoff, from how the cars lined up to where the ball went.
+#[derive(Debug, Clone, Serialize)]
+pub struct Kickoff {
+    /// Network frame the countdown started on.
+    pub frame: usize,
+    /// When the countdown ended and the cars were let go.
+    pub time: f32,
+    /// Every player in the kickoff, by team and then by name. Empty if the replay ends during the
+    /// countdown.
+    pub players: Vec<KickoffPlayer>,
+    pub first_touch: Option<FirstTouch>,
+    /// Whose half the ball was in `OUTCOME_SECONDS` after the first touch, or when a goal was
+    /// scored if that was sooner. `None` while it was still near halfway.
+    pub ball_side: Option<Team>,
+    /// The team that last touched the ball by then.
+    pub possession: Option<Team>,
+    /// The team that won the kickoff: the one whose opponents' half the ball went into, or with it
+    /// still near halfway, the one with the ball.
+    pub winner: Option<Team>,
+}
+
+impl Kickoff {
+    /// Settles who won from where the ball is and who has it in the engine's current frame.
+    fn settle(&mut self, engine: &ReplayEngine) {
+        let Some(ball) = engine.ball() else {
+            return;
+        };
+        let y = ball.location.y;
+        self.ball_side = (y.abs() > HALFWAY_MARGIN).then(|| Team::defending(y));
+        self.possession = engine.possession().team();
+        self.winner = match self.ball_side {
+            Some(Team::Orange) => Some(Team::Blue),
+            Some(Team::Blue) => Some(Team::Orange),
+            None => self.possession,
+        };
+    }
+}
+
+/// Walks the whole replay, working out how each kickoff went.
+pub fn kickoffs(engine: &ReplayEngine) -> Vec<Kickoff> {
+    let mut engine = engine.clone();
+    engine.seek(0);
+
+    let mut kickoffs: Vec<Kickoff> = vec![];
+    let mut detector = TouchDetector::default();
+    // The time of the first touch of the last kickoff, until its outcome is settled
+    let mut unsettled = None;
+    while engine.frame_index() < engine.frame_count() {
+        let countdown = engine.kickoff();
+        engine.step();
+        let touch = match detector.update(&engine) {
+            Some(TouchUpdate::Touch(touch)) => Some(touch),
+            _ => None,
+        };
+
+        if let (Some(kickoff), Some(touched)) = (kickoffs.last_mut(), unsettled) {
+            let next_kickoff = countdown.is_none() && engine.kickoff().is_some();
+            if engine.time() - touched >= OUTCOME_SECONDS || engine.goal_replay() || next_kickoff {
+                kickoff.settle(&engine);
+                unsettled = None;
+            }
+        }
+
+        match (countdown, engine.kickoff()) {
+            (None, Some(_)) => kickoffs.push(Kickoff {
+                frame: engine.frame_index() - 1,
+                time: engine.time(),
+                players: vec![],
+                first_touch: None,
+                ball_side: None,
+                possession: None,
+                winner: None,
+            }),
+            (Some(countdown), Some(0)) if countdown != 0 => {
+                let Some(kickoff) = kickoffs.last_mut() else {
+                    continue;
+                };
+                kickoff.time = engine.time();
+                kickoff.players = engine
+                    .player_positions()
+                    .map(|(player, car)| KickoffPlayer {
+                        name: player.name.clone(),
+                        team: player.team,
+                        spot: Spot::at(car.location.x),
+                        location: [car.location.x, car.location.y],
+                        went_for_ball: false,
+                    })
+                    .collect();
+                kickoff
+                    .players
+                    .sort_by_key(|player| (player.team == Team::Blue, player.name.clone()));
+            }
+            _ => {}
+        }
+
+        let Some(kickoff) = kickoffs.last_mut() else {
+            continue;
+        };
+        let (Some(touch), None) = (touch, &kickoff.first_touch) else {
+            continue;
+        };
+        if kickoff.players.is_empty() {
+            continue;
+        }
+        let Some(ball) = engine.ball() else {
+            continue;
+        };
+        for (player, car) in engine.player_positions() {
+            let distance = (car.location.x - ball.location.x)
+                .hypot(car.location.y - ball.location.y)
+                .hypot(car.location.z - ball.location.z);
+            if let Some(entry) = kickoff
+                .players
+                .iter_mut()
+                .find(|entry| entry.name == player.name)
+            {
+                entry.went_for_ball = distance <= GO_DISTANCE;
+            }
+        }
+        kickoff.first_touch = Some(FirstTouch {
+            player: touch.player,
+            team: touch.team,
+            seconds: touch.time - kickoff.time,
+        });
+        unsettled = Some(touch.time);
+    }
+    if let (Some(kickoff), Some(_)) = (kickoffs.last_mut(), unsettled) {
+        kickoff.settle(&engine);
+    }
+    kickoffs
+}
+
+/// How one player did at kickoffs over a replay.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerKickoffs {
+    pub player: String,
+    pub team: Team,
+    /// Kickoffs they lined up for.
+    pub kickoffs: usize,
+    /// Kickoffs they went for the ball at.
+    pub went_for_ball: usize,
+    /// Kickoffs they hit the ball first at.
+    pub first_touches: usize,
+    /// Kickoffs their team won when they went for the ball.
+    pub won: usize,
+}
+
+impl PlayerKickoffs {
+    /// Share of the kickoffs they went for the ball at that their team won, between 0 and 1.
+    pub fn win_rate(&self) -> Option<f64> {
+        (self.went_for_ball > 0).then(|| self.won as f64 / self.went_for_ball as f64)
+    }
+}
+
+/// Each player's kickoffs added up, by team and then by name.
+pub fn player_kickoffs(kickoffs: &[Kickoff]) -> Vec<PlayerKickoffs> {
+    let mut players: Vec<PlayerKickoffs> = vec![];
+    for kickoff in kickoffs {
+        for lined_up in &kickoff.players {
+            let index = match players.iter().position(|p| p.player == lined_up.name) {
+                Some(index) => index,
+                None => {
+                    players.push(PlayerKickoffs {
+                        player: lined_up.name.clone(),
+                        team: lined_up.team,
+                        kickoffs: 0,
+                        went_for_ball: 0,
+                        first_touches: 0,
+                        won: 0,
+                    });
+                    players.len() - 1
+                }
+            };
+            let player = &mut players[index];
+            player.team = lined_up.team;
+            player.kickoffs += 1;
+            if kickoff
+                .first_touch
+                .as_ref()
+                .is_some_and(|touch| touch.player == lined_up.name)
+            {
+                player.first_touches += 1;
+            }
+            if lined_up.went_for_ball {
+                player.went_for_ball += 1;
+                if kickoff.winner == Some(lined_up.team) {
+                    player.won += 1;
+                }
+            }
+        }
+    }
+    players.sort_by_key(|player| (player.team == Team::Blue, player.player.clone()));
+    players
+}
@@ -0,0 +1,1719 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::mem;
+use std::path::Path;
+
+use boxcars::{
+    ActorId, Attribute, Frame, HeaderProp, NetworkFrames, ObjectId, RemoteId, Replay, RigidBody,
+    UniqueId, Vector3f,
+};
+use serde::Serialize;
+
+use actors::{ActorEvent, ActorTracker};
+use arena::{Arena, BoostPad, Tile, TileState};
+use boost::{BoostStats, PadState, BOOST_PER_SECOND};
+use events::ReplayEvent;
+use header::{MatchInfo, PlayerStats};
+use movement::{AirState, Movement};
+use possession::Possession;
+use thirds::{Occupancy, Third};
+use touches::{TouchDetector, TouchUpdate};
+
+pub mod actors;
+#[cfg(feature = "app")]
+pub mod app;
+pub mod arena;
+pub mod boost;
+pub mod coverage;
+mod error;
+pub mod events;
+pub mod header;
+pub mod heatmap;
+pub mod kickoffs;
+pub mod movement;
+pub mod overlay;
+#[cfg(feature = "parquet")]
+pub mod parquet_dump;
+pub mod possession;
+pub mod prediction;
+pub mod rotation;
+pub mod shots;
+pub mod situations;
+pub mod thirds;
+pub mod timeline;
+pub mod touches;
+pub mod zones;
+
+pub use error::VisError;
+
+pub const STANDARD_MAP_HEIGHT: f64 = 10280.0;
+pub const STANDARD_MAP_WIDTH: f64 = 8240.0;
+/// Depth of both goals together, which sit beyond the back walls.
+pub const STANDARD_GOAL_SIZE: f64 = 2. * 880.;
+pub const STANDARD_GOAL_WIDTH: f64 = 1786.;
+
+pub const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
+pub const GREY: [f32; 4] = [0.0, 153.0 / 256.0, 51.0 / 256.0, 1.0];
+pub const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+pub const PURPLE: [f32; 4] = [0.5, 0.0, 0.5, 1.0];
+
+pub const ORANGE: [[f32; 4]; 4] = [
+    [245.0 / 256.0, 146.0 / 256.0, 0.0, 1.0],
+    [224.0 / 256.0, 81.0 / 256.0, 0.0, 1.0],
+    [250.0 / 256.0, 40.0 / 256.0, 13.0 / 256.0, 1.0],
+    [1.0, 0.0, 0.0, 1.0],
+];
+pub const BLUE: [[f32; 4]; 4] = [
+    [0.0, 45.0 / 256.0, 245.0 / 256.0, 1.0],
+    [68.0 / 256.0, 11.0 / 256.0, 222.0 / 256.0, 1.0],
+    [0.0, 141.0 / 256.0, 224.0 / 256.0, 1.0],
+    [0.0, 0.0, 1.0, 1.0],
+];
+
+/// The shades each team's players are drawn in, handed out one per player as they join.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct TeamColors {
+    pub orange: [[f32; 4]; 4],
+    pub blue: [[f32; 4]; 4],
+}
+
+impl Default for TeamColors {
+    fn default() -> Self {
+        Self {
+            orange: ORANGE,
+            blue: BLUE,
+        }
+    }
+}
+
+impl TeamColors {
+    /// Amber and blue shades from the Okabe-Ito palette, leaving out the reds that are hard to
+    /// tell from the purple ball with red-green colorblindness.
+    pub const COLORBLIND: Self = Self {
+        orange: [
+            [230.0 / 255.0, 159.0 / 255.0, 0.0, 1.0],
+            [184.0 / 255.0, 125.0 / 255.0, 0.0, 1.0],
+            [245.0 / 255.0, 194.0 / 255.0, 107.0 / 255.0, 1.0],
+            [138.0 / 255.0, 93.0 / 255.0, 0.0, 1.0],
+        ],
+        blue: [
+            [0.0, 114.0 / 255.0, 178.0 / 255.0, 1.0],
+            [31.0 / 255.0, 78.0 / 255.0, 140.0 / 255.0, 1.0],
+            [86.0 / 255.0, 180.0 / 255.0, 233.0 / 255.0, 1.0],
+            [166.0 / 255.0, 216.0 / 255.0, 247.0 / 255.0, 1.0],
+        ],
+    };
+
+    /// The four shades of `team`'s palette.
+    pub fn palette(&self, team: Team) -> [[f32; 4]; 4] {
+        match team {
+            Team::Orange => self.orange,
+            Team::Blue => self.blue,
+        }
+    }
+
+    /// The one color standing for `team` wherever the field, the seek bar, the panels or the charts
+    /// pick out a team. Blue's is its lighter shade, which reads as well as orange's on the dark
+    /// field.
+    pub fn team(&self, team: Team) -> [f32; 4] {
+        match team {
+            Team::Orange => self.orange[0],
+            Team::Blue => self.blue[2],
+        }
+    }
+}
+
+/// Four shades of `color` for a team's players, in the same order as the built-in palettes: the
+/// color itself, darker, lighter, then darker still.
+pub fn team_shades(color: [f32; 4]) -> [[f32; 4]; 4] {
+    let mix = |toward: f32, amount: f32| {
+        let mut shade = color;
+        for channel in &mut shade[..3] {
+            *channel += (toward - *channel) * amount;
+        }
+        shade
+    };
+    [color, mix(0.0, 0.25), mix(1.0, 0.35), mix(0.0, 0.5)]
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, Serialize)]
+pub enum Team {
+    #[default]
+    Orange,
+    Blue,
+}
+
+impl Team {
+    /// How far `y` is up the field toward the goal this team attacks. Blue, team 0, defends the
+    /// goal at negative y, so `y` is flipped for orange.
+    pub fn forward(self, y: f32) -> f32 {
+        match self {
+            Team::Blue => y,
+            Team::Orange => -y,
+        }
+    }
+
+    /// The team defending the end of the field `y` points toward.
+    pub fn defending(y: f32) -> Team {
+        match Team::Blue.forward(y) < 0.0 {
+            true => Team::Blue,
+            false => Team::Orange,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PlayerDetails {
+    pub platform_id: Option<UniqueId>,
+    pub name: String,
+    pub color: [f32; 4],
+    pub car_actor_id: Option<ActorId>,
+    pub team: Team,
+}
+
+impl PlayerDetails {
+    /// The player's account id on their platform, like a Steam id or an Epic account id, once
+    /// it's replicated. Splitscreen players don't have one of their own.
+    pub fn online_id(&self) -> Option<String> {
+        match &self.platform_id.as_ref()?.remote_id {
+            RemoteId::PlayStation(id) => Some(id.online_id.to_string()),
+            RemoteId::PsyNet(id) => Some(id.online_id.to_string()),
+            RemoteId::Switch(id) => Some(id.online_id.to_string()),
+            RemoteId::Steam(id) | RemoteId::Xbox(id) | RemoteId::QQ(id) => Some(id.to_string()),
+            RemoteId::Epic(id) => Some(id.clone()),
+            RemoteId::SplitScreen(_) => None,
+        }
+    }
+
+    /// Which platform the player plays on, once their id is replicated.
+    pub fn platform(&self) -> Option<&'static str> {
+        Some(match &self.platform_id.as_ref()?.remote_id {
+            RemoteId::PlayStation(_) | RemoteId::PsyNet(_) => "PlayStation",
+            RemoteId::Switch(_) => "Switch",
+            RemoteId::Steam(_) => "Steam",
+            RemoteId::Xbox(_) => "Xbox",
+            RemoteId::QQ(_) => "QQ",
+            RemoteId::Epic(_) => "Epic",
+            RemoteId::SplitScreen(_) => "Splitscreen",
+        })
+    }
+}
+
+/// Everything computed for a single network frame, in a form that can be written out by tools.
+#[derive(Debug, Clone, Serialize)]
+pub struct FrameSummary {
+    pub frame: usize,
+    pub time: f32,
+    pub players: Vec<PlayerPosition>,
+    pub ball: Option<Vector3f>,
+    pub zone_control: Option<zones::ZoneControl>,
+    pub spread: Option<zones::TeamSpread>,
+    pub score: Score,
+    pub phase: GamePhase,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerPosition {
+    pub name: String,
+    pub team: Team,
+    pub location: Vector3f,
+    pub boost: Option<f32>,
+    pub third: Third,
+}
+
+/// How long a demolished car takes to respawn.
+pub const RESPAWN_SECONDS: f32 = 3.0;
+
+/// How far the ball can be from the center of the field while still waiting to be hit at kickoff.
+const KICKOFF_SPOT_RADIUS: f32 = 50.0;
+
+/// A car that was demolished and hasn't respawned yet.
+#[derive(Debug, Clone, Serialize)]
+pub struct Demolition {
+    /// The player whose car was demolished.
+    pub player: ActorId,
+    /// Where the car was when it blew up.
+    pub location: Vector3f,
+    /// Replay time of the demolition.
+    pub time: f32,
+}
+
+/// A demolition as it's kept in the log of every one so far.
+#[derive(Debug, Clone, Serialize)]
+pub struct DemolitionRecord {
+    /// Network frame it happened on.
+    pub frame: usize,
+    /// Replay time of the demolition.
+    pub time: f32,
+    /// The player whose car did it, if the replay says.
+    pub attacker: Option<String>,
+    /// The player whose car was demolished.
+    pub victim: String,
+    /// The demolished player's team.
+    pub team: Team,
+    /// Where the car was when it blew up.
+    pub location: Vector3f,
+}
+
+/// A goal listed in the replay header.
+#[derive(Debug, Clone, Serialize)]
+pub struct Goal {
+    /// Network frame the goal was scored on.
+    pub frame: usize,
+    pub player: String,
+    pub team: Team,
+}
+
+/// Goals each team has scored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Score {
+    pub orange: usize,
+    pub blue: usize,
+}
+
+/// What part of the game a frame is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum GamePhase {
+    /// The ball is in play before time runs out.
+    Regulation,
+    /// The ball is in play after time ran out with the score tied.
+    Overtime,
+    /// From the countdown until the ball is first hit.
+    Kickoff,
+    /// Before the first kickoff and between a goal and the next kickoff, while the goal replay shows.
+    DeadBall,
+}
+
+/// The goals listed in a replay's header, in the order they were scored.
+fn header_goals(replay: &Replay) -> Vec<Goal> {
+    let goals = replay
+        .properties
+        .iter()
+        .find_map(|(key, prop)| (key == "Goals").then(|| prop.as_array()).flatten());
+
+    goals
+        .into_iter()
+        .flatten()
+        .filter_map(|goal| {
+            let find = |name: &str| goal.iter().find(|(key, _)| key == name).map(|(_, v)| v);
+            Some(Goal {
+                frame: find("frame")?.as_i32()? as usize,
+                player: find("PlayerName")?.as_string()?.to_string(),
+                team: match find("PlayerTeam")?.as_i32()? {
+                    0 => Team::Blue,
+                    _ => Team::Orange,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Every network frame the game clock changed on, with the seconds it showed from then.
+fn clock_changes(replay: &Replay) -> Vec<(usize, i32)> {
+    let Some(seconds_id) = replay
+        .objects
+        .iter()
+        .position(|name| name == "TAGame.GameEvent_Soccar_TA:SecondsRemaining")
+    else {
+        return vec![];
+    };
+
+    let frames = network_frames(replay);
+    let mut changes: Vec<(usize, i32)> = vec![];
+    for (index, frame) in frames.iter().enumerate() {
+        for actor in &frame.updated_actors {
+            if actor.object_id.0 as usize != seconds_id {
+                continue;
+            }
+            if let Attribute::Int(seconds) = actor.attribute {
+                if changes.last().is_none_or(|&(_, last)| last != seconds) {
+                    changes.push((index, seconds));
+                }
+            }
+        }
+    }
+    changes
+}
+
+/// Reads and fully parses a replay file, including its network frames.
+pub fn parse_replay(path: &Path) -> Result<Replay, VisError> {
+    parse_replay_data(&read_replay(path)?)
+}
+
+/// Reads a replay file the way `parse_replay_data_lenient` parses it.
+pub fn parse_replay_lenient(path: &Path) -> Result<(Replay, Recovery), VisError> {
+    parse_replay_data_lenient(&read_replay(path)?)
+}
+
+fn read_replay(path: &Path) -> Result<Vec<u8>, VisError> {
+    let file = fs::File::open(path).map_err(|source| VisError::Open {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mut f = BufReader::new(file);
+
+    let mut replay_data = vec![];
+    let _read_bytes = f.read_to_end(&mut replay_data)?;
+    Ok(replay_data)
+}
+
+/// Fully parses a replay already read into memory, like one picked in a browser.
+pub fn parse_replay_data(data: &[u8]) -> Result<Replay, VisError> {
+    let replay = boxcars::ParserBuilder::new(data)
+        .always_check_crc()
+        .must_parse_network_data()
+        .parse()?;
+
+    if replay.network_frames.is_none() {
+        return Err(VisError::NoNetworkData);
+    }
+    Ok(replay)
+}
+
+/// How much of a damaged replay `parse_replay_data_lenient` got back.
+#[derive(Debug, Clone, Copy)]
+pub struct Recovery {
+    /// Network frames that could be read.
+    pub frames: usize,
+    /// Network frames the replay's header says it has, if it says.
+    pub expected: Option<usize>,
+}
+
+impl Recovery {
+    /// Whether every frame the header promised was read.
+    pub fn complete(&self) -> bool {
+        self.expected.is_none_or(|expected| self.frames >= expected)
+    }
+}
+
+/// Parses a replay without checking its CRC, keeping the network frames read before the first one
+/// that couldn't be, so a slightly damaged replay can still be played up to where it goes wrong.
+pub fn parse_replay_data_lenient(data: &[u8]) -> Result<(Replay, Recovery), VisError> {
+    let parsed = boxcars::ParserBuilder::new(data)
+        .never_check_crc()
+        .must_parse_network_data()
+        .parse();
+    let replay = match parsed {
+        Ok(replay) => replay,
+        Err(boxcars::ParseError::NetworkError(error)) => {
+            // The frames decoded so far come back with the error, but the rest of the replay doesn't
+            let boxcars::NetworkError::FrameError(_, context) = *error else {
+                return Err(boxcars::ParseError::NetworkError(error).into());
+            };
+            let mut replay = boxcars::ParserBuilder::new(data)
+                .never_check_crc()
+                .never_parse_network_data()
+                .parse()?;
+            replay.network_frames = Some(NetworkFrames {
+                frames: context.frames,
+            });
+            replay
+        }
+        Err(error) => return Err(error.into()),
+    };
+
+    let frames = network_frames(&replay).len();
+    if frames == 0 {
+        return Err(VisError::NoNetworkData);
+    }
+    let expected = replay
+        .properties
+        .iter()
+        .find_map(|(key, prop)| match (key.as_str(), prop) {
+            ("NumFrames", HeaderProp::Int(frames)) => Some(*frames as usize),
+            _ => None,
+        });
+    Ok((replay, Recovery { frames, expected }))
+}
+
+/// A replay's network frames, or none if they weren't parsed.
+fn network_frames(replay: &Replay) -> &[Frame] {
+    replay
+        .network_frames
+        .as_ref()
+        .map_or(&[], |network| &network.frames)
+}
+
+/// Name of an object a replay's frames refer to by its index.
+fn object_name(replay: &Replay, object_id: ObjectId) -> Result<String, VisError> {
+    replay
+        .objects
+        .get(object_id.0 as usize)
+        .cloned()
+        .ok_or(VisError::MissingEntry {
+            kind: "object",
+            index: object_id.0,
+        })
+}
+
+/// Name of an actor, or "Unknown" for actors that weren't given one.
+fn actor_name(replay: &Replay, name_id: Option<i32>) -> Result<String, VisError> {
+    let Some(name_id) = name_id else {
+        return Ok("Unknown".to_string());
+    };
+    replay
+        .names
+        .get(name_id as usize)
+        .cloned()
+        .ok_or(VisError::MissingEntry {
+            kind: "name",
+            index: name_id,
+        })
+}
+
+/// Where the cars and ball are drawn between two network frames, moved on from their last rigid
+/// body along its velocity.
+#[derive(Debug, Clone)]
+struct Interpolation {
+    cars: HashMap<ActorId, Option<RigidBody>>,
+    ball: Option<RigidBody>,
+}
+
+type Listener = Box<dyn FnMut(&ReplayEvent) + Send>;
+
+/// Closures added with `ReplayEngine::on_event`. A clone of an engine starts without any, so
+/// stepping one to count something up doesn't call them again.
+#[derive(Default)]
+struct Listeners(Vec<Listener>);
+
+impl Clone for Listeners {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// A car's boost component, which replicates separately from the car it belongs to.
+#[derive(Debug, Default, Clone)]
+struct BoostComponent {
+    car: Option<ActorId>,
+    /// Boost left out of 255, once it's replicated. The replay only sends it when the car starts or
+    /// stops boosting or picks up a pad, so it's run down here while the car is boosting.
+    amount: Option<f32>,
+    active: bool,
+    /// `amount` as of the end of the last frame, to tell how much was collected or used since.
+    counted: Option<f32>,
+}
+
+/// A car's jump, double jump or dodge, which is active while the car is using it.
+#[derive(Debug, Clone, Default)]
+struct JumpComponent {
+    /// Which of the three it is, in the order jump, double jump, dodge.
+    kind: usize,
+    car: Option<ActorId>,
+    active: bool,
+}
+
+/// Everything `ReplayEngine::step` changes, kept together so seeking backwards can save it as a
+/// snapshot and resume from one.
+#[derive(Clone)]
+struct FrameState {
+    frame_index: usize,
+    time: f32,
+
+    actors: ActorTracker,
+    boost_components: HashMap<ActorId, BoostComponent>,
+    jump_components: HashMap<ActorId, JumpComponent>,
+    // Cars holding their handbrake
+    handbrakes: HashSet<ActorId>,
+    tiles: HashMap<ActorId, Tile>,
+    demolitions: Vec<Demolition>,
+    demolition_log: Vec<DemolitionRecord>,
+    kickoff: Option<i32>,
+    goal_replay: bool,
+    kicked_off: bool,
+    overtime: bool,
+    possession: Possession,
+    occupancy: Occupancy,
+    movement: Movement,
+    pad_actors: HashMap<ActorId, ObjectId>,
+    pads: HashMap<ObjectId, PadState>,
+    boost_stats: BoostStats,
+    touch_detector: TouchDetector,
+}
+
+/// Walks a replay's network frames one at a time, tracking where every player's car and the ball are.
+#[derive(Clone)]
+pub struct ReplayEngine {
+    replay: Replay,
+    arena: Arena,
+    // A labs map with no arena of its own, played out on a standard pitch instead
+    unknown_map: Option<String>,
+    cells: RefCell<zones::CellCache>,
+    goals: Vec<Goal>,
+    clock: Vec<(usize, i32)>,
+    skip_goal_replays: bool,
+    team_colors: TeamColors,
+    state: FrameState,
+
+    // Events from the frames being stepped through, passed to `listeners` once `step` is done
+    pending_events: Vec<ReplayEvent>,
+    listeners: Listeners,
+    // The header's scoreboard, to tell players' teams from before the replay gets around to it
+    roster: Vec<PlayerStats>,
+    // Positions to draw in place of the last frame's, until the next frame is processed
+    interpolation: Option<Interpolation>,
+
+    // Object IDs
+    boost_component_object_id: Option<ObjectId>,
+    // The jump, double jump and dodge components, in that order
+    jump_component_object_ids: [Option<ObjectId>; 3],
+    component_vehicle_object_id: Option<ObjectId>,
+    boost_amount_object_id: Option<ObjectId>,
+    component_active_object_id: Option<ObjectId>,
+    countdown_object_id: Option<ObjectId>,
+    ball_hit_object_id: Option<ObjectId>,
+    scored_on_object_id: Option<ObjectId>,
+    overtime_object_id: Option<ObjectId>,
+    hit_team_object_id: Option<ObjectId>,
+    handbrake_object_id: Option<ObjectId>,
+    // Every Dropshot tile is an object of its own
+    tile_object_ids: HashSet<ObjectId>,
+    pad_object_ids: HashSet<ObjectId>,
+
+    // Frames a snapshot is taken on, and the snapshots taken so far ordered by frame
+    keyframes: Vec<usize>,
+    snapshots: Vec<FrameState>,
+}
+
+impl ReplayEngine {
+    pub fn new(replay: Replay) -> Self {
+        let map = replay
+            .properties
+            .iter()
+            .find_map(|(key, prop)| (key == "MapName").then(|| prop.as_string()).flatten());
+        let (arena, unknown_map) = match map {
+            Some(map) => match Arena::for_map(map) {
+                Some(arena) => (arena, None),
+                None => (Arena::STANDARD, Some(map.to_string())),
+            },
+            None => (Arena::STANDARD, None),
+        };
+
+        let goals = header_goals(&replay);
+        let clock = clock_changes(&replay);
+        let roster = MatchInfo::from_replay(&replay).players;
+        let actors = ActorTracker::new(&replay.objects);
+
+        let mut this = Self {
+            replay,
+            arena,
+            unknown_map,
+            cells: Default::default(),
+            goals,
+            clock,
+            skip_goal_replays: false,
+            team_colors: TeamColors::default(),
+            state: FrameState {
+                frame_index: 0,
+                time: 0.0,
+
+                actors,
+                boost_components: Default::default(),
+                jump_components: Default::default(),
+                handbrakes: Default::default(),
+                tiles: Default::default(),
+                demolitions: vec![],
+                demolition_log: vec![],
+                kickoff: None,
+                goal_replay: false,
+                kicked_off: false,
+                overtime: false,
+                possession: Default::default(),
+                occupancy: Default::default(),
+                movement: Default::default(),
+                pad_actors: Default::default(),
+                pads: Default::default(),
+                boost_stats: Default::default(),
+                touch_detector: Default::default(),
+            },
+
+            pending_events: vec![],
+            listeners: Default::default(),
+            roster,
+            interpolation: None,
+
+            boost_component_object_id: None,
+            jump_component_object_ids: [None; 3],
+            component_vehicle_object_id: None,
+            boost_amount_object_id: None,
+            component_active_object_id: None,
+            countdown_object_id: None,
+            ball_hit_object_id: None,
+            scored_on_object_id: None,
+            overtime_object_id: None,
+            hit_team_object_id: None,
+            handbrake_object_id: None,
+            tile_object_ids: Default::default(),
+            pad_object_ids: Default::default(),
+
+            keyframes: vec![],
+            snapshots: vec![],
+        };
+        this.prepare();
+        this.snapshots.push(this.state.clone());
+        this
+    }
+
+    fn prepare(&mut self) {
+        for (index, object_name) in self.replay.objects.iter().enumerate() {
+            let id = Some(ObjectId(index as i32));
+            match object_name.as_str() {
+                "Archetypes.CarComponents.CarComponent_Boost" => {
+                    self.boost_component_object_id = id;
+                }
+                "Archetypes.CarComponents.CarComponent_Jump" => {
+                    self.jump_component_object_ids[0] = id
+                }
+                "Archetypes.CarComponents.CarComponent_DoubleJump" => {
+                    self.jump_component_object_ids[1] = id
+                }
+                "Archetypes.CarComponents.CarComponent_Dodge" => {
+                    self.jump_component_object_ids[2] = id
+                }
+                "TAGame.CarComponent_TA:Vehicle" => self.component_vehicle_object_id = id,
+                "TAGame.CarComponent_Boost_TA:ReplicatedBoostAmount" => {
+                    self.boost_amount_object_id = id;
+                }
+                "TAGame.CarComponent_TA:ReplicatedActive" => self.component_active_object_id = id,
+                "TAGame.GameEvent_TA:ReplicatedRoundCountDownNumber" => {
+                    self.countdown_object_id = id;
+                }
+                "TAGame.GameEvent_Soccar_TA:bBallHasBeenHit" => self.ball_hit_object_id = id,
+                "TAGame.GameEvent_Soccar_TA:ReplicatedScoredOnTeam" => {
+                    self.scored_on_object_id = id;
+                }
+                "TAGame.GameEvent_Soccar_TA:bOverTime" => self.overtime_object_id = id,
+                "TAGame.Ball_TA:HitTeamNum" => self.hit_team_object_id = id,
+                "TAGame.Vehicle_TA:bReplicatedHandbrake" => self.handbrake_object_id = id,
+                name if name.contains("TheWorld:PersistentLevel.BreakOutActor_Platform_TA") => {
+                    self.tile_object_ids.insert(ObjectId(index as i32));
+                }
+                name if name.contains("TheWorld:PersistentLevel.VehiclePickup_Boost_TA") => {
+                    self.pad_object_ids.insert(ObjectId(index as i32));
+                }
+                _ => {}
+            }
+        }
+
+        self.keyframes = self
+            .replay
+            .keyframes
+            .iter()
+            .map(|keyframe| keyframe.frame as usize)
+            .collect();
+        self.keyframes.sort_unstable();
+    }
+
+    /// Picks a color for a player joining `team`, avoiding colors held by the team's other players
+    /// and then colors being kept for players who left. Teams bigger than the palette share colors.
+    fn free_color(&self, team: Team, player: ActorId) -> [f32; 4] {
+        let palette = self.team_colors.palette(team);
+        let taken: Vec<_> = self
+            .state
+            .actors
+            .players()
+            .iter()
+            .filter(|(actor_id, _)| **actor_id != player)
+            .map(|(_, details)| details.color)
+            .collect();
+        let kept: Vec<_> = self
+            .state
+            .actors
+            .departed()
+            .map(|details| details.color)
+            .collect();
+
+        palette
+            .iter()
+            .find(|color| !taken.contains(color) && !kept.contains(color))
+            .or_else(|| palette.iter().find(|color| !taken.contains(color)))
+            .copied()
+            .unwrap_or(palette[taken.len() % palette.len()])
+    }
+
+    /// The name and team the header's scoreboard gives a player who isn't on a team yet, matched by
+    /// their name or, before that's replicated, their platform id.
+    fn roster_entry(&self, player: ActorId) -> Option<(String, Team)> {
+        let details = self
+            .state
+            .actors
+            .players()
+            .get(&player)
+            .filter(|details| details.color == PURPLE)?;
+        let online_id = details.online_id();
+        let stats = self.roster.iter().find(|stats| {
+            stats.name == details.name
+                || (stats.online_id.is_some()
+                    && stats.online_id.map(|id| id.to_string()) == online_id)
+        })?;
+        Some((stats.name.clone(), stats.team))
+    }
+
+    /// Seconds of game time from the last processed network frame to the next one, or `None` once
+    /// every frame has been. The game doesn't send frames at an even rate, so playback goes by these
+    /// rather than `record_fps`.
+    pub fn next_frame_delta(&self) -> Option<f32> {
+        network_frames(&self.replay)
+            .get(self.state.frame_index)
+            .map(|frame| frame.delta)
+    }
+
+    /// Moves the cars and ball on by `seconds` of game time along their velocities, up to the next
+    /// network frame, so playback looks smooth however far apart the frames are. Only what's drawn
+    /// moves: the next `step` or `seek` carries on from the frame as it was.
+    pub fn interpolate(&mut self, seconds: f32) {
+        let Some(delta) = self.next_frame_delta() else {
+            self.interpolation = None;
+            return;
+        };
+        let seconds = seconds.clamp(0.0, delta);
+        let advance = |body: &RigidBody| {
+            let mut body = *body;
+            if let Some(velocity) = body.linear_velocity {
+                body.location.x += velocity.x * seconds;
+                body.location.y += velocity.y * seconds;
+                body.location.z += velocity.z * seconds;
+            }
+            body
+        };
+        self.interpolation = Some(Interpolation {
+            cars: self
+                .state
+                .actors
+                .cars()
+                .iter()
+                .map(|(id, body)| (*id, body.as_ref().map(advance)))
+                .collect(),
+            ball: self.state.actors.ball().as_ref().map(advance),
+        });
+    }
+
+    /// Makes `frame` the next frame `step` will process, with every actor where it was at that
+    /// point in the replay.
+    ///
+    /// Seeking backwards restores the closest snapshot before `frame` and steps forward from there,
+    /// so it only ever replays the frames since the last keyframe.
+    pub fn seek(&mut self, frame: usize) {
+        self.interpolation = None;
+        let frame = frame.min(self.frame_count());
+        if frame < self.state.frame_index {
+            let nearest = self
+                .snapshots
+                .partition_point(|snapshot| snapshot.frame_index <= frame);
+            self.state = self.snapshots[nearest - 1].clone();
+        }
+
+        while self.state.frame_index < frame {
+            self.step();
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Index of the next network frame `step` will process.
+    pub fn frame_index(&self) -> usize {
+        self.state.frame_index
+    }
+
+    pub fn frame_count(&self) -> usize {
+        network_frames(&self.replay).len()
+    }
+
+    /// Network frames recorded per second, read from the replay header.
+    pub fn record_fps(&self) -> f32 {
+        self.replay
+            .properties
+            .iter()
+            .find_map(|(key, prop)| match (key.as_str(), prop) {
+                ("RecordFPS", HeaderProp::Float(fps)) => Some(*fps),
+                _ => None,
+            })
+            .unwrap_or(30.0)
+    }
+
+    /// Every goal in the replay, in the order they were scored.
+    pub fn goals(&self) -> Vec<Goal> {
+        self.goals.clone()
+    }
+
+    /// The match as the replay's header describes it, with the final scoreboard.
+    pub fn match_info(&self) -> MatchInfo {
+        MatchInfo::from_replay(&self.replay)
+    }
+
+    /// The network frame where the game clock shows `seconds` left in regulation, or `None` if it
+    /// never gets that low or the replay has no clock. The clock only ticks in whole seconds, so a
+    /// fraction of a second is counted on from the tick with frame times.
+    pub fn clock_frame(&self, seconds: f32) -> Option<usize> {
+        let shown = seconds.ceil() as i32;
+        let tick = self.clock.iter().position(|&(_, clock)| clock <= shown)?;
+        let (frame, clock) = self.clock[tick];
+        if clock < shown {
+            return Some(frame);
+        }
+
+        // The clock shows the same second until the next tick, which caps how far to count on
+        let frames = &network_frames(&self.replay);
+        let next_tick = self
+            .clock
+            .get(tick + 1)
+            .map_or(frames.len(), |&(frame, _)| frame);
+        let target = frames[frame].time + (shown as f32 - seconds);
+        Some(
+            (frame..next_tick)
+                .find(|&i| frames[i].time >= target)
+                .unwrap_or(next_tick.saturating_sub(1)),
+        )
+    }
+
+    /// Seconds the game clock shows at network frame `frame`, or `None` if the replay has no clock.
+    pub fn clock_at(&self, frame: usize) -> Option<i32> {
+        let ticks = self.clock.partition_point(|&(tick, _)| tick <= frame);
+        Some(self.clock[ticks.checked_sub(1)?].1)
+    }
+
+    /// Goals each team scored before the frame `step` last processed.
+    pub fn score(&self) -> Score {
+        let frame = self.state.frame_index.saturating_sub(1);
+        let mut score = Score::default();
+        for goal in self.goals.iter().filter(|goal| goal.frame <= frame) {
+            match goal.team {
+                Team::Orange => score.orange += 1,
+                Team::Blue => score.blue += 1,
+            }
+        }
+        score
+    }
+
+    /// What part of the game the frame `step` last processed is in.
+    pub fn phase(&self) -> GamePhase {
+        if self.state.kickoff.is_some() {
+            GamePhase::Kickoff
+        } else if self.state.goal_replay || !self.state.kicked_off {
+            GamePhase::DeadBall
+        } else if self.state.overtime {
+            GamePhase::Overtime
+        } else {
+            GamePhase::Regulation
+        }
+    }
+
+    /// The arena this replay was played in.
+    pub fn arena(&self) -> &Arena {
+        &self.arena
+    }
+
+    /// The replay's map, if it's a labs map with no arena known for it. `arena` is then a standard
+    /// pitch, which the field drawn may not match.
+    pub fn unknown_map(&self) -> Option<&str> {
+        self.unknown_map.as_deref()
+    }
+
+    pub(crate) fn cell_cache(&self) -> &RefCell<zones::CellCache> {
+        &self.cells
+    }
+
+    /// Replay time, in seconds, of the last processed frame.
+    pub fn time(&self) -> f32 {
+        self.state.time
+    }
+
+    /// Seconds between the first network frame and the last processed frame.
+    pub fn elapsed(&self) -> f32 {
+        let frames = &network_frames(&self.replay);
+        frames
+            .first()
+            .map_or(0.0, |first| self.state.time - first.time)
+    }
+
+    pub fn players(&self) -> &HashMap<ActorId, PlayerDetails> {
+        self.state.actors.players()
+    }
+
+    /// Every car by its actor id, with its rigid body once it's replicated. Between frames, these
+    /// are where `interpolate` moved them to.
+    pub fn cars(&self) -> &HashMap<ActorId, Option<RigidBody>> {
+        self.interpolation
+            .as_ref()
+            .map_or(self.state.actors.cars(), |interpolation| {
+                &interpolation.cars
+            })
+    }
+
+    /// The floor tiles of a Dropshot arena, empty on every other arena.
+    pub fn tiles(&self) -> impl Iterator<Item = &Tile> {
+        self.state.tiles.values()
+    }
+
+    /// Cars that have been demolished in the last few seconds and are waiting to respawn.
+    pub fn demolitions(&self) -> &[Demolition] {
+        &self.state.demolitions
+    }
+
+    /// Every demolition so far, oldest first, leaving out the ones shown again in goal replays.
+    pub fn demolition_log(&self) -> &[DemolitionRecord] {
+        &self.state.demolition_log
+    }
+
+    /// The kickoff countdown while players wait for it, then 0 until the ball is first hit, or
+    /// `None` while the ball is in play.
+    pub fn kickoff(&self) -> Option<i32> {
+        self.state.kickoff
+    }
+
+    /// Whether a goal was just scored and the game is showing its replay before the next kickoff.
+    pub fn goal_replay(&self) -> bool {
+        self.state.goal_replay
+    }
+
+    /// Which team has the ball, and how long each team has had it up to this frame.
+    pub fn possession(&self) -> &Possession {
+        &self.state.possession
+    }
+
+    /// How long each player has spent in each third of the field during live play so far.
+    pub fn occupancy(&self) -> &Occupancy {
+        &self.state.occupancy
+    }
+
+    /// How far and how fast each player has driven during live play so far.
+    pub fn movement(&self) -> &Movement {
+        &self.state.movement
+    }
+
+    /// Every boost pad in the arena, paired with whether it's there to be picked up right now.
+    pub fn boost_pads(&self) -> Vec<(BoostPad, bool)> {
+        self.arena
+            .boost_pads
+            .iter()
+            .enumerate()
+            .map(|(index, pad)| {
+                let taken = self
+                    .state
+                    .pads
+                    .values()
+                    .any(|state| state.pad == Some(index) && state.taken);
+                (*pad, !taken)
+            })
+            .collect()
+    }
+
+    /// Boost pads each player has picked up so far. Pickups are only counted on arenas whose pad
+    /// layout is known, since that's how they're told apart.
+    pub fn boost_stats(&self) -> &BoostStats {
+        &self.state.boost_stats
+    }
+
+    pub fn ball(&self) -> Option<RigidBody> {
+        self.interpolation
+            .as_ref()
+            .map_or(self.state.actors.ball(), |interpolation| interpolation.ball)
+    }
+
+    /// How full a car's boost tank is, between 0 and 1, once its boost component has replicated.
+    pub fn boost(&self, car: ActorId) -> Option<f32> {
+        self.state
+            .boost_components
+            .values()
+            .find(|component| component.car == Some(car))
+            .and_then(|component| component.amount)
+            .map(|amount| amount / 255.0)
+    }
+
+    /// Whether a car is driving on something or in the air right now, once its position is known.
+    pub fn air_state(&self, car: ActorId) -> Option<AirState> {
+        let body = self.cars().get(&car)?.as_ref()?;
+        let mut using = [false; 3];
+        for component in self
+            .state
+            .jump_components
+            .values()
+            .filter(|component| component.car == Some(car))
+        {
+            using[component.kind] |= component.active;
+        }
+        Some(AirState::new(&self.arena, &body.location, using))
+    }
+
+    /// Whether a car is powersliding: holding its handbrake while driving on something.
+    pub fn powersliding(&self, car: ActorId) -> bool {
+        self.state.handbrakes.contains(&car) && self.air_state(car) == Some(AirState::Grounded)
+    }
+
+    /// Every player whose car currently has a known position, paired with that car's rigid body.
+    pub fn player_positions(&self) -> impl Iterator<Item = (&PlayerDetails, &RigidBody)> {
+        let cars = self.cars();
+        self.state
+            .actors
+            .players()
+            .values()
+            .filter_map(move |player| {
+                let car = player.car_actor_id?;
+                match cars.get(&car) {
+                    Some(Some(r)) => Some((player, r)),
+                    _ => None,
+                }
+            })
+    }
+
+    /// Captures the positions and zone control of the last processed frame.
+    pub fn summary(&self) -> FrameSummary {
+        FrameSummary {
+            frame: self.state.frame_index.saturating_sub(1),
+            time: self.state.time,
+            players: self
+                .player_positions()
+                .map(|(player, body)| PlayerPosition {
+                    name: player.name.clone(),
+                    team: player.team,
+                    location: body.location,
+                    boost: player.car_actor_id.and_then(|car| self.boost(car)),
+                    third: Third::at(&self.arena, player.team, body.location.y),
+                })
+                .collect(),
+            ball: self.ball().map(|ball| ball.location),
+            zone_control: zones::zone_control(self),
+            spread: zones::team_spread(self),
+            score: self.score(),
+            phase: self.phase(),
+        }
+    }
+
+    pub fn move_frame(&mut self, frame: i32) {
+        if frame > 0 {
+            for _ in 0..frame {
+                self.step();
+            }
+            return;
+        }
+        let total_frames = self.frame_count() as i64;
+        if frame == 0 || total_frames == 0 {
+            return;
+        }
+        // Going back past the start wraps around to the end, however many times over it goes
+        let target = (self.state.frame_index as i64 + frame as i64).rem_euclid(total_frames);
+        self.seek(target as usize);
+    }
+
+    /// Has `step` pass straight through the frames between a goal and the next kickoff, so the goal
+    /// replay doesn't show up in playback or anything counted from it.
+    pub fn skip_goal_replays(&mut self, skip: bool) {
+        self.skip_goal_replays = skip;
+    }
+
+    /// Draws players in these shades instead of the game's own. Players already in the game keep
+    /// their colors, so set this before stepping.
+    pub fn set_team_colors(&mut self, colors: TeamColors) {
+        self.team_colors = colors;
+    }
+
+    pub fn team_colors(&self) -> &TeamColors {
+        &self.team_colors
+    }
+
+    /// Calls `listener` with every kickoff, touch, demolition and goal in the frames `step` processes
+    /// from now on, including the ones `seek` steps through on the way to a frame. Goal replays show
+    /// touches and demolitions again, unless they're skipped with `skip_goal_replays`.
+    pub fn on_event(&mut self, listener: impl FnMut(&ReplayEvent) + Send + 'static) {
+        self.listeners.0.push(Box::new(listener));
+    }
+
+    /// Processes the next network frame, wrapping back to the start once the replay ends.
+    pub fn step(&mut self) {
+        self.process_frame();
+        while self.skip_goal_replays
+            && self.state.goal_replay
+            && self.state.frame_index < self.frame_count()
+        {
+            self.process_frame();
+        }
+
+        let mut detector = mem::take(&mut self.state.touch_detector);
+        if let Some(TouchUpdate::Touch(touch)) = detector.update(self) {
+            self.pending_events.push(ReplayEvent::Touch(touch));
+        }
+        self.state.touch_detector = detector;
+
+        for event in self.pending_events.drain(..) {
+            for listener in &mut self.listeners.0 {
+                listener(&event);
+            }
+        }
+    }
+
+    fn process_frame(&mut self) {
+        self.interpolation = None;
+        if self.state.frame_index >= self.frame_count() {
+            self.state = self.snapshots[0].clone();
+        }
+        if self
+            .keyframes
+            .binary_search(&self.state.frame_index)
+            .is_ok()
+        {
+            let position = self
+                .snapshots
+                .partition_point(|snapshot| snapshot.frame_index < self.state.frame_index);
+            if self.snapshots.get(position).map(|s| s.frame_index) != Some(self.state.frame_index) {
+                self.snapshots.insert(position, self.state.clone());
+            }
+        }
+
+        let frames = &network_frames(&self.replay);
+        let frame = &frames[self.state.frame_index];
+        self.state.time = frame.time;
+
+        for goal in self
+            .goals
+            .iter()
+            .filter(|goal| goal.frame == self.state.frame_index)
+        {
+            self.pending_events.push(ReplayEvent::Goal(goal.clone()));
+        }
+
+        for actor in &frame.new_actors {
+            // Players, cars, teams and the ball
+            self.state.actors.apply_new(actor);
+
+            // When a car's boost component is created
+            if self.boost_component_object_id == Some(actor.object_id) {
+                self.state
+                    .boost_components
+                    .insert(actor.actor_id, BoostComponent::default());
+            }
+            // When a car's jump, double jump or dodge component is created
+            if let Some(kind) = self
+                .jump_component_object_ids
+                .iter()
+                .position(|id| *id == Some(actor.object_id))
+            {
+                self.state.jump_components.insert(
+                    actor.actor_id,
+                    JumpComponent {
+                        kind,
+                        ..Default::default()
+                    },
+                );
+            }
+
+            // When a Dropshot tile is created, which happens once at the start of the match
+            let tile = actor
+                .initial_trajectory
+                .location
+                .filter(|_| self.tile_object_ids.contains(&actor.object_id));
+            if let Some(location) = tile {
+                self.state.tiles.insert(
+                    actor.actor_id,
+                    Tile {
+                        location: [location.x as f64, location.y as f64],
+                        state: TileState::Intact,
+                    },
+                );
+            }
+
+            // When a boost pad is created, which happens again every so often for the same pad
+            if self.pad_object_ids.contains(&actor.object_id) {
+                self.state
+                    .pad_actors
+                    .insert(actor.actor_id, actor.object_id);
+            }
+        }
+
+        // Boosting cars burn through their tank until the replay says otherwise
+        for component in self.state.boost_components.values_mut() {
+            if component.active {
+                if let Some(amount) = &mut component.amount {
+                    *amount = (*amount - BOOST_PER_SECOND * frame.delta).max(0.0);
+                }
+            }
+        }
+
+        for actor in &frame.updated_actors {
+            match self.state.actors.apply_update(actor) {
+                // Players keep their color if they switch teams
+                Some(ActorEvent::TeamChanged { player, team }) => {
+                    let color = self.free_color(team, player);
+                    if let Some(details) = self
+                        .state
+                        .actors
+                        .player_mut(player)
+                        .filter(|details| details.color == PURPLE)
+                    {
+                        details.color = color;
+                    }
+                }
+                // A demolished player is back once their new car shows up
+                Some(ActorEvent::CarAssigned { player, .. }) => {
+                    self.state.demolitions.retain(|demo| demo.player != player)
+                }
+                // Remember where the car was until its player respawns
+                Some(ActorEvent::Demolition {
+                    player,
+                    location,
+                    attacker,
+                    ..
+                }) => {
+                    let players = self.state.actors.players();
+                    if let Some(details) = players.get(&player) {
+                        let attacker = attacker
+                            .and_then(|attacker| players.get(&attacker))
+                            .map(|attacker| attacker.name.clone());
+                        self.pending_events.push(ReplayEvent::Demolition {
+                            frame: self.state.frame_index,
+                            player: details.name.clone(),
+                            team: details.team,
+                            location,
+                            attacker: attacker.clone(),
+                        });
+                        if !self.state.goal_replay {
+                            self.state.demolition_log.push(DemolitionRecord {
+                                frame: self.state.frame_index,
+                                time: self.state.time,
+                                attacker,
+                                victim: details.name.clone(),
+                                team: details.team,
+                                location,
+                            });
+                        }
+                    }
+                    self.state.demolitions.push(Demolition {
+                        player,
+                        location,
+                        time: self.state.time,
+                    });
+                }
+                _ => {}
+            }
+
+            match actor.object_id {
+                // When a car component is attached to a car
+                object_id if self.component_vehicle_object_id == Some(object_id) => {
+                    if let Attribute::ActiveActor(car) = &actor.attribute {
+                        if let Some(component) =
+                            self.state.boost_components.get_mut(&actor.actor_id)
+                        {
+                            component.car = Some(car.actor);
+                        }
+                        if let Some(component) = self.state.jump_components.get_mut(&actor.actor_id)
+                        {
+                            component.car = Some(car.actor);
+                        }
+                    }
+                }
+                // When a car's boost goes up or down
+                object_id if self.boost_amount_object_id == Some(object_id) => {
+                    if let Some(component) = self.state.boost_components.get_mut(&actor.actor_id) {
+                        if let Attribute::Byte(amount) = actor.attribute {
+                            component.amount = Some(amount as f32);
+                        }
+                    }
+                }
+                // When a car starts or stops boosting, jumping or dodging, which counts up and is
+                // odd while it's on
+                object_id if self.component_active_object_id == Some(object_id) => {
+                    if let Attribute::Byte(active) = actor.attribute {
+                        if let Some(component) =
+                            self.state.boost_components.get_mut(&actor.actor_id)
+                        {
+                            component.active = active % 2 == 1;
+                        }
+                        if let Some(component) = self.state.jump_components.get_mut(&actor.actor_id)
+                        {
+                            component.active = active % 2 == 1;
+                        }
+                    }
+                }
+                // When the countdown before a kickoff ticks down
+                object_id if self.countdown_object_id == Some(object_id) => {
+                    if let Attribute::Int(countdown) = actor.attribute {
+                        if countdown > 0 || self.state.kickoff.is_some() {
+                            if self.state.kickoff.is_none() {
+                                self.state.possession.reset();
+                                self.pending_events.push(ReplayEvent::Kickoff {
+                                    frame: self.state.frame_index,
+                                });
+                            }
+                            self.state.kickoff = Some(countdown);
+                            self.state.kicked_off = true;
+                            self.state.goal_replay = false;
+                        }
+                    }
+                }
+                // When a different team touches the ball
+                object_id if self.hit_team_object_id == Some(object_id) => {
+                    if let Attribute::Byte(team) = actor.attribute {
+                        self.state.possession.touch(if team == 0 {
+                            Team::Blue
+                        } else {
+                            Team::Orange
+                        });
+                    }
+                }
+                // When a car's handbrake is pulled or let go
+                object_id if self.handbrake_object_id == Some(object_id) => {
+                    if let Attribute::Boolean(held) = actor.attribute {
+                        if held {
+                            self.state.handbrakes.insert(actor.actor_id);
+                        } else {
+                            self.state.handbrakes.remove(&actor.actor_id);
+                        }
+                    }
+                }
+                // When time runs out with the score tied
+                object_id if self.overtime_object_id == Some(object_id) => {
+                    if let Attribute::Boolean(overtime) = actor.attribute {
+                        self.state.overtime = overtime;
+                    }
+                }
+                // When a goal is scored, which is followed by the goal replay until the next kickoff
+                object_id if self.scored_on_object_id == Some(object_id) => {
+                    // The team is cleared to 255 once the goal replay is over
+                    if matches!(actor.attribute, Attribute::Byte(team) if team != 255) {
+                        self.state.goal_replay = true;
+                    }
+                }
+                // When the ball is first touched after a kickoff
+                object_id if self.ball_hit_object_id == Some(object_id) => {
+                    if let Attribute::Boolean(true) = actor.attribute {
+                        self.state.kickoff = None;
+                    }
+                }
+                _ => {}
+            }
+
+            // Players show up before the replay says which team they're on, so the header's
+            // scoreboard puts them on one as soon as their name or platform id is known
+            if let Some((name, team)) = self.roster_entry(actor.actor_id) {
+                let color = self.free_color(team, actor.actor_id);
+                if let Some(player) = self.state.actors.player_mut(actor.actor_id) {
+                    player.name = name;
+                    player.team = team;
+                    player.color = color;
+                }
+            }
+
+            // When the ball damages or destroys a Dropshot tile
+            if let Attribute::DamageState(damage) = &actor.attribute {
+                if let Some(tile) = self.state.tiles.get_mut(&actor.actor_id) {
+                    tile.state = match damage.tile_state {
+                        0 => TileState::Intact,
+                        1 => TileState::Damaged,
+                        _ => TileState::Destroyed,
+                    };
+                }
+            }
+            // When a boost pad is picked up or respawns. Newer replays count pickups up and send
+            // 255 when the pad is back, older ones just say whether it's taken. Pads are recreated
+            // every so often and resend their state, so only a change counts as a pickup.
+            let pickup = match actor.attribute {
+                Attribute::PickupNew(pickup) => {
+                    Some((pickup.instigator, pickup.picked_up != 255, pickup.picked_up))
+                }
+                Attribute::Pickup(pickup) => Some((pickup.instigator, pickup.picked_up, 0)),
+                _ => None,
+            };
+            let pickup = pickup.zip(self.state.pad_actors.get(&actor.actor_id));
+            if let Some(((instigator, taken, pickups), object_id)) = pickup {
+                let pad = self.state.pads.entry(*object_id).or_default();
+                let picked_up = taken && (!pad.taken || pickups != pad.pickups);
+                pad.taken = taken;
+                pad.pickups = pickups;
+
+                let car = instigator
+                    .and_then(|car| Some((car, self.state.actors.cars().get(&car)?.as_ref()?)));
+                if let Some((car, body)) = car.filter(|_| picked_up) {
+                    let location = [body.location.x as f64, body.location.y as f64];
+                    if pad.pad.is_none() {
+                        pad.pad = boost::nearest_pad(self.arena.boost_pads, location);
+                    }
+                    let player = self.state.actors.car_owner(car);
+                    if let Some((index, (_, player))) = pad.pad.zip(player) {
+                        self.state.boost_stats.pick_up(
+                            &player.name,
+                            player.team,
+                            &self.arena.boost_pads[index],
+                        );
+                    }
+                }
+            }
+        }
+
+        for actor in &frame.deleted_actors {
+            self.state.actors.apply_deleted(*actor);
+            self.state.boost_components.remove(actor);
+            self.state.jump_components.remove(actor);
+            self.state.handbrakes.remove(actor);
+            self.state.pad_actors.remove(actor);
+        }
+
+        // The ball leaving the center spot ends a kickoff, in case nobody is said to have hit it
+        if self.state.kickoff == Some(0)
+            && self
+                .state
+                .actors
+                .ball()
+                .is_some_and(|ball| ball.location.x.hypot(ball.location.y) > KICKOFF_SPOT_RADIUS)
+        {
+            self.state.kickoff = None;
+        }
+
+        // Older replays don't say who touched the ball, so go by who's right next to it instead
+        let ball = self
+            .state
+            .actors
+            .ball()
+            .filter(|_| self.hit_team_object_id.is_none());
+        if let Some(ball) = ball {
+            let cars = self
+                .player_positions()
+                .map(|(player, body)| (player.team, body));
+            if let Some(team) = touches::nearest_to_ball(&ball, cars) {
+                self.state.possession.touch(team);
+            }
+        }
+        if matches!(self.phase(), GamePhase::Regulation | GamePhase::Overtime) {
+            self.state.possession.add_time(frame.delta);
+            let thirds = self
+                .player_positions()
+                .map(|(player, body)| {
+                    let third = Third::at(&self.arena, player.team, body.location.y);
+                    (player.name.clone(), player.team, third)
+                })
+                .collect();
+            self.state.occupancy.add_time(thirds, frame.delta);
+            let speeds = self
+                .player_positions()
+                .map(|(player, body)| {
+                    let speed = body
+                        .linear_velocity
+                        .map_or(0.0, |v| (v.x * v.x + v.y * v.y + v.z * v.z).sqrt());
+                    let airborne = player
+                        .car_actor_id
+                        .and_then(|car| self.air_state(car))
+                        .is_some_and(AirState::in_air);
+                    (player.name.clone(), player.team, speed, airborne)
+                })
+                .collect();
+            self.state.movement.add_time(speeds, frame.delta);
+        }
+
+        // How each player's tank changed over the frame, which only counts towards their stats in
+        // live play but is always taken so nothing from a break is counted once play resumes
+        let mut usage = vec![];
+        for component in self.state.boost_components.values_mut() {
+            let (Some(car), Some(amount)) = (component.car, component.amount) else {
+                continue;
+            };
+            let change = amount - component.counted.replace(amount).unwrap_or(amount);
+            if let Some((_, player)) = self.state.actors.car_owner(car) {
+                usage.push((player.name.clone(), player.team, change, amount));
+            }
+        }
+        if matches!(self.phase(), GamePhase::Regulation | GamePhase::Overtime) {
+            self.state.boost_stats.add_usage(usage, frame.delta);
+        }
+
+        let now = self.state.time;
+        self.state
+            .demolitions
+            .retain(|demo| now - demo.time < RESPAWN_SECONDS);
+        self.state.frame_index += 1;
+    }
+}
+
+/// Writes a readable listing of every network frame of a replay to `path`, with actor and object
+/// names resolved.
+pub fn dump(replay: &Replay, path: &Path) -> Result<(), VisError> {
+    let network_frames = replay
+        .network_frames
+        .as_ref()
+        .ok_or(VisError::NoNetworkData)?;
+    let mut actors: HashMap<ActorId, NewActorResolved> = Default::default();
+
+    let mut f = BufWriter::new(create(path)?);
+    for frame in &network_frames.frames {
+        f.write_all("=====================\n".as_bytes())?;
+        f.write_all(format!("Time: {:?}\n", frame.time).as_bytes())?;
+        f.write_all(format!("Delt: {:?}\n", frame.delta).as_bytes())?;
+        f.write_all("--------\n".as_bytes())?;
+        f.write_all("New Actors\n".as_bytes())?;
+        f.write_all("---\n".as_bytes())?;
+        for actor in &frame.new_actors {
+            let actor = NewActorResolved {
+                actor_id: actor.actor_id,
+                name: actor_name(replay, actor.name_id)?,
+                object: object_name(replay, actor.object_id)?,
+                trajectory: actor.initial_trajectory,
+            };
+            actors.insert(actor.actor_id, actor.clone());
+
+            f.write_all(format!("Actor: {:?}\n", actor).as_bytes())?;
+        }
+        f.write_all("--------\n".as_bytes())?;
+        f.write_all("Updated Actors\n".as_bytes())?;
+        f.write_all("---\n".as_bytes())?;
+        for actor in &frame.updated_actors {
+            let actor = UpdatedActorResolved {
+                actor_id: actor.actor_id,
+                actor: actors
+                    .get(&actor.actor_id)
+                    .map_or_else(|| "Unknown".to_string(), |actor| actor.name.clone()),
+                object: object_name(replay, actor.object_id)?,
+                attribute: actor.attribute.clone(),
+                stream_id: actor.stream_id,
+            };
+
+            f.write_all(format!("Actor: {:?}\n", actor).as_bytes())?;
+        }
+        f.write_all("--------\n".as_bytes())?;
+        f.write_all("Deleted Actors\n".as_bytes())?;
+        f.write_all("---\n".as_bytes())?;
+        for actor in &frame.deleted_actors {
+            f.write_all(format!("Actor: {:?}\n", actor).as_bytes())?;
+        }
+        f.write_all("--------\n".as_bytes())?;
+        f.write_all("=====================\n".as_bytes())?;
+    }
+    f.flush()?;
+    Ok(())
+}
+
+/// Bumped whenever the shape of `dump_json`'s output changes.
+pub const DUMP_SCHEMA_VERSION: u32 = 1;
+
+/// Writes every network frame of a replay to `path` as JSON, with actor and object names resolved
+/// so tools don't need the replay's name tables.
+///
+/// The output is `{"version": DUMP_SCHEMA_VERSION, "frames": [...]}`, where each frame lists the
+/// actors created, the attributes updated on each actor, and the ids of actors deleted.
+pub fn dump_json(replay: &Replay, path: &Path) -> Result<(), VisError> {
+    let network_frames = replay
+        .network_frames
+        .as_ref()
+        .ok_or(VisError::NoNetworkData)?;
+
+    let mut names: HashMap<ActorId, String> = Default::default();
+    let mut frames = vec![];
+    for frame in &network_frames.frames {
+        let mut new_actors = vec![];
+        for actor in &frame.new_actors {
+            let actor = NewActorResolved {
+                actor_id: actor.actor_id,
+                name: actor_name(replay, actor.name_id)?,
+                object: object_name(replay, actor.object_id)?,
+                trajectory: actor.initial_trajectory,
+            };
+            names.insert(actor.actor_id, actor.name.clone());
+            new_actors.push(actor);
+        }
+
+        // Updates to the same actor arrive together, so consecutive ones are grouped
+        let mut updated_actors: Vec<ActorUpdates> = vec![];
+        for update in &frame.updated_actors {
+            let attribute = AttributeUpdate {
+                object: object_name(replay, update.object_id)?,
+                value: update.attribute.clone(),
+            };
+            match updated_actors.last_mut() {
+                Some(last) if last.actor_id == update.actor_id => last.attributes.push(attribute),
+                _ => updated_actors.push(ActorUpdates {
+                    actor_id: update.actor_id,
+                    name: names
+                        .get(&update.actor_id)
+                        .cloned()
+                        .unwrap_or_else(|| "Unknown".to_string()),
+                    attributes: vec![attribute],
+                }),
+            }
+        }
+
+        for actor in &frame.deleted_actors {
+            names.remove(actor);
+        }
+
+        frames.push(DumpFrame {
+            time: frame.time,
+            delta: frame.delta,
+            new_actors,
+            updated_actors,
+            deleted_actors: frame.deleted_actors.clone(),
+        });
+    }
+
+    let mut out = BufWriter::new(create(path)?);
+    serde_json::to_writer(
+        &mut out,
+        &Dump {
+            version: DUMP_SCHEMA_VERSION,
+            frames,
+        },
+    )?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Creates a file to write to, saying which one if it can't be.
+pub fn create(path: &Path) -> Result<fs::File, VisError> {
+    fs::File::create(path).map_err(|source| VisError::Create {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Writes the whole of a file at once, saying which one if it can't be.
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), VisError> {
+    fs::write(path, contents).map_err(|source| VisError::Write {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[derive(Serialize)]
+struct Dump {
+    version: u32,
+    frames: Vec<DumpFrame>,
+}
+
+#[derive(Serialize)]
+struct DumpFrame {
+    time: f32,
+    delta: f32,
+    new_actors: Vec<NewActorResolved>,
+    updated_actors: Vec<ActorUpdates>,
+    deleted_actors: Vec<ActorId>,
+}
+
+#[derive(Serialize)]
+struct ActorUpdates {
+    actor_id: ActorId,
+    name: String,
+    attributes: Vec<AttributeUpdate>,
+}
+
+#[derive(Serialize)]
+struct AttributeUpdate {
+    object: String,
+    value: Attribute,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct NewActorResolved {
+    actor_id: ActorId,
+    name: String,
+    object: String,
+    trajectory: boxcars::Trajectory,
+}
+
+#[derive(Debug, Clone)]
+#[expect(dead_code, reason = "only read through the Debug output `dump` writes")]
+struct UpdatedActorResolved {
+    actor_id: ActorId,
+    actor: String,
+    attribute: Attribute,
+    object: String,
+    stream_id: boxcars::StreamId,
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use boxcars::Vector3f;
+use serde::Serialize;
+
+use crate::arena::Arena;
+use crate::Team;
+
+/// How fast a car has to go, in unreal units per second, to be supersonic.
+pub const SUPERSONIC_SPEED: f32 = 2200.0;
+
+/// How far a car's middle can be from the floor, a wall or the ceiling and still be driving on it.
+/// A car resting on its wheels sits about 17 uu off the floor.
+const SURFACE_REACH: f64 = 60.0;
+const CEILING_HEIGHT: f64 = 2044.0;
+
+/// Whether a car is driving on something or in the air, and what put it there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AirState {
+    /// On the floor, a wall or the ceiling.
+    Grounded,
+    /// Holding its first jump.
+    Jumping,
+    DoubleJumping,
+    /// Flipping after using its second jump to dodge.
+    Dodging,
+    /// Off every surface with no jump going, like at the top of a jump, flying on boost or after
+    /// driving off a wall.
+    Airborne,
+}
+
+impl AirState {
+    /// Works out a car's state from where it is and which of its jump, double jump and dodge are
+    /// going on.
+    pub fn new(arena: &Arena, location: &Vector3f, [jump, double_jump, dodge]: [bool; 3]) -> Self {
+        if dodge {
+            AirState::Dodging
+        } else if double_jump {
+            AirState::DoubleJumping
+        } else if jump {
+            AirState::Jumping
+        } else if on_surface(arena, location) {
+            AirState::Grounded
+        } else {
+            AirState::Airborne
+        }
+    }
+
+    pub fn in_air(self) -> bool {
+        self != AirState::Grounded
+    }
+}
+
+/// Whether a car's middle is close enough to the floor, a wall or the ceiling to be driving on it.
+fn on_surface(arena: &Arena, location: &Vector3f) -> bool {
+    let [x, y, z] = [location.x, location.y, location.z].map(f64::from);
+    if !(SURFACE_REACH..=CEILING_HEIGHT - SURFACE_REACH).contains(&z) {
+        return true;
+    }
+    // The corners go counterclockwise, so each wall's distance from the car is how far the car is
+    // to its left
+    let corners = arena.wall_corners();
+    corners.iter().enumerate().any(|(i, &[from_x, from_y])| {
+        let [to_x, to_y] = corners[(i + 1) % corners.len()];
+        let length = (to_x - from_x).hypot(to_y - from_y);
+        let distance = ((to_x - from_x) * (y - from_y) - (to_y - from_y) * (x - from_x)) / length;
+        distance < SURFACE_REACH
+    })
+}
+
+/// How far and how fast a player drove during live play.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct PlayerMovement {
+    /// Unreal units travelled.
+    pub distance: f32,
+    /// Seconds the player's car was on the field.
+    pub seconds: f32,
+    pub seconds_supersonic: f32,
+    /// Seconds the player's car was off the floor, walls and ceiling.
+    pub seconds_airborne: f32,
+}
+
+impl PlayerMovement {
+    /// Average speed in unreal units per second.
+    pub fn average_speed(&self) -> f32 {
+        if self.seconds > 0.0 {
+            self.distance / self.seconds
+        } else {
+            0.0
+        }
+    }
+}
+
+/// How every player seen in the replay so far has moved, added up one frame at a time.
+#[derive(Debug, Default, Clone)]
+pub struct Movement {
+    players: HashMap<String, (Team, PlayerMovement)>,
+}
+
+impl Movement {
+    /// Every player's movement, sorted by team and then by name.
+    pub fn players(&self) -> Vec<(&str, Team, PlayerMovement)> {
+        let mut players: Vec<_> = self
+            .players
+            .iter()
+            .map(|(name, (team, movement))| (name.as_str(), *team, *movement))
+            .collect();
+        players.sort_by_key(|(name, team, _)| (*team == Team::Blue, name.to_string()));
+        players
+    }
+
+    /// Counts `seconds` of driving at each player's speed, and whether they were in the air.
+    pub(crate) fn add_time(&mut self, players: Vec<(String, Team, f32, bool)>, seconds: f32) {
+        for (name, team, speed, airborne) in players {
+            let (player_team, movement) = self
+                .players
+                .entry(name)
+                .or_insert((team, PlayerMovement::default()));
+            *player_team = team;
+            movement.distance += speed * seconds;
+            movement.seconds += seconds;
+            if speed >= SUPERSONIC_SPEED {
+                movement.seconds_supersonic += seconds;
+            }
+            if airborne {
+                movement.seconds_airborne += seconds;
+            }
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use crate::coverage::{self, Shadow};
+use crate::shots::{self, Shot, ShotKind};
+use crate::{ReplayEngine, Team, TeamColors};
+
+/// A layer drawn over the field on top of everything else, like a team's own metrics. Register one
+/// with `OverlayRegistry::register` and pass the registry to `app::run_with` and it can be picked
+/// with `--layer`.
+pub trait OverlayPlugin {
+    /// Records the frame the engine just processed, for overlays that build something up over
+    /// several frames. Seeking backwards or looping makes the engine's time go back.
+    fn observe(&mut self, _engine: &ReplayEngine) {}
+
+    /// Draws the overlay for the engine's current frame.
+    fn draw(&self, engine: &ReplayEngine, painter: &mut dyn Painter);
+}
+
+/// Draws shapes on the field. Points and sizes are in unreal units, the same as the replay's
+/// locations, so overlays don't need to know how the field is scaled or zoomed.
+pub trait Painter {
+    fn circle(&mut self, center: [f64; 2], radius: f64, color: [f32; 4]);
+    /// An unfilled circle, with its outline `width` across.
+    fn ring(&mut self, center: [f64; 2], radius: f64, width: f64, color: [f32; 4]);
+    fn line(&mut self, from: [f64; 2], to: [f64; 2], width: f64, color: [f32; 4]);
+    fn polygon(&mut self, points: &[[f64; 2]], color: [f32; 4]);
+}
+
+/// Makes a fresh overlay, once for every window, video or snapshot drawn with it.
+pub type Constructor = fn() -> Box<dyn OverlayPlugin>;
+
+/// Overlays by the name `--layer` picks them with.
+#[derive(Debug)]
+pub struct OverlayRegistry {
+    overlays: Vec<(&'static str, Constructor)>,
+}
+
+impl Default for OverlayRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl OverlayRegistry {
+    /// Every overlay that comes with the visualizer.
+    pub fn builtin() -> Self {
+        let mut registry = Self { overlays: vec![] };
+        registry.register("team-centers", || Box::new(TeamCenters));
+        registry.register("nearest-to-ball", || Box::new(NearestToBall));
+        registry.register("goal-coverage", || Box::new(GoalCoverageLayer));
+        registry.register("shot-chart", || Box::new(ShotChart::default()));
+        registry
+    }
+
+    pub fn register(&mut self, name: &'static str, constructor: Constructor) {
+        self.overlays.push((name, constructor));
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.overlays.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// A fresh instance of the overlay called `name`.
+    pub fn create(&self, name: &str) -> Option<Box<dyn OverlayPlugin>> {
+        let (_, constructor) = self.overlays.iter().find(|(overlay, _)| *overlay == name)?;
+        Some(constructor())
+    }
+}
+
+/// Marks the middle of each team's cars and shades the area between them, to show how spread out
+/// the team is.
+struct TeamCenters;
+
+impl OverlayPlugin for TeamCenters {
+    fn draw(&self, engine: &ReplayEngine, painter: &mut dyn Painter) {
+        for team in [Team::Orange, Team::Blue] {
+            let mut cars: Vec<_> = engine
+                .player_positions()
+                .filter(|(player, _)| player.team == team)
+                .map(|(_, body)| [body.location.x as f64, body.location.y as f64])
+                .collect();
+            if cars.is_empty() {
+                continue;
+            }
+            let [x, y] = [
+                cars.iter().map(|[x, _]| x).sum::<f64>() / cars.len() as f64,
+                cars.iter().map(|[_, y]| y).sum::<f64>() / cars.len() as f64,
+            ];
+
+            let mut color = engine.team_colors().team(team);
+            color[3] = 0.15;
+            // Going around the middle keeps the outline from crossing itself
+            cars.sort_by(|a, b| {
+                (a[1] - y)
+                    .atan2(a[0] - x)
+                    .total_cmp(&(b[1] - y).atan2(b[0] - x))
+            });
+            if cars.len() >= 3 {
+                painter.polygon(&cars, color);
+            }
+            color[3] = 0.6;
+            for car in cars {
+                painter.line([x, y], car, 15.0, color);
+            }
+            painter.circle([x, y], 80.0, color);
+        }
+    }
+}
+
+/// Joins the ball to the car on each team closest to it, the one most likely to go for it next.
+struct NearestToBall;
+
+impl OverlayPlugin for NearestToBall {
+    fn draw(&self, engine: &ReplayEngine, painter: &mut dyn Painter) {
+        let Some(ball) = engine.ball() else {
+            return;
+        };
+        let ball = [ball.location.x as f64, ball.location.y as f64];
+        let distance = |[x, y]: [f64; 2]| (x - ball[0]).hypot(y - ball[1]);
+
+        for team in [Team::Orange, Team::Blue] {
+            let nearest = engine
+                .player_positions()
+                .filter(|(player, _)| player.team == team)
+                .map(|(player, body)| {
+                    let car = [body.location.x as f64, body.location.y as f64];
+                    (player.color, car)
+                })
+                .min_by(|(_, a), (_, b)| distance(*a).total_cmp(&distance(*b)));
+            if let Some((color, car)) = nearest {
+                painter.line(car, ball, 20.0, color);
+                painter.ring(car, 150.0, 20.0, color);
+            }
+        }
+    }
+}
+
+/// Shades the shadow each defender casts from the ball toward their own goal, and marks which parts
+/// of the goal mouth a shot from the ball would find blocked and which are open.
+struct GoalCoverageLayer;
+
+impl OverlayPlugin for GoalCoverageLayer {
+    fn draw(&self, engine: &ReplayEngine, painter: &mut dyn Painter) {
+        for goal in coverage::goal_coverage(engine) {
+            let [r, g, b, _] = engine.team_colors().palette(goal.team)[1];
+            let [left, right] = goal.posts;
+            // A shadow that misses the goal mouth doesn't cover anything worth shooting at
+            let reaches_goal = |shadow: &&Shadow| {
+                let [a, b] = shadow.goal_line.map(|[x, _]| x);
+                a.min(b) < right && a.max(b) > left
+            };
+            for shadow in goal.shadows.iter().filter(reaches_goal) {
+                let [near_left, near_right] = shadow.car;
+                let [far_left, far_right] = shadow.goal_line;
+                painter.polygon(
+                    &[near_left, near_right, far_right, far_left],
+                    [r, g, b, 0.2],
+                );
+            }
+
+            // The open net in white under the covered parts in the defenders' color
+            let y = goal.goal_line;
+            painter.line([left, y], [right, y], 60.0, [1.0, 1.0, 1.0, 0.8]);
+            for [from, to] in &goal.covered {
+                painter.line([*from, y], [*to, y], 60.0, [r, g, b, 1.0]);
+            }
+        }
+    }
+}
+
+/// Marks where every shot so far was taken from and every save so far was made, building up into
+/// the whole match's shot chart by the end of the replay.
+#[derive(Default)]
+struct ShotChart {
+    /// Every shot and save in the replay, found the first time a frame is observed.
+    shots: Option<Vec<Shot>>,
+}
+
+impl OverlayPlugin for ShotChart {
+    fn observe(&mut self, engine: &ReplayEngine) {
+        if self.shots.is_none() {
+            self.shots = Some(shots::shots(engine));
+        }
+    }
+
+    fn draw(&self, engine: &ReplayEngine, painter: &mut dyn Painter) {
+        let Some(shots) = &self.shots else {
+            return;
+        };
+        let end = shots.partition_point(|shot| shot.frame < engine.frame_index());
+        paint_shots(&shots[..end], engine.team_colors(), painter);
+    }
+}
+
+/// Marks each of `shots` where the ball was when it was hit, in the color of the team that hit it:
+/// a dot for a shot, bigger the better its chance of going in, and a ring for a save.
+pub fn paint_shots(shots: &[Shot], colors: &TeamColors, painter: &mut dyn Painter) {
+    for shot in shots {
+        let center = [shot.location.x as f64, shot.location.y as f64];
+        let color = colors.team(shot.team);
+        match shot.kind {
+            ShotKind::Shot => {
+                let radius = 100.0 + 150.0 * shot.quality.unwrap_or_default() as f64;
+                painter.circle(center, radius, [color[0], color[1], color[2], 0.8]);
+                painter.ring(center, radius, 20.0, [0.0, 0.0, 0.0, 0.8]);
+            }
+            ShotKind::Save => painter.ring(center, 150.0, 50.0, color),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Marker;
+
+    impl OverlayPlugin for Marker {
+        fn draw(&self, _engine: &ReplayEngine, _painter: &mut dyn Painter) {}
+    }
+
+    #[test]
+    fn registered_overlays_can_be_picked_next_to_the_builtin_ones() {
+        let mut registry = OverlayRegistry::builtin();
+        registry.register("marker", || Box::new(Marker));
+        assert_eq!(
+            registry.names(),
+            [
+                "team-centers",
+                "nearest-to-ball",
+                "goal-coverage",
+                "shot-chart",
+                "marker"
+            ]
+        );
+        assert!(registry.create("marker").is_some());
+        assert!(registry.create("unknown").is_none());
+    }
+}
@@ -0,0 +1,122 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Float32Array, Float64Array, RecordBatch, StringArray, UInt32Array};
+use arrow_schema::{DataType, Field, Schema};
+use boxcars::Replay;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use crate::{create, ReplayEngine, VisError};
+
+/// Column values for every row written so far, one row per player per frame.
+#[derive(Default)]
+struct Columns {
+    frame: Vec<u32>,
+    time: Vec<f32>,
+    player: Vec<String>,
+    team: Vec<String>,
+    x: Vec<f32>,
+    y: Vec<f32>,
+    z: Vec<f32>,
+    boost: Vec<Option<f32>>,
+    third: Vec<String>,
+    ball_x: Vec<Option<f32>>,
+    ball_y: Vec<Option<f32>>,
+    ball_z: Vec<Option<f32>>,
+    orange_control: Vec<Option<f64>>,
+    blue_control: Vec<Option<f64>>,
+    orange_score: Vec<u32>,
+    blue_score: Vec<u32>,
+    phase: Vec<String>,
+}
+
+fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("frame", DataType::UInt32, false),
+        Field::new("time", DataType::Float32, false),
+        Field::new("player", DataType::Utf8, false),
+        Field::new("team", DataType::Utf8, false),
+        Field::new("x", DataType::Float32, false),
+        Field::new("y", DataType::Float32, false),
+        Field::new("z", DataType::Float32, false),
+        Field::new("boost", DataType::Float32, true),
+        Field::new("third", DataType::Utf8, false),
+        Field::new("ball_x", DataType::Float32, true),
+        Field::new("ball_y", DataType::Float32, true),
+        Field::new("ball_z", DataType::Float32, true),
+        Field::new("orange_control", DataType::Float64, true),
+        Field::new("blue_control", DataType::Float64, true),
+        Field::new("orange_score", DataType::UInt32, false),
+        Field::new("blue_score", DataType::UInt32, false),
+        Field::new("phase", DataType::Utf8, false),
+    ])
+}
+
+/// Writes where every player was on every frame of a replay to `path` as a Parquet table, with the
+/// ball, zone control, score and game phase of that frame repeated on each row so it can be filtered
+/// on directly.
+pub fn dump_parquet(replay: &Replay, path: &Path) -> Result<(), VisError> {
+    let mut columns = Columns::default();
+    let mut engine = ReplayEngine::new(replay.clone());
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+        let summary = engine.summary();
+        for player in summary.players {
+            columns.frame.push(summary.frame as u32);
+            columns.time.push(summary.time);
+            columns.player.push(player.name);
+            columns.team.push(format!("{:?}", player.team));
+            columns.x.push(player.location.x);
+            columns.y.push(player.location.y);
+            columns.z.push(player.location.z);
+            columns.boost.push(player.boost);
+            columns.third.push(format!("{:?}", player.third));
+            columns.ball_x.push(summary.ball.map(|ball| ball.x));
+            columns.ball_y.push(summary.ball.map(|ball| ball.y));
+            columns.ball_z.push(summary.ball.map(|ball| ball.z));
+            columns
+                .orange_control
+                .push(summary.zone_control.map(|control| control.orange));
+            columns
+                .blue_control
+                .push(summary.zone_control.map(|control| control.blue));
+            columns.orange_score.push(summary.score.orange as u32);
+            columns.blue_score.push(summary.score.blue as u32);
+            columns.phase.push(format!("{:?}", summary.phase));
+        }
+    }
+
+    let schema = Arc::new(schema());
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(UInt32Array::from(columns.frame)) as ArrayRef,
+            Arc::new(Float32Array::from(columns.time)),
+            Arc::new(StringArray::from(columns.player)),
+            Arc::new(StringArray::from(columns.team)),
+            Arc::new(Float32Array::from(columns.x)),
+            Arc::new(Float32Array::from(columns.y)),
+            Arc::new(Float32Array::from(columns.z)),
+            Arc::new(Float32Array::from(columns.boost)),
+            Arc::new(StringArray::from(columns.third)),
+            Arc::new(Float32Array::from(columns.ball_x)),
+            Arc::new(Float32Array::from(columns.ball_y)),
+            Arc::new(Float32Array::from(columns.ball_z)),
+            Arc::new(Float64Array::from(columns.orange_control)),
+            Arc::new(Float64Array::from(columns.blue_control)),
+            Arc::new(UInt32Array::from(columns.orange_score)),
+            Arc::new(UInt32Array::from(columns.blue_score)),
+            Arc::new(StringArray::from(columns.phase)),
+        ],
+    )?;
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(create(path)?, schema, Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use crate::Team;
+
+/// Which team has the ball, taken to be the last team to touch it, and how long each team has had
+/// it so far.
+#[derive(Debug, Default, Clone)]
+pub struct Possession {
+    team: Option<Team>,
+    orange_seconds: f32,
+    blue_seconds: f32,
+}
+
+impl Possession {
+    /// The team that last touched the ball, or `None` before anyone has since the last kickoff.
+    pub fn team(&self) -> Option<Team> {
+        self.team
+    }
+
+    /// Fraction of the time either team had the ball that each team had it, as (orange, blue).
+    pub fn shares(&self) -> Option<(f64, f64)> {
+        let total = self.orange_seconds + self.blue_seconds;
+        if total <= 0.0 {
+            return None;
+        }
+        Some((
+            (self.orange_seconds / total) as f64,
+            (self.blue_seconds / total) as f64,
+        ))
+    }
+
+    pub(crate) fn touch(&mut self, team: Team) {
+        self.team = Some(team);
+    }
+
+    /// Gives the ball to nobody, like at a kickoff.
+    pub(crate) fn reset(&mut self) {
+        self.team = None;
+    }
+
+    /// Counts `seconds` towards whichever team has the ball.
+    pub(crate) fn add_time(&mut self, seconds: f32) {
+        match self.team {
+            Some(Team::Orange) => self.orange_seconds += seconds,
+            Some(Team::Blue) => self.blue_seconds += seconds,
+            None => {}
+        }
+    }
+}
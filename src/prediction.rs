@@ -0,0 +1,168 @@
+use boxcars::Vector3f;
+
+use crate::arena::Arena;
+use crate::shots::GOAL_HEIGHT;
+
+/// The ball's downward acceleration, in uu/s².
+const GRAVITY: f64 = 650.0;
+const BALL_RADIUS: f64 = 92.75;
+const CEILING_HEIGHT: f64 = 2044.0;
+/// Share of its speed into a surface the ball keeps when it bounces off it.
+const RESTITUTION: f64 = 0.6;
+/// Share of its speed the ball loses to the air each second.
+const DRAG: f64 = 0.0305;
+/// Seconds between the points of a predicted path, the rate the game runs its physics at.
+pub const STEP: f64 = 1.0 / 120.0;
+
+/// A flat surface the ball bounces off: the points `p` with `normal · p == offset`, with the
+/// field on the side `normal` points to.
+struct Surface {
+    normal: [f64; 3],
+    offset: f64,
+    /// Whether this is a back wall, which has a goal mouth in it for the ball to go through.
+    back_wall: bool,
+}
+
+/// Where the ball will be over the next `seconds`, one point every `STEP`, if nobody touches it.
+/// It falls under gravity, slows in the air and bounces off the floor, the ceiling and the walls,
+/// including the diagonal corners. The path stops early once the ball is all the way into a goal.
+pub fn predict(
+    arena: &Arena,
+    location: &Vector3f,
+    velocity: &Vector3f,
+    seconds: f64,
+) -> Vec<[f64; 3]> {
+    let mut location = [location.x, location.y, location.z].map(f64::from);
+    let mut velocity = [velocity.x, velocity.y, velocity.z].map(f64::from);
+    let surfaces = surfaces(arena);
+    let steps = (seconds / STEP).round() as usize;
+
+    let mut path = Vec::with_capacity(steps);
+    for _ in 0..steps {
+        velocity[2] -= GRAVITY * STEP;
+        velocity = velocity.map(|axis| axis * (1.0 - DRAG * STEP));
+        location = add(location, velocity, STEP);
+
+        for surface in &surfaces {
+            let depth = dot(surface.normal, location) - surface.offset - BALL_RADIUS;
+            if depth >= 0.0 || surface.back_wall && in_goal_mouth(arena, location) {
+                continue;
+            }
+            location = add(location, surface.normal, -depth);
+            let into = dot(surface.normal, velocity);
+            if into < 0.0 {
+                velocity = add(velocity, surface.normal, -(1.0 + RESTITUTION) * into);
+            }
+        }
+
+        path.push(location);
+        if location[1].abs() > arena.height / 2.0 + BALL_RADIUS {
+            break;
+        }
+    }
+    path
+}
+
+/// The floor, the ceiling and every wall of `arena`, going around the field.
+fn surfaces(arena: &Arena) -> Vec<Surface> {
+    let corners = arena.wall_corners();
+    let walls = corners.iter().enumerate().map(|(i, &[x, y])| {
+        let [next_x, next_y] = corners[(i + 1) % corners.len()];
+        let length = (next_x - x).hypot(next_y - y);
+        // The corners go counterclockwise, so the field is to the left of each wall
+        let normal = [(y - next_y) / length, (next_x - x) / length, 0.0];
+        Surface {
+            normal,
+            offset: normal[0] * x + normal[1] * y,
+            // The first corner starts the back wall at negative y, and the fifth the one at
+            // positive y
+            back_wall: i % 4 == 0,
+        }
+    });
+    [
+        Surface {
+            normal: [0.0, 0.0, 1.0],
+            offset: 0.0,
+            back_wall: false,
+        },
+        Surface {
+            normal: [0.0, 0.0, -1.0],
+            offset: -CEILING_HEIGHT,
+            back_wall: false,
+        },
+    ]
+    .into_iter()
+    .chain(walls)
+    .collect()
+}
+
+/// Whether the ball is lined up to go through a goal mouth rather than hit the back wall around it.
+fn in_goal_mouth(arena: &Arena, [x, _, z]: [f64; 3]) -> bool {
+    x.abs() < arena.goal_width / 2.0 && z < GOAL_HEIGHT as f64
+}
+
+/// `a` moved along `b` scaled by `scale`.
+fn add(a: [f64; 3], b: [f64; 3], scale: f64) -> [f64; 3] {
+    [0, 1, 2].map(|axis| a[axis] + b[axis] * scale)
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector(x: f32, y: f32, z: f32) -> Vector3f {
+        Vector3f { x, y, z }
+    }
+
+    #[test]
+    fn a_dropped_ball_bounces_lower_each_time() {
+        let path = predict(
+            &Arena::STANDARD,
+            &vector(0.0, 0.0, 1000.0),
+            &vector(0.0, 0.0, 0.0),
+            3.0,
+        );
+        assert_eq!(path.len(), 360);
+        assert!(path.iter().all(|[_, _, z]| *z >= BALL_RADIUS - 1e-9));
+
+        // The highest point between each pair of bounces
+        let peaks: Vec<_> = path
+            .windows(3)
+            .filter(|points| points[1][2] > points[0][2] && points[1][2] >= points[2][2])
+            .map(|points| points[1][2])
+            .collect();
+        assert!(!peaks.is_empty());
+        assert!(peaks.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!(peaks[0] < 1000.0);
+    }
+
+    #[test]
+    fn a_ball_rolled_at_a_side_wall_comes_back() {
+        let path = predict(
+            &Arena::STANDARD,
+            &vector(3000.0, 0.0, BALL_RADIUS as f32),
+            &vector(2000.0, 0.0, 0.0),
+            2.0,
+        );
+        let half_width = Arena::STANDARD.width / 2.0;
+        assert!(path.iter().all(|[x, ..]| *x <= half_width - BALL_RADIUS));
+        assert!(path.last().unwrap()[0] < 3000.0);
+    }
+
+    #[test]
+    fn a_ball_rolled_into_a_goal_stops_inside_it() {
+        let path = predict(
+            &Arena::STANDARD,
+            &vector(0.0, 4000.0, BALL_RADIUS as f32),
+            &vector(0.0, 2000.0, 0.0),
+            3.0,
+        );
+        let [_, y, _] = *path.last().unwrap();
+        assert!(path.len() < 360);
+        assert!(y > Arena::STANDARD.height / 2.0 + BALL_RADIUS);
+    }
+}
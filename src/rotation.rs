@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::mem;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GamePhase, PlayerDetails, ReplayEngine, Team};
+
+/// How far past the ball, towards the other team's goal, a car can be and still count as being in
+/// the play. First men challenging for the ball often end up just beyond it.
+const PAST_BALL_SLACK: f32 = 500.0;
+
+/// Rules broken for less than this many seconds at a time are left out of `violation_log`, since a
+/// first man challenging for the ball passes it all the time.
+const MIN_VIOLATION_SECONDS: f32 = 0.5;
+
+/// Where a player sits in their team's rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum Role {
+    /// Closest to the ball, challenging for it.
+    First,
+    /// Next in line, ready to follow up.
+    Second,
+    /// Furthest back, covering the goal. Anyone behind the third man in bigger teams counts too.
+    Third,
+}
+
+impl Role {
+    /// 1, 2 or 3, as the role is usually called.
+    pub fn number(self) -> usize {
+        match self {
+            Role::First => 1,
+            Role::Second => 2,
+            Role::Third => 3,
+        }
+    }
+}
+
+/// Each player's role in their team's rotation right now. Players on the near side of the ball to
+/// their own goal are ranked first by how close they are to the ball, then anyone caught past it,
+/// since they have to get back before they can play it.
+pub fn roles(engine: &ReplayEngine) -> Vec<(&PlayerDetails, Role)> {
+    let Some(ball) = engine.ball() else {
+        return vec![];
+    };
+
+    let mut roles = vec![];
+    for team in [Team::Orange, Team::Blue] {
+        let mut players: Vec<_> = engine
+            .player_positions()
+            .filter(|(player, _)| player.team == team)
+            .map(|(player, car)| {
+                let past_ball = team.forward(car.location.y - ball.location.y) > PAST_BALL_SLACK;
+                let distance = (car.location.x - ball.location.x)
+                    .hypot(car.location.y - ball.location.y)
+                    .hypot(car.location.z - ball.location.z);
+                (player, past_ball, distance)
+            })
+            .collect();
+        players.sort_by(|a, b| a.1.cmp(&b.1).then(a.2.total_cmp(&b.2)));
+
+        roles.extend(players.into_iter().enumerate().map(|(rank, (player, ..))| {
+            let role = match rank {
+                0 => Role::First,
+                1 => Role::Second,
+                _ => Role::Third,
+            };
+            (player, role)
+        }));
+    }
+    roles
+}
+
+/// How long each player has spent in each role, added up one frame at a time.
+#[derive(Debug, Default, Clone)]
+pub struct RoleShares {
+    seconds: HashMap<String, (Team, HashMap<Role, f32>)>,
+}
+
+impl RoleShares {
+    /// Counts `seconds` towards the role each player has in the engine's current frame.
+    pub fn observe(&mut self, engine: &ReplayEngine, seconds: f32) {
+        for (player, role) in roles(engine) {
+            let (team, roles) = self
+                .seconds
+                .entry(player.name.clone())
+                .or_insert_with(|| (player.team, HashMap::new()));
+            *team = player.team;
+            *roles.entry(role).or_default() += seconds;
+        }
+    }
+
+    /// Fraction of each player's observed time they spent as first, second and third man, sorted
+    /// by team and then by name.
+    pub fn shares(&self) -> Vec<(&str, Team, [f64; 3])> {
+        let mut shares: Vec<_> = self
+            .seconds
+            .iter()
+            .map(|(name, (team, roles))| {
+                let total: f32 = roles.values().sum();
+                let share = |role| {
+                    if total > 0.0 {
+                        (roles.get(&role).copied().unwrap_or_default() / total) as f64
+                    } else {
+                        0.0
+                    }
+                };
+                (
+                    name.as_str(),
+                    *team,
+                    [share(Role::First), share(Role::Second), share(Role::Third)],
+                )
+            })
+            .collect();
+        shares.sort_by_key(|(name, team, _)| (*team == Team::Blue, name.to_string()));
+        shares
+    }
+}
+
+/// A rule for where a team's players should be, from the config file, checked on every frame of
+/// live play.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "rule", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Rule {
+    /// While their team has the ball, at least `players` of them stay between it and their own goal.
+    BehindBall { players: usize },
+    /// The player furthest back never goes past halfway with less than `boost` boost, out of 100.
+    LastManInHalf { boost: f32 },
+}
+
+impl Rule {
+    /// What the rule asks for, in a few words.
+    pub fn describe(&self) -> String {
+        match self {
+            Rule::BehindBall { players: 1 } => "one player behind the ball on offense".to_string(),
+            Rule::BehindBall { players } => format!("{players} players behind the ball on offense"),
+            Rule::LastManInHalf { boost } => {
+                format!("last man stays in their half under {boost:.0} boost")
+            }
+        }
+    }
+
+    /// The players on `team` breaking the rule right now, or `None` if it holds.
+    fn broken_by(&self, engine: &ReplayEngine, team: Team) -> Option<Vec<String>> {
+        let ball = engine.ball()?;
+        let cars: Vec<_> = engine
+            .player_positions()
+            .filter(|(player, _)| player.team == team)
+            .collect();
+        let forward = |y: f32| team.forward(y);
+
+        match *self {
+            Rule::BehindBall { players } => {
+                if engine.possession().team() != Some(team) {
+                    return None;
+                }
+                let ahead: Vec<_> = cars
+                    .iter()
+                    .filter(|(_, car)| forward(car.location.y) > forward(ball.location.y))
+                    .map(|(player, _)| player.name.clone())
+                    .collect();
+                // A team smaller than the rule asks for only needs everyone back
+                (cars.len() - ahead.len() < players.min(cars.len())).then_some(ahead)
+            }
+            Rule::LastManInHalf { boost } => {
+                let (last, car) = cars
+                    .iter()
+                    .min_by(|a, b| forward(a.1.location.y).total_cmp(&forward(b.1.location.y)))?;
+                let tank = engine.boost(last.car_actor_id?)? * 100.0;
+                (forward(car.location.y) > 0.0 && tank < boost).then(|| vec![last.name.clone()])
+            }
+        }
+    }
+}
+
+/// A team breaking one of the rules.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    /// Which rule, by its place in the list checked.
+    pub rule: usize,
+    pub team: Team,
+    /// The players to blame: anyone ahead of the ball, or the last man.
+    pub players: Vec<String>,
+}
+
+/// Every rule either team is breaking in the engine's current frame. Nothing is broken outside of
+/// live play.
+pub fn violations(engine: &ReplayEngine, rules: &[Rule]) -> Vec<Violation> {
+    if !matches!(engine.phase(), GamePhase::Regulation | GamePhase::Overtime) {
+        return vec![];
+    }
+    let mut violations = vec![];
+    for (index, rule) in rules.iter().enumerate() {
+        for team in [Team::Orange, Team::Blue] {
+            if let Some(players) = rule.broken_by(engine, team) {
+                violations.push(Violation {
+                    rule: index,
+                    team,
+                    players,
+                });
+            }
+        }
+    }
+    violations
+}
+
+/// A stretch of frames one team spent breaking one rule.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ViolationSpan {
+    pub rule: usize,
+    pub team: Team,
+    /// Everyone to blame at some point during it, in the order they were first.
+    pub players: Vec<String>,
+    /// The first and last network frames the rule was broken on.
+    pub start_frame: usize,
+    pub end_frame: usize,
+    /// When it started, and how long it went on for.
+    pub time: f32,
+    pub seconds: f32,
+}
+
+/// Walks the whole replay, finding every time a team broke one of `rules` for at least
+/// `MIN_VIOLATION_SECONDS`.
+pub fn violation_log(engine: &ReplayEngine, rules: &[Rule]) -> Vec<ViolationSpan> {
+    let mut engine = engine.clone();
+    engine.seek(0);
+
+    let mut log = vec![];
+    let mut open: Vec<ViolationSpan> = vec![];
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+        let frame = engine.frame_index() - 1;
+        let mut still_open = vec![];
+        for violation in violations(&engine, rules) {
+            let span = match open
+                .iter()
+                .position(|span| span.rule == violation.rule && span.team == violation.team)
+            {
+                Some(index) => open.swap_remove(index),
+                None => ViolationSpan {
+                    rule: violation.rule,
+                    team: violation.team,
+                    players: vec![],
+                    start_frame: frame,
+                    end_frame: frame,
+                    time: engine.time(),
+                    seconds: 0.0,
+                },
+            };
+            let mut span = ViolationSpan {
+                end_frame: frame,
+                seconds: engine.time() - span.time,
+                ..span
+            };
+            for player in violation.players {
+                if !span.players.contains(&player) {
+                    span.players.push(player);
+                }
+            }
+            still_open.push(span);
+        }
+        // Whatever wasn't broken on this frame is over
+        log.extend(mem::replace(&mut open, still_open));
+    }
+    log.extend(open);
+    log.retain(|span| span.seconds >= MIN_VIOLATION_SECONDS);
+    log.sort_by_key(|span| (span.start_frame, span.rule));
+    log
+}
@@ -0,0 +1,186 @@
+use boxcars::{RigidBody, Vector3f};
+use serde::Serialize;
+
+use crate::arena::Arena;
+use crate::touches::{self, Touch};
+use crate::{ReplayEngine, Team};
+
+/// The ball's downward acceleration, in uu/s².
+const GRAVITY: f32 = 650.0;
+
+/// Height of the crossbar above the floor on every soccar map.
+pub const GOAL_HEIGHT: f32 = 642.775;
+
+/// How far ahead a ball's flight is followed when deciding whether it's on target. Anything that
+/// takes longer than this to reach the goal would have bounced off something on the way.
+const SHOT_HORIZON: f32 = 3.0;
+
+/// How far from the line between the ball and the goal a defender's car still gets in the way,
+/// roughly half a car's length plus the ball's radius.
+const BLOCKING_REACH: f32 = 200.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ShotKind {
+    /// A touch that sent the ball towards the other team's goal.
+    Shot,
+    /// A touch that turned away a ball heading into the player's own goal.
+    Save,
+}
+
+/// A touch that put the ball on target or kept it out.
+#[derive(Debug, Clone, Serialize)]
+pub struct Shot {
+    pub kind: ShotKind,
+    /// Network frame the touch was seen on.
+    pub frame: usize,
+    pub time: f32,
+    pub player: String,
+    pub team: Team,
+    /// Where the ball was when it was touched.
+    pub location: Vector3f,
+    /// The ball's velocity just after the touch, in uu/s.
+    pub velocity: Vector3f,
+    /// Rough chance between 0 and 1 that a shot goes in, from how far out and how wide of the goal
+    /// it was taken, how hard it was hit and how many defenders were in the way. `None` for saves.
+    pub quality: Option<f32>,
+}
+
+/// The y coordinate of the goal line `defending` guards.
+fn goal_line(arena: &Arena, defending: Team) -> f32 {
+    match defending {
+        Team::Blue => -arena.height as f32 / 2.0,
+        Team::Orange => arena.height as f32 / 2.0,
+    }
+}
+
+/// When and where a ball at `location` moving at `velocity` would cross the goal line of the goal
+/// `defending` guards, following it under gravity alone, as seconds from now and `[x, z]` on the
+/// line. `None` if it's moving away from the line or would take longer than `SHOT_HORIZON`.
+pub fn crossing(
+    arena: &Arena,
+    location: &Vector3f,
+    velocity: &Vector3f,
+    defending: Team,
+) -> Option<(f32, [f32; 2])> {
+    let seconds = (goal_line(arena, defending) - location.y) / velocity.y;
+    if !(0.0..=SHOT_HORIZON).contains(&seconds) {
+        return None;
+    }
+    let x = location.x + velocity.x * seconds;
+    let z = location.z + velocity.z * seconds - GRAVITY * seconds * seconds / 2.0;
+    Some((seconds, [x, z]))
+}
+
+/// Whether a ball at `location` moving at `velocity` would cross the goal line of the goal
+/// `defending` guards between the posts and under the crossbar, following it under gravity alone.
+fn on_target(arena: &Arena, location: &Vector3f, velocity: &Vector3f, defending: Team) -> bool {
+    if arena.goal_width <= 0.0 {
+        return false;
+    }
+    crossing(arena, location, velocity, defending)
+        .is_some_and(|(_, [x, z])| x.abs() <= arena.goal_width as f32 / 2.0 && z <= GOAL_HEIGHT)
+}
+
+/// Picks the shots and saves out of `touches`. A shot is a touch after which the ball is on target
+/// for the other team's goal, and a save is the defending team's next touch after a shot, as long
+/// as the ball was still on target and the touch keeps it out.
+pub fn classify(arena: &Arena, touches: &[Touch]) -> Vec<Shot> {
+    let mut last_shot = None;
+    touches
+        .iter()
+        .filter_map(|touch| {
+            let opponent = match touch.team {
+                Team::Orange => Team::Blue,
+                Team::Blue => Team::Orange,
+            };
+            let answers_shot = last_shot.take() == Some(opponent);
+            let kind = if on_target(arena, &touch.location, &touch.velocity_after, opponent) {
+                last_shot = Some(touch.team);
+                ShotKind::Shot
+            } else if answers_shot
+                && on_target(arena, &touch.location, &touch.velocity_before, touch.team)
+                && !on_target(arena, &touch.location, &touch.velocity_after, touch.team)
+            {
+                ShotKind::Save
+            } else {
+                return None;
+            };
+            Some(Shot {
+                kind,
+                frame: touch.frame,
+                time: touch.time,
+                player: touch.player.clone(),
+                team: touch.team,
+                location: touch.location,
+                velocity: touch.velocity_after,
+                quality: None,
+            })
+        })
+        .collect()
+}
+
+/// Scores how likely `shot` was to go in, given where the defending team's cars were when it was
+/// taken. This is a hand-tuned logistic curve rather than a fitted model, so it's only good for
+/// comparing shots with each other.
+pub fn quality<'a>(
+    arena: &Arena,
+    shot: &Shot,
+    defenders: impl Iterator<Item = &'a RigidBody>,
+) -> f32 {
+    let defending = match shot.team {
+        Team::Orange => Team::Blue,
+        Team::Blue => Team::Orange,
+    };
+    let goal_y = goal_line(arena, defending);
+    let post = arena.goal_width as f32 / 2.0;
+    let ball = &shot.location;
+
+    let to_goal = [-ball.x, goal_y - ball.y];
+    let distance = to_goal[0].hypot(to_goal[1]).max(1.0);
+    // How much of the shooter's view the goal mouth takes up, which covers both distance and how
+    // wide of the goal the shot was taken from
+    let angle = ((post - ball.x).atan2((goal_y - ball.y).abs())
+        - (-post - ball.x).atan2((goal_y - ball.y).abs()))
+    .abs();
+    let speed = shot
+        .velocity
+        .x
+        .hypot(shot.velocity.y)
+        .hypot(shot.velocity.z);
+
+    // Defenders count as in the way when they're inside the cone from the ball to the goal mouth,
+    // including the goalkeeper standing in the goal itself
+    let depth = arena.goal_depth as f32;
+    let blockers = defenders
+        .filter(|car| {
+            let offset = [car.location.x - ball.x, car.location.y - ball.y];
+            let along = (offset[0] * to_goal[0] + offset[1] * to_goal[1]) / distance;
+            let across = (offset[0] * to_goal[1] - offset[1] * to_goal[0]).abs() / distance;
+            along > 0.0
+                && along <= distance + depth
+                && across <= post * (along / distance).min(1.0) + BLOCKING_REACH
+        })
+        .count();
+
+    let logit = -2.0 + 2.5 * angle - 0.25 * distance / 1000.0 + 0.5 * speed / 1000.0
+        - 1.2 * blockers as f32;
+    1.0 / (1.0 + (-logit).exp())
+}
+
+/// Every shot and save in the replay, in the order they happened, with each shot's quality filled
+/// in.
+pub fn shots(engine: &ReplayEngine) -> Vec<Shot> {
+    let mut shots = classify(engine.arena(), &touches::touches(engine));
+
+    let mut engine = engine.clone();
+    engine.seek(0);
+    for shot in shots.iter_mut().filter(|shot| shot.kind == ShotKind::Shot) {
+        engine.seek(shot.frame + 1);
+        let defenders = engine
+            .player_positions()
+            .filter(|(player, _)| player.team != shot.team)
+            .map(|(_, car)| car);
+        shot.quality = Some(quality(engine.arena(), shot, defenders));
+    }
+    shots
+}
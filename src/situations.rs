@@ -0,0 +1,116 @@
+use serde::Serialize;
+
+use crate::thirds::Third;
+use crate::{GamePhase, ReplayEngine, Team};
+
+/// Situations that last less than this many seconds are left out of `situation_log`, since a
+/// defender turning back past the ball leaves the net open for a moment all the time.
+const MIN_SITUATION_SECONDS: f32 = 0.5;
+
+/// A moment worth reviewing where one team is caught short at the back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SituationKind {
+    /// The ball is in the team's half with none of them between it and their goal.
+    OpenNet,
+    /// The ball is in the team's defensive third with more of the other team in there than them.
+    Outnumbered,
+}
+
+impl SituationKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            SituationKind::OpenNet => "Open net",
+            SituationKind::Outnumbered => "Outnumbered",
+        }
+    }
+}
+
+/// The situations each team is in right now, with the team caught short. There aren't any outside
+/// of live play.
+pub fn situations(engine: &ReplayEngine) -> Vec<(SituationKind, Team)> {
+    if !matches!(engine.phase(), GamePhase::Regulation | GamePhase::Overtime) {
+        return vec![];
+    }
+    let Some(ball) = engine.ball() else {
+        return vec![];
+    };
+    let arena = engine.arena();
+    let team = Team::defending(ball.location.y);
+
+    let mut situations = vec![];
+    let (mut defenders, mut behind_ball, mut attackers) = (0, 0, 0);
+    for (player, car) in engine.player_positions() {
+        let in_third = Third::at(arena, team, car.location.y) == Third::Defensive;
+        if player.team == team {
+            defenders += usize::from(in_third);
+            behind_ball +=
+                usize::from(team.forward(car.location.y) < team.forward(ball.location.y));
+        } else {
+            attackers += usize::from(in_third);
+        }
+    }
+    if behind_ball == 0 {
+        situations.push((SituationKind::OpenNet, team));
+    }
+    if Third::at(arena, team, ball.location.y) == Third::Defensive && attackers > defenders {
+        situations.push((SituationKind::Outnumbered, team));
+    }
+    situations
+}
+
+/// A stretch of frames one team spent in a situation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Situation {
+    pub kind: SituationKind,
+    /// The team caught short.
+    pub team: Team,
+    /// The first and last network frames it lasted for.
+    pub start_frame: usize,
+    pub end_frame: usize,
+    /// When it started, and how long it went on for.
+    pub time: f32,
+    pub seconds: f32,
+}
+
+/// Walks the whole replay, finding every time a team was caught short for at least
+/// `MIN_SITUATION_SECONDS`.
+pub fn situation_log(engine: &ReplayEngine) -> Vec<Situation> {
+    let mut engine = engine.clone();
+    engine.seek(0);
+
+    let mut log = vec![];
+    let mut open: Vec<Situation> = vec![];
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+        let frame = engine.frame_index() - 1;
+        let mut still_open = vec![];
+        for (kind, team) in situations(&engine) {
+            let situation = match open
+                .iter()
+                .position(|situation| situation.kind == kind && situation.team == team)
+            {
+                Some(index) => open.swap_remove(index),
+                None => Situation {
+                    kind,
+                    team,
+                    start_frame: frame,
+                    end_frame: frame,
+                    time: engine.time(),
+                    seconds: 0.0,
+                },
+            };
+            still_open.push(Situation {
+                end_frame: frame,
+                seconds: engine.time() - situation.time,
+                ..situation
+            });
+        }
+        // Whatever isn't going on in this frame is over
+        log.append(&mut open);
+        open = still_open;
+    }
+    log.extend(open);
+    log.retain(|situation| situation.seconds >= MIN_SITUATION_SECONDS);
+    log.sort_by_key(|situation| situation.start_frame);
+    log
+}
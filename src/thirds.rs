@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::arena::Arena;
+use crate::Team;
+
+/// Which third of the field a player is in, from their own team's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Third {
+    /// The third with the player's own goal in it.
+    Defensive,
+    Middle,
+    /// The third with the other team's goal in it.
+    Offensive,
+}
+
+impl Third {
+    /// The third a car at `y` is in, for a player on `team`.
+    pub fn at(arena: &Arena, team: Team, y: f32) -> Third {
+        let forward = team.forward(y) as f64;
+        let boundary = arena.height / 6.0;
+        if forward < -boundary {
+            Third::Defensive
+        } else if forward > boundary {
+            Third::Offensive
+        } else {
+            Third::Middle
+        }
+    }
+}
+
+/// How long each player has spent in each third of the field during live play.
+#[derive(Debug, Default, Clone)]
+pub struct Occupancy {
+    seconds: HashMap<String, (Team, [f32; 3])>,
+}
+
+impl Occupancy {
+    /// Fraction of each player's time they spent in their defensive, middle and offensive thirds,
+    /// sorted by team and then by name.
+    pub fn shares(&self) -> Vec<(&str, Team, [f64; 3])> {
+        let mut shares: Vec<_> = self
+            .seconds
+            .iter()
+            .map(|(name, (team, seconds))| {
+                let total: f32 = seconds.iter().sum();
+                let share = |seconds: f32| {
+                    if total > 0.0 {
+                        (seconds / total) as f64
+                    } else {
+                        0.0
+                    }
+                };
+                (name.as_str(), *team, seconds.map(share))
+            })
+            .collect();
+        shares.sort_by_key(|(name, team, _)| (*team == Team::Blue, name.to_string()));
+        shares
+    }
+
+    /// Counts `seconds` towards the third each player is in.
+    pub(crate) fn add_time(&mut self, players: Vec<(String, Team, Third)>, seconds: f32) {
+        for (name, team, third) in players {
+            let (player_team, thirds) = self.seconds.entry(name).or_insert((team, [0.0; 3]));
+            *player_team = team;
+            thirds[third as usize] += seconds;
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::mem;
+
+use boxcars::RigidBody;
+
+use crate::arena::Arena;
+use crate::thirds::Third;
+use crate::zones::{self, ZoneControl};
+use crate::{FrameState, FrameSummary, GamePhase, PlayerPosition, ReplayEngine, Score, Team};
+
+/// Where everything was on every frame of a replay, worked out in one pass so any frame can be
+/// looked up again without stepping through the frames before it.
+#[derive(Clone)]
+pub struct Timeline {
+    arena: Arena,
+    players: Vec<TimelinePlayer>,
+    frames: Vec<TimelineFrame>,
+    /// The engine as it was on every keyframe, taken while stepping through, for `seek` to start
+    /// from.
+    snapshots: Vec<FrameState>,
+}
+
+/// A player on the timeline, which its cars refer to by index so names aren't repeated every frame.
+#[derive(Debug, Clone)]
+pub struct TimelinePlayer {
+    pub name: String,
+    /// The player's color the last time their car was seen.
+    pub color: [f32; 4],
+}
+
+/// One processed network frame.
+#[derive(Debug, Clone)]
+pub struct TimelineFrame {
+    /// The network frame's index. Goal replays skipped by the engine leave gaps between these.
+    pub frame: usize,
+    pub time: f32,
+    pub cars: Vec<TimelineCar>,
+    pub ball: Option<RigidBody>,
+    /// The team that last touched the ball.
+    pub possession: Option<Team>,
+    pub zone_control: Option<ZoneControl>,
+    pub score: Score,
+    pub phase: GamePhase,
+}
+
+#[derive(Debug, Clone)]
+pub struct TimelineCar {
+    /// Index into `Timeline::players`.
+    pub player: usize,
+    pub team: Team,
+    pub body: RigidBody,
+    pub boost: Option<f32>,
+}
+
+impl Timeline {
+    /// Steps a copy of `engine` through the rest of the replay from where it is, which is all of it
+    /// for a freshly opened one, recording every frame.
+    pub fn build(engine: &ReplayEngine) -> Self {
+        let mut engine = engine.clone();
+        let mut timeline = Self {
+            arena: *engine.arena(),
+            players: vec![],
+            frames: Vec::with_capacity(engine.frame_count() - engine.frame_index()),
+            snapshots: vec![],
+        };
+
+        while engine.frame_index() < engine.frame_count() {
+            engine.step();
+            let cars = engine
+                .player_positions()
+                .map(|(player, body)| TimelineCar {
+                    player: timeline.player_index(&player.name, player.color),
+                    team: player.team,
+                    body: *body,
+                    boost: player.car_actor_id.and_then(|car| engine.boost(car)),
+                })
+                .collect();
+            timeline.frames.push(TimelineFrame {
+                frame: engine.frame_index() - 1,
+                time: engine.time(),
+                cars,
+                ball: engine.ball(),
+                possession: engine.possession().team(),
+                zone_control: zones::zone_control(&engine),
+                score: engine.score(),
+                phase: engine.phase(),
+            });
+        }
+        timeline.snapshots = engine.snapshots;
+        timeline
+    }
+
+    fn player_index(&mut self, name: &str, color: [f32; 4]) -> usize {
+        match self.players.iter().position(|player| player.name == name) {
+            Some(index) => {
+                self.players[index].color = color;
+                index
+            }
+            None => {
+                self.players.push(TimelinePlayer {
+                    name: name.to_string(),
+                    color,
+                });
+                self.players.len() - 1
+            }
+        }
+    }
+
+    pub fn arena(&self) -> &Arena {
+        &self.arena
+    }
+
+    pub fn players(&self) -> &[TimelinePlayer] {
+        &self.players
+    }
+
+    pub fn frames(&self) -> &[TimelineFrame] {
+        &self.frames
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The last recorded frame at or before network frame `frame`, or `None` before the first.
+    pub fn at(&self, frame: usize) -> Option<&TimelineFrame> {
+        let recorded = self.frames.partition_point(|entry| entry.frame <= frame);
+        self.frames.get(recorded.checked_sub(1)?)
+    }
+
+    /// Moves `engine`, the one the timeline was built from, on or back to network frame `frame` the
+    /// same as `ReplayEngine::seek`, but starting from the last keyframe before it either way, so
+    /// seeking ahead doesn't step through every frame in between either.
+    pub fn seek(&self, engine: &mut ReplayEngine, frame: usize) {
+        let frame = frame.min(engine.frame_count());
+        let nearest = self
+            .snapshots
+            .partition_point(|snapshot| snapshot.frame_index <= frame);
+        if let Some(snapshot) = nearest.checked_sub(1).map(|index| &self.snapshots[index]) {
+            if frame < engine.frame_index() || snapshot.frame_index > engine.frame_index() {
+                engine.state = snapshot.clone();
+            }
+        }
+        engine.seek(frame);
+    }
+
+    /// The same summary `ReplayEngine::summary` gives for a frame, rebuilt from the timeline.
+    pub fn summary(&self, entry: &TimelineFrame) -> FrameSummary {
+        FrameSummary {
+            frame: entry.frame,
+            time: entry.time,
+            players: entry
+                .cars
+                .iter()
+                .map(|car| PlayerPosition {
+                    name: self.players[car.player].name.clone(),
+                    team: car.team,
+                    location: car.body.location,
+                    boost: car.boost,
+                    third: Third::at(&self.arena, car.team, car.body.location.y),
+                })
+                .collect(),
+            ball: entry.ball.map(|ball| ball.location),
+            zone_control: entry.zone_control,
+            spread: entry.ball.and_then(|ball| {
+                let cars = entry.cars.iter().map(|car| (car.team, car.body.location));
+                zones::spread(cars, ball.location)
+            }),
+            score: entry.score,
+            phase: entry.phase,
+        }
+    }
+
+    /// Roughly how many bytes the timeline takes up, counting what its frames and names point to.
+    /// Snapshots are only counted by their own size, not the actors and stats they hold.
+    pub fn heap_size(&self) -> usize {
+        let players: usize = self
+            .players
+            .iter()
+            .map(|player| mem::size_of::<TimelinePlayer>() + player.name.capacity())
+            .sum();
+        let frames: usize = self
+            .frames
+            .iter()
+            .map(|entry| entry.cars.capacity() * mem::size_of::<TimelineCar>())
+            .sum();
+        mem::size_of::<Self>()
+            + players
+            + self.frames.capacity() * mem::size_of::<TimelineFrame>()
+            + frames
+            + self.snapshots.capacity() * mem::size_of::<FrameState>()
+    }
+}
@@ -0,0 +1,145 @@
+use boxcars::{RigidBody, Vector3f};
+use serde::Serialize;
+
+use crate::{ReplayEngine, Team};
+
+/// How close a car has to be to the ball to touch it. Measured between centers, so it's a little
+/// more than a ball and a car's reach, with some slack for car positions lagging a frame behind.
+const TOUCH_DISTANCE: f32 = 300.0;
+
+/// How much the ball's velocity has to change from one frame to the next for a car next to it to
+/// count as touching it. Gravity alone changes it by around 20 uu/s a frame.
+const MIN_VELOCITY_CHANGE: f32 = 250.0;
+
+/// Touches by the same player closer together than this are counted as one, since a car can push
+/// the ball along over several frames.
+const TOUCH_GAP: f32 = 0.2;
+
+/// A player hitting the ball.
+#[derive(Debug, Clone, Serialize)]
+pub struct Touch {
+    /// Network frame the touch was seen on.
+    pub frame: usize,
+    pub time: f32,
+    pub player: String,
+    pub team: Team,
+    /// Where the ball was when it was touched.
+    pub location: Vector3f,
+    /// The ball's velocity just before and just after the touch, in uu/s.
+    pub velocity_before: Vector3f,
+    pub velocity_after: Vector3f,
+}
+
+impl Touch {
+    pub fn speed_before(&self) -> f32 {
+        length(&self.velocity_before)
+    }
+
+    pub fn speed_after(&self) -> f32 {
+        length(&self.velocity_after)
+    }
+}
+
+fn length(v: &Vector3f) -> f32 {
+    (v.x * v.x + v.y * v.y + v.z * v.z).sqrt()
+}
+
+/// Whatever belongs to the closest car within touching distance of the ball, if any are.
+pub(crate) fn nearest_to_ball<'a, T>(
+    ball: &RigidBody,
+    cars: impl Iterator<Item = (T, &'a RigidBody)>,
+) -> Option<T> {
+    let distance = |car: &RigidBody| {
+        length(&Vector3f {
+            x: car.location.x - ball.location.x,
+            y: car.location.y - ball.location.y,
+            z: car.location.z - ball.location.z,
+        })
+    };
+    cars.map(|(owner, car)| (owner, distance(car)))
+        .filter(|(_, distance)| *distance <= TOUCH_DISTANCE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(owner, _)| owner)
+}
+
+/// What `TouchDetector` made of a frame.
+pub(crate) enum TouchUpdate {
+    /// A player started touching the ball.
+    Touch(Touch),
+    /// The player who last touched the ball is still pushing it along, and it's now going this fast.
+    Continued(Vector3f),
+}
+
+/// Tells touches apart frame by frame. A touch is the ball suddenly changing velocity with a car
+/// right next to it, which leaves out bounces off the walls and floor.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TouchDetector {
+    last_velocity: Option<Vector3f>,
+    // Who touched the ball last and when, to merge a push over several frames into one touch
+    last_touch: Option<(String, f32)>,
+}
+
+impl TouchDetector {
+    /// Looks at the frame `engine` last processed.
+    pub(crate) fn update(&mut self, engine: &ReplayEngine) -> Option<TouchUpdate> {
+        let Some(ball) = engine.ball() else {
+            self.last_velocity = None;
+            return None;
+        };
+        // A ball sitting still, like at kickoff, has no velocity at all
+        let velocity = ball.linear_velocity.unwrap_or(Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        });
+        let before = self.last_velocity.replace(velocity)?;
+
+        let change = length(&Vector3f {
+            x: velocity.x - before.x,
+            y: velocity.y - before.y,
+            z: velocity.z - before.z,
+        });
+        if change < MIN_VELOCITY_CHANGE {
+            return None;
+        }
+        let player = nearest_to_ball(&ball, engine.player_positions())?;
+
+        if let Some((name, time)) = &self.last_touch {
+            if *name == player.name && engine.time() - time < TOUCH_GAP {
+                return Some(TouchUpdate::Continued(velocity));
+            }
+        }
+        self.last_touch = Some((player.name.clone(), engine.time()));
+        Some(TouchUpdate::Touch(Touch {
+            frame: engine.frame_index() - 1,
+            time: engine.time(),
+            player: player.name.clone(),
+            team: player.team,
+            location: ball.location,
+            velocity_before: before,
+            velocity_after: velocity,
+        }))
+    }
+}
+
+/// Walks the whole replay, finding every time a player touched the ball.
+pub fn touches(engine: &ReplayEngine) -> Vec<Touch> {
+    let mut engine = engine.clone();
+    engine.seek(0);
+
+    let mut touches: Vec<Touch> = vec![];
+    let mut detector = TouchDetector::default();
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+        match detector.update(&engine) {
+            Some(TouchUpdate::Touch(touch)) => touches.push(touch),
+            Some(TouchUpdate::Continued(velocity)) => {
+                if let Some(last) = touches.last_mut() {
+                    last.velocity_after = velocity;
+                }
+            }
+            None => {}
+        }
+    }
+    touches
+}
@@ -0,0 +1,596 @@
+use boxcars::{ActorId, RigidBody, Vector3f};
+use serde::Serialize;
+use voronoice::{BoundingBox, Point, VoronoiBuilder};
+
+use crate::arena::Arena;
+use crate::{PlayerDetails, ReplayEngine, Team};
+
+/// Share of the field, between 0 and 1, that is closer to each team than to the other.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct ZoneControl {
+    pub orange: f64,
+    pub blue: f64,
+}
+
+/// How far, in unreal units, any car can move before the voronoi diagram is built again. This is
+/// under a pixel even zoomed all the way in, so the cells drawn never lag behind the dots.
+const SITE_TOLERANCE: f64 = 1.0;
+
+/// The last voronoi diagram built for an engine and the car positions it was built from, so
+/// drawing and counting up the same frame several times, or a frame where nobody moved, builds it
+/// only once.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CellCache {
+    sites: Vec<(ActorId, [f64; 2])>,
+    cells: Vec<(ActorId, Vec<[f64; 2]>)>,
+}
+
+impl CellCache {
+    fn matches(&self, sites: &[(ActorId, [f64; 2])]) -> bool {
+        self.sites.len() == sites.len()
+            && sites.iter().all(|(car, [x, y])| {
+                self.sites.iter().any(|(cached, [cached_x, cached_y])| {
+                    cached == car && (x - cached_x).hypot(y - cached_y) <= SITE_TOLERANCE
+                })
+            })
+    }
+
+    fn build(arena: &Arena, sites: Vec<(ActorId, [f64; 2])>) -> Self {
+        let points = sites.iter().map(|&(_, [x, y])| Point { x, y }).collect();
+        let cells = VoronoiBuilder::default()
+            .set_sites(points)
+            .set_bounding_box(BoundingBox::new_centered(arena.width, arena.height))
+            .build()
+            .map(|voronoi| {
+                voronoi
+                    .iter_cells()
+                    .map(|cell| {
+                        let vertices = cell.iter_vertices().map(|p| [p.x, p.y]).collect();
+                        (sites[cell.site()].0, vertices)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { sites, cells }
+    }
+}
+
+/// Area of a simple polygon using the shoelace formula.
+pub fn polygon_area(vertices: &[[f64; 2]]) -> f64 {
+    let mut sum = 0.0;
+    for (i, a) in vertices.iter().enumerate() {
+        let b = vertices[(i + 1) % vertices.len()];
+        sum += a[0] * b[1] - b[0] * a[1];
+    }
+    sum.abs() / 2.0
+}
+
+/// Where a car sits in the diagram, clamped onto the field.
+fn site(arena: &Arena, body: &RigidBody) -> [f64; 2] {
+    arena.clamp(body.location.x as f64, body.location.y as f64)
+}
+
+/// The voronoi cell of every player with a known position, in field coordinates.
+///
+/// Cars inside the goals sit outside the field rectangle, so sites are clamped onto it to keep
+/// every player's cell. The diagram is reused until a car moves more than `SITE_TOLERANCE`.
+pub fn player_cells(engine: &ReplayEngine) -> Vec<(&PlayerDetails, Vec<[f64; 2]>)> {
+    let arena = engine.arena();
+    let players: Vec<_> = engine
+        .player_positions()
+        .filter_map(|(player, body)| Some((player.car_actor_id?, player, site(arena, body))))
+        .collect();
+    let sites: Vec<_> = players.iter().map(|&(car, _, site)| (car, site)).collect();
+
+    let mut cache = engine.cell_cache().borrow_mut();
+    if !cache.matches(&sites) {
+        *cache = CellCache::build(arena, sites);
+    }
+    cache
+        .cells
+        .iter()
+        .filter_map(|(car, vertices)| {
+            let (_, player, _) = players.iter().find(|(other, ..)| other == car)?;
+            Some((*player, vertices.clone()))
+        })
+        .collect()
+}
+
+/// Edges of the voronoi diagram with an orange player on one side and a blue player on the other,
+/// which together trace the line between the space each team controls.
+pub fn contested_edges(engine: &ReplayEngine) -> Vec<[[f64; 2]; 2]> {
+    let arena = engine.arena();
+    let cells = player_cells(engine);
+    let team_at = |p: [f64; 2]| {
+        engine
+            .player_positions()
+            .map(|(player, body)| {
+                let [x, y] = site(arena, body);
+                (player.team, (x - p[0]).powi(2) + (y - p[1]).powi(2))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(team, _)| team)
+    };
+
+    let mut edges = vec![];
+    for (player, vertices) in cells
+        .iter()
+        .filter(|(player, _)| player.team == Team::Orange)
+    {
+        for (i, &a) in vertices.iter().enumerate() {
+            let b = vertices[(i + 1) % vertices.len()];
+            let middle = [(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0];
+            let center = polygon_center(vertices);
+
+            // Step just across the edge, away from this cell, to see who owns the other side
+            let outward = [middle[0] - center[0], middle[1] - center[1]];
+            let length = (outward[0].powi(2) + outward[1].powi(2)).sqrt();
+            if length == 0.0 {
+                continue;
+            }
+            let beyond = [
+                middle[0] + outward[0] / length,
+                middle[1] + outward[1] / length,
+            ];
+            if arena.contains(beyond[0], beyond[1])
+                && team_at(beyond).is_some_and(|team| team != player.team)
+            {
+                edges.push([a, b]);
+            }
+        }
+    }
+    edges
+}
+
+/// Average of a polygon's vertices, which is always inside it for the convex voronoi cells.
+fn polygon_center(vertices: &[[f64; 2]]) -> [f64; 2] {
+    let count = vertices.len() as f64;
+    let (x, y) = vertices
+        .iter()
+        .fold((0.0, 0.0), |(x, y), p| (x + p[0], y + p[1]));
+    [x / count, y / count]
+}
+
+/// Distance, in unreal units, between the points checked along a passing lane.
+const LANE_STEP: f64 = 100.0;
+
+/// A straight line along the floor between two teammates that the ball could be passed down.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PassingLane {
+    pub team: Team,
+    pub from: [f64; 2],
+    pub to: [f64; 2],
+    /// Share of the line, between 0 and 1, that runs through the team's own space instead of an
+    /// opponent's voronoi cell. A lane at 1 can't be cut off by anyone getting there first.
+    pub open: f64,
+}
+
+/// Every lane between two teammates with known positions, with how open each one is.
+pub fn passing_lanes(engine: &ReplayEngine) -> Vec<PassingLane> {
+    let arena = engine.arena();
+    let sites: Vec<_> = engine
+        .player_positions()
+        .map(|(player, body)| (player.team, site(arena, body)))
+        .collect();
+    lanes_between(&sites)
+}
+
+/// The lanes between every pair of teammates among `sites`, each checked against whichever site
+/// is closest along it.
+fn lanes_between(sites: &[(Team, [f64; 2])]) -> Vec<PassingLane> {
+    let team_at = |p: [f64; 2]| {
+        sites
+            .iter()
+            .map(|(team, site)| (*team, (site[0] - p[0]).powi(2) + (site[1] - p[1]).powi(2)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(team, _)| team)
+    };
+
+    let mut lanes = vec![];
+    for (i, &(team, from)) in sites.iter().enumerate() {
+        for &(_, to) in sites[i + 1..].iter().filter(|(other, _)| *other == team) {
+            let length = ((to[0] - from[0]).powi(2) + (to[1] - from[1]).powi(2)).sqrt();
+            let steps = (length / LANE_STEP).ceil().max(1.0) as usize;
+            let owned = (0..=steps)
+                .filter(|&step| {
+                    let t = step as f64 / steps as f64;
+                    let point = [
+                        from[0] + (to[0] - from[0]) * t,
+                        from[1] + (to[1] - from[1]) * t,
+                    ];
+                    team_at(point) == Some(team)
+                })
+                .count();
+            lanes.push(PassingLane {
+                team,
+                from,
+                to,
+                open: owned as f64 / (steps + 1) as f64,
+            });
+        }
+    }
+    lanes
+}
+
+/// Seconds of driving at a player's current speed that count towards their reach.
+const REACH_SECONDS: f64 = 0.5;
+/// Extra reach, in unreal units, that a full tank of boost is worth.
+const FULL_BOOST_REACH: f64 = 800.0;
+
+/// How far a player can get in a short time, given how fast they are already moving and how much
+/// boost they have left to accelerate with.
+pub fn reach(body: &RigidBody, boost: Option<f32>) -> f64 {
+    let speed = body.linear_velocity.map_or(0.0, |v| {
+        ((v.x as f64).powi(2) + (v.y as f64).powi(2)).sqrt()
+    });
+    speed * REACH_SECONDS + boost.unwrap_or(0.0) as f64 * FULL_BOOST_REACH
+}
+
+/// Keeps the part of a convex polygon where `normal · p <= offset`.
+fn clip(vertices: &[[f64; 2]], normal: [f64; 2], offset: f64) -> Vec<[f64; 2]> {
+    let inside = |p: [f64; 2]| normal[0] * p[0] + normal[1] * p[1] <= offset;
+    let mut clipped = vec![];
+    for (i, &a) in vertices.iter().enumerate() {
+        let b = vertices[(i + 1) % vertices.len()];
+        if inside(a) {
+            clipped.push(a);
+        }
+        if inside(a) != inside(b) {
+            let da = normal[0] * a[0] + normal[1] * a[1] - offset;
+            let db = normal[0] * b[0] + normal[1] * b[1] - offset;
+            let t = da / (da - db);
+            clipped.push([a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]);
+        }
+    }
+    clipped
+}
+
+/// Like `player_cells`, but each cell is grown or shrunk by the player's `reach`, so a player
+/// flying in with boost claims more space than one sitting still.
+///
+/// This is a power diagram: a point belongs to whoever minimizes distance squared minus reach
+/// squared, which keeps every cell a convex polygon.
+pub fn weighted_player_cells(engine: &ReplayEngine) -> Vec<(&PlayerDetails, Vec<[f64; 2]>)> {
+    let arena = engine.arena();
+    let players: Vec<_> = engine
+        .player_positions()
+        .map(|(player, body)| {
+            let boost = player.car_actor_id.and_then(|car| engine.boost(car));
+            (player, (site(arena, body), reach(body, boost).powi(2)))
+        })
+        .collect();
+    let sites: Vec<_> = players.iter().map(|&(_, site)| site).collect();
+
+    players
+        .iter()
+        .zip(power_cells(arena, &sites))
+        .filter_map(|(&(player, _), cell)| (cell.len() >= 3).then_some((player, cell)))
+        .collect()
+}
+
+/// The cell of the power diagram around each of `sites`, given as a point and its weight, cut to
+/// the field. A site outweighed by its neighbors can be left with no cell at all.
+fn power_cells(arena: &Arena, sites: &[([f64; 2], f64)]) -> Vec<Vec<[f64; 2]>> {
+    let (half_width, half_height) = (arena.width / 2.0, arena.height / 2.0);
+    let field = vec![
+        [-half_width, -half_height],
+        [half_width, -half_height],
+        [half_width, half_height],
+        [-half_width, half_height],
+    ];
+
+    sites
+        .iter()
+        .map(|&(a, weight_a)| {
+            let mut cell = field.clone();
+            for &(b, weight_b) in sites {
+                let normal = [2.0 * (b[0] - a[0]), 2.0 * (b[1] - a[1])];
+                // The same site, or another car in exactly the same spot
+                if normal == [0.0, 0.0] {
+                    continue;
+                }
+                let offset =
+                    b[0] * b[0] + b[1] * b[1] - a[0] * a[0] - a[1] * a[1] + weight_a - weight_b;
+                cell = clip(&cell, normal, offset);
+            }
+            cell
+        })
+        .collect()
+}
+
+/// Share of the field, between 0 and 1, in each player's voronoi cell.
+pub fn player_control(engine: &ReplayEngine) -> Vec<(&PlayerDetails, f64)> {
+    let field_area = engine.arena().area();
+    player_cells(engine)
+        .into_iter()
+        .map(|(player, vertices)| (player, polygon_area(&vertices) / field_area))
+        .collect()
+}
+
+/// How much of the field each team controls right now, or `None` if there are too few players
+/// on the field to build a diagram.
+pub fn zone_control(engine: &ReplayEngine) -> Option<ZoneControl> {
+    let shares = player_control(engine);
+    if shares.is_empty() {
+        return None;
+    }
+
+    let mut control = ZoneControl::default();
+    for (player, share) in shares {
+        match player.team {
+            Team::Orange => control.orange += share,
+            Team::Blue => control.blue += share,
+        }
+    }
+
+    Some(control)
+}
+
+/// The area a team's cars cover between them, to see how compact or spread out they are.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TeamShape {
+    pub team: Team,
+    /// Corners of the convex hull around the team's cars, in field coordinates going around it.
+    /// A team of three always makes a triangle, and one or two cars make a point or a line.
+    pub hull: Vec<[f64; 2]>,
+    /// The middle of the team's cars.
+    pub center: [f64; 2],
+}
+
+/// Each team's shape on the field right now, for the teams with any cars on it.
+pub fn team_shapes(engine: &ReplayEngine) -> Vec<TeamShape> {
+    [Team::Orange, Team::Blue]
+        .into_iter()
+        .filter_map(|team| {
+            let cars: Vec<_> = engine
+                .player_positions()
+                .filter(|(player, _)| player.team == team)
+                .map(|(_, body)| [body.location.x as f64, body.location.y as f64])
+                .collect();
+            if cars.is_empty() {
+                return None;
+            }
+            Some(TeamShape {
+                team,
+                center: polygon_center(&cars),
+                hull: convex_hull(cars),
+            })
+        })
+        .collect()
+}
+
+/// The smallest convex polygon around `points`, by Andrew's monotone chain, going around it
+/// counterclockwise without repeating its first corner.
+fn convex_hull(mut points: Vec<[f64; 2]>) -> Vec<[f64; 2]> {
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    // Positive when going from `o` to `a` to `b` turns left
+    let turn = |o: [f64; 2], a: [f64; 2], b: [f64; 2]| {
+        (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+    };
+
+    let mut hull: Vec<[f64; 2]> = vec![];
+    // The lower half left to right, then the upper half back, each dropping corners that turn in
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2
+                && turn(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // The last point of each half is the first of the other
+        hull.pop();
+    }
+    hull
+}
+
+/// How spread out a team's cars are, in unreal units along the floor.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct Spacing {
+    /// Average distance between each pair of teammates, or 0 for a team of one.
+    pub teammates: f64,
+    /// Average distance from each of the team's cars to the ball.
+    pub ball: f64,
+}
+
+/// How spread out each team is on one frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct TeamSpread {
+    pub orange: Spacing,
+    pub blue: Spacing,
+}
+
+impl TeamSpread {
+    pub fn team(&self, team: Team) -> Spacing {
+        match team {
+            Team::Orange => self.orange,
+            Team::Blue => self.blue,
+        }
+    }
+}
+
+/// How spread out each team is right now, or `None` without a ball or a car on each team.
+pub fn team_spread(engine: &ReplayEngine) -> Option<TeamSpread> {
+    let ball = engine.ball()?;
+    spread(
+        engine
+            .player_positions()
+            .map(|(player, body)| (player.team, body.location)),
+        ball.location,
+    )
+}
+
+/// `team_spread` from where each team's cars and the ball are, for the timeline to work it out
+/// without an engine.
+pub(crate) fn spread(
+    cars: impl IntoIterator<Item = (Team, Vector3f)>,
+    ball: Vector3f,
+) -> Option<TeamSpread> {
+    let floor = |location: Vector3f| [location.x as f64, location.y as f64];
+    let distance = |a: [f64; 2], b: [f64; 2]| (a[0] - b[0]).hypot(a[1] - b[1]);
+    let cars: Vec<_> = cars
+        .into_iter()
+        .map(|(team, location)| (team, floor(location)))
+        .collect();
+    let ball = floor(ball);
+
+    let spacing = |team: Team| {
+        let cars: Vec<_> = cars
+            .iter()
+            .filter(|(other, _)| *other == team)
+            .map(|(_, car)| *car)
+            .collect();
+        if cars.is_empty() {
+            return None;
+        }
+        let pairs: Vec<_> = cars
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &a)| cars[i + 1..].iter().map(move |&b| distance(a, b)))
+            .collect();
+        Some(Spacing {
+            teammates: pairs.iter().sum::<f64>() / pairs.len().max(1) as f64,
+            ball: cars.iter().map(|&car| distance(car, ball)).sum::<f64>() / cars.len() as f64,
+        })
+    };
+    Some(TeamSpread {
+        orange: spacing(Team::Orange)?,
+        blue: spacing(Team::Blue)?,
+    })
+}
+
+/// Which way play is leaning, as two measures between -1 and 1 that are positive when orange is
+/// pressing into blue's half and negative when blue is pressing into orange's.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct FieldTilt {
+    /// How far the ball is up the field, from orange's back wall at -1 to blue's at 1.
+    pub ball: f64,
+    /// How much more of the field orange controls than blue.
+    pub territory: f64,
+}
+
+impl FieldTilt {
+    /// Both measures averaged into one.
+    pub fn pressure(&self) -> f64 {
+        (self.ball + self.territory) / 2.0
+    }
+}
+
+/// The field tilt right now, or `None` without a ball or enough players to build a diagram.
+pub fn field_tilt(engine: &ReplayEngine) -> Option<FieldTilt> {
+    let ball = engine.ball()?;
+    let control = zone_control(engine)?;
+    Some(FieldTilt {
+        ball: (Team::Orange.forward(ball.location.y) as f64 / (engine.arena().height / 2.0))
+            .clamp(-1.0, 1.0),
+        territory: control.orange - control.blue,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARE: [[f64; 2]; 4] = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]];
+
+    #[test]
+    fn clipping_keeps_the_part_of_a_polygon_inside_the_line() {
+        let left = clip(&SQUARE, [1.0, 0.0], 0.0);
+        assert_eq!(left, [[-1.0, -1.0], [0.0, -1.0], [0.0, 1.0], [-1.0, 1.0]]);
+        assert_eq!(clip(&SQUARE, [1.0, 0.0], 2.0), SQUARE);
+        assert!(clip(&SQUARE, [1.0, 0.0], -2.0).is_empty());
+    }
+
+    #[test]
+    fn equal_weights_split_the_field_halfway_between_sites() {
+        let arena = Arena::STANDARD;
+        let cells = power_cells(&arena, &[([-1000.0, 0.0], 0.0), ([1000.0, 0.0], 0.0)]);
+        for cell in &cells {
+            assert!((polygon_area(cell) - arena.area() / 2.0).abs() < 1e-6);
+        }
+        assert!(cells[0].iter().all(|[x, _]| *x <= 0.0));
+    }
+
+    #[test]
+    fn a_heavier_site_pushes_the_boundary_toward_its_neighbor() {
+        let cells = power_cells(
+            &Arena::STANDARD,
+            &[([-1000.0, 0.0], 500.0_f64.powi(2)), ([1000.0, 0.0], 0.0)],
+        );
+        // Where (x + 1000)² - 500² = (x - 1000)²
+        let edge = cells[0].iter().map(|[x, _]| *x).fold(f64::MIN, f64::max);
+        assert!((edge - 62.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn coincident_sites_share_the_same_cell() {
+        let arena = Arena::STANDARD;
+        let cells = power_cells(
+            &arena,
+            &[([0.0, 0.0], 0.0), ([0.0, 0.0], 0.0), ([2000.0, 0.0], 0.0)],
+        );
+        assert_eq!(cells[0], cells[1]);
+        assert!(cells[0].iter().flatten().all(|value| value.is_finite()));
+        let expected = (1000.0 + arena.width / 2.0) * arena.height;
+        assert!((polygon_area(&cells[0]) - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn a_site_outweighed_by_its_neighbor_gets_no_cell() {
+        let cells = power_cells(&Arena::STANDARD, &[([0.0, 0.0], 0.0), ([10.0, 0.0], 1e8)]);
+        assert!(cells[0].is_empty());
+        assert!((polygon_area(&cells[1]) - Arena::STANDARD.area()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn the_hull_leaves_out_inner_and_repeated_points() {
+        let hull = convex_hull(vec![
+            [2.0, 2.0],
+            [1.0, 1.0],
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [0.0, 2.0],
+            [2.0, 0.0],
+        ]);
+        assert_eq!(hull, [[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0]]);
+    }
+
+    #[test]
+    fn the_hull_of_collinear_points_is_its_two_ends() {
+        let hull = convex_hull(vec![[0.0, 0.0], [3.0, 3.0], [1.0, 1.0], [2.0, 2.0]]);
+        assert_eq!(hull, [[0.0, 0.0], [3.0, 3.0]]);
+        assert_eq!(convex_hull(vec![[1.0, 1.0], [1.0, 1.0]]), [[1.0, 1.0]]);
+    }
+
+    #[test]
+    fn lanes_only_join_teammates() {
+        let lanes = lanes_between(&[
+            (Team::Orange, [0.0, 0.0]),
+            (Team::Blue, [3000.0, 0.0]),
+            (Team::Orange, [0.0, 1000.0]),
+            (Team::Blue, [3000.0, 1000.0]),
+        ]);
+        assert_eq!(lanes.len(), 2);
+        assert_eq!(lanes[0].team, Team::Orange);
+        assert_eq!(lanes[1].team, Team::Blue);
+        assert!(lanes.iter().all(|lane| lane.open == 1.0));
+    }
+
+    #[test]
+    fn an_opponent_in_the_middle_of_a_lane_closes_part_of_it() {
+        let lanes = lanes_between(&[
+            (Team::Orange, [0.0, 0.0]),
+            (Team::Orange, [0.0, 1000.0]),
+            (Team::Blue, [0.0, 500.0]),
+        ]);
+        assert_eq!(lanes.len(), 1);
+        // Points 0 to 200 and 800 to 1000 along it are closer to one of the two ends
+        assert_eq!(lanes[0].open, 6.0 / 11.0);
+    }
+}
@@ -0,0 +1,555 @@
+//! Known facts about a short 3v3 replay bundled in `replays/`, checked against what the engine
+//! works out from its network frames, so a change to how actors are tracked that gets any of them
+//! wrong fails here instead of going unnoticed on screen.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use boxcars::HeaderProp;
+use rl_replay_zone_visualizer::arena::Arena;
+use rl_replay_zone_visualizer::coverage;
+use rl_replay_zone_visualizer::events::{self, EventKind, ReplayEvent};
+use rl_replay_zone_visualizer::kickoffs::{self, Spot};
+use rl_replay_zone_visualizer::movement::AirState;
+use rl_replay_zone_visualizer::rotation::{self, Rule};
+use rl_replay_zone_visualizer::shots::{self, ShotKind};
+use rl_replay_zone_visualizer::situations::{self, SituationKind};
+use rl_replay_zone_visualizer::timeline::Timeline;
+use rl_replay_zone_visualizer::touches;
+use rl_replay_zone_visualizer::zones;
+use rl_replay_zone_visualizer::{parse_replay, FrameSummary, GamePhase, ReplayEngine, Team};
+
+const SAMPLE_REPLAY: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/replays/97d29586-530f-484e-aa0b-ac8ad8b54217.replay"
+);
+
+fn engine() -> ReplayEngine {
+    ReplayEngine::new(parse_replay(Path::new(SAMPLE_REPLAY)).expect("the sample replay parses"))
+}
+
+/// The summary of network frame `frame`, with players in name order.
+fn summary_at(engine: &mut ReplayEngine, frame: usize) -> FrameSummary {
+    engine.seek(frame + 1);
+    let mut summary = engine.summary();
+    summary.players.sort_by(|a, b| a.name.cmp(&b.name));
+    summary
+}
+
+/// Each player's name, team and location as `(x, y, z)`, in name order.
+fn positions(summary: &FrameSummary) -> Vec<(&str, Team, (f32, f32, f32))> {
+    summary
+        .players
+        .iter()
+        .map(|player| {
+            let location = player.location;
+            (
+                player.name.as_str(),
+                player.team,
+                (location.x, location.y, location.z),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn header() {
+    let engine = engine();
+    assert_eq!(engine.frame_count(), 2851);
+    assert_eq!(engine.record_fps(), 30.0);
+    assert_eq!(
+        engine.match_info().description(),
+        "Blue 0 - 1 Orange, 3v3 Private on EuroStadium_Night_P"
+    );
+}
+
+#[test]
+fn players_are_on_their_teams_from_the_first_frame() {
+    let mut engine = engine();
+    let summary = summary_at(&mut engine, 0);
+    let teams: Vec<_> = summary
+        .players
+        .iter()
+        .map(|player| (player.name.as_str(), player.team))
+        .collect();
+    assert_eq!(
+        teams,
+        [
+            ("Lich", Team::Orange),
+            ("Private Boi", Team::Orange),
+            ("Profile", Team::Orange),
+            ("muffinoss", Team::Blue),
+            ("popo", Team::Blue),
+            ("丶Ej", Team::Blue),
+        ]
+    );
+}
+
+#[test]
+fn cars_start_in_their_kickoff_spots() {
+    let mut engine = engine();
+    let summary = summary_at(&mut engine, 0);
+    assert_eq!(summary.phase, GamePhase::DeadBall);
+    assert_eq!(
+        positions(&summary),
+        [
+            ("Lich", Team::Orange, (256.0, 3840.0, 17.01)),
+            ("Private Boi", Team::Orange, (2048.0, 2560.0, 17.01)),
+            ("Profile", Team::Orange, (-256.0, 3840.0, 17.01)),
+            ("muffinoss", Team::Blue, (-256.0, -3840.0, 17.01)),
+            ("popo", Team::Blue, (256.0, -3840.0, 17.01)),
+            ("丶Ej", Team::Blue, (-2048.0, -2560.0, 17.01)),
+        ]
+    );
+    let ball = summary
+        .ball
+        .expect("the ball is replicated on the first frame");
+    assert_eq!((ball.x, ball.y, ball.z), (0.0, 0.0, 92.75));
+    assert_eq!(summary_at(&mut engine, 100).phase, GamePhase::Kickoff);
+}
+
+#[test]
+fn positions_in_play() {
+    let mut engine = engine();
+    let summary = summary_at(&mut engine, 1000);
+    assert_eq!(summary.phase, GamePhase::Regulation);
+    assert_eq!(
+        positions(&summary),
+        [
+            ("Lich", Team::Orange, (-1379.96, 2016.91, 18.83)),
+            ("Private Boi", Team::Orange, (-2325.63, 4432.48, 17.01)),
+            ("Profile", Team::Orange, (-3624.8, -855.18, 16.04)),
+            ("muffinoss", Team::Blue, (-2359.25, -4291.35, 17.01)),
+            ("popo", Team::Blue, (310.62, 1236.62, 17.01)),
+            ("丶Ej", Team::Blue, (-2058.2, -267.34, 1209.81)),
+        ]
+    );
+    let control = summary.zone_control.expect("every car is on the field");
+    assert!((control.blue - 0.7302).abs() < 1e-3, "{control:?}");
+    assert!((control.orange + control.blue - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn each_team_of_three_makes_a_triangle() {
+    let mut engine = engine();
+    summary_at(&mut engine, 1000);
+    let shapes = zones::team_shapes(&engine);
+    assert_eq!(shapes.len(), 2);
+    for shape in &shapes {
+        assert_eq!(shape.hull.len(), 3, "{shape:?}");
+    }
+    // The middle of muffinoss, popo and 丶Ej in `positions_in_play`
+    let blue = shapes
+        .iter()
+        .find(|shape| shape.team == Team::Blue)
+        .expect("blue has cars on the field");
+    let [x, y] = blue.center;
+    assert!((x - -1368.94).abs() < 0.1, "{x}");
+    assert!((y - -1107.36).abs() < 0.1, "{y}");
+
+    let spread = summary_at(&mut engine, 1000)
+        .spread
+        .expect("there's a ball and cars on both teams");
+    assert!((spread.blue.teammates - 4326.7).abs() < 0.1, "{spread:?}");
+}
+
+#[test]
+fn covered_parts_of_the_goal_mouth_stay_between_the_posts() {
+    let mut engine = engine();
+    for frame in (500..2500).step_by(100) {
+        summary_at(&mut engine, frame);
+        for goal in coverage::goal_coverage(&engine) {
+            let [left, right] = goal.posts;
+            let mut edge = left;
+            for [from, to] in &goal.covered {
+                assert!(edge <= *from && from < to && *to <= right, "{goal:?}");
+                edge = *to;
+            }
+            assert!((0.0..=1.0).contains(&goal.open()), "{goal:?}");
+        }
+    }
+}
+
+#[test]
+fn the_goal_is_a_threat_on_its_way_in() {
+    let mut engine = engine();
+    summary_at(&mut engine, 1620);
+    let threat = coverage::threat(&engine).expect("Profile's shot is heading in");
+    assert_eq!(threat.team, Team::Blue);
+    assert!(threat.seconds < 1.0, "{threat:?}");
+
+    // Waiting for the kickoff, the ball isn't going anywhere
+    summary_at(&mut engine, 100);
+    assert_eq!(coverage::threat(&engine), None);
+}
+
+#[test]
+fn final_positions() {
+    let mut engine = engine();
+    let summary = summary_at(&mut engine, 2850);
+    assert_eq!(
+        positions(&summary),
+        [
+            ("Lich", Team::Orange, (2669.76, -655.21, 92.16)),
+            ("Private Boi", Team::Orange, (2655.39, -1071.11, 68.1)),
+            ("Profile", Team::Orange, (259.67, 12.01, 17.01)),
+            ("muffinoss", Team::Blue, (-332.29, -1047.88, 17.01)),
+            ("popo", Team::Blue, (4062.22, 874.42, 173.11)),
+            ("丶Ej", Team::Blue, (1458.86, -2052.56, 17.0)),
+        ]
+    );
+    assert_eq!(engine.possession().team(), Some(Team::Orange));
+}
+
+#[test]
+fn goals() {
+    let mut engine = engine();
+    let goals = engine.goals();
+    assert_eq!(goals.len(), 1);
+    assert_eq!(goals[0].frame, 1631);
+    assert_eq!(goals[0].player, "Profile");
+    assert_eq!(goals[0].team, Team::Orange);
+
+    let before = summary_at(&mut engine, 1630).score;
+    assert_eq!((before.blue, before.orange), (0, 0));
+    let after = summary_at(&mut engine, 1631).score;
+    assert_eq!((after.blue, after.orange), (0, 1));
+}
+
+#[test]
+fn seeking_back_matches_stepping_forward() {
+    let mut stepped = engine();
+    let forward = summary_at(&mut stepped, 1000);
+
+    let mut seeked = engine();
+    summary_at(&mut seeked, 2000);
+    let back = summary_at(&mut seeked, 1000);
+    assert_eq!(positions(&back), positions(&forward));
+    assert_eq!(back.score, forward.score);
+}
+
+#[test]
+fn timeline_matches_the_engine() {
+    let mut engine = engine();
+    let timeline = Timeline::build(&engine);
+    assert_eq!(timeline.len(), engine.frame_count());
+
+    let entry = timeline.at(2000).expect("every frame is on the timeline");
+    let mut from_timeline = timeline.summary(entry);
+    from_timeline.players.sort_by(|a, b| a.name.cmp(&b.name));
+    let from_engine = summary_at(&mut engine, 2000);
+    assert_eq!(positions(&from_timeline), positions(&from_engine));
+    assert_eq!(from_timeline.spread, from_engine.spread);
+    assert_eq!(from_timeline.score, from_engine.score);
+    assert_eq!(from_timeline.phase, from_engine.phase);
+}
+
+#[test]
+fn seeking_through_the_timeline_matches_stepping() {
+    let mut engine = engine();
+    let timeline = Timeline::build(&engine);
+    let forward = summary_at(&mut engine.clone(), 2000);
+
+    let mut seeked = engine.clone();
+    timeline.seek(&mut seeked, 2001);
+    let mut ahead = seeked.summary();
+    ahead.players.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(positions(&ahead), positions(&forward));
+    assert_eq!(ahead.score, forward.score);
+
+    let back = summary_at(&mut engine, 1000);
+    timeline.seek(&mut seeked, 1001);
+    let mut behind = seeked.summary();
+    behind.players.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(positions(&behind), positions(&back));
+}
+
+#[test]
+fn stepping_back_past_the_start_of_a_short_replay_wraps_around() {
+    let mut replay = parse_replay(Path::new(SAMPLE_REPLAY)).expect("the sample replay parses");
+    if let Some(network) = &mut replay.network_frames {
+        network.frames.truncate(40);
+    }
+    let mut engine = ReplayEngine::new(replay);
+    assert_eq!(engine.frame_count(), 40);
+
+    engine.move_frame(5);
+    // More than twice the whole replay back from frame 5
+    engine.move_frame(-100);
+    assert_eq!(engine.frame_index(), 25);
+    engine.move_frame(-25);
+    assert_eq!(engine.frame_index(), 0);
+    engine.move_frame(-1);
+    assert_eq!(engine.frame_index(), 39);
+}
+
+#[test]
+fn an_unknown_labs_map_is_reported_and_played_on_a_standard_pitch() {
+    assert_eq!(engine().unknown_map(), None);
+
+    let mut replay = parse_replay(Path::new(SAMPLE_REPLAY)).expect("the sample replay parses");
+    for (key, prop) in &mut replay.properties {
+        if key == "MapName" {
+            *prop = HeaderProp::Name("Labs_Unheard_P".to_string());
+        }
+    }
+    let engine = ReplayEngine::new(replay);
+    assert_eq!(engine.unknown_map(), Some("Labs_Unheard_P"));
+    assert_eq!(*engine.arena(), Arena::STANDARD);
+}
+
+#[test]
+fn listeners_hear_what_the_replay_walks_find() {
+    let mut engine = engine();
+    let heard = Arc::new(Mutex::new(vec![]));
+    let listener = heard.clone();
+    engine.on_event(move |event| listener.lock().unwrap().push(event.clone()));
+
+    let touches: Vec<_> = touches::touches(&engine)
+        .into_iter()
+        .map(|touch| (touch.frame, touch.player))
+        .collect();
+    let kickoffs = events::kickoffs(&engine);
+    // Walking a clone doesn't call the original's listeners
+    assert!(heard.lock().unwrap().is_empty());
+
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+    }
+    let heard = heard.lock().unwrap();
+    let heard_touches: Vec<_> = heard
+        .iter()
+        .filter_map(|event| match event {
+            ReplayEvent::Touch(touch) => Some((touch.frame, touch.player.clone())),
+            _ => None,
+        })
+        .collect();
+    let heard_kickoffs: Vec<_> = heard
+        .iter()
+        .filter_map(|event| match event {
+            ReplayEvent::Kickoff { frame } => Some(*frame),
+            _ => None,
+        })
+        .collect();
+    let heard_goals: Vec<_> = heard
+        .iter()
+        .filter_map(|event| match event {
+            ReplayEvent::Goal(goal) => Some((goal.frame, goal.player.as_str())),
+            _ => None,
+        })
+        .collect();
+    let demolitions = heard
+        .iter()
+        .filter(|event| matches!(event, ReplayEvent::Demolition { .. }))
+        .count();
+
+    assert_eq!(heard_touches, touches);
+    assert_eq!(heard_kickoffs, kickoffs);
+    assert_eq!(heard_goals, [(1631, "Profile")]);
+    assert_eq!(demolitions, 5);
+}
+
+#[test]
+fn demolitions_know_who_did_them() {
+    let mut engine = engine();
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+    }
+    let log: Vec<_> = engine
+        .demolition_log()
+        .iter()
+        .map(|demo| {
+            (
+                demo.frame,
+                demo.attacker.as_deref(),
+                demo.victim.as_str(),
+                demo.team,
+            )
+        })
+        .collect();
+    assert_eq!(
+        log,
+        [
+            (247, Some("Profile"), "丶Ej", Team::Blue),
+            (300, Some("Profile"), "丶Ej", Team::Blue),
+            (891, Some("popo"), "Private Boi", Team::Orange),
+            (902, Some("popo"), "Private Boi", Team::Orange),
+            (1626, Some("Lich"), "muffinoss", Team::Blue),
+        ]
+    );
+
+    // The seek bar marks the same demolitions
+    let marked: Vec<_> = events::events(&engine, &[], &[])
+        .into_iter()
+        .filter(|event| event.kind == EventKind::Demolition)
+        .map(|event| event.frame)
+        .collect();
+    assert_eq!(marked, [247, 300, 891, 902, 1626]);
+}
+
+#[test]
+fn cars_leave_the_ground_for_part_of_the_game() {
+    let mut engine = engine();
+    // Everyone waits on the floor for the kickoff
+    engine.seek(101);
+    let states: Vec<_> = engine
+        .player_positions()
+        .filter_map(|(player, _)| engine.air_state(player.car_actor_id?))
+        .collect();
+    assert_eq!(states, [AirState::Grounded; 6]);
+
+    while engine.frame_index() < engine.frame_count() {
+        engine.step();
+    }
+    for (player, _, movement) in engine.movement().players() {
+        let share = movement.seconds_airborne / movement.seconds;
+        assert!(
+            (0.2..0.7).contains(&share),
+            "{player} was in the air {share}"
+        );
+    }
+}
+
+#[test]
+fn powerslides_come_from_the_handbrake() {
+    let mut engine = engine();
+    engine.seek(177);
+    let mut sliding: Vec<_> = engine
+        .player_positions()
+        .filter(|(player, _)| {
+            player
+                .car_actor_id
+                .is_some_and(|car| engine.powersliding(car))
+        })
+        .map(|(player, _)| player.name.as_str())
+        .collect();
+    sliding.sort();
+    assert_eq!(sliding, ["Lich", "Private Boi"]);
+
+    // Nobody slides while they wait for the kickoff
+    engine.seek(100);
+    let sliding = engine.player_positions().any(|(player, _)| {
+        player
+            .car_actor_id
+            .is_some_and(|car| engine.powersliding(car))
+    });
+    assert!(!sliding);
+}
+
+#[test]
+fn kickoffs_go_to_whoever_pushes_the_ball_forward() {
+    let kickoffs = kickoffs::kickoffs(&engine());
+    assert_eq!(kickoffs.len(), 2);
+
+    let going = |kickoff: &kickoffs::Kickoff| -> Vec<(String, Spot)> {
+        kickoff
+            .players
+            .iter()
+            .filter(|player| player.went_for_ball)
+            .map(|player| (player.name.clone(), player.spot))
+            .collect()
+    };
+    let first = &kickoffs[0];
+    assert_eq!(
+        going(first),
+        [
+            ("Private Boi".to_string(), Spot::Diagonal),
+            ("丶Ej".to_string(), Spot::Diagonal)
+        ]
+    );
+    let touch = first.first_touch.as_ref().unwrap();
+    assert_eq!(touch.player, "Private Boi");
+    assert!((touch.seconds - 2.0).abs() < 0.1, "{}", touch.seconds);
+    assert_eq!(first.winner, Some(Team::Orange));
+
+    // The second kickoff is after the goal, with the ball sent into Orange's half
+    let second = &kickoffs[1];
+    assert_eq!(second.first_touch.as_ref().unwrap().player, "Lich");
+    assert_eq!(second.ball_side, Some(Team::Orange));
+    assert_eq!(second.winner, Some(Team::Blue));
+
+    let players = kickoffs::player_kickoffs(&kickoffs);
+    let lich = players
+        .iter()
+        .find(|player| player.player == "Lich")
+        .unwrap();
+    assert_eq!((lich.kickoffs, lich.went_for_ball, lich.won), (2, 1, 0));
+    assert_eq!(lich.win_rate(), Some(0.0));
+    let popo = players
+        .iter()
+        .find(|player| player.player == "popo")
+        .unwrap();
+    assert_eq!(popo.win_rate(), None);
+}
+
+#[test]
+fn rotation_rules_are_broken_where_expected() {
+    let rules = [
+        Rule::BehindBall { players: 1 },
+        Rule::LastManInHalf { boost: 30.0 },
+    ];
+    let log = rotation::violation_log(&engine(), &rules);
+    let spans: Vec<_> = log
+        .iter()
+        .map(|span| (span.start_frame, span.rule, span.team))
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            (351, 1, Team::Orange),
+            (1492, 1, Team::Blue),
+            (1519, 0, Team::Blue)
+        ]
+    );
+    assert_eq!(log[0].players, ["Profile", "Lich"]);
+    assert!(log.iter().all(|span| span.seconds >= 0.5));
+
+    // Every Blue car is up past the ball while they have it
+    let mut engine = engine();
+    engine.seek(1530);
+    let violations = rotation::violations(&engine, &rules[..1]);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].players.len(), 3);
+}
+
+#[test]
+fn blue_leave_the_net_open_before_conceding() {
+    let log = situations::situation_log(&engine());
+    let found: Vec<_> = log
+        .iter()
+        .map(|situation| (situation.kind, situation.team, situation.start_frame))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (SituationKind::OpenNet, Team::Orange, 1397),
+            (SituationKind::Outnumbered, Team::Orange, 1465),
+            (SituationKind::OpenNet, Team::Blue, 1570),
+            (SituationKind::Outnumbered, Team::Blue, 1596),
+        ]
+    );
+    // The net stays open right up to the goal
+    assert_eq!(log[2].end_frame, 1630);
+
+    let mut engine = engine();
+    engine.seek(1601);
+    assert!(situations::situations(&engine).contains(&(SituationKind::OpenNet, Team::Blue)));
+    // Nobody is caught short waiting for the kickoff
+    engine.seek(100);
+    assert!(situations::situations(&engine).is_empty());
+}
+
+#[test]
+fn the_only_shot_is_the_goal() {
+    let shots = shots::shots(&engine());
+    assert_eq!(shots.len(), 1);
+    let shot = &shots[0];
+    assert_eq!(shot.kind, ShotKind::Shot);
+    assert_eq!((shot.player.as_str(), shot.team), ("Profile", Team::Orange));
+    assert_eq!(shot.frame, 1594);
+    // Taken from the right of the blue half, going in at frame 1631
+    let location = shot.location;
+    assert!(location.x > 0.0 && location.y < 0.0, "{location:?}");
+    let quality = shot.quality.expect("shots have a quality");
+    assert!((0.0..=1.0).contains(&quality), "{quality}");
+}
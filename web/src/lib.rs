@@ -0,0 +1,190 @@
+//! The visualizer in a browser. A replay picked from disk plays on an HTML canvas, showing each
+//! player's voronoi cell, the cars, the ball and how much of the field each team controls.
+
+use std::f64::consts::TAU;
+
+use rl_replay_zone_visualizer::arena::Arena;
+use rl_replay_zone_visualizer::{parse_replay_data, zones, ReplayEngine, Team};
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+/// Unreal units per pixel, the same as in the window.
+const SCALE_FACTOR: f64 = 10.0;
+const DOT_RADIUS: f64 = 6.0;
+const BALL_RADIUS: f64 = 7.0;
+const BACKGROUND: &str = "#1a1a1a";
+const FIELD_LINES: &str = "rgba(255, 255, 255, 0.6)";
+const BALL: &str = "#b05cd6";
+
+/// A replay being played on a canvas.
+#[wasm_bindgen]
+pub struct Viewer {
+    engine: ReplayEngine,
+    /// Game time owed to playback that's less than the next frame's delta, in seconds.
+    seconds_due: f64,
+}
+
+#[wasm_bindgen]
+impl Viewer {
+    /// Parses the bytes of a `.replay` file.
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8]) -> Result<Viewer, JsError> {
+        let replay = parse_replay_data(data).map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(Self {
+            engine: ReplayEngine::new(replay),
+            seconds_due: 0.0,
+        })
+    }
+
+    /// Width of the canvas that fits the whole field, in pixels.
+    pub fn width(&self) -> f64 {
+        self.engine.arena().width / SCALE_FACTOR
+    }
+
+    /// Height of the canvas that fits the whole field and both goals, in pixels.
+    pub fn height(&self) -> f64 {
+        let arena = self.engine.arena();
+        (arena.height + 2.0 * arena.goal_depth) / SCALE_FACTOR
+    }
+
+    /// Plays on by `seconds` of real time.
+    pub fn advance(&mut self, seconds: f64) {
+        self.seconds_due += seconds;
+        while let Some(delta) = self.engine.next_frame_delta() {
+            if self.seconds_due < delta as f64 {
+                break;
+            }
+            self.engine.step();
+            self.seconds_due -= delta as f64;
+        }
+        self.engine.interpolate(self.seconds_due as f32);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.engine.frame_index() >= self.engine.frame_count()
+    }
+
+    /// How far through the replay playback is, from 0 to 1.
+    pub fn progress(&self) -> f64 {
+        self.engine.frame_index() as f64 / self.engine.frame_count().max(1) as f64
+    }
+
+    /// Jumps to `progress` of the way through the replay, from 0 to 1.
+    pub fn seek(&mut self, progress: f64) {
+        let frame = progress.clamp(0.0, 1.0) * self.engine.frame_count() as f64;
+        self.engine.seek(frame as usize);
+        self.seconds_due = 0.0;
+    }
+
+    /// Draws the field for the current frame, filling a canvas `width` by `height` pixels.
+    pub fn draw(&self, context: &CanvasRenderingContext2d) {
+        let engine = &self.engine;
+        let arena = engine.arena();
+
+        context.set_fill_style_str(BACKGROUND);
+        context.fill_rect(0.0, 0.0, self.width(), self.height());
+
+        context.save();
+        // A failed transform just leaves the field drawn against the top of the canvas
+        let _ = context.translate(0.0, arena.goal_depth / SCALE_FACTOR);
+
+        for (player, cell) in zones::player_cells(engine) {
+            let points: Vec<_> = cell.iter().map(|p| to_screen(arena, p[0], p[1])).collect();
+            trace(context, &points);
+            context.set_fill_style_str(&css_color(player.color));
+            context.fill();
+        }
+
+        context.set_stroke_style_str(FIELD_LINES);
+        context.set_line_width(2.0);
+        trace(context, &wall_outline(arena));
+        context.stroke();
+        context.set_line_width(1.0);
+        let [_, middle] = to_screen(arena, 0.0, 0.0);
+        context.begin_path();
+        context.move_to(0.0, middle);
+        context.line_to(arena.width / SCALE_FACTOR, middle);
+        context.stroke();
+
+        for (player, body) in engine.player_positions() {
+            let [x, y] = to_screen(arena, body.location.x as f64, body.location.y as f64);
+            circle(context, x, y, DOT_RADIUS + 3.0, "black");
+            circle(context, x, y, DOT_RADIUS, &css_color(player.color));
+        }
+        if let Some(ball) = engine.ball() {
+            let [x, y] = to_screen(arena, ball.location.x as f64, ball.location.y as f64);
+            circle(context, x, y, BALL_RADIUS, BALL);
+        }
+        context.restore();
+
+        if let Some(control) = zones::zone_control(engine) {
+            let colors = engine.team_colors();
+            context.set_font("12px sans-serif");
+            let lines = [
+                (
+                    colors.team(Team::Orange),
+                    format!("Orange {:.0}%", control.orange * 100.0),
+                ),
+                (
+                    colors.team(Team::Blue),
+                    format!("Blue {:.0}%", control.blue * 100.0),
+                ),
+            ];
+            for (i, (color, line)) in lines.iter().enumerate() {
+                context.set_fill_style_str(&css_color(*color));
+                // Text that fails to draw just leaves the line out
+                let _ = context.fill_text(line, 6.0, 16.0 * (i + 1) as f64);
+            }
+        }
+    }
+}
+
+fn to_screen(arena: &Arena, x: f64, y: f64) -> [f64; 2] {
+    [
+        (x + arena.width / 2.0) / SCALE_FACTOR,
+        (y + arena.height / 2.0) / SCALE_FACTOR,
+    ]
+}
+
+/// Corners of the field, on screen, going around the walls and their diagonal corner cuts.
+fn wall_outline(arena: &Arena) -> [[f64; 2]; 8] {
+    let half_width = arena.width / 2.0;
+    let half_height = arena.height / 2.0;
+    let [along_back, along_side] = arena.corner;
+    [
+        to_screen(arena, -half_width + along_back, -half_height),
+        to_screen(arena, half_width - along_back, -half_height),
+        to_screen(arena, half_width, -half_height + along_side),
+        to_screen(arena, half_width, half_height - along_side),
+        to_screen(arena, half_width - along_back, half_height),
+        to_screen(arena, -half_width + along_back, half_height),
+        to_screen(arena, -half_width, half_height - along_side),
+        to_screen(arena, -half_width, -half_height + along_side),
+    ]
+}
+
+/// Starts a closed path through `points`, ready to fill or stroke.
+fn trace(context: &CanvasRenderingContext2d, points: &[[f64; 2]]) {
+    context.begin_path();
+    for (i, [x, y]) in points.iter().enumerate() {
+        if i == 0 {
+            context.move_to(*x, *y);
+        } else {
+            context.line_to(*x, *y);
+        }
+    }
+    context.close_path();
+}
+
+fn circle(context: &CanvasRenderingContext2d, x: f64, y: f64, radius: f64, color: &str) {
+    context.begin_path();
+    // An arc only fails for a negative radius
+    let _ = context.arc(x, y, radius, 0.0, TAU);
+    context.set_fill_style_str(color);
+    context.fill();
+}
+
+fn css_color([r, g, b, a]: [f32; 4]) -> String {
+    let channel = |value: f32| (value * 255.0).round() as u8;
+    format!("rgba({}, {}, {}, {a})", channel(r), channel(g), channel(b))
+}